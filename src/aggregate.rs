@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::{AggregateConfig, AggregateMethod};
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::ChangeReason;
+use crate::stats::RollingStats;
+
+// Source name of the synthetic updates; the trackers comparing venues skip them
+pub const SOURCE: &str = "Aggregate";
+
+pub fn is_aggregate(update: &PriceUpdate) -> bool {
+    update.source == SOURCE
+}
+
+// A venue's liquidity in quote tokens, both reserves valued at its own price
+fn liquidity(update: &PriceUpdate) -> f64 {
+    let base = update.base_reserve as f64 / 10f64.powi(update.base_decimals as i32);
+    let quote = update.quote_reserve as f64 / 10f64.powi(update.quote_decimals as i32);
+    quote + base * update.price
+}
+
+// Cross-venue price per symbol, published every interval_secs as a PriceUpdate
+// from the "Aggregate" source with the symbol as its pool. Venues without
+// reserves (oracles) don't count, nor do updates older than max_age_secs.
+// Reserves and TVL are the venues' totals, so the update reads like one
+// pool holding all the liquidity.
+pub struct PriceAggregator {
+    config: AggregateConfig,
+    // pool address -> latest update of that pool
+    latest: HashMap<String, PriceUpdate>,
+    // symbol -> last published price, for change_percent
+    published: HashMap<String, f64>,
+    stats: RollingStats,
+}
+
+impl PriceAggregator {
+    pub fn new(config: AggregateConfig, stats: RollingStats) -> Self {
+        Self {
+            config,
+            latest: HashMap::new(),
+            published: HashMap::new(),
+            stats,
+        }
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, tx: Arc<broadcast::Sender<MarketEvent>>) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(self.config.interval_secs.max(1)));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => {
+                        if !is_aggregate(&update) && update.price > 0.0 && liquidity(&update) > 0.0 {
+                            self.latest.insert(update.pool.clone(), update);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "price aggregator lagged, skipped updates");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    for update in self.aggregates(unix_now()) {
+                        let _ = tx.send(MarketEvent::Price(update));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn aggregates(&mut self, now: u64) -> Vec<PriceUpdate> {
+        let mut by_symbol: BTreeMap<&str, Vec<&PriceUpdate>> = BTreeMap::new();
+        for update in self.latest.values() {
+            if now.saturating_sub(update.timestamp) <= self.config.max_age_secs {
+                by_symbol.entry(update.symbol.as_str()).or_default().push(update);
+            }
+        }
+
+        let mut aggregates = Vec::new();
+        for (symbol, venues) in by_symbol {
+            if venues.len() < self.config.min_venues.max(1) {
+                continue;
+            }
+            let Some(price) = aggregate_price(self.config.method, &venues) else {
+                continue;
+            };
+            aggregates.push(self.aggregate(symbol, price, &venues));
+        }
+
+        for update in &aggregates {
+            self.published.insert(update.symbol.clone(), update.price);
+        }
+        aggregates
+    }
+
+    fn aggregate(&self, symbol: &str, price: f64, venues: &[&PriceUpdate]) -> PriceUpdate {
+        // Reserves are summed in the decimals of the deepest venue
+        let deepest = venues
+            .iter()
+            .max_by(|a, b| liquidity(a).total_cmp(&liquidity(b)))
+            .expect("aggregates have at least one venue");
+        let total = |side: &str, reserves: Vec<(u64, u8)>, decimals: u8| {
+            sum_reserves(&reserves, decimals).unwrap_or_else(|| {
+                tracing::warn!(symbol = %symbol, side = %side, "aggregate reserve doesn't fit in u64, capped");
+                u64::MAX
+            })
+        };
+        let base_reserve = total("base", venues.iter().map(|venue| (venue.base_reserve, venue.base_decimals)).collect(), deepest.base_decimals);
+        let quote_reserve = total("quote", venues.iter().map(|venue| (venue.quote_reserve, venue.quote_decimals)).collect(), deepest.quote_decimals);
+
+        let tvl: Vec<f64> = venues.iter().filter_map(|venue| venue.tvl_usd).collect();
+        let volume: Vec<f64> = venues.iter().filter_map(|venue| venue.volume_24h).collect();
+        let change_percent = match self.published.get(symbol) {
+            Some(previous) if *previous > 0.0 => (price - previous) / previous * 100.0,
+            _ => 0.0,
+        };
+        let rolling = self.stats.record(&format!("{}:{}", SOURCE, symbol), price);
+
+        PriceUpdate {
+            symbol: symbol.to_string(),
+            price,
+            change_percent,
+            timestamp: venues.iter().map(|venue| venue.timestamp).max().unwrap_or_default(),
+            source: SOURCE.to_string(),
+            pool: symbol.to_string(),
+            base_reserve,
+            quote_reserve,
+            base_decimals: deepest.base_decimals,
+            quote_decimals: deepest.quote_decimals,
+            // Venues move for their own reasons; an aggregate can't tell one
+            change_reason: match self.published.get(symbol) {
+                None => ChangeReason::Initial,
+                Some(previous) if *previous == price => ChangeReason::Unchanged,
+                Some(_) => ChangeReason::Unknown,
+            },
+            lp_supply: None,
+            // What trading the aggregate would cost on average
+            fee_bps: venues.iter().map(|venue| venue.fee_bps).sum::<f64>() / venues.len() as f64,
+            slot: venues.iter().map(|venue| venue.slot).min().unwrap_or_default(),
+            slot_lag: venues.iter().map(|venue| venue.slot_lag).max().unwrap_or_default(),
+            tvl_usd: (!tvl.is_empty()).then(|| tvl.iter().sum()),
+            high_24h: rolling.high,
+            low_24h: rolling.low,
+            volume_24h: (!volume.is_empty()).then(|| volume.iter().sum()),
+            // Best bid and ask across the order book venues
+            bid: venues.iter().filter_map(|venue| venue.bid).reduce(f64::max),
+            ask: venues.iter().filter_map(|venue| venue.ask).reduce(f64::min),
+            active_index: None,
+            microprice: None,
+            imbalance: None,
+            change_1m: rolling.change_1m,
+            change_5m: rolling.change_5m,
+            change_24h: rolling.change_24h,
+            unchanged: false,
+        }
+    }
+}
+
+// Raw reserves (amount, decimals) converted to `to` decimals and added up in
+// u128, None if the total doesn't fit in u64
+fn sum_reserves(reserves: &[(u64, u8)], to: u8) -> Option<u64> {
+    let total = reserves.iter().try_fold(0u128, |total, &(amount, from)| {
+        let amount = match to.checked_sub(from) {
+            Some(up) => 10u128.checked_pow(up as u32)?.checked_mul(amount as u128)?,
+            // Past 10^38 the divisor doesn't fit in u128 and nothing is left anyway
+            None => 10u128.checked_pow((from - to) as u32).map_or(0, |unit| amount as u128 / unit),
+        };
+        total.checked_add(amount)
+    })?;
+    u64::try_from(total).ok()
+}
+
+fn aggregate_price(method: AggregateMethod, venues: &[&PriceUpdate]) -> Option<f64> {
+    match method {
+        AggregateMethod::LiquidityWeighted => {
+            let total: f64 = venues.iter().map(|venue| liquidity(venue)).sum();
+            (total > 0.0).then(|| venues.iter().map(|venue| venue.price * liquidity(venue)).sum::<f64>() / total)
+        }
+        // Middle price, or the mean of the two middle ones
+        AggregateMethod::Median => {
+            let mut prices: Vec<f64> = venues.iter().map(|venue| venue.price).collect();
+            prices.sort_by(f64::total_cmp);
+            let middle = prices.len() / 2;
+            match prices.len() {
+                0 => None,
+                len if len % 2 == 0 => Some((prices[middle - 1] + prices[middle]) / 2.0),
+                _ => Some(prices[middle]),
+            }
+        }
+    }
+}
@@ -0,0 +1,515 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::aggregate;
+use crate::clock::unix_now;
+use crate::config::AlertsConfig;
+use crate::events::MarketEvent;
+use crate::health::HealthEvent;
+use crate::supervisor::Heartbeat;
+use crate::tvl::USD_STABLES;
+use crate::holders::HolderReport;
+use crate::latency;
+use crate::links::{self, Link};
+use crate::pairs::PairTable;
+use crate::pyth;
+use crate::raydium::PriceUpdate;
+use crate::wallets::{WalletActivity, WalletTradeEvent};
+use crate::warmup::Ready;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    PriceMove,       // change over the configured window exceeded the threshold
+    SpreadWidened,   // cross-DEX spread exceeded the threshold
+    ReconnectStorm,  // a monitor keeps restarting
+    StaleData,       // a pool stopped producing updates
+    WalletActivity,  // a watched wallet traded in a watched pool
+    HolderMoved,     // a dominant holder of a pool's mint changed its balance
+    DecoderMismatch, // a decoded pool field disagrees with the RPC node's parser
+    ProgramUpgraded, // a watched DEX program was redeployed
+    OracleDeviation, // a pool's price strayed from the Pyth reference
+    LatencyBudget,   // a source's pipeline latency exceeded the budget
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub symbol: String,
+    pub source: Option<String>,
+    pub message: String,
+    pub links: Vec<Link>,
+    pub timestamp: u64,
+}
+
+impl Alert {
+    // Message followed by one line per deep link, for chat sinks
+    pub fn text(&self) -> String {
+        let mut text = self.message.clone();
+        for link in &self.links {
+            text.push_str(&format!("\n🔗 {}: {}", link.label, link.url));
+        }
+        text
+    }
+}
+
+pub struct AlertEngine {
+    config: AlertsConfig,
+    // Latest quote of every pool
+    pairs: PairTable,
+    // pair id -> (timestamp, price) within the alert window
+    history: Vec<VecDeque<(u64, f64)>>,
+    // source -> recent reconnect timestamps
+    reconnects: HashMap<String, VecDeque<u64>>,
+    // (kind, key) -> when it last fired, so a condition doesn't re-alert every tick
+    last_fired: HashMap<(AlertKind, String), u64>,
+    // Sources paused through the control API, whose pools are stale on purpose
+    paused: HashSet<String>,
+    // base token -> (price, publish time) of its latest Pyth USD price
+    oracle: HashMap<String, (f64, u64)>,
+}
+
+impl AlertEngine {
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            pairs: PairTable::new(),
+            history: Vec::new(),
+            reconnects: HashMap::new(),
+            last_fired: HashMap::new(),
+            paused: HashSet::new(),
+            oracle: HashMap::new(),
+        }
+    }
+
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        mut health_rx: broadcast::Receiver<HealthEvent>,
+        mut wallet_rx: broadcast::Receiver<WalletTradeEvent>,
+        mut holder_rx: broadcast::Receiver<HolderReport>,
+        tx: Arc<broadcast::Sender<Alert>>,
+        heartbeat: Heartbeat,
+        ready: Ready,
+    ) {
+        let mut stale_check = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        // A closed channel stays ready with an error, so its arm is switched off
+        // instead of spinning the loop
+        let (mut health_open, mut wallets_open, mut holders_open) = (true, true, true);
+
+        loop {
+            heartbeat.beat();
+            let alerts = tokio::select! {
+                update = rx.recv() => match update {
+                    // Price moves and spreads are tracked during warm-up but only alerted on after it
+                    Ok(MarketEvent::Price(price_update)) if !aggregate::is_aggregate(&price_update) => {
+                        let alerts = self.on_price_update(price_update);
+                        if ready.is_ready() { alerts } else { Vec::new() }
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "alert engine lagged, skipped updates");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                event = health_rx.recv(), if health_open => match event {
+                    Ok(event) => self.on_health_event(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "alert engine lagged, skipped health events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        health_open = false;
+                        continue;
+                    }
+                },
+                event = wallet_rx.recv(), if wallets_open => match event {
+                    Ok(event) => self.on_wallet_event(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "alert engine lagged, skipped wallet events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        wallets_open = false;
+                        continue;
+                    }
+                },
+                report = holder_rx.recv(), if holders_open => match report {
+                    Ok(report) => self.on_holder_report(report),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "alert engine lagged, skipped holder reports");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        holders_open = false;
+                        continue;
+                    }
+                },
+                _ = stale_check.tick() => {
+                    let now = unix_now();
+                    let mut alerts = self.check_stale(now);
+                    alerts.extend(self.check_latency(now));
+                    alerts
+                }
+            };
+
+            for alert in alerts {
+                let _ = tx.send(alert);
+            }
+        }
+    }
+
+    pub fn on_price_update(&mut self, update: PriceUpdate) -> Vec<Alert> {
+        let now = update.timestamp;
+        let mut alerts = Vec::new();
+
+        if update.price <= 0.0 {
+            return alerts;
+        }
+
+        latency::observe_slot_lag(&update.source, update.slot_lag);
+
+        let pair = self.pairs.upsert(&update);
+        if pair == self.history.len() {
+            self.history.push(VecDeque::new());
+        }
+
+        // Price move over the window, measured against the oldest retained price
+        let history = &mut self.history[pair];
+        history.push_back((now, update.price));
+        while history.front().is_some_and(|(ts, _)| now.saturating_sub(*ts) > self.config.window_secs) {
+            history.pop_front();
+        }
+
+        if let Some(&(_, oldest)) = history.front() {
+            let change_percent = (update.price - oldest) / oldest * 100.0;
+            if change_percent.abs() >= self.config.move_threshold_percent
+                && self.cooldown_elapsed(AlertKind::PriceMove, &update.pool, now)
+            {
+                alerts.push(Alert {
+                    kind: AlertKind::PriceMove,
+                    symbol: update.symbol.clone(),
+                    source: Some(update.source.clone()),
+                    message: format!(
+                        "{} {} on {} moved {:+.2}% in {}s (${:.4} → ${:.4})",
+                        if change_percent > 0.0 { "📈" } else { "📉" },
+                        update.symbol,
+                        update.source,
+                        change_percent,
+                        self.config.window_secs,
+                        oldest,
+                        update.price,
+                    ),
+                    links: vec![links::birdeye_pool(&update.pool)],
+                    timestamp: now,
+                });
+            }
+        }
+
+        // Cross-DEX spread between the freshest quotes for the symbol
+        let pairs = &self.pairs;
+        let fresh = pairs
+            .symbol_pairs(&update.symbol)
+            .iter()
+            .copied()
+            .filter(|&pair| pairs.timestamps[pair] > 0 && now.saturating_sub(pairs.timestamps[pair]) <= self.config.window_secs);
+        let extremes = fresh.fold(None, |extremes: Option<(usize, usize)>, pair| match extremes {
+            None => Some((pair, pair)),
+            Some((low, high)) => Some((
+                if pairs.prices[pair] < pairs.prices[low] { pair } else { low },
+                if pairs.prices[pair] > pairs.prices[high] { pair } else { high },
+            )),
+        });
+
+        if let Some((low, high)) = extremes {
+            let (low_price, high_price) = (pairs.prices[low], pairs.prices[high]);
+            let spread_bps = (high_price - low_price) / low_price * 10_000.0;
+            let message = format!(
+                "↔️ {} spread {:.1} bps: {} ${:.4} vs {} ${:.4}",
+                update.symbol, spread_bps, pairs.sources[low], low_price, pairs.sources[high], high_price,
+            );
+            let links = vec![links::birdeye_pool(&pairs.pools[low]), links::birdeye_pool(&pairs.pools[high])];
+
+            if spread_bps >= self.config.spread_threshold_bps
+                && self.cooldown_elapsed(AlertKind::SpreadWidened, &update.symbol, now)
+            {
+                alerts.push(Alert {
+                    kind: AlertKind::SpreadWidened,
+                    symbol: update.symbol.clone(),
+                    source: None,
+                    message,
+                    links,
+                    timestamp: now,
+                });
+            }
+        }
+
+        // Deviation of USD-quoted pools from the oracle, which catches dislocated
+        // markets and decoders misreading a pool alike
+        let Some((base, quote)) = update.symbol.split_once('/') else {
+            return alerts;
+        };
+        if update.source == pyth::SOURCE {
+            self.oracle.insert(base.to_string(), (update.price, now));
+        } else if USD_STABLES.contains(&quote) {
+            if let Some(&(oracle_price, published)) = self.oracle.get(base) {
+                let deviation_bps = (update.price - oracle_price) / oracle_price * 10_000.0;
+                if now.saturating_sub(published) <= self.config.window_secs
+                    && deviation_bps.abs() >= self.config.oracle_deviation_bps
+                    && self.cooldown_elapsed(AlertKind::OracleDeviation, &update.pool, now)
+                {
+                    alerts.push(Alert {
+                        kind: AlertKind::OracleDeviation,
+                        symbol: update.symbol.clone(),
+                        source: Some(update.source.clone()),
+                        message: format!(
+                            "🔮 {} on {} deviates {:+.1} bps from Pyth (${:.4} vs ${:.4})",
+                            update.symbol, update.source, deviation_bps, update.price, oracle_price,
+                        ),
+                        links: vec![links::birdeye_pool(&update.pool)],
+                        timestamp: now,
+                    });
+                }
+            }
+        }
+
+        alerts
+    }
+
+    pub fn on_health_event(&mut self, event: HealthEvent) -> Vec<Alert> {
+        match &event {
+            HealthEvent::Paused { source, .. } => {
+                self.paused.insert(source.clone());
+            }
+            HealthEvent::Resumed { source, .. } => {
+                self.paused.remove(source);
+                // Quotes from before the pause would read as stale until the first fresh one
+                self.pairs.clear_source(source);
+            }
+            HealthEvent::DecoderMismatch { source, symbol, pool, field, decoded, parsed, slot, timestamp } => {
+                let key = format!("{}:{}", pool, field);
+                if !self.cooldown_elapsed(AlertKind::DecoderMismatch, &key, *timestamp) {
+                    return Vec::new();
+                }
+                return vec![Alert {
+                    kind: AlertKind::DecoderMismatch,
+                    symbol: symbol.clone(),
+                    source: Some(source.clone()),
+                    message: format!(
+                        "🧬 {} {} decoder mismatch at slot {}: {} decoded as {}, RPC parsed {} - check the program for an upgrade",
+                        source, symbol, slot, field, decoded, parsed,
+                    ),
+                    links: vec![links::solanafm_account(pool)],
+                    timestamp: *timestamp,
+                }];
+            }
+            // Always raised: each upgrade happens once and decoders may already be misreading pools
+            HealthEvent::ProgramUpgraded { source, program, previous_slot, slot, timestamp } => {
+                return vec![Alert {
+                    kind: AlertKind::ProgramUpgraded,
+                    symbol: String::new(),
+                    source: Some(source.clone()),
+                    message: format!(
+                        "🚨 {} program upgraded at slot {} (previously deployed at slot {}) - verify the decoders still parse its accounts",
+                        source, slot, previous_slot,
+                    ),
+                    links: vec![links::solanafm_account(program)],
+                    timestamp: *timestamp,
+                }];
+            }
+            _ => {}
+        }
+
+        // Backoff is the monitors handling errors themselves; only restarts count towards a storm
+        let (HealthEvent::Reconnecting { source, timestamp, .. }
+        | HealthEvent::Panicked { source, timestamp, .. }
+        | HealthEvent::Stalled { source, timestamp, .. }) = event
+        else {
+            return Vec::new();
+        };
+
+        let reconnects = self.reconnects.entry(source.clone()).or_default();
+        reconnects.push_back(timestamp);
+        while reconnects.front().is_some_and(|ts| timestamp.saturating_sub(*ts) > self.config.reconnect_storm_window_secs) {
+            reconnects.pop_front();
+        }
+        let count = reconnects.len();
+
+        if count >= self.config.reconnect_storm_count
+            && self.cooldown_elapsed(AlertKind::ReconnectStorm, &source, timestamp)
+        {
+            return vec![Alert {
+                kind: AlertKind::ReconnectStorm,
+                symbol: String::new(),
+                source: Some(source.clone()),
+                message: format!(
+                    "🌪️ {} reconnected {} times in {}s",
+                    source, count, self.config.reconnect_storm_window_secs,
+                ),
+                links: Vec::new(),
+                timestamp,
+            }];
+        }
+
+        Vec::new()
+    }
+
+    // Every swap or liquidity change of a watched wallet inside a watched pool,
+    // no cooldown since each one is a distinct transaction
+    pub fn on_wallet_event(&mut self, event: WalletTradeEvent) -> Vec<Alert> {
+        if !self.config.wallet_activity {
+            return Vec::new();
+        }
+        let (Some(pool), Some(symbol)) = (&event.pool, &event.symbol) else {
+            return Vec::new();
+        };
+
+        let action = match event.activity {
+            WalletActivity::Swap => "swapped in",
+            WalletActivity::LiquidityAdd => "added liquidity to",
+            WalletActivity::LiquidityRemove => "removed liquidity from",
+        };
+        let deltas: Vec<String> = event
+            .deltas
+            .iter()
+            .map(|delta| format!("{:+.4} {}", delta.amount, delta.mint))
+            .collect();
+
+        vec![Alert {
+            kind: AlertKind::WalletActivity,
+            symbol: symbol.clone(),
+            source: Some(event.source.clone()),
+            message: format!(
+                "🐋 {} {} {} {} ({}) at slot {} - {}",
+                event.wallet, action, event.source, symbol, deltas.join(", "), event.slot, event.signature,
+            ),
+            links: vec![
+                links::solscan_tx(&event.signature),
+                links::solanafm_account(&event.wallet),
+                links::birdeye_pool(pool),
+            ],
+            timestamp: event.timestamp,
+        }]
+    }
+
+    pub fn on_holder_report(&mut self, report: HolderReport) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for holder_move in &report.moves {
+            if !self.cooldown_elapsed(AlertKind::HolderMoved, &holder_move.address, report.timestamp) {
+                continue;
+            }
+            alerts.push(Alert {
+                kind: AlertKind::HolderMoved,
+                symbol: report.symbol.clone(),
+                source: Some(report.source.clone()),
+                message: format!(
+                    "🏦 {} holder {} ({:.1}% of {:?} supply) moved {:+.2}%: {:.2} → {:.2}",
+                    report.symbol,
+                    holder_move.address,
+                    holder_move.share_percent,
+                    report.mint_kind,
+                    holder_move.change_percent,
+                    holder_move.previous_amount,
+                    holder_move.amount,
+                ),
+                links: vec![links::solanafm_account(&holder_move.address), links::birdeye_pool(&report.pool)],
+                timestamp: report.timestamp,
+            });
+        }
+
+        alerts
+    }
+
+    // Pools whose latest update is older than stale_after_secs, unless their source is paused
+    pub fn check_stale(&mut self, now: u64) -> Vec<Alert> {
+        let stale: Vec<usize> = self
+            .pairs
+            .quoted()
+            .filter(|&pair| now.saturating_sub(self.pairs.timestamps[pair]) > self.config.stale_after_secs)
+            .filter(|&pair| !self.paused.contains(&self.pairs.sources[pair]))
+            .collect();
+
+        let mut alerts = Vec::new();
+        for pair in stale {
+            let pool = self.pairs.pools[pair].clone();
+            if self.cooldown_elapsed(AlertKind::StaleData, &pool, now) {
+                alerts.push(Alert {
+                    kind: AlertKind::StaleData,
+                    symbol: self.pairs.symbols[pair].clone(),
+                    source: Some(self.pairs.sources[pair].clone()),
+                    message: format!(
+                        "🧊 {} on {} has not updated for {}s (last at slot {})",
+                        self.pairs.symbols[pair],
+                        self.pairs.sources[pair],
+                        now.saturating_sub(self.pairs.timestamps[pair]),
+                        self.pairs.slots[pair],
+                    ),
+                    links: vec![links::birdeye_pool(&pool)],
+                    timestamp: now,
+                });
+            }
+        }
+
+        alerts
+    }
+
+    // Sources over the latency budget, with the stage that blew it
+    pub fn check_latency(&mut self, now: u64) -> Vec<Alert> {
+        if self.config.latency_budget_ms == 0 && self.config.latency_stage_budgets_ms.is_empty() {
+            return Vec::new();
+        }
+
+        let breaches = latency::breaches(
+            self.config.latency_percentile,
+            self.config.latency_budget_ms,
+            &self.config.latency_stage_budgets_ms,
+        );
+        let mut alerts = Vec::new();
+        for breach in breaches {
+            if self.paused.contains(&breach.source) || !self.cooldown_elapsed(AlertKind::LatencyBudget, &breach.source, now) {
+                continue;
+            }
+            let stages: Vec<String> = breach.stages
+                .iter()
+                .map(|(stage, ms)| format!("{} {:.0}ms", stage.name(), ms))
+                .collect();
+            let budget = match self.config.latency_stage_budgets_ms.get(&breach.culprit) {
+                Some(budget) if breach.stages.iter().any(|(stage, ms)| *stage == breach.culprit && *ms > *budget as f64) => {
+                    format!("{} over its {}ms stage budget", breach.culprit.name(), budget)
+                }
+                _ => format!("over the {}ms budget, mostly {}", self.config.latency_budget_ms, breach.culprit.name()),
+            };
+            alerts.push(Alert {
+                kind: AlertKind::LatencyBudget,
+                symbol: String::new(),
+                source: Some(breach.source.clone()),
+                message: format!(
+                    "🐢 {} p{} latency {:.0}ms, {} ({})",
+                    breach.source,
+                    self.config.latency_percentile,
+                    breach.total_ms,
+                    budget,
+                    stages.join(", "),
+                ),
+                links: Vec::new(),
+                timestamp: now,
+            });
+        }
+        alerts
+    }
+
+    fn cooldown_elapsed(&mut self, kind: AlertKind, key: &str, now: u64) -> bool {
+        let key = (kind, key.to_string());
+        if let Some(&fired) = self.last_fired.get(&key) {
+            if now.saturating_sub(fired) < self.config.cooldown_secs {
+                return false;
+            }
+        }
+        self.last_fired.insert(key, now);
+        true
+    }
+}
@@ -0,0 +1,432 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::capabilities::Capabilities;
+use crate::raydium::PriceUpdate;
+use crate::events::MarketEvent;
+use crate::health::StaleSources;
+use crate::pause::{Pause, PauseState};
+use crate::quote::{self, Quote};
+use crate::routing::{self, RouteScore, RouteScores, RouteSplit, Side};
+use crate::rpc::RpcProvider;
+use crate::schema::{self, Schemas};
+use crate::sla::{SlaHistory, SlaReport, SlaReports};
+use crate::twap::TwapUpdate;
+use crate::warmup::Ready;
+
+// Latest update per pool address, shared between the feed task and the HTTP handlers
+pub type LatestPrices = Arc<RwLock<HashMap<String, PriceUpdate>>>;
+// Latest TWAPs per pool address, fed from the same stream
+pub type LatestTwaps = Arc<RwLock<HashMap<String, TwapUpdate>>>;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub prices: LatestPrices,
+    pub twaps: LatestTwaps,
+    pub routes: RouteScores,
+    pub updates: Arc<broadcast::Sender<MarketEvent>>,  // Live stream for WebSocket clients
+    pub ready: Ready,                                  // Startup warm-up barrier passed
+    pub pause: Pause,
+    pub control_token: Option<String>,                 // Required by the pause/resume endpoints
+    pub rpc: RpcProvider,                              // Tick and bin arrays for /quote
+    pub sla: SlaReports,
+    pub capabilities: Arc<Capabilities>,               // Fixed at startup
+    pub stale: StaleSources,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Health {
+    pub status: String,                // "ok", or "stale" while any source is
+    pub stale: BTreeMap<String, u64>,  // Source -> Unix time it went stale
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spread {
+    pub symbol: String,
+    pub low_source: String,
+    pub low_price: f64,
+    pub high_source: String,
+    pub high_price: f64,
+    pub spread_bps: f64,
+    pub venues: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SymbolQuery {
+    pub symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteQuery {
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+}
+
+// Per-client WebSocket filter, e.g. /ws?source=orca,raydium&symbol=SOL/USDC
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamFilter {
+    pub source: Option<String>,
+    pub symbol: Option<String>,
+}
+
+// Update schema a WebSocket client subscribes with, e.g. /ws?schema=2
+#[derive(Debug, Deserialize)]
+pub struct SchemaQuery {
+    pub schema: Option<u32>,
+}
+
+impl StreamFilter {
+    fn matches(&self, update: &PriceUpdate) -> bool {
+        let allows = |filter: &Option<String>, value: &str| {
+            filter
+                .as_deref()
+                .is_none_or(|wanted| wanted.split(',').any(|item| item.trim().eq_ignore_ascii_case(value)))
+        };
+
+        allows(&self.source, &update.source) && allows(&self.symbol, &update.symbol)
+    }
+}
+
+// Feed the shared state from the broadcast channel
+pub async fn track_prices(mut rx: broadcast::Receiver<MarketEvent>, prices: LatestPrices, twaps: LatestTwaps) {
+    loop {
+        match rx.recv().await {
+            Ok(MarketEvent::Price(update)) => {
+                prices.write().await.insert(update.pool.clone(), update);
+            }
+            Ok(MarketEvent::Twap(twap)) => {
+                twaps.write().await.insert(twap.pool.clone(), twap);
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/prices", get(get_prices))
+        .route("/prices/:source", get(get_prices_by_source))
+        .route("/spread", get(get_spread))
+        .route("/twap", get(get_twap))
+        .route("/routes", get(get_routes))
+        .route("/route", get(get_route))
+        .route("/quote", get(get_quote))
+        .route("/ws", get(ws_handler))
+        .route("/schema", get(get_schema))
+        .route("/capabilities", get(get_capabilities))
+        .route("/ready", get(get_ready))
+        .route("/health", get(get_health))
+        .route("/sla", get(get_sla))
+        .route("/sla/:date", get(get_sla_day))
+        .route("/pause", get(get_pause).post(pause_all))
+        .route("/pause/:source", post(pause_source))
+        .route("/resume", post(resume_all))
+        .route("/resume/:source", post(resume_source))
+        .with_state(state)
+}
+
+pub async fn serve(addr: SocketAddr, state: ApiState) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(addr = %addr, "REST API listening");
+    axum::serve(listener, router(state)).await?;
+
+    Ok(())
+}
+
+// GET /ready - 200 once enough pools have a first price, 503 while warming up
+async fn get_ready(State(state): State<ApiState>) -> StatusCode {
+    if state.ready.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+// GET /health - 200 while every source publishes prices, 503 with the stale
+// ones once any has been silent for [supervisor] stale_source_secs
+async fn get_health(State(state): State<ApiState>) -> (StatusCode, Json<Health>) {
+    let stale = state.stale.read().await.clone();
+    let (code, status) = if stale.is_empty() {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "stale")
+    };
+    (code, Json(Health { status: status.to_string(), stale }))
+}
+
+// GET /sla - today's feed availability so far and the completed days before it
+async fn get_sla(State(state): State<ApiState>) -> Json<SlaHistory> {
+    Json(state.sla.read().await.clone())
+}
+
+// GET /sla/:date - one day's report, e.g. /sla/2026-10-15
+async fn get_sla_day(State(state): State<ApiState>, Path(date): Path<String>) -> Result<Json<SlaReport>, StatusCode> {
+    let history = state.sla.read().await;
+    history
+        .today
+        .iter()
+        .chain(&history.days)
+        .find(|report| report.date == date)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// GET /pause - which sources are paused
+async fn get_pause(State(state): State<ApiState>) -> Json<PauseState> {
+    Json(state.pause.state())
+}
+
+// POST /pause - stop polling every source, keeping their state
+async fn pause_all(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<PauseState>, StatusCode> {
+    authorize(&state, &headers)?;
+    state.pause.set(None, true);
+    Ok(Json(state.pause.state()))
+}
+
+// POST /resume - lift the global pause; sources paused one by one stay paused
+async fn resume_all(State(state): State<ApiState>, headers: HeaderMap) -> Result<Json<PauseState>, StatusCode> {
+    authorize(&state, &headers)?;
+    state.pause.set(None, false);
+    Ok(Json(state.pause.state()))
+}
+
+// POST /pause/{source} - stop polling one DEX (case-insensitive)
+async fn pause_source(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<PauseState>, StatusCode> {
+    authorize(&state, &headers)?;
+    let source = Pause::source(&source).ok_or(StatusCode::NOT_FOUND)?;
+    state.pause.set(Some(source), true);
+    Ok(Json(state.pause.state()))
+}
+
+// POST /resume/{source} - restart polling one DEX
+async fn resume_source(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<PauseState>, StatusCode> {
+    authorize(&state, &headers)?;
+    let source = Pause::source(&source).ok_or(StatusCode::NOT_FOUND)?;
+    state.pause.set(Some(source), false);
+    Ok(Json(state.pause.state()))
+}
+
+// Control endpoints need `Authorization: Bearer <control_token>`, and are off without a token
+fn authorize(state: &ApiState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(token) = state.control_token.as_deref() else {
+        return Err(StatusCode::FORBIDDEN);
+    };
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if bearer != Some(token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+// GET /prices - latest update from every watched pool
+async fn get_prices(State(state): State<ApiState>) -> Json<Vec<PriceUpdate>> {
+    let prices = state.prices.read().await;
+    let mut updates: Vec<PriceUpdate> = prices.values().cloned().collect();
+    updates.sort_by(|a, b| (&a.symbol, &a.source).cmp(&(&b.symbol, &b.source)));
+
+    Json(updates)
+}
+
+// GET /prices/{source} - latest updates from one DEX (case-insensitive)
+async fn get_prices_by_source(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+) -> Result<Json<Vec<PriceUpdate>>, StatusCode> {
+    let prices = state.prices.read().await;
+    let updates: Vec<PriceUpdate> = prices
+        .values()
+        .filter(|update| update.source.eq_ignore_ascii_case(&source))
+        .cloned()
+        .collect();
+
+    if updates.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(updates))
+}
+
+// GET /spread[?symbol=SOL/USDC] - cheapest vs. most expensive venue per symbol
+async fn get_spread(
+    State(state): State<ApiState>,
+    Query(query): Query<SymbolQuery>,
+) -> Json<Vec<Spread>> {
+    let prices = state.prices.read().await;
+
+    let mut by_symbol: HashMap<&str, Vec<&PriceUpdate>> = HashMap::new();
+    for update in prices.values().filter(|update| update.price > 0.0) {
+        by_symbol.entry(update.symbol.as_str()).or_default().push(update);
+    }
+
+    let mut spreads: Vec<Spread> = by_symbol
+        .into_iter()
+        .filter(|(symbol, _)| query.symbol.as_deref().is_none_or(|wanted| wanted.eq_ignore_ascii_case(symbol)))
+        .filter_map(|(symbol, quotes)| {
+            let low = quotes.iter().min_by(|a, b| a.price.total_cmp(&b.price))?;
+            let high = quotes.iter().max_by(|a, b| a.price.total_cmp(&b.price))?;
+
+            Some(Spread {
+                symbol: symbol.to_string(),
+                low_source: low.source.clone(),
+                low_price: low.price,
+                high_source: high.source.clone(),
+                high_price: high.price,
+                spread_bps: (high.price - low.price) / low.price * 10_000.0,
+                venues: quotes.len(),
+            })
+        })
+        .collect();
+    spreads.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    Json(spreads)
+}
+
+// GET /twap[?symbol=SOL/USDC] - latest TWAPs of every pool, empty unless [twap] is enabled
+async fn get_twap(
+    State(state): State<ApiState>,
+    Query(query): Query<SymbolQuery>,
+) -> Json<Vec<TwapUpdate>> {
+    let twaps = state.twaps.read().await;
+    let mut updates: Vec<TwapUpdate> = twaps
+        .values()
+        .filter(|twap| query.symbol.as_deref().is_none_or(|wanted| wanted.eq_ignore_ascii_case(&twap.symbol)))
+        .cloned()
+        .collect();
+    updates.sort_by(|a, b| (&a.symbol, &a.source).cmp(&(&b.symbol, &b.source)));
+
+    Json(updates)
+}
+
+// GET /routes[?symbol=SOL/USDC] - venues ranked by route score, best first
+async fn get_routes(
+    State(state): State<ApiState>,
+    Query(query): Query<SymbolQuery>,
+) -> Json<Vec<RouteScore>> {
+    let routes = state.routes.read().await;
+    let mut scores: Vec<RouteScore> = routes
+        .values()
+        .filter(|score| query.symbol.as_deref().is_none_or(|wanted| wanted.eq_ignore_ascii_case(&score.symbol)))
+        .cloned()
+        .collect();
+    scores.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(b.score.total_cmp(&a.score)));
+
+    Json(scores)
+}
+
+// GET /route?symbol=SOL/USDC&side=sell&size=100 - split an order across venues
+async fn get_route(
+    State(state): State<ApiState>,
+    Query(query): Query<RouteQuery>,
+) -> Result<Json<RouteSplit>, StatusCode> {
+    let venues: Vec<PriceUpdate> = state.prices.read().await.values().cloned().collect();
+    let rpc_client = state.rpc.client(CommitmentConfig::confirmed());
+
+    routing::split_order(&rpc_client, venues, &query.symbol, query.side, query.size)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// GET /quote?symbol=SOL/USDC&side=sell&size=100 - expected fill and slippage on each venue
+async fn get_quote(
+    State(state): State<ApiState>,
+    Query(query): Query<RouteQuery>,
+) -> Result<Json<Quote>, StatusCode> {
+    let venues: Vec<PriceUpdate> = state.prices.read().await.values().cloned().collect();
+    let rpc_client = state.rpc.client(CommitmentConfig::confirmed());
+
+    quote::quote(&rpc_client, venues, &query.symbol, query.side, query.size)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// GET /schema - update schema versions the stream can serve, with their fields
+async fn get_schema() -> Json<Schemas> {
+    Json(schema::schemas())
+}
+
+// GET /capabilities - the sources, pairs, sinks and features this instance runs with
+async fn get_capabilities(State(state): State<ApiState>) -> Json<Capabilities> {
+    Json((*state.capabilities).clone())
+}
+
+// GET /ws - push every matching PriceUpdate to the client as a JSON text frame,
+// in the schema version it asked for. The version served is echoed in the
+// X-Schema-Version header of the upgrade response.
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<ApiState>,
+    Query(filter): Query<StreamFilter>,
+    Query(query): Query<SchemaQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let version = query.schema.unwrap_or(schema::DEFAULT);
+    if !schema::supported(version) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported schema version {}, this server serves 1 to {}", version, schema::LATEST),
+        ));
+    }
+
+    let rx = state.updates.subscribe();
+    let mut response = ws.on_upgrade(move |socket| stream_updates(socket, rx, filter, version));
+    response.headers_mut().insert("x-schema-version", version.into());
+    Ok(response)
+}
+
+async fn stream_updates(mut socket: WebSocket, mut rx: broadcast::Receiver<MarketEvent>, filter: StreamFilter, version: u32) {
+    loop {
+        tokio::select! {
+            update = rx.recv() => match update {
+                Ok(MarketEvent::Price(update)) => {
+                    if !filter.matches(&update) {
+                        continue;
+                    }
+                    let Ok(json) = schema::price_update(&update, version).map(|value| value.to_string()) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                // Slow clients skip ahead instead of holding up the feed
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::aggregate;
+use crate::config::ArbitrageConfig;
+use crate::events::MarketEvent;
+use crate::pairs::PairTable;
+use crate::raydium::PriceUpdate;
+use crate::warmup::Ready;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageOpportunity {
+    pub symbol: String,
+    pub buy_source: String,
+    pub buy_pool: String,
+    pub buy_price: f64,
+    pub sell_source: String,
+    pub sell_pool: String,
+    pub sell_price: f64,
+    pub gross_spread_bps: f64,  // Before fees
+    pub net_spread_bps: f64,    // After buy + sell fee assumptions
+    pub timestamp: u64,
+}
+
+pub struct ArbitrageDetector {
+    config: ArbitrageConfig,
+    // Latest quote of every pool
+    pairs: PairTable,
+}
+
+impl ArbitrageDetector {
+    pub fn new(config: ArbitrageConfig) -> Self {
+        Self {
+            config,
+            pairs: PairTable::new(),
+        }
+    }
+
+    // Consume price updates from every DEX and publish opportunities on their own
+    // channel. Before warm-up completes prices are recorded but nothing is published.
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<ArbitrageOpportunity>>,
+        ready: Ready,
+    ) {
+        loop {
+            match rx.recv().await {
+                Ok(MarketEvent::Price(price_update)) if !aggregate::is_aggregate(&price_update) => {
+                    let opportunity = self.on_price_update(price_update);
+                    if let Some(opportunity) = opportunity.filter(|_| ready.is_ready()) {
+                        let _ = tx.send(opportunity);
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "arbitrage detector lagged, skipped updates");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    // Record the update and check the widest spread across venues for its symbol
+    pub fn on_price_update(&mut self, update: PriceUpdate) -> Option<ArbitrageOpportunity> {
+        let now = update.timestamp;
+        self.pairs.upsert(&update);
+
+        // Only compare venues that reported recently
+        let pairs = &self.pairs;
+        let fresh = pairs
+            .symbol_pairs(&update.symbol)
+            .iter()
+            .copied()
+            .filter(|&pair| pairs.prices[pair] > 0.0 && now.saturating_sub(pairs.timestamps[pair]) <= self.config.max_age_secs);
+
+        let (buy, sell) = fresh.fold(None, |extremes: Option<(usize, usize)>, pair| match extremes {
+            None => Some((pair, pair)),
+            Some((buy, sell)) => Some((
+                if pairs.prices[pair] < pairs.prices[buy] { pair } else { buy },
+                if pairs.prices[pair] > pairs.prices[sell] { pair } else { sell },
+            )),
+        })?;
+
+        if buy == sell {
+            return None;
+        }
+
+        let (buy_price, sell_price) = (pairs.prices[buy], pairs.prices[sell]);
+        let gross_spread_bps = (sell_price - buy_price) / buy_price * 10_000.0;
+        let fee_bps = |pair: usize| self.config.fee_bps(&pairs.sources[pair], pairs.fees_bps[pair]);
+        let net_spread_bps = gross_spread_bps - fee_bps(buy) - fee_bps(sell);
+
+        if net_spread_bps < self.config.threshold_bps {
+            return None;
+        }
+
+        Some(ArbitrageOpportunity {
+            symbol: update.symbol,
+            buy_source: pairs.sources[buy].clone(),
+            buy_pool: pairs.pools[buy].clone(),
+            buy_price,
+            sell_source: pairs.sources[sell].clone(),
+            sell_pool: pairs.pools[sell].clone(),
+            sell_price,
+            gross_spread_bps,
+            net_spread_bps,
+            timestamp: now,
+        })
+    }
+}
@@ -0,0 +1,224 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::TryStreamExt;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::clock::unix_now;
+use crate::config::ArchiveConfig;
+use crate::events::MarketEvent;
+
+// Written while a file is being filled, so a crash never leaves a truncated
+// file that looks finished
+const PARTIAL_SUFFIX: &str = ".partial";
+const FILE_SUFFIX: &str = ".jsonl.gz";
+// With keep_local, uploaded files move here so they aren't uploaded again
+const UPLOADED_DIR: &str = "uploaded";
+
+// Rolling archive of the market bus. Events are appended to a local gzipped
+// JSON Lines file, one MarketEvent per line as the bus serializes it. Every
+// roll_secs the file is closed, every closed file in the directory is uploaded
+// to the bucket, and objects past the retention window are deleted.
+pub struct Archiver {
+    config: ArchiveConfig,
+    store: Box<dyn ObjectStore>,
+    prefix: ObjectPath,
+    current: Option<(PathBuf, GzEncoder<BufWriter<File>>)>,
+}
+
+impl Archiver {
+    // Credentials come from the environment: the usual AWS_* variables for
+    // s3:// URLs, GOOGLE_SERVICE_ACCOUNT / GOOGLE_APPLICATION_CREDENTIALS for gs://
+    pub fn new(config: ArchiveConfig) -> anyhow::Result<Self> {
+        let Some((scheme, location)) = config.url.split_once("://") else {
+            return Err(anyhow::anyhow!("Invalid [archive] url {}: expected s3://bucket/prefix or gs://bucket/prefix", config.url));
+        };
+        let store: Box<dyn ObjectStore> = match scheme {
+            "s3" => Box::new(object_store::aws::AmazonS3Builder::from_env().with_url(config.url.as_str()).build()?),
+            "gs" => Box::new(object_store::gcp::GoogleCloudStorageBuilder::from_env().with_url(config.url.as_str()).build()?),
+            scheme => return Err(anyhow::anyhow!("Unsupported [archive] url scheme {}: expected s3:// or gs://", scheme)),
+        };
+        // Everything after the bucket is the object prefix
+        let prefix = ObjectPath::from(location.split_once('/').map(|(_, prefix)| prefix).unwrap_or("").trim_matches('/'));
+        std::fs::create_dir_all(&config.dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create archive directory {}: {}", config.dir.display(), e))?;
+
+        Ok(Self {
+            config,
+            store,
+            prefix,
+            current: None,
+        })
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, shutdown: CancellationToken) {
+        let mut roll = tokio::time::interval(tokio::time::Duration::from_secs(self.config.roll_secs.max(1)));
+        roll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(url = %self.config.url, roll_secs = %self.config.roll_secs, dir = %self.config.dir.display(), "archiving market events");
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) => self.write(&event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "archiver lagged, events missing from the archive");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                // The first tick fires at once, uploading files left over from a previous run
+                _ = roll.tick() => {
+                    if let Err(e) = self.close_current() {
+                        tracing::error!(error = %e, "failed to close archive file");
+                    }
+                    self.upload_closed().await;
+                    self.apply_retention().await;
+                }
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        // The monitors have stopped by now; what they published last still goes in the file
+        while let Ok(event) = rx.try_recv() {
+            self.write(&event);
+        }
+        if let Err(e) = self.close_current() {
+            tracing::error!(error = %e, "failed to close archive file");
+        }
+    }
+
+    fn write(&mut self, event: &MarketEvent) {
+        if let Err(e) = self.append(event) {
+            tracing::error!(error = %e, "failed to write archive file");
+            // Start over in a fresh file rather than keep writing to a broken one
+            self.current = None;
+        }
+    }
+
+    fn append(&mut self, event: &MarketEvent) -> anyhow::Result<()> {
+        if self.current.is_none() {
+            let path = self.config.dir.join(format!("events-{}{}{}", unix_now(), FILE_SUFFIX, PARTIAL_SUFFIX));
+            let file = File::create(&path).map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+            self.current = Some((path, GzEncoder::new(BufWriter::new(file), Compression::default())));
+        }
+        let (_, writer) = self.current.as_mut().unwrap();
+        serde_json::to_writer(&mut *writer, event)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    // Finish the file being written and drop its partial suffix, making it
+    // eligible for upload
+    fn close_current(&mut self) -> anyhow::Result<()> {
+        let Some((path, writer)) = self.current.take() else {
+            return Ok(());
+        };
+        writer.finish()?.flush()?;
+        let closed = path.with_file_name(path.file_name().unwrap().to_string_lossy().trim_end_matches(PARTIAL_SUFFIX).to_string());
+        std::fs::rename(&path, &closed)?;
+        Ok(())
+    }
+
+    // Upload every closed file in the directory, oldest first. Files that fail
+    // stay on disk and are retried at the next roll.
+    async fn upload_closed(&self) {
+        let mut files = match closed_files(&self.config.dir) {
+            Ok(files) => files,
+            Err(e) => {
+                tracing::error!(error = %e, dir = %self.config.dir.display(), "failed to list archive directory");
+                return;
+            }
+        };
+        files.sort();
+
+        for path in files {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            let location = self.prefix.child(name.as_str());
+            let uploaded = match std::fs::read(&path) {
+                Ok(data) => self.store.put(&location, PutPayload::from(data)).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            match uploaded {
+                Ok(_) => {
+                    tracing::info!(name = %name, location = %location, "archived file");
+                    let done = if self.config.keep_local {
+                        let uploaded = self.config.dir.join(UPLOADED_DIR);
+                        std::fs::create_dir_all(&uploaded).and_then(|_| std::fs::rename(&path, uploaded.join(&name)))
+                    } else {
+                        std::fs::remove_file(&path)
+                    };
+                    if let Err(e) = done {
+                        tracing::warn!(error = %e, path = %path.display(), "failed to move archived file out of the upload queue");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, name = %name, location = %location, "failed to upload, retrying at the next roll");
+                    // Later files would most likely fail the same way
+                    break;
+                }
+            }
+        }
+    }
+
+    // Delete archived objects past retention_days, and uploaded files kept on
+    // disk past the same window
+    async fn apply_retention(&self) {
+        if self.config.retention_days == 0 {
+            return;
+        }
+        let cutoff = unix_now().saturating_sub(self.config.retention_days * 86_400);
+
+        let expired: Vec<ObjectPath> = match self
+            .store
+            .list(Some(&self.prefix))
+            // Only our own files, in case the prefix is shared
+            .try_filter(|meta| {
+                let ours = meta.location.filename().is_some_and(|name| name.starts_with("events-") && name.ends_with(FILE_SUFFIX));
+                std::future::ready(ours && meta.last_modified.timestamp() < cutoff as i64)
+            })
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await
+        {
+            Ok(expired) => expired,
+            Err(e) => {
+                tracing::warn!(error = %e, prefix = %self.prefix, "failed to list archived objects");
+                return;
+            }
+        };
+        for location in expired {
+            match self.store.delete(&location).await {
+                Ok(()) => tracing::info!(location = %location, "deleted expired archive"),
+                Err(e) => tracing::warn!(error = %e, location = %location, "failed to delete expired archive"),
+            }
+        }
+
+        if self.config.keep_local {
+            for path in closed_files(&self.config.dir.join(UPLOADED_DIR)).unwrap_or_default() {
+                let modified = std::fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok());
+                if modified.is_some_and(|modified| modified.as_secs() < cutoff) {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+    }
+}
+
+// Finished archive files in `dir`, leaving out the one being written
+fn closed_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name().is_some_and(|name| name.to_string_lossy().ends_with(FILE_SUFFIX)) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
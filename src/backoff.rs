@@ -0,0 +1,44 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+// Exponential backoff for a monitor's failing fetches: 2s, 4s, 8s ... up to a
+// minute, with jitter so monitors sharing a rate-limited endpoint don't retry
+// in lockstep
+#[derive(Debug, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    // Record another failure and return how long to wait before retrying. A
+    // Retry-After from the provider is a floor for the delay, but never past
+    // MAX_DELAY: the monitor doesn't beat its heartbeat while it sleeps, and a
+    // longer wait would get it restarted as stalled.
+    pub fn next_delay(&mut self, retry_after: Option<Duration>) -> Duration {
+        let exponential = BASE_DELAY.saturating_mul(1 << self.failures.min(16)).min(MAX_DELAY);
+        self.failures += 1;
+
+        // Anywhere between half and the full delay
+        let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        let delay = exponential.mul_f64(0.5 + jitter / 2.0);
+
+        delay.max(retry_after.unwrap_or_default()).min(MAX_DELAY)
+    }
+
+    // Back to normal after a success; true if it had been backing off
+    pub fn reset(&mut self) -> bool {
+        std::mem::take(&mut self.failures) > 0
+    }
+}
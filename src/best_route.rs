@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::aggregate;
+use crate::clock::unix_now;
+use crate::config::BestRouteConfig;
+use crate::events::MarketEvent;
+use crate::quote;
+use crate::raydium::PriceUpdate;
+use crate::routing::Side;
+use crate::rpc::RpcProvider;
+
+// Routing hint: the venue with the best fill for one trade size and direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestRoute {
+    pub symbol: String,
+    pub side: Side,
+    pub input_token: String,
+    pub output_token: String,
+    pub source: String,
+    pub pool: String,
+    pub amount_in: f64,
+    pub amount_out: f64,
+    pub execution_price: f64,
+    pub slippage_bps: f64,       // Execution vs. mid, fee included
+    pub runner_up: Option<String>,
+    pub runner_up_bps: Option<f64>,  // How much worse the second-best venue's execution price is
+    pub timestamp: u64,
+}
+
+// Keep the latest update per pool from the bus and every interval quote each
+// configured size both ways on every symbol, publishing the winner back on the bus
+pub async fn run(
+    config: BestRouteConfig,
+    mut rx: broadcast::Receiver<MarketEvent>,
+    tx: Arc<broadcast::Sender<MarketEvent>>,
+    rpc: RpcProvider,
+) {
+    let rpc_client = rpc.client(CommitmentConfig::confirmed());
+    let mut latest: HashMap<String, PriceUpdate> = HashMap::new();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(MarketEvent::Price(update)) if !aggregate::is_aggregate(&update) => {
+                    latest.insert(update.pool.clone(), update);
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {
+                let symbols: BTreeSet<String> = latest.values().map(|update| update.symbol.clone()).collect();
+                for symbol in symbols {
+                    for &size in &config.sizes {
+                        for side in [Side::Sell, Side::Buy] {
+                            let venues = latest.values().cloned().collect();
+                            let Some(quote) = quote::quote(&rpc_client, venues, &symbol, side, size).await else {
+                                continue;
+                            };
+                            if let Some(route) = best_route(&quote) {
+                                let _ = tx.send(MarketEvent::Route(route));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Quotes come best first; only venues that fill the whole size compete
+fn best_route(quote: &quote::Quote) -> Option<BestRoute> {
+    let mut full = quote.venues.iter().filter(|venue| venue.filled >= quote.size);
+    let best = full.next()?;
+    let runner_up = full.next();
+
+    let (base, quote_token) = quote.symbol.split_once('/').unwrap_or((quote.symbol.as_str(), ""));
+    let (input_token, output_token, amount_in, amount_out) = match quote.side {
+        Side::Sell => (base, quote_token, quote.size, best.quote_amount),
+        Side::Buy => (quote_token, base, best.quote_amount, quote.size),
+    };
+
+    Some(BestRoute {
+        symbol: quote.symbol.clone(),
+        side: quote.side,
+        input_token: input_token.to_string(),
+        output_token: output_token.to_string(),
+        source: best.source.clone(),
+        pool: best.pool.clone(),
+        amount_in,
+        amount_out,
+        execution_price: best.execution_price,
+        slippage_bps: best.slippage_bps,
+        runner_up: runner_up.map(|venue| venue.source.clone()),
+        runner_up_bps: runner_up.map(|venue| (venue.execution_price - best.execution_price).abs() / best.execution_price * 10_000.0),
+        timestamp: unix_now(),
+    })
+}
@@ -0,0 +1,28 @@
+// Best level of each side of an order book: price in UI units and the size
+// resting at it, in base lots of the market (only comparable across sides)
+#[derive(Debug, Clone, Copy)]
+pub struct Level {
+    pub price: f64,
+    pub size: u64,
+}
+
+// Short-horizon signals off the top of the book, set on CLOB price updates
+#[derive(Debug, Clone, Copy)]
+pub struct BookPressure {
+    // Mid weighted towards the thinner side, where the next trade is likelier
+    // to move the price: (bid * ask_size + ask * bid_size) / (bid_size + ask_size)
+    pub microprice: f64,
+    // (bid_size - ask_size) / (bid_size + ask_size), from -1 (all asks) to 1 (all bids)
+    pub imbalance: f64,
+}
+
+pub fn pressure(bid: Level, ask: Level) -> Option<BookPressure> {
+    let total = bid.size as f64 + ask.size as f64;
+    if total <= 0.0 {
+        return None;
+    }
+    Some(BookPressure {
+        microprice: (bid.price * ask.size as f64 + ask.price * bid.size as f64) / total,
+        imbalance: (bid.size as f64 - ask.size as f64) / total,
+    })
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+
+use crate::config::{Backend, Cluster, Config};
+use crate::monitor;
+use crate::schema;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolCapability {
+    pub symbol: String,
+    pub address: String,
+}
+
+// A monitored source, named as in the `source` of its price updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceCapability {
+    pub source: String,
+    pub kind: String,
+    pub backend: Backend,
+    pub pools: Vec<PoolCapability>,
+}
+
+// What this instance provides, from the configuration it runs with after
+// --pair/--sources and pool migrations. Served at GET /capabilities and
+// printed by --print-capabilities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub version: String,
+    pub schema_version: u32,
+    pub cluster: Cluster,
+    pub sources: Vec<SourceCapability>,
+    // Every symbol quoted by at least one source
+    pub pairs: Vec<String>,
+    // Where market events go besides the console
+    pub sinks: Vec<String>,
+    // Config sections that are switched on
+    pub features: Vec<String>,
+    // Cargo features compiled in
+    pub build_features: Vec<String>,
+    pub api: Option<SocketAddr>,
+    pub metrics: Option<SocketAddr>,
+}
+
+pub fn from_config(config: &Config) -> Capabilities {
+    let sources: Vec<SourceCapability> = monitor::sources(config)
+        .into_iter()
+        .filter(|source| !source.dex.pools.is_empty())
+        .map(|source| SourceCapability {
+            source: source.name.to_string(),
+            kind: source.kind.to_string(),
+            backend: source.dex.backend,
+            pools: source.dex.pools.iter().map(|pool| PoolCapability { symbol: pool.symbol.clone(), address: pool.address.clone() }).collect(),
+        })
+        .collect();
+    let pairs: BTreeSet<String> = sources.iter().flat_map(|source| source.pools.iter().map(|pool| pool.symbol.clone())).collect();
+
+    let mut sinks = Vec::new();
+    if config.redis.enabled {
+        sinks.push("redis");
+    }
+    if cfg!(feature = "kinesis") && config.kinesis.enabled {
+        sinks.push("kinesis");
+    }
+    if cfg!(feature = "archive") && config.archive.enabled {
+        sinks.push("archive");
+    }
+    if config.dataset.enabled {
+        sinks.push("dataset");
+    }
+    if config.event_log.enabled {
+        sinks.push("event_log");
+    }
+    if std::env::var("DEX_WATCHER_SHM_PATH").is_ok() {
+        sinks.push("shm");
+    }
+    if cfg!(feature = "iceoryx2") && std::env::var("DEX_WATCHER_IPC_SERVICE").is_ok() {
+        sinks.push("iceoryx2");
+    }
+
+    let features = [
+        ("arbitrage", config.arbitrage.enabled),
+        ("alerts", config.alerts.enabled),
+        ("heatmap", config.heatmap.enabled),
+        ("depth", config.depth.enabled),
+        ("best_route", config.best_route.enabled),
+        ("jupiter", config.jupiter.enabled),
+        ("grafana", config.grafana.enabled),
+        ("wallets", config.wallets.enabled),
+        ("swaps", config.swaps.enabled),
+        ("holders", config.holders.enabled),
+        ("crosscheck", config.crosscheck.enabled),
+        ("upgrades", config.upgrades.enabled),
+        ("derived", !config.derived.is_empty()),
+        ("tvl", config.tvl.summary_secs > 0),
+        ("aggregate", config.aggregate.enabled),
+        ("twap", config.twap.enabled),
+        ("stats", config.stats.enabled),
+        ("sanity", config.sanity.enabled),
+        ("dedup", config.dedup.enabled),
+        ("checkpoint", config.checkpoint.interval_secs > 0),
+        ("deadman", !config.deadman.urls.is_empty()),
+        ("sla", config.sla.enabled),
+    ];
+    let build_features = [
+        ("geyser", cfg!(feature = "geyser")),
+        ("kinesis", cfg!(feature = "kinesis")),
+        ("archive", cfg!(feature = "archive")),
+        ("duckdb", cfg!(feature = "duckdb")),
+        ("parquet", cfg!(feature = "parquet")),
+        ("iceoryx2", cfg!(feature = "iceoryx2")),
+    ];
+    let enabled = |flags: &[(&str, bool)]| flags.iter().filter(|(_, on)| *on).map(|(name, _)| name.to_string()).collect();
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: schema::LATEST,
+        cluster: config.cluster,
+        sources,
+        pairs: pairs.into_iter().collect(),
+        sinks: sinks.into_iter().map(String::from).collect(),
+        features: enabled(&features),
+        build_features: enabled(&build_features),
+        api: config.api.enabled.then_some(config.api.listen),
+        metrics: config.metrics.enabled.then_some(config.metrics.listen),
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::Hasher;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::aggregate;
+use crate::clock::unix_now;
+use crate::events::MarketEvent;
+
+// Checksum over the feeds of one source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceChecksum {
+    pub feeds: usize,
+    pub checksum: String,
+}
+
+// Reconciliation checkpoint: a checksum over the latest (slot, price) of every
+// feed. Two instances, or a consumer replaying the stream, that agree on it
+// have the same view; the per-source checksums narrow down where they don't.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub timestamp: u64,
+    pub feeds: usize,
+    pub checksum: String,
+    pub sources: BTreeMap<String, SourceChecksum>,
+}
+
+// Every interval_secs, on multiples of it on the Unix clock so redundant
+// instances checkpoint at the same moments, publish a Checkpoint of the
+// latest price of every pool. The aggregate source is left out; it depends
+// on when each instance computed it.
+pub async fn run(interval_secs: u64, mut rx: broadcast::Receiver<MarketEvent>, tx: Arc<broadcast::Sender<MarketEvent>>) {
+    let interval_secs = interval_secs.max(1);
+    // (source, pool) -> (slot, price), sorted as the checksum reads them
+    let mut latest: BTreeMap<(String, String), (u64, f64)> = BTreeMap::new();
+
+    loop {
+        let next = tokio::time::sleep(Duration::from_secs(interval_secs - unix_now() % interval_secs));
+        tokio::pin!(next);
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) if !aggregate::is_aggregate(&update) => {
+                        latest.insert((update.source, update.pool), (update.slot, update.price));
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "checkpoints lagged, skipped updates");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                _ = &mut next => break,
+            }
+        }
+        if !latest.is_empty() {
+            let _ = tx.send(MarketEvent::Checkpoint(checkpoint(&latest, unix_now())));
+        }
+    }
+}
+
+// SHA-256 over one line per feed, `<source>\t<pool>\t<slot>\t<price>\n`, in
+// (source, pool) order, with the price written as JSON writes it. The overall
+// checksum covers every feed; each source's covers just its own.
+pub fn checkpoint(latest: &BTreeMap<(String, String), (u64, f64)>, timestamp: u64) -> Checkpoint {
+    let mut all = Hasher::default();
+    let mut by_source: BTreeMap<&str, (usize, Hasher)> = BTreeMap::new();
+    for ((source, pool), (slot, price)) in latest {
+        let price = serde_json::Number::from_f64(*price).map(|price| price.to_string()).unwrap_or_default();
+        let line = format!("{}\t{}\t{}\t{}\n", source, pool, slot, price);
+        all.hash(line.as_bytes());
+        let (feeds, hasher) = by_source.entry(source.as_str()).or_default();
+        *feeds += 1;
+        hasher.hash(line.as_bytes());
+    }
+
+    Checkpoint {
+        timestamp,
+        feeds: latest.len(),
+        checksum: hex::encode(all.result().to_bytes()),
+        sources: by_source
+            .into_iter()
+            .map(|(source, (feeds, hasher))| (source.to_string(), SourceChecksum { feeds, checksum: hex::encode(hasher.result().to_bytes()) }))
+            .collect(),
+    }
+}
@@ -0,0 +1,306 @@
+use clap::{Parser, Subcommand};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::config::{Config, LogFormat, OutputFormat, PoolConfig, PoolStatus};
+use crate::display;
+use crate::events::MarketEvent;
+use crate::exit::{self, ExitReason};
+use crate::raydium::PriceUpdate;
+use crate::record::RecordCommand;
+use crate::repl::ReplArgs;
+use crate::simulate::SimulateCommand;
+
+// How long `snapshot` waits for every pool's first price
+const SNAPSHOT_TIMEOUT_SECS: u64 = 30;
+
+// The command line. The output flags are global so they can go before or
+// after the command, e.g. `dex-watcher snapshot --pair SOL/USDC --ascii`.
+#[derive(Debug, Parser)]
+#[command(name = "dex-watcher", about = "Watch Solana DEX pool prices")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(long, global = true, value_delimiter = ',', help = "Only pools with this symbol (repeatable, or comma separated)")]
+    pub pair: Vec<String>,
+    #[arg(long, global = true, value_delimiter = ',', help = "Only these config sections, e.g. raydium,orca")]
+    pub sources: Vec<String>,
+    #[arg(long, global = true, value_enum, help = "Market events as text or JSON lines on stdout")]
+    pub output: Option<OutputFormat>,
+    #[arg(long, global = true, help = "Strip emoji from the output")]
+    pub no_emoji: bool,
+    #[arg(long, global = true, help = "ASCII-only output")]
+    pub ascii: bool,
+    #[arg(long, global = true, help = "Number formatting locale, e.g. de-DE, or auto")]
+    pub locale: Option<String>,
+    #[arg(long, global = true, value_enum, help = "Log lines as text or JSON")]
+    pub log_format: Option<LogFormat>,
+    #[arg(long, global = true, help = "Live dashboard in place of the scrolling output")]
+    pub tui: bool,
+    #[arg(long, global = true, help = "Print what this instance would provide as JSON and exit")]
+    pub print_capabilities: bool,
+}
+
+impl Cli {
+    // --help prints the usage and exits 0; anything else clap rejects is a bad
+    // command line, reported like a config error
+    pub fn from_args() -> Self {
+        match Cli::try_parse() {
+            Ok(cli) => cli,
+            Err(e) if !e.use_stderr() => e.exit(),
+            Err(e) => {
+                let _ = e.print();
+                let message = e.to_string();
+                exit::exit(ExitReason::ConfigError, message.lines().next().unwrap_or_default());
+            }
+        }
+    }
+
+    // No command means `watch`
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or(Command::Watch)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum Command {
+    #[command(about = "Run the watcher (the default)")]
+    Watch,
+    #[command(about = "Fetch every pool once, print a comparison table and exit")]
+    Snapshot,
+    #[command(about = "Print the configured pools without connecting")]
+    ListPools,
+    #[command(about = "Load and check the config, then exit")]
+    ValidateConfig,
+    #[command(about = "Capture a session bundle while watching")]
+    Record {
+        #[command(subcommand)]
+        command: RecordCommand,
+    },
+    #[command(about = "Run one SQL query over the local history and exit")]
+    Sql {
+        #[arg(help = "e.g. \"SELECT source, avg(price) FROM prices GROUP BY source\"")]
+        query: String,
+    },
+    #[command(about = "Replay the local history against a hypothetical position")]
+    Simulate {
+        #[command(subcommand)]
+        command: SimulateCommand,
+    },
+    #[command(about = "Interactive prompt over a running watcher's API")]
+    Repl(ReplArgs),
+}
+
+// Pool lifecycle: migrated pools are dropped and the pool at the end of each
+// chain of migrations is watched under the symbol of the chain's first pool,
+// so series keyed by symbol carry on. A successor that isn't configured is
+// added to the section of the pool it replaces. Deprecated pools stay watched.
+pub fn apply_lifecycle(config: &mut Config) -> anyhow::Result<()> {
+    // migrated pool address -> (its section, the pool)
+    let mut migrated: HashMap<String, (&'static str, PoolConfig)> = HashMap::new();
+    for (name, dex) in config.dexes_mut() {
+        let (moved, kept): (Vec<PoolConfig>, Vec<PoolConfig>) =
+            std::mem::take(&mut dex.pools).into_iter().partition(|pool| pool.status == PoolStatus::Migrated);
+        dex.pools = kept;
+        migrated.extend(moved.into_iter().map(|pool| (pool.address.clone(), (name, pool))));
+    }
+
+    let successors: HashSet<&str> = migrated.values().filter_map(|(_, pool)| pool.migrated_to.as_deref()).collect();
+    let mut firsts: Vec<&PoolConfig> = migrated
+        .values()
+        .map(|(_, pool)| pool)
+        .filter(|pool| !successors.contains(pool.address.as_str()))
+        .collect();
+    firsts.sort_by(|a, b| a.address.cmp(&b.address));
+
+    let mut seen = HashSet::new();
+    for first in firsts {
+        let mut chain = HashSet::from([first.address.as_str()]);
+        let (mut section, mut last) = migrated[&first.address].clone();
+        // Validated to be set on every migrated pool
+        let mut successor = last.migrated_to.clone().unwrap_or_default();
+        while let Some((next_section, next)) = migrated.get(&successor) {
+            if !chain.insert(next.address.as_str()) {
+                return Err(anyhow::anyhow!("Pool migrations loop back to {}", next.address));
+            }
+            (section, last) = (*next_section, next.clone());
+            successor = last.migrated_to.clone().unwrap_or_default();
+        }
+        seen.extend(chain);
+
+        let mut found = false;
+        for (name, dex) in config.dexes_mut() {
+            for pool in dex.pools.iter_mut().filter(|pool| pool.address == successor) {
+                found = true;
+                if pool.symbol != first.symbol {
+                    tracing::info!(dex = %name, pool = %pool.address, symbol = %first.symbol, migrated = %first.address, "pool publishes under the symbol of a migrated pool");
+                    pool.symbol = first.symbol.clone();
+                }
+            }
+        }
+        if !found {
+            let mut pool = PoolConfig::new(&successor, &first.symbol);
+            pool.active_hours = last.active_hours.clone();
+            if let Some((_, dex)) = config.dexes_mut().into_iter().find(|(name, _)| *name == section) {
+                dex.pools.push(pool);
+            }
+        }
+        tracing::info!(symbol = %first.symbol, from = %first.address, to = %successor, "pool migrated");
+    }
+
+    // Pools left over are on a loop with no way in
+    if let Some(address) = migrated.keys().find(|address| !seen.contains(address.as_str())) {
+        return Err(anyhow::anyhow!("Pool migrations loop back to {}", address));
+    }
+
+    for (name, dex) in config.dexes() {
+        for pool in dex.pools.iter().filter(|pool| pool.status == PoolStatus::Deprecated) {
+            tracing::warn!(symbol = %pool.symbol, pool = %pool.address, name = %name, "pool is deprecated");
+        }
+    }
+    Ok(())
+}
+
+// `--pair SYMBOL` (repeatable, or comma separated) and `--sources a,b` narrow
+// the configured pools to the given symbols and config sections, for
+// `watch`, `snapshot` and `list-pools`
+pub fn apply_filters(config: &mut Config, cli: &Cli) -> anyhow::Result<()> {
+    let trimmed = |items: &[String]| -> Vec<String> {
+        items.iter().map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect()
+    };
+    let (pairs, sources) = (trimmed(&cli.pair), trimmed(&cli.sources));
+
+    let known = config.dexes().map(|(name, _)| name);
+    let sources: Vec<String> = sources.iter().map(|source| source.to_lowercase().replace('-', "_")).collect();
+    if let Some(unknown) = sources.iter().find(|source| !known.contains(&source.as_str())) {
+        return Err(anyhow::anyhow!("Unknown source {} (expected one of {})", unknown, known.join(", ")));
+    }
+
+    for (name, dex) in config.dexes_mut() {
+        if !sources.is_empty() && !sources.iter().any(|source| source == name) {
+            dex.pools.clear();
+        }
+        if !pairs.is_empty() {
+            dex.pools.retain(|pool| pairs.iter().any(|pair| pair.eq_ignore_ascii_case(&pool.symbol)));
+        }
+    }
+
+    if config.dexes().iter().all(|(_, dex)| dex.pools.is_empty()) {
+        return Err(anyhow::anyhow!("No configured pool matches --pair {} --sources {}", pairs.join(","), sources.join(",")));
+    }
+    Ok(())
+}
+
+// `list-pools`: every pool the watcher would monitor, by config section
+pub fn list_pools(config: &Config) {
+    for (name, dex) in config.dexes() {
+        for pool in &dex.pools {
+            let hours = pool.active_hours.as_deref().map(|hours| format!(" (active {})", hours)).unwrap_or_default();
+            let status = if pool.status == PoolStatus::Deprecated { " (deprecated)" } else { "" };
+            println!("{}", display::render(format!("{:<13} {:<14} {}{}{}", name, pool.symbol, pool.address, hours, status)));
+        }
+    }
+}
+
+// `validate-config`: Config::load has already checked everything by now
+pub fn validate_config(config: &Config) {
+    let dexes = config.dexes();
+    let pools: usize = dexes.iter().map(|(_, dex)| dex.pools.len()).sum();
+    let sources = dexes.iter().filter(|(_, dex)| !dex.pools.is_empty()).count();
+    println!("{}", display::render(format!("✅ Config is valid: {} pools across {} sources", pools, sources)));
+}
+
+// `snapshot`: wait for the first price of every pool, print them side by side
+// and return. Pools still missing after SNAPSHOT_TIMEOUT_SECS are listed on
+// stderr and make it an error, so a cron job can tell a partial table apart.
+// Without pools (any other command) it never returns.
+pub async fn snapshot(pools: Option<Vec<(String, PoolConfig)>>, mut rx: broadcast::Receiver<MarketEvent>) -> anyhow::Result<()> {
+    let Some(pools) = pools else {
+        return std::future::pending().await;
+    };
+    let mut pending: BTreeMap<(String, String), String> = pools
+        .into_iter()
+        .map(|(source, pool)| ((source, pool.address), pool.symbol))
+        .collect();
+    let mut prices: Vec<PriceUpdate> = Vec::with_capacity(pending.len());
+
+    let timeout = tokio::time::sleep(Duration::from_secs(SNAPSHOT_TIMEOUT_SECS));
+    tokio::pin!(timeout);
+    while !pending.is_empty() {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(MarketEvent::Price(update)) => {
+                    if pending.remove(&(update.source.clone(), update.pool.clone())).is_some() {
+                        prices.push(update);
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = &mut timeout => break,
+        }
+    }
+
+    print_comparison(&mut prices);
+    for ((source, pool), symbol) in &pending {
+        tracing::warn!(source = %source, symbol = %symbol, snapshot_timeout_secs = SNAPSHOT_TIMEOUT_SECS, pool = %pool, "no price within the snapshot timeout");
+    }
+    if pending.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} of {} pools didn't report a price within {}s", pending.len(), pending.len() + prices.len(), SNAPSHOT_TIMEOUT_SECS))
+    }
+}
+
+// One row per pool, grouped by symbol, cheapest first. `vs best` is how much
+// dearer than the cheapest pool of the same symbol it is, in bps.
+fn print_comparison(prices: &mut [PriceUpdate]) {
+    prices.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(a.price.total_cmp(&b.price)));
+    let mut best: BTreeMap<&str, f64> = BTreeMap::new();
+    for update in prices.iter() {
+        best.entry(update.symbol.as_str()).or_insert(update.price);
+    }
+
+    let columns = ["SYMBOL", "SOURCE", "PRICE", "BASE RESERVE", "QUOTE RESERVE", "TVL", "VS BEST", "SLOT", "POOL"];
+    let reserve = |amount: u64, decimals: u8| display::number(amount as f64 / 10f64.powi(decimals as i32), 2);
+    let rows: Vec<[String; 9]> = prices
+        .iter()
+        .map(|update| {
+            let best = best[update.symbol.as_str()];
+            let spread_bps = if best > 0.0 { (update.price / best - 1.0) * 10_000.0 } else { 0.0 };
+            [
+                update.symbol.clone(),
+                update.source.clone(),
+                display::number(update.price, 6),
+                reserve(update.base_reserve, update.base_decimals),
+                reserve(update.quote_reserve, update.quote_decimals),
+                update.tvl_usd.map(|tvl| format!("${}", display::number(tvl, 0))).unwrap_or_else(|| "-".to_string()),
+                if spread_bps == 0.0 { "best".to_string() } else { format!("+{:.1} bps", spread_bps) },
+                update.slot.to_string(),
+                update.pool.clone(),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|index| rows.iter().map(|row| row[index].chars().count()).chain([columns[index].len()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    // The table is the command's output, not log lines
+    println!("{}", line(&columns.map(String::from)));
+    println!("{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("  "));
+    for row in &rows {
+        println!("{}", line(row));
+    }
+}
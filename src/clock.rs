@@ -0,0 +1,18 @@
+// Wall-clock time since the Unix epoch, for event timestamps and windows
+pub fn unix_now() -> u64 {
+    since_epoch().as_secs()
+}
+
+pub fn unix_millis() -> u64 {
+    since_epoch().as_millis() as u64
+}
+
+pub fn unix_nanos() -> u64 {
+    since_epoch().as_nanos() as u64
+}
+
+fn since_epoch() -> std::time::Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+}
@@ -0,0 +1,1611 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::derived::Expr;
+use crate::latency::Stage;
+use crate::schedule::ActiveHours;
+use crate::schema;
+
+// Watcher configuration, loaded from a TOML file:
+//
+//   [[raydium.pools]]
+//   address = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2"
+//   symbol = "SOL/USDC"
+//   active_hours = "13:30-20:00"   # optional, UTC; parked at [schedule] keepalive_secs outside it
+//   status = "migrated"            # optional: "active" (default), "deprecated" or "migrated"
+//   migrated_to = "<new pool>"     # with "migrated": the pool its liquidity moved to
+//
+// The file path comes from DEX_WATCHER_CONFIG, falling back to ./config.toml.
+// Without a file the watcher monitors the SOL/USDC pool on each DEX.
+//
+// A top-level `cluster = "devnet"` (or DEX_WATCHER_CLUSTER) points the watcher
+// at another cluster: [rpc] defaults to its public endpoint, and DEX sections
+// the file doesn't list pools for get the cluster's presets instead of the
+// mainnet defaults. `cluster = "custom"` has no presets or endpoint, so
+// [rpc] endpoints and every pool must be listed.
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoolConfig {
+    pub address: String,
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_hours: Option<String>,
+    #[serde(default, skip_serializing_if = "PoolStatus::is_active")]
+    pub status: PoolStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrated_to: Option<String>,
+}
+
+// Where a pool is in its life. A migrated pool isn't watched; its successor
+// takes over its symbol, so series keyed by symbol carry on across the move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolStatus {
+    #[default]
+    Active,
+    Deprecated,  // Still watched, with a warning at startup until it's migrated or removed
+    Migrated,    // Replaced by migrated_to
+}
+
+impl PoolStatus {
+    fn is_active(&self) -> bool {
+        *self == PoolStatus::Active
+    }
+}
+
+impl PoolConfig {
+    pub fn new(address: &str, symbol: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            symbol: symbol.to_string(),
+            active_hours: None,
+            status: PoolStatus::Active,
+            migrated_to: None,
+        }
+    }
+
+    pub fn pubkey(&self) -> anyhow::Result<Pubkey> {
+        Pubkey::from_str(&self.address)
+            .map_err(|e| anyhow::anyhow!("Invalid pool address {} ({}): {}", self.address, self.symbol, e))
+    }
+
+    pub fn active_hours(&self) -> anyhow::Result<Option<ActiveHours>> {
+        self.active_hours
+            .as_deref()
+            .map(|hours| hours.parse().map_err(|e| anyhow::anyhow!("{} ({})", e, self.symbol)))
+            .transpose()
+    }
+}
+
+// Where a monitor gets its pool accounts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    Rpc,     // Poll getMultipleAccounts every 2 seconds
+    Geyser,  // Account updates pushed over Yellowstone gRPC, see [geyser]
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DexConfig {
+    #[serde(default)]
+    pub pools: Vec<PoolConfig>,
+    #[serde(default)]
+    pub backend: Backend,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cluster {
+    #[default]
+    Mainnet,
+    Devnet,
+    Custom,   // e.g. a local validator with cloned accounts
+}
+
+impl FromStr for Cluster {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "mainnet" => Ok(Cluster::Mainnet),
+            "devnet" => Ok(Cluster::Devnet),
+            "custom" => Ok(Cluster::Custom),
+            _ => Err(anyhow::anyhow!("Unknown cluster {}: expected mainnet, devnet or custom", s)),
+        }
+    }
+}
+
+impl Cluster {
+    // Public RPC endpoint, used unless [rpc] endpoints lists others
+    pub fn endpoint(self) -> Option<&'static str> {
+        match self {
+            Cluster::Mainnet => Some("https://api.mainnet-beta.solana.com"),
+            Cluster::Devnet => Some("https://api.devnet.solana.com"),
+            Cluster::Custom => None,
+        }
+    }
+
+    // Pools a DEX section watches when the config file doesn't list any. Only
+    // DEXes with a maintained devnet deployment and pool have a devnet preset.
+    pub fn pools(self, section: &str) -> Vec<PoolConfig> {
+        let preset: &[(&str, &str)] = match (self, section) {
+            (Cluster::Mainnet, "raydium") => &[("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2", "SOL/USDC")],
+            (Cluster::Mainnet, "orca") => &[("Czfq3xZZDmsdGdUyrNLtRhGc47cXcZtLG4crryfu44zE", "SOL/USDC")],
+            (Cluster::Mainnet, "meteora") => &[("5rCf1DM8LjKTw4YqhnoLcngyZYeNnQqztScTogYHAS6", "SOL/USDC")],
+            // Quoted in devUSDC, the token Orca's devnet pools are set up with
+            (Cluster::Devnet, "orca") => &[("3KBZiL2g8C7tiJ32hTv5v3KM7aK9htpqTw4cTXz1HvPt", "SOL/devUSDC")],
+            // Sponsored feeds live at the same address on both clusters
+            (Cluster::Devnet, "pyth") => &[("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE", "SOL/USD")],
+            _ => &[],
+        };
+        preset.iter().map(|(address, symbol)| PoolConfig::new(address, symbol)).collect()
+    }
+}
+
+// JSON-RPC endpoints shared by every monitor. Requests rotate round-robin and
+// fail over to the next endpoint on rate limits, timeouts and server errors:
+//
+//   [rpc]
+//   endpoints = [
+//       "https://mainnet.helius-rpc.com/?api-key=...",
+//       "https://my-endpoint.solana-mainnet.quiknode.pro/.../",
+//   ]
+//   requests_per_second = 10.0
+//
+// DEX_WATCHER_RPC_URLS (comma separated) replaces the configured list.
+// requests_per_second is one budget for all monitors together; 0 disables it.
+// Websocket subscriptions use the same hosts over wss://.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    pub endpoints: Vec<String>,
+    pub timeout_secs: u64,
+    pub cooldown_secs: u64,            // How long a failing endpoint is skipped, unless it sent Retry-After
+    pub requests_per_second: f64,
+    pub burst: u32,                    // Requests allowed back to back after an idle spell
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Cluster::Mainnet.endpoint().into_iter().map(String::from).collect(),
+            timeout_secs: 30,
+            cooldown_secs: 30,
+            // The public endpoint allows about 100 requests per 10 seconds per IP
+            requests_per_second: 10.0,
+            burst: 20,
+        }
+    }
+}
+
+// Yellowstone gRPC endpoint shared by every monitor with backend = "geyser":
+//
+//   [geyser]
+//   endpoint = "https://my-node.example.com:10000"
+//   x_token = "..."
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GeyserConfig {
+    pub endpoint: String,
+    pub x_token: Option<String>,
+}
+
+// Cross-DEX arbitrage detection:
+//
+//   [arbitrage]
+//   threshold_bps = 10.0
+//   fees_bps = { Raydium = 25.0, Orca = 30.0 }
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ArbitrageConfig {
+    pub enabled: bool,
+    pub threshold_bps: f64,         // Minimum net spread to report
+    pub max_age_secs: u64,          // Ignore quotes older than this
+    pub default_fee_bps: f64,       // Fee assumed for pools that report none and aren't in fees_bps
+    pub fees_bps: HashMap<String, f64>,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_bps: 10.0,
+            max_age_secs: 10,
+            default_fee_bps: 30.0,
+            fees_bps: HashMap::from([("Raydium".to_string(), 25.0)]),
+        }
+    }
+}
+
+impl ArbitrageConfig {
+    // A fee listed for the source wins; otherwise the pool's own fee, which is
+    // dynamic on Meteora DLMM, and the default for pools that report none
+    pub fn fee_bps(&self, source: &str, pool_fee_bps: f64) -> f64 {
+        self.fees_bps
+            .get(source)
+            .copied()
+            .unwrap_or(if pool_fee_bps > 0.0 { pool_fee_bps } else { self.default_fee_bps })
+    }
+}
+
+// Prometheus /metrics endpoint
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub listen: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: SocketAddr::from(([0, 0, 0, 0], 9898)),
+        }
+    }
+}
+
+// HTTP REST API (/prices, /prices/{source}, /spread)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub listen: SocketAddr,
+    pub control_token: Option<String>,  // Bearer token for POST /pause and /resume; unset disables them
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: SocketAddr::from(([0, 0, 0, 0], 8080)),
+            control_token: None,
+        }
+    }
+}
+
+// Market bus output to Redis, one JSON message per event:
+//
+//   [redis]
+//   enabled = true
+//   url = "redis://127.0.0.1:6379"
+//   mode = "stream"
+//   key = "dex-watcher"
+//   maxlen = 100000
+//   group = "bots"   # optional; created with the stream for XREADGROUP consumers
+//
+// pubsub mode PUBLISHes each event on the `key` channel, seen only by clients
+// subscribed at the time. stream mode XADDs it to the stream at `key`, trimmed
+// to about maxlen entries, so consumers can resume from the last ID they read.
+// Price updates are shaped as the given streaming schema version.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedisConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub mode: RedisMode,
+    pub key: String,
+    pub maxlen: u64,
+    pub group: Option<String>,
+    pub schema: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedisMode {
+    Pubsub,  // PUBLISH, fire and forget
+    Stream,  // XADD with MAXLEN, durable up to the cap
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "redis://127.0.0.1:6379".to_string(),
+            mode: RedisMode::Pubsub,
+            key: "dex-watcher".to_string(),
+            maxlen: 100_000,
+            group: None,
+            schema: schema::DEFAULT,
+        }
+    }
+}
+
+// Market bus output to an Amazon Kinesis data stream (requires the kinesis feature):
+//
+//   [kinesis]
+//   enabled = true
+//   stream_name = "dex-watcher"
+//   region = "us-east-1"   # optional; defaults to the AWS environment's
+//   batch_size = 500
+//   flush_ms = 1000
+//
+// Credentials come from the standard AWS chain. Price updates are shaped as
+// the given streaming schema version.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KinesisConfig {
+    pub enabled: bool,
+    pub stream_name: String,
+    pub region: Option<String>,
+    pub batch_size: usize,  // Records per PutRecords call, at most 500
+    pub flush_ms: u64,      // Longest a record waits for its batch to fill
+    pub schema: u32,
+}
+
+impl Default for KinesisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stream_name: String::new(),
+            region: None,
+            batch_size: 500,
+            flush_ms: 1000,
+            schema: schema::DEFAULT,
+        }
+    }
+}
+
+// Rolling archive of the market bus uploaded to S3 or GCS (requires the archive feature):
+//
+//   [archive]
+//   enabled = true
+//   url = "s3://my-bucket/dex-watcher"   # or gs://my-bucket/dex-watcher
+//   dir = "archive"
+//   roll_secs = 3600
+//   retention_days = 90
+//   keep_local = false
+//
+// Credentials come from the environment (AWS_* variables for S3,
+// GOOGLE_APPLICATION_CREDENTIALS for GCS).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub dir: PathBuf,          // Where files are written before upload
+    pub roll_secs: u64,        // How long each file covers
+    pub retention_days: u64,   // Archived objects older than this are deleted; 0 keeps them forever
+    pub keep_local: bool,      // Keep uploaded files on disk (subject to the same retention)
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            dir: PathBuf::from("archive"),
+            roll_secs: 3600,
+            retention_days: 0,
+            keep_local: false,
+        }
+    }
+}
+
+// Price updates appended to local CSV or Parquet files for offline analysis:
+//
+//   [dataset]
+//   enabled = true
+//   format = "csv"      # or "parquet" (requires the parquet feature)
+//   dir = "datasets"
+//   rotate_secs = 3600  # Start a new file at least this often
+//   rotate_mb = 100     # ...or once the file reaches this size; 0 for no limit
+//   gzip = true         # .csv.gz, or GZIP-compressed Parquet pages
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DatasetConfig {
+    pub enabled: bool,
+    pub format: DatasetFormat,
+    pub dir: PathBuf,
+    pub rotate_secs: u64,
+    pub rotate_mb: u64,
+    pub gzip: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    Csv,
+    Parquet,
+}
+
+impl Default for DatasetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: DatasetFormat::Csv,
+            dir: PathBuf::from("datasets"),
+            rotate_secs: 3600,
+            rotate_mb: 100,
+            gzip: true,
+        }
+    }
+}
+
+// Periodic liquidity distribution snapshots for Orca/Meteora pools
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub output_dir: PathBuf,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 300,
+            output_dir: PathBuf::from("heatmaps"),
+        }
+    }
+}
+
+// Liquidity near the current price of Orca/Meteora pools, read from the
+// tick and bin arrays around it:
+//
+//   [depth]
+//   enabled = true
+//   interval_secs = 30
+//   bands_percent = [0.5, 1.0, 2.0]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DepthConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub bands_percent: Vec<f64>,       // Distances from the current price, each side
+}
+
+impl Default for DepthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 30,
+            bands_percent: vec![0.5, 1.0, 2.0],
+        }
+    }
+}
+
+// Periodic best-venue hints for trading bots, quoting each size (in base
+// tokens) both ways on every watched symbol:
+//
+//   [best_route]
+//   enabled = true
+//   interval_secs = 30
+//   sizes = [1.0, 10.0, 100.0]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BestRouteConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub sizes: Vec<f64>,
+}
+
+impl Default for BestRouteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 30,
+            sizes: vec![1.0, 10.0, 100.0],
+        }
+    }
+}
+
+// Jupiter's aggregated route price for one pair and trade size (in base
+// tokens), quoted both ways next to the best single watched pool:
+//
+//   [jupiter]
+//   enabled = true
+//   interval_secs = 30
+//   symbol = "SOL/USDC"
+//   base_mint = "So11111111111111111111111111111111111111112"
+//   quote_mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+//   size = 10.0
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct JupiterConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub api_url: String,
+    pub symbol: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub size: f64,
+    pub slippage_bps: u16,  // Passed on to Jupiter; only limits the route's minimum output
+}
+
+impl Default for JupiterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 30,
+            api_url: "https://lite-api.jup.ag/swap/v1/quote".to_string(),
+            symbol: "SOL/USDC".to_string(),
+            base_mint: "So11111111111111111111111111111111111111112".to_string(),
+            quote_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            size: 10.0,
+            slippage_bps: 50,
+        }
+    }
+}
+
+// Annotations on Grafana dashboards for alerts, pools coming online after
+// warm-up and source outages:
+//
+//   [grafana]
+//   enabled = true
+//   url = "http://localhost:3000"
+//   api_token = "glsa_..."          # service account token allowed to write annotations
+//   dashboard_uid = "dex-watcher"   # unset annotates organization-wide
+//   tags = ["dex-watcher"]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GrafanaConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub api_token: String,
+    pub dashboard_uid: Option<String>,
+    pub tags: Vec<String>,             // Added to every annotation, next to the event's own
+}
+
+impl Default for GrafanaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://localhost:3000".to_string(),
+            api_token: String::new(),
+            dashboard_uid: None,
+            tags: vec!["dex-watcher".to_string()],
+        }
+    }
+}
+
+// Per-venue route scoring for external order routers
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    pub reference_size: f64,          // Trade size in quote tokens used for slippage
+    pub contention_penalty_bps: f64,  // Cost added for a pool that changes every tick
+    pub ema_alpha: f64,               // Smoothing for the rolling components
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            reference_size: 1_000.0,
+            contention_penalty_bps: 5.0,
+            ema_alpha: 0.1,
+        }
+    }
+}
+
+// Price-move and spread alerts, delivered to the configured sinks:
+//
+//   [alerts]
+//   enabled = true
+//   move_threshold_percent = 2.0
+//   oracle_deviation_bps = 100.0
+//   latency_budget_ms = 800               # p95 from chain state to sink write; 0 turns it off
+//   latency_percentile = 95.0
+//   latency_stage_budgets_ms = { rpc = 500 }   # optional, per stage: chain, rpc, decode, sink
+//
+//   [alerts.telegram]
+//   bot_token = "123456:ABC..."
+//   chat_ids = ["-1001234567890"]
+//
+//   [alerts.discord]
+//   webhook_url = "https://discord.com/api/webhooks/..."
+//   mode = "updates"
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub enabled: bool,
+    pub window_secs: u64,              // Window for price-move detection
+    pub move_threshold_percent: f64,
+    pub spread_threshold_bps: f64,
+    pub cooldown_secs: u64,            // Minimum time between repeats of the same alert
+    pub wallet_activity: bool,         // Alert when a watched wallet trades in a watched pool
+    pub stale_after_secs: u64,         // Alert when a pool has not updated for this long
+    pub reconnect_storm_count: usize,  // Reconnects within the storm window that trigger an alert
+    pub reconnect_storm_window_secs: u64,
+    pub oracle_deviation_bps: f64,     // Alert when a USD-quoted pool strays this far from Pyth
+    pub latency_budget_ms: u64,        // Alert when a source's pipeline latency percentile exceeds this; 0 = off
+    pub latency_percentile: f64,
+    pub latency_stage_budgets_ms: HashMap<Stage, u64>,
+    pub telegram: Option<TelegramConfig>,
+    pub discord: Option<DiscordConfig>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 300,
+            move_threshold_percent: 2.0,
+            spread_threshold_bps: 50.0,
+            cooldown_secs: 300,
+            wallet_activity: true,
+            stale_after_secs: 60,
+            reconnect_storm_count: 3,
+            reconnect_storm_window_secs: 300,
+            oracle_deviation_bps: 100.0,
+            latency_budget_ms: 0,
+            latency_percentile: 95.0,
+            latency_stage_budgets_ms: HashMap::new(),
+            telegram: None,
+            discord: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscordMode {
+    Alerts,   // Only alert-level events
+    Updates,  // Alerts plus a throttled digest of every pool's latest price
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+    #[serde(default = "default_discord_mode")]
+    pub mode: DiscordMode,
+    #[serde(default = "default_discord_throttle_secs")]
+    pub throttle_secs: u64,            // Minimum time between price digests in updates mode
+}
+
+fn default_discord_mode() -> DiscordMode {
+    DiscordMode::Alerts
+}
+
+fn default_discord_throttle_secs() -> u64 {
+    60
+}
+
+// Wallets whose swaps and liquidity changes on the monitored DEX programs are reported, e.g.
+//
+//   [wallets]
+//   enabled = true
+//   addresses = ["5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1"]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WalletsConfig {
+    pub enabled: bool,
+    pub addresses: Vec<String>,
+}
+
+impl Default for WalletsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            addresses: Vec::new(),
+        }
+    }
+}
+
+impl WalletsConfig {
+    pub fn pubkeys(&self) -> anyhow::Result<Vec<Pubkey>> {
+        self.addresses
+            .iter()
+            .map(|address| {
+                Pubkey::from_str(address)
+                    .map_err(|e| anyhow::anyhow!("Invalid wallet address {}: {}", address, e))
+            })
+            .collect()
+    }
+}
+
+// Swap decoding for every watched pool over logsSubscribe
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SwapsConfig {
+    pub enabled: bool,
+    pub volume_interval_secs: u64,     // How often rolling per-pool volume is published
+}
+
+impl Default for SwapsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume_interval_secs: 60,
+        }
+    }
+}
+
+// Top-holder concentration of each pool's base and LP mints
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HoldersConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub dominant_share_percent: f64,   // Holders above this share of supply are watched for moves
+    pub move_threshold_percent: f64,   // Balance change of a dominant holder that counts as a move
+}
+
+impl Default for HoldersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 600,
+            dominant_share_percent: 5.0,
+            move_threshold_percent: 1.0,
+        }
+    }
+}
+
+// Periodic check of our pool decoders against the RPC node's jsonParsed view
+// of the vaults and mints they point at, to catch layout drift after upgrades:
+//
+//   [crosscheck]
+//   enabled = true
+//   interval_secs = 600
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CrossCheckConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for CrossCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 600,
+        }
+    }
+}
+
+// Derived series computed from the latest update of each source for a symbol,
+// published on the market bus and to /metrics:
+//
+//   [[derived]]
+//   name = "spread_bps"
+//   symbol = "SOL/USDC"
+//   expr = "(orca.price - raydium.price) / raydium.price * 10000"
+//   max_age_secs = 30   # optional; updates older than this don't count
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DerivedConfig {
+    pub name: String,
+    pub symbol: String,
+    pub expr: String,
+    #[serde(default = "default_derived_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+fn default_derived_max_age_secs() -> u64 {
+    30
+}
+
+// Deployment slot watch on the programs of the monitored DEXes. An upgrade
+// raises a ProgramUpgraded health event and alert:
+//
+//   [upgrades]
+//   enabled = true
+//   interval_secs = 60
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UpgradesConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+impl Default for UpgradesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+        }
+    }
+}
+
+// Restart policy for monitor and sink tasks that panic:
+//
+//   [supervisor]
+//   max_restarts = 5
+//   restart_window_secs = 300
+//   stall_secs = 120
+//   stale_source_secs = 120   # 0 turns the stale-source watchdog off
+//
+// A task panicking more often than that within the window is left dead. The
+// watchdog restarts polling tasks whose loop hasn't iterated for stall_secs,
+// under the same limit, and warns about buses that stop draining. A source
+// publishing no price update for stale_source_secs is reported stale, even
+// when its task is still looping, and fails GET /health until it recovers.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    pub max_restarts: usize,
+    pub restart_window_secs: u64,
+    pub restart_delay_secs: u64,
+    pub stall_secs: u64,               // Keep above the 60s maximum fetch backoff
+    pub watchdog_interval_secs: u64,
+    pub stale_source_secs: u64,        // Keep above [schedule] and [dedup] keepalive_secs
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            restart_window_secs: 300,
+            restart_delay_secs: 5,
+            stall_secs: 120,
+            watchdog_interval_secs: 10,
+            stale_source_secs: 120,
+        }
+    }
+}
+
+// Startup barrier before consumers act on prices:
+//
+//   [warmup]
+//   quorum_percent = 100.0
+//   timeout_secs = 60
+//
+// Arbitrage and price alerts stay quiet until this share of the configured
+// pools has produced a first valid price, then a Ready event is published.
+// Past timeout_secs the missing pools are logged; the barrier keeps waiting.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WarmupConfig {
+    pub quorum_percent: f64,
+    pub timeout_secs: u64,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            quorum_percent: 100.0,
+            timeout_secs: 60,
+        }
+    }
+}
+
+// Polling rate of pools outside their active_hours:
+//
+//   [schedule]
+//   keepalive_secs = 30
+//   skip_unchanged = true
+//
+// Pools inside their window, or without one, are read every 2 seconds.
+// Keep this below [alerts] stale_after_secs so parked pools don't alert.
+// With skip_unchanged, Raydium, Orca and Meteora pools whose pool and vault
+// accounts are byte-for-byte unchanged since the last read are only
+// republished once per keepalive_secs too.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub keepalive_secs: u64,
+    pub skip_unchanged: bool,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_secs: 30,
+            skip_unchanged: true,
+        }
+    }
+}
+
+// Duplicate price updates of every monitor: one with no newer slot, or the
+// same reserves and price as the pool's last update. Unlike skip_unchanged,
+// this goes by the decoded update, so it covers every source:
+//
+//   [dedup]
+//   enabled = true
+//   mode = "suppress"     # or "mark" to publish them with unchanged = true
+//   keepalive_secs = 30   # suppressed pools are still republished this often
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DedupConfig {
+    pub enabled: bool,
+    pub mode: DedupMode,
+    pub keepalive_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupMode {
+    Suppress,  // Held back until the pool's keep-alive is due
+    Mark,      // Published with unchanged = true
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: DedupMode::Suppress,
+            keepalive_secs: 30,
+        }
+    }
+}
+
+// Cross-venue TVL comparison of pools quoted in a USD stable:
+//
+//   [tvl]
+//   summary_secs = 300   # 0 turns the periodic summary off
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TvlConfig {
+    pub summary_secs: u64,
+}
+
+impl Default for TvlConfig {
+    fn default() -> Self {
+        Self { summary_secs: 300 }
+    }
+}
+
+// Cross-venue price per symbol, published as a PriceUpdate from the
+// "Aggregate" source for symbols quoted on at least min_venues venues:
+//
+//   [aggregate]
+//   enabled = true
+//   interval_secs = 5
+//   method = "liquidity_weighted"   # or "median"
+//   max_age_secs = 30               # venue updates older than this don't count
+//   min_venues = 2
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AggregateConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub method: AggregateMethod,
+    pub max_age_secs: u64,
+    pub min_venues: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateMethod {
+    LiquidityWeighted,  // Venue prices weighted by their reserves, valued in the quote token
+    Median,             // Middle venue price; ignores how deep each venue is
+}
+
+impl Default for AggregateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 5,
+            method: AggregateMethod::LiquidityWeighted,
+            max_age_secs: 30,
+            min_venues: 2,
+        }
+    }
+}
+
+// Reconciliation checkpoints: a checksum over every feed's latest slot and
+// price, published on multiples of interval_secs on the Unix clock:
+//
+//   [checkpoint]
+//   interval_secs = 300   # 0 turns checkpoints off
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CheckpointConfig {
+    pub interval_secs: u64,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self { interval_secs: 300 }
+    }
+}
+
+// EMA, standard deviation and realized volatility of every pool over each
+// window, published every interval_secs:
+//
+//   [stats]
+//   enabled = true
+//   windows_secs = [60, 300, 3600]
+//   interval_secs = 10
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    pub enabled: bool,
+    pub windows_secs: Vec<u64>,
+    pub interval_secs: u64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            windows_secs: vec![60, 300, 3600],
+            interval_secs: 10,
+        }
+    }
+}
+
+// Sanity filter on every price a monitor reads: one more than
+// max_deviation_percent off the median of the pool's last `window` prices is
+// published as an anomaly event, instead of the price update or next to it:
+//
+//   [sanity]
+//   enabled = true
+//   max_deviation_percent = 20.0
+//   window = 20
+//   min_samples = 5       # prices a pool needs before its median counts
+//   mode = "reject"       # or "flag" to publish the price anyway
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SanityConfig {
+    pub enabled: bool,
+    pub max_deviation_percent: f64,
+    pub window: usize,
+    pub min_samples: usize,
+    pub mode: SanityMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanityMode {
+    Reject,  // Only the anomaly is published
+    Flag,    // The anomaly, then the price update as usual
+}
+
+impl Default for SanityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_deviation_percent: 20.0,
+            window: 20,
+            min_samples: 5,
+            mode: SanityMode::Reject,
+        }
+    }
+}
+
+// Rolling time-weighted average price of every pool over each window,
+// published every interval_secs:
+//
+//   [twap]
+//   enabled = true
+//   windows_secs = [60, 300, 1800]
+//   interval_secs = 10
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TwapConfig {
+    pub enabled: bool,
+    pub windows_secs: Vec<u64>,
+    pub interval_secs: u64,
+}
+
+impl Default for TwapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            windows_secs: vec![60, 300, 1800],
+            interval_secs: 10,
+        }
+    }
+}
+
+// Dead-man's switch: GET pings to healthchecks.io-style URLs every
+// interval_secs, for as long as price updates keep arriving. Off without URLs.
+//
+//   [deadman]
+//   urls = ["https://hc-ping.com/<uuid>"]
+//   interval_secs = 60
+//   max_silence_secs = 120   # no price update for this long withholds the pings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DeadmanConfig {
+    pub urls: Vec<String>,
+    pub interval_secs: u64,
+    pub max_silence_secs: u64,
+}
+
+impl Default for DeadmanConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            interval_secs: 60,
+            max_silence_secs: 120,
+        }
+    }
+}
+
+// Reward tokens to price incentive APRs by, keyed by mint, on top of the
+// built-in ones (ORCA, SOL, USDC, USDT, mSOL, JitoSOL, BONK). A reward is
+// priced by any watched pool quoting its symbol in a USD stable.
+//
+//   [rewards]
+//   tokens = { "<reward mint>" = "JUP" }   # priced from e.g. a JUP/USDC pool
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RewardsConfig {
+    pub tokens: HashMap<String, String>,
+}
+
+// Per-feed availability: the share of intervals in which each pool produced a
+// fresh price, rolled up per UTC day. Monitors that skip unchanged pools still
+// publish every keepalive_secs, so the interval should be at least that long.
+//
+//   [sla]
+//   enabled = true
+//   interval_secs = 60
+//   target_percent = 99.5   # Feeds below it are listed in the daily report
+//   history_days = 30       # Completed days kept for GET /sla
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SlaConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub target_percent: f64,
+    pub history_days: usize,
+}
+
+impl Default for SlaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            target_percent: 99.5,
+            history_days: 30,
+        }
+    }
+}
+
+// Console output formatting, e.g. for log aggregators that mangle emoji:
+//
+//   [display]
+//   locale = "de-DE"   # or "auto" for LC_NUMERIC / LANG
+//   emoji = false
+//   output = "json"    # market events as JSON lines on stdout, logs on stderr
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    pub locale: Option<String>,        // Thousands separator and decimal mark; unset keeps 1234.5678
+    pub emoji: bool,
+    pub ascii: bool,                   // Transliterate or drop every non-ASCII character
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            emoji: true,
+            ascii: false,
+            output: OutputFormat::Text,
+        }
+    }
+}
+
+// Log output through tracing, for systemd/Kubernetes log pipelines:
+//
+//   [logging]
+//   format = "json"                              # or "text"
+//   filter = "info,price_service::rpc=debug"     # env-filter syntax; RUST_LOG overrides it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    pub filter: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            filter: "info".to_string(),
+        }
+    }
+}
+
+// Operational events as JSON lines with stable names and severities, for
+// SIEM or log pipeline ingestion:
+//
+//   [event_log]
+//   enabled = true
+//   path = "/var/log/dex-watcher/events.jsonl"   # unset writes to stderr
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventLogConfig {
+    pub enabled: bool,
+    pub path: Option<PathBuf>,         // Appended to; unset writes to stderr
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub cluster: Cluster,
+    pub rpc: RpcConfig,
+    pub raydium: DexConfig,
+    pub raydium_clmm: DexConfig,
+    pub orca: DexConfig,
+    pub meteora: DexConfig,
+    pub meteora_amm: DexConfig,
+    pub phoenix: DexConfig,
+    pub openbook: DexConfig,
+    pub lifinity: DexConfig,
+    pub pump_fun: DexConfig,
+    pub pumpswap: DexConfig,
+    pub saber: DexConfig,
+    pub lst: DexConfig,
+    pub pyth: DexConfig,
+    pub arbitrage: ArbitrageConfig,
+    pub metrics: MetricsConfig,
+    pub api: ApiConfig,
+    pub redis: RedisConfig,
+    pub kinesis: KinesisConfig,
+    pub archive: ArchiveConfig,
+    pub dataset: DatasetConfig,
+    pub heatmap: HeatmapConfig,
+    pub depth: DepthConfig,
+    pub routing: RoutingConfig,
+    pub best_route: BestRouteConfig,
+    pub jupiter: JupiterConfig,
+    pub grafana: GrafanaConfig,
+    pub alerts: AlertsConfig,
+    pub wallets: WalletsConfig,
+    pub geyser: GeyserConfig,
+    pub swaps: SwapsConfig,
+    pub holders: HoldersConfig,
+    pub crosscheck: CrossCheckConfig,
+    pub upgrades: UpgradesConfig,
+    pub derived: Vec<DerivedConfig>,
+    pub display: DisplayConfig,
+    pub logging: LoggingConfig,
+    pub event_log: EventLogConfig,
+    pub supervisor: SupervisorConfig,
+    pub warmup: WarmupConfig,
+    pub tvl: TvlConfig,
+    pub aggregate: AggregateConfig,
+    pub deadman: DeadmanConfig,
+    pub twap: TwapConfig,
+    pub checkpoint: CheckpointConfig,
+    pub stats: StatsConfig,
+    pub sanity: SanityConfig,
+    pub dedup: DedupConfig,
+    pub rewards: RewardsConfig,
+    pub sla: SlaConfig,
+    pub schedule: ScheduleConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cluster: Cluster::Mainnet,
+            rpc: RpcConfig::default(),
+            raydium: DexConfig {
+                pools: Cluster::Mainnet.pools("raydium"),
+                backend: Backend::Rpc,
+            },
+            // Opt-in: list pools under [[raydium_clmm.pools]]
+            raydium_clmm: DexConfig::default(),
+            orca: DexConfig {
+                pools: Cluster::Mainnet.pools("orca"),
+                backend: Backend::Rpc,
+            },
+            meteora: DexConfig {
+                pools: Cluster::Mainnet.pools("meteora"),
+                backend: Backend::Rpc,
+            },
+            // Opt-in: list pools under [[meteora_amm.pools]]
+            meteora_amm: DexConfig::default(),
+            // Opt-in: list markets under [[phoenix.pools]]
+            phoenix: DexConfig::default(),
+            // Opt-in: list markets under [[openbook.pools]]
+            openbook: DexConfig::default(),
+            // Opt-in: list pools under [[lifinity.pools]]
+            lifinity: DexConfig::default(),
+            // Opt-in: list bonding curves under [[pump_fun.pools]]
+            pump_fun: DexConfig::default(),
+            // Opt-in: list pools under [[pumpswap.pools]]
+            pumpswap: DexConfig::default(),
+            // Opt-in: list swaps under [[saber.pools]]
+            saber: DexConfig::default(),
+            // Opt-in: list stake pools under [[lst.pools]]
+            lst: DexConfig::default(),
+            // Opt-in: list price feed accounts under [[pyth.pools]]
+            pyth: DexConfig::default(),
+            arbitrage: ArbitrageConfig::default(),
+            metrics: MetricsConfig::default(),
+            api: ApiConfig::default(),
+            redis: RedisConfig::default(),
+            kinesis: KinesisConfig::default(),
+            archive: ArchiveConfig::default(),
+            dataset: DatasetConfig::default(),
+            heatmap: HeatmapConfig::default(),
+            depth: DepthConfig::default(),
+            routing: RoutingConfig::default(),
+            best_route: BestRouteConfig::default(),
+            jupiter: JupiterConfig::default(),
+            grafana: GrafanaConfig::default(),
+            alerts: AlertsConfig::default(),
+            wallets: WalletsConfig::default(),
+            geyser: GeyserConfig::default(),
+            swaps: SwapsConfig::default(),
+            holders: HoldersConfig::default(),
+            crosscheck: CrossCheckConfig::default(),
+            upgrades: UpgradesConfig::default(),
+            derived: Vec::new(),
+            display: DisplayConfig::default(),
+            logging: LoggingConfig::default(),
+            event_log: EventLogConfig::default(),
+            supervisor: SupervisorConfig::default(),
+            warmup: WarmupConfig::default(),
+            tvl: TvlConfig::default(),
+            aggregate: AggregateConfig::default(),
+            deadman: DeadmanConfig::default(),
+            twap: TwapConfig::default(),
+            checkpoint: CheckpointConfig::default(),
+            stats: StatsConfig::default(),
+            sanity: SanityConfig::default(),
+            dedup: DedupConfig::default(),
+            rewards: RewardsConfig::default(),
+            sla: SlaConfig::default(),
+            schedule: ScheduleConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = std::env::var("DEX_WATCHER_CONFIG").ok();
+        let explicit = path.is_some();
+        let path = path.unwrap_or_else(|| "config.toml".to_string());
+
+        // The parsed config, and the file's raw tables to tell listed settings from defaults
+        let (mut config, file) = match std::fs::read_to_string(&path) {
+            Ok(contents) => (
+                toml::from_str::<Config>(&contents)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path, e))?,
+                toml::from_str::<toml::Table>(&contents)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path, e))?,
+            ),
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => (Config::default(), toml::Table::new()),
+            Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path, e)),
+        };
+
+        if let Ok(cluster) = std::env::var("DEX_WATCHER_CLUSTER") {
+            config.cluster = cluster.trim().parse()?;
+        }
+        config.apply_cluster(&file);
+
+        if let Ok(urls) = std::env::var("DEX_WATCHER_RPC_URLS") {
+            config.rpc.endpoints = urls
+                .split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect();
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Config sections by the names --sources takes
+    pub fn dexes(&self) -> [(&'static str, &DexConfig); 13] {
+        [
+            ("raydium", &self.raydium),
+            ("raydium_clmm", &self.raydium_clmm),
+            ("orca", &self.orca),
+            ("meteora", &self.meteora),
+            ("meteora_amm", &self.meteora_amm),
+            ("phoenix", &self.phoenix),
+            ("openbook", &self.openbook),
+            ("lifinity", &self.lifinity),
+            ("pump_fun", &self.pump_fun),
+            ("pumpswap", &self.pumpswap),
+            ("saber", &self.saber),
+            ("lst", &self.lst),
+            ("pyth", &self.pyth),
+        ]
+    }
+
+    pub fn dexes_mut(&mut self) -> [(&'static str, &mut DexConfig); 13] {
+        [
+            ("raydium", &mut self.raydium),
+            ("raydium_clmm", &mut self.raydium_clmm),
+            ("orca", &mut self.orca),
+            ("meteora", &mut self.meteora),
+            ("meteora_amm", &mut self.meteora_amm),
+            ("phoenix", &mut self.phoenix),
+            ("openbook", &mut self.openbook),
+            ("lifinity", &mut self.lifinity),
+            ("pump_fun", &mut self.pump_fun),
+            ("pumpswap", &mut self.pumpswap),
+            ("saber", &mut self.saber),
+            ("lst", &mut self.lst),
+            ("pyth", &mut self.pyth),
+        ]
+    }
+
+    // Replaces the mainnet defaults the file didn't override with the
+    // cluster's endpoint and pool presets
+    fn apply_cluster(&mut self, file: &toml::Table) {
+        let cluster = self.cluster;
+        if cluster == Cluster::Mainnet {
+            return;
+        }
+        let listed = |section: &str, key: &str| file.get(section).and_then(|table| table.get(key)).is_some();
+
+        if !listed("rpc", "endpoints") {
+            self.rpc.endpoints = cluster.endpoint().into_iter().map(String::from).collect();
+        }
+        for (section, dex) in self.dexes_mut() {
+            if !listed(section, "pools") {
+                dex.pools = cluster.pools(section);
+            }
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let pools = self.raydium.pools.iter()
+            .chain(&self.raydium_clmm.pools)
+            .chain(&self.orca.pools)
+            .chain(&self.meteora.pools)
+            .chain(&self.meteora_amm.pools)
+            .chain(&self.phoenix.pools)
+            .chain(&self.openbook.pools)
+            .chain(&self.lifinity.pools)
+            .chain(&self.pump_fun.pools)
+            .chain(&self.pumpswap.pools)
+            .chain(&self.saber.pools)
+            .chain(&self.lst.pools)
+            .chain(&self.pyth.pools);
+        for pool in pools {
+            pool.pubkey()?;
+            pool.active_hours()?;
+            match (pool.status, &pool.migrated_to) {
+                (PoolStatus::Migrated, None) => {
+                    return Err(anyhow::anyhow!("Pool {} ({}) is migrated but has no migrated_to", pool.address, pool.symbol));
+                }
+                (PoolStatus::Migrated, Some(successor)) => {
+                    Pubkey::from_str(successor)
+                        .map_err(|e| anyhow::anyhow!("Invalid migrated_to {} ({}): {}", successor, pool.symbol, e))?;
+                }
+                (_, Some(_)) => {
+                    return Err(anyhow::anyhow!("Pool {} ({}) has migrated_to but isn't status = \"migrated\"", pool.address, pool.symbol));
+                }
+                (_, None) => {}
+            }
+        }
+        self.wallets.pubkeys()?;
+
+        if self.rpc.endpoints.is_empty() && self.cluster == Cluster::Custom {
+            return Err(anyhow::anyhow!("cluster = \"custom\" requires [rpc] endpoints or DEX_WATCHER_RPC_URLS"));
+        }
+        if self.rpc.endpoints.is_empty() {
+            return Err(anyhow::anyhow!("[rpc] endpoints must list at least one URL"));
+        }
+        if self.rpc.requests_per_second.is_nan() || self.rpc.requests_per_second < 0.0 {
+            return Err(anyhow::anyhow!("[rpc] requests_per_second must not be negative"));
+        }
+        if let Some(url) = self.rpc.endpoints.iter().find(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+            return Err(anyhow::anyhow!("Invalid RPC endpoint {}: expected an http(s) URL", url));
+        }
+        let quorum = self.warmup.quorum_percent;
+        if quorum.is_nan() || quorum <= 0.0 || quorum > 100.0 {
+            return Err(anyhow::anyhow!("[warmup] quorum_percent must be above 0 and at most 100"));
+        }
+        if self.best_route.sizes.iter().any(|size| size.is_nan() || *size <= 0.0) {
+            return Err(anyhow::anyhow!("[best_route] sizes must all be positive"));
+        }
+        if self.jupiter.enabled && (self.jupiter.size.is_nan() || self.jupiter.size <= 0.0) {
+            return Err(anyhow::anyhow!("[jupiter] size must be positive"));
+        }
+        if self.dataset.enabled && self.dataset.format == DatasetFormat::Parquet && !cfg!(feature = "parquet") {
+            return Err(anyhow::anyhow!("[dataset] format = \"parquet\" requires building with --features parquet"));
+        }
+        if self.grafana.enabled && self.grafana.api_token.is_empty() {
+            return Err(anyhow::anyhow!("[grafana] requires api_token"));
+        }
+        for series in &self.derived {
+            Expr::parse(&series.expr)
+                .map_err(|e| anyhow::anyhow!("[[derived]] {}: {}", series.name, e))?;
+        }
+        if !schema::supported(self.redis.schema) {
+            return Err(anyhow::anyhow!("[redis] schema must be between 1 and {}", schema::LATEST));
+        }
+        if self.redis.mode == RedisMode::Stream && self.redis.maxlen == 0 {
+            return Err(anyhow::anyhow!("[redis] maxlen must be positive"));
+        }
+        if self.kinesis.enabled {
+            if !cfg!(feature = "kinesis") {
+                return Err(anyhow::anyhow!("[kinesis] requires building with --features kinesis"));
+            }
+            if self.kinesis.stream_name.is_empty() {
+                return Err(anyhow::anyhow!("[kinesis] requires stream_name"));
+            }
+            if !schema::supported(self.kinesis.schema) {
+                return Err(anyhow::anyhow!("[kinesis] schema must be between 1 and {}", schema::LATEST));
+            }
+        }
+        if self.archive.enabled {
+            if !cfg!(feature = "archive") {
+                return Err(anyhow::anyhow!("[archive] requires building with --features archive"));
+            }
+            if !self.archive.url.starts_with("s3://") && !self.archive.url.starts_with("gs://") {
+                return Err(anyhow::anyhow!("[archive] url must be s3://bucket/prefix or gs://bucket/prefix"));
+            }
+        }
+        if self.depth.bands_percent.iter().any(|band| band.is_nan() || *band <= 0.0 || *band >= 100.0) {
+            return Err(anyhow::anyhow!("[depth] bands_percent must each be above 0 and below 100"));
+        }
+        if self.twap.enabled && (self.twap.windows_secs.is_empty() || self.twap.windows_secs.contains(&0)) {
+            return Err(anyhow::anyhow!("[twap] windows_secs must list at least one window, each above 0"));
+        }
+        if self.stats.enabled && (self.stats.windows_secs.is_empty() || self.stats.windows_secs.contains(&0)) {
+            return Err(anyhow::anyhow!("[stats] windows_secs must list at least one window, each above 0"));
+        }
+        if self.sanity.enabled {
+            if self.sanity.max_deviation_percent.is_nan() || self.sanity.max_deviation_percent <= 0.0 {
+                return Err(anyhow::anyhow!("[sanity] max_deviation_percent must be above 0"));
+            }
+            if self.sanity.min_samples == 0 || self.sanity.min_samples > self.sanity.window {
+                return Err(anyhow::anyhow!("[sanity] min_samples must be between 1 and window"));
+            }
+        }
+        let stale_after = self.supervisor.stale_source_secs;
+        if stale_after > 0 && (stale_after <= self.schedule.keepalive_secs || (self.dedup.enabled && stale_after <= self.dedup.keepalive_secs)) {
+            return Err(anyhow::anyhow!("[supervisor] stale_source_secs must be above the keepalive_secs of [schedule] and [dedup]"));
+        }
+
+        // Book sides live in separate accounts from the market, which is all Geyser would push
+        if self.openbook.backend == Backend::Geyser {
+            return Err(anyhow::anyhow!("[openbook] only supports backend = \"rpc\""));
+        }
+        // Reserves sit in shared vaults that change without the pool account changing
+        if self.meteora_amm.backend == Backend::Geyser {
+            return Err(anyhow::anyhow!("[meteora_amm] only supports backend = \"rpc\""));
+        }
+        // Reserves sit in token accounts that change without the swap account changing
+        if self.saber.backend == Backend::Geyser {
+            return Err(anyhow::anyhow!("[saber] only supports backend = \"rpc\""));
+        }
+        // Rates only change once per epoch, so there is nothing for a stream to add
+        if self.lst.backend == Backend::Geyser {
+            return Err(anyhow::anyhow!("[lst] only supports backend = \"rpc\""));
+        }
+        // Feeds are checked for a stale publish time on every poll, which a stream wouldn't redo
+        if self.pyth.backend == Backend::Geyser {
+            return Err(anyhow::anyhow!("[pyth] only supports backend = \"rpc\""));
+        }
+        // Each poll also reads the program's fee settings, which Geyser wouldn't push with the pools
+        for (section, dex) in [("pump_fun", &self.pump_fun), ("pumpswap", &self.pumpswap)] {
+            if dex.backend == Backend::Geyser {
+                return Err(anyhow::anyhow!("[{}] only supports backend = \"rpc\"", section));
+            }
+        }
+        let geyser_monitors = [&self.raydium, &self.raydium_clmm, &self.orca, &self.meteora, &self.phoenix, &self.lifinity]
+            .iter()
+            .any(|dex| dex.backend == Backend::Geyser);
+        if geyser_monitors {
+            if !cfg!(feature = "geyser") {
+                return Err(anyhow::anyhow!("backend = \"geyser\" requires building with --features geyser"));
+            }
+            if self.geyser.endpoint.is_empty() {
+                return Err(anyhow::anyhow!("backend = \"geyser\" requires [geyser] endpoint"));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_lifinity_amm_v2_decoder::accounts::amm::Amm as LifinityAmm;
+use carbon_meteora_dlmm_decoder::accounts::lb_pair::LbPair;
+use carbon_raydium_amm_v4_decoder::accounts::amm_info::AmmInfo as RaydiumAmmInfo;
+use carbon_raydium_clmm_decoder::accounts::pool_state::PoolState as RaydiumClmmPool;
+use orca_whirlpools_client::Whirlpool;
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountData};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::{CrossCheckConfig, PoolConfig};
+use crate::health::HealthEvent;
+use crate::openbook::MarketHeader as OpenBookMarket;
+use crate::phoenix::MarketHeader;
+use crate::pump::PoolHeader as PumpSwapPool;
+use crate::rpc::RpcProvider;
+use crate::saber::PoolHeader as SaberPool;
+
+// What the monitors take from a pool account with our own decoders
+struct DecodedPool {
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+    base_vault: Pubkey,
+    quote_vault: Pubkey,
+    decimals: Option<(u8, u8)>,  // Only where the pool stores them itself
+}
+
+// One field our decoder and the RPC node's parser disagree on
+struct Mismatch {
+    field: &'static str,
+    decoded: String,
+    parsed: String,
+}
+
+// Every interval, read each pool together with its vaults and mints in one
+// jsonParsed getMultipleAccounts call, so all of them come from the same slot.
+// The pool is decoded with our own layout; the node parses the token accounts
+// itself. Vaults that aren't token accounts of the decoded mints, or decimals
+// that differ, mean the layout has drifted under the decoder.
+pub async fn run(
+    config: CrossCheckConfig,
+    pools: Vec<(String, PoolConfig)>,
+    rpc: RpcProvider,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+) {
+    let rpc_client = rpc.client(CommitmentConfig::confirmed());
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let mut checked = 0;
+        for (source, pool) in &pools {
+            match check_pool(&rpc_client, source, pool).await {
+                Ok((slot, mismatches)) => {
+                    checked += 1;
+                    for mismatch in mismatches {
+                        tracing::warn!(source = %source, symbol = %pool.symbol, slot = slot, field = %mismatch.field, decoded = %mismatch.decoded, parsed = %mismatch.parsed, "decoder mismatch");
+                        let _ = health_tx.send(HealthEvent::decoder_mismatch(
+                            source,
+                            pool,
+                            mismatch.field,
+                            &mismatch.decoded,
+                            &mismatch.parsed,
+                            slot,
+                        ));
+                    }
+                }
+                Err(e) => tracing::warn!(source = %source, symbol = %pool.symbol, error = %e, "decoder self-check failed"),
+            }
+        }
+        if checked == 0 && !pools.is_empty() {
+            tracing::warn!("decoder self-check could not read any pool");
+        }
+    }
+}
+
+async fn check_pool(rpc_client: &RpcClient, source: &str, pool: &PoolConfig) -> anyhow::Result<(u64, Vec<Mismatch>)> {
+    let address = pool.pubkey()?;
+    let raw = rpc_client.get_account_data(&address).await?;
+    let decoded = decode_pool(source, &raw)?;
+
+    let accounts = [address, decoded.base_vault, decoded.quote_vault, decoded.base_mint, decoded.quote_mint];
+    let (slot, accounts) = get_parsed_accounts(rpc_client, &accounts).await?;
+    let [pool_account, base_vault, quote_vault, base_mint, quote_mint] = accounts.as_slice() else {
+        return Err(anyhow::anyhow!("Unexpected getMultipleAccounts response length"));
+    };
+
+    // The pool itself may have changed since the first read; re-decode the copy from this slot
+    let decoded = pool_account
+        .as_ref()
+        .and_then(|account| account.decode::<Account>())
+        .ok_or_else(|| anyhow::anyhow!("Pool account missing from the parsed response"))
+        .and_then(|account| decode_pool(source, &account.data))?;
+
+    let mut mismatches = Vec::new();
+    for (field, vault, mint) in [
+        ("base_vault", base_vault, decoded.base_mint),
+        ("quote_vault", quote_vault, decoded.quote_mint),
+    ] {
+        match parsed_info(vault, "account").and_then(|info| info["mint"].as_str().map(str::to_string)) {
+            Some(parsed_mint) if parsed_mint == mint.to_string() => {}
+            Some(parsed_mint) => mismatches.push(Mismatch {
+                field,
+                decoded: format!("a {} account", mint),
+                parsed: format!("a {} account", parsed_mint),
+            }),
+            None => mismatches.push(Mismatch {
+                field,
+                decoded: format!("a {} account", mint),
+                parsed: "not a token account".to_string(),
+            }),
+        }
+    }
+
+    let parsed_decimals = |mint: &Option<UiAccount>| parsed_info(mint, "mint").and_then(|info| info["decimals"].as_u64());
+    for (field, mint, decimals) in [
+        ("base_mint", base_mint, decoded.decimals.map(|(base, _)| base)),
+        ("quote_mint", quote_mint, decoded.decimals.map(|(_, quote)| quote)),
+    ] {
+        match (parsed_decimals(mint), decimals) {
+            (None, _) => mismatches.push(Mismatch {
+                field,
+                decoded: "a mint".to_string(),
+                parsed: "not a mint".to_string(),
+            }),
+            (Some(parsed), Some(decimals)) if parsed != decimals as u64 => mismatches.push(Mismatch {
+                field,
+                decoded: format!("{} decimals", decimals),
+                parsed: format!("{} decimals", parsed),
+            }),
+            _ => {}
+        }
+    }
+
+    Ok((slot, mismatches))
+}
+
+fn decode_pool(source: &str, data: &[u8]) -> anyhow::Result<DecodedPool> {
+    match source {
+        "Raydium" => {
+            let amm_info = <RaydiumAmmInfo as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium pool data"))?;
+            Ok(DecodedPool {
+                base_mint: amm_info.coin_mint,
+                quote_mint: amm_info.pc_mint,
+                base_vault: amm_info.token_coin,
+                quote_vault: amm_info.token_pc,
+                decimals: Some((amm_info.coin_decimals as u8, amm_info.pc_decimals as u8)),
+            })
+        }
+        "Raydium CLMM" => {
+            let pool_state = <RaydiumClmmPool as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium CLMM pool data"))?;
+            Ok(DecodedPool {
+                base_mint: pool_state.token_mint0,
+                quote_mint: pool_state.token_mint1,
+                base_vault: pool_state.token_vault0,
+                quote_vault: pool_state.token_vault1,
+                decimals: Some((pool_state.mint_decimals0, pool_state.mint_decimals1)),
+            })
+        }
+        "Orca" => {
+            let whirlpool = Whirlpool::from_bytes(data)?;
+            Ok(DecodedPool {
+                base_mint: whirlpool.token_mint_a,
+                quote_mint: whirlpool.token_mint_b,
+                base_vault: whirlpool.token_vault_a,
+                quote_vault: whirlpool.token_vault_b,
+                decimals: None,
+            })
+        }
+        "Meteora" => {
+            let lb_pair = <LbPair as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora DLMM data"))?;
+            Ok(DecodedPool {
+                base_mint: lb_pair.token_x_mint,
+                quote_mint: lb_pair.token_y_mint,
+                base_vault: lb_pair.reserve_x,
+                quote_vault: lb_pair.reserve_y,
+                decimals: None,
+            })
+        }
+        "Phoenix" => {
+            let header = MarketHeader::parse(data)?;
+            Ok(DecodedPool {
+                base_mint: header.base_mint,
+                quote_mint: header.quote_mint,
+                base_vault: header.base_vault,
+                quote_vault: header.quote_vault,
+                decimals: Some((header.base_decimals, header.quote_decimals)),
+            })
+        }
+        "OpenBook" => {
+            let market = OpenBookMarket::parse(data)?;
+            Ok(DecodedPool {
+                base_mint: market.base_mint,
+                quote_mint: market.quote_mint,
+                base_vault: market.base_vault,
+                quote_vault: market.quote_vault,
+                decimals: Some((market.base_decimals, market.quote_decimals)),
+            })
+        }
+        "Lifinity" => {
+            let amm = <LifinityAmm as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Lifinity pool data"))?;
+            Ok(DecodedPool {
+                base_mint: amm.token_a_mint,
+                quote_mint: amm.token_b_mint,
+                base_vault: amm.token_a_account,
+                quote_vault: amm.token_b_account,
+                decimals: None,
+            })
+        }
+        "PumpSwap" => {
+            let pool = PumpSwapPool::parse(data)?;
+            Ok(DecodedPool {
+                base_mint: pool.base_mint,
+                quote_mint: pool.quote_mint,
+                base_vault: pool.base_vault,
+                quote_vault: pool.quote_vault,
+                decimals: None,
+            })
+        }
+        "Saber" => {
+            let pool = SaberPool::parse(data)?;
+            Ok(DecodedPool {
+                base_mint: pool.token_a_mint,
+                quote_mint: pool.token_b_mint,
+                base_vault: pool.token_a_reserves,
+                quote_vault: pool.token_b_reserves,
+                decimals: None,
+            })
+        }
+        _ => Err(anyhow::anyhow!("Unknown source {}", source)),
+    }
+}
+
+// getMultipleAccounts with jsonParsed encoding. The typed client call decodes
+// binary data only, so this goes through the raw request. Accounts the node has
+// no parser for (the pools) come back base64 encoded.
+async fn get_parsed_accounts(rpc_client: &RpcClient, addresses: &[Pubkey]) -> anyhow::Result<(u64, Vec<Option<UiAccount>>)> {
+    let addresses: Vec<String> = addresses.iter().map(|address| address.to_string()).collect();
+    let response: Value = rpc_client
+        .send(
+            RpcRequest::GetMultipleAccounts,
+            json!([addresses, { "encoding": "jsonParsed", "commitment": "confirmed" }]),
+        )
+        .await?;
+
+    let slot = response["context"]["slot"]
+        .as_u64()
+        .ok_or_else(|| anyhow::anyhow!("getMultipleAccounts response has no slot"))?;
+    let accounts = serde_json::from_value(response["value"].clone())?;
+    Ok((slot, accounts))
+}
+
+// `info` of a token account or mint as parsed by the node, if it is one of the expected type
+fn parsed_info<'a>(account: &'a Option<UiAccount>, expected_type: &str) -> Option<&'a Value> {
+    let UiAccountData::Json(parsed) = &account.as_ref()?.data else {
+        return None;
+    };
+    let program = parsed.program.as_str();
+    if program != "spl-token" && program != "spl-token-2022" {
+        return None;
+    }
+    (parsed.parsed["type"] == expected_type).then(|| &parsed.parsed["info"])
+}
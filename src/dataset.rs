@@ -0,0 +1,303 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::clock::unix_now;
+use crate::config::{DatasetConfig, DatasetFormat};
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+
+// Written while a file is being filled, so a reader never picks up one that
+// isn't finished (a Parquet file is unreadable until its footer is written)
+const PARTIAL_SUFFIX: &str = ".partial";
+// Rows buffered per Parquet record batch
+#[cfg(feature = "parquet")]
+const BATCH_ROWS: usize = 1024;
+
+// One row per price update; the column order of the CSV files
+const COLUMNS: &[&str] = &[
+    "timestamp", "source", "symbol", "pool", "price", "change_percent", "base_reserve", "quote_reserve",
+    "base_decimals", "quote_decimals", "change_reason", "fee_bps", "slot", "slot_lag", "tvl_usd",
+    "high_24h", "low_24h", "volume_24h", "bid", "ask", "active_index",
+    "microprice", "imbalance", "change_1m", "change_5m", "change_24h", "unchanged",
+];
+
+enum Output {
+    Csv(BufWriter<File>),
+    CsvGzip(GzEncoder<BufWriter<File>>),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet::arrow::ArrowWriter<File>, Vec<PriceUpdate>),
+}
+
+// Price updates appended to local files for offline analysis, as CSV (with a
+// header row) or Parquet. A new file is started every rotate_secs, or sooner
+// once the current one reaches rotate_mb.
+pub struct DatasetRecorder {
+    config: DatasetConfig,
+    current: Option<(PathBuf, Output)>,
+}
+
+impl DatasetRecorder {
+    pub fn new(config: DatasetConfig) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&config.dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create dataset directory {}: {}", config.dir.display(), e))?;
+        Ok(Self { config, current: None })
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, shutdown: CancellationToken) {
+        let mut rotate = tokio::time::interval(tokio::time::Duration::from_secs(self.config.rotate_secs.max(1)));
+        rotate.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick fires immediately, with nothing to rotate yet
+        rotate.tick().await;
+
+        tracing::info!(format = ?self.config.format, dir = %self.config.dir.display(), "recording price updates");
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => self.write(update),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "dataset recorder lagged, events missing from the files");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = rotate.tick() => self.close(),
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        // The monitors have stopped by now; what they published last still goes in the file
+        while let Ok(event) = rx.try_recv() {
+            if let MarketEvent::Price(update) = event {
+                self.write(update);
+            }
+        }
+        self.close();
+    }
+
+    fn write(&mut self, update: PriceUpdate) {
+        if let Err(e) = self.append(update) {
+            tracing::error!(error = %e, "failed to write dataset file");
+            // Start over in a fresh file rather than keep writing to a broken one
+            self.current = None;
+            return;
+        }
+        if self.config.rotate_mb > 0 && self.current_size() >= self.config.rotate_mb * 1024 * 1024 {
+            self.close();
+        }
+    }
+
+    fn append(&mut self, update: PriceUpdate) -> anyhow::Result<()> {
+        if self.current.is_none() {
+            self.current = Some(self.open()?);
+        }
+        let (_, output) = self.current.as_mut().unwrap();
+        match output {
+            Output::Csv(writer) => writeln!(writer, "{}", csv_row(&update))?,
+            Output::CsvGzip(writer) => writeln!(writer, "{}", csv_row(&update))?,
+            #[cfg(feature = "parquet")]
+            Output::Parquet(writer, rows) => {
+                rows.push(update);
+                if rows.len() >= BATCH_ROWS {
+                    writer.write(&parquet_batch(rows)?)?;
+                    rows.clear();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn open(&self) -> anyhow::Result<(PathBuf, Output)> {
+        let extension = match (self.config.format, self.config.gzip) {
+            (DatasetFormat::Csv, true) => "csv.gz",
+            (DatasetFormat::Csv, false) => "csv",
+            (DatasetFormat::Parquet, _) => "parquet",
+        };
+        let path = self.config.dir.join(format!("prices-{}.{}{}", unix_now(), extension, PARTIAL_SUFFIX));
+        let file = File::create(&path).map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+
+        let header = COLUMNS.join(",");
+        let output = match self.config.format {
+            DatasetFormat::Csv if self.config.gzip => {
+                let mut writer = GzEncoder::new(BufWriter::new(file), Compression::default());
+                writeln!(writer, "{}", header)?;
+                Output::CsvGzip(writer)
+            }
+            DatasetFormat::Csv => {
+                let mut writer = BufWriter::new(file);
+                writeln!(writer, "{}", header)?;
+                Output::Csv(writer)
+            }
+            #[cfg(feature = "parquet")]
+            DatasetFormat::Parquet => {
+                use parquet::basic::{Compression, GzipLevel};
+                use parquet::file::properties::WriterProperties;
+
+                let compression = if self.config.gzip { Compression::GZIP(GzipLevel::default()) } else { Compression::UNCOMPRESSED };
+                let properties = WriterProperties::builder().set_compression(compression).build();
+                let writer = parquet::arrow::ArrowWriter::try_new(file, parquet_schema(), Some(properties))?;
+                Output::Parquet(writer, Vec::with_capacity(BATCH_ROWS))
+            }
+            // Rejected when the config is loaded
+            #[cfg(not(feature = "parquet"))]
+            DatasetFormat::Parquet => return Err(anyhow::anyhow!("Parquet output requires the parquet feature")),
+        };
+        Ok((path, output))
+    }
+
+    // Bytes the current file takes, or will once buffered rows are flushed
+    fn current_size(&self) -> u64 {
+        match &self.current {
+            #[cfg(feature = "parquet")]
+            Some((_, Output::Parquet(writer, _))) => (writer.bytes_written() + writer.in_progress_size()) as u64,
+            Some((path, _)) => std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    // Finish the file being written and drop its partial suffix
+    fn close(&mut self) {
+        let Some((path, output)) = self.current.take() else {
+            return;
+        };
+        let finished: anyhow::Result<()> = (|| {
+            match output {
+                Output::Csv(mut writer) => writer.flush()?,
+                Output::CsvGzip(writer) => writer.finish()?.flush()?,
+                #[cfg(feature = "parquet")]
+                Output::Parquet(mut writer, rows) => {
+                    if !rows.is_empty() {
+                        writer.write(&parquet_batch(&rows)?)?;
+                    }
+                    writer.close()?;
+                }
+            }
+            let closed = path.with_file_name(path.file_name().unwrap().to_string_lossy().trim_end_matches(PARTIAL_SUFFIX).to_string());
+            std::fs::rename(&path, &closed)?;
+            Ok(())
+        })();
+        if let Err(e) = finished {
+            tracing::error!(error = %e, path = %path.display(), "failed to close dataset file");
+        }
+    }
+}
+
+fn csv_row(update: &PriceUpdate) -> String {
+    let optional = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+    [
+        update.timestamp.to_string(),
+        csv_field(&update.source),
+        csv_field(&update.symbol),
+        update.pool.clone(),
+        update.price.to_string(),
+        update.change_percent.to_string(),
+        update.base_reserve.to_string(),
+        update.quote_reserve.to_string(),
+        update.base_decimals.to_string(),
+        update.quote_decimals.to_string(),
+        change_reason(update),
+        update.fee_bps.to_string(),
+        update.slot.to_string(),
+        update.slot_lag.to_string(),
+        optional(update.tvl_usd),
+        update.high_24h.to_string(),
+        update.low_24h.to_string(),
+        optional(update.volume_24h),
+        optional(update.bid),
+        optional(update.ask),
+        update.active_index.map(|index| index.to_string()).unwrap_or_default(),
+        optional(update.microprice),
+        optional(update.imbalance),
+        optional(update.change_1m),
+        optional(update.change_5m),
+        optional(update.change_24h),
+        update.unchanged.to_string(),
+    ]
+    .join(",")
+}
+
+// Quoted only when it has to be, per RFC 4180
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// As it serializes, e.g. liquidity_add
+fn change_reason(update: &PriceUpdate) -> String {
+    serde_json::to_value(update.change_reason)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_schema() -> std::sync::Arc<arrow_schema::Schema> {
+    use arrow_schema::{DataType, Field, Schema};
+
+    let types = [
+        DataType::UInt64, DataType::Utf8, DataType::Utf8, DataType::Utf8, DataType::Float64, DataType::Float64,
+        DataType::UInt64, DataType::UInt64, DataType::UInt8, DataType::UInt8, DataType::Utf8, DataType::Float64,
+        DataType::UInt64, DataType::UInt64, DataType::Float64, DataType::Float64, DataType::Float64,
+        DataType::Float64, DataType::Float64, DataType::Float64, DataType::Int64, DataType::Float64, DataType::Float64,
+        DataType::Float64, DataType::Float64, DataType::Float64, DataType::Boolean,
+    ];
+    let nullable = [
+        "tvl_usd", "volume_24h", "bid", "ask", "active_index", "microprice", "imbalance", "change_1m", "change_5m", "change_24h",
+    ];
+    let fields: Vec<Field> = COLUMNS
+        .iter()
+        .zip(types)
+        .map(|(name, data_type)| Field::new(*name, data_type, nullable.contains(name)))
+        .collect();
+    std::sync::Arc::new(Schema::new(fields))
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_batch(rows: &[PriceUpdate]) -> anyhow::Result<arrow_array::RecordBatch> {
+    use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, UInt64Array, UInt8Array};
+    use std::sync::Arc;
+
+    let u64s = |value: fn(&PriceUpdate) -> u64| Arc::new(UInt64Array::from_iter_values(rows.iter().map(value))) as ArrayRef;
+    let u8s = |value: fn(&PriceUpdate) -> u8| Arc::new(UInt8Array::from_iter_values(rows.iter().map(value))) as ArrayRef;
+    let f64s = |value: fn(&PriceUpdate) -> f64| Arc::new(Float64Array::from_iter_values(rows.iter().map(value))) as ArrayRef;
+    let optional_f64s = |value: fn(&PriceUpdate) -> Option<f64>| Arc::new(Float64Array::from_iter(rows.iter().map(value))) as ArrayRef;
+    let strings = |value: fn(&PriceUpdate) -> String| Arc::new(StringArray::from_iter_values(rows.iter().map(value))) as ArrayRef;
+
+    let columns = vec![
+        u64s(|update| update.timestamp),
+        strings(|update| update.source.clone()),
+        strings(|update| update.symbol.clone()),
+        strings(|update| update.pool.clone()),
+        f64s(|update| update.price),
+        f64s(|update| update.change_percent),
+        u64s(|update| update.base_reserve),
+        u64s(|update| update.quote_reserve),
+        u8s(|update| update.base_decimals),
+        u8s(|update| update.quote_decimals),
+        strings(change_reason),
+        f64s(|update| update.fee_bps),
+        u64s(|update| update.slot),
+        u64s(|update| update.slot_lag),
+        optional_f64s(|update| update.tvl_usd),
+        f64s(|update| update.high_24h),
+        f64s(|update| update.low_24h),
+        optional_f64s(|update| update.volume_24h),
+        optional_f64s(|update| update.bid),
+        optional_f64s(|update| update.ask),
+        Arc::new(Int64Array::from_iter(rows.iter().map(|update| update.active_index))) as ArrayRef,
+        optional_f64s(|update| update.microprice),
+        optional_f64s(|update| update.imbalance),
+        optional_f64s(|update| update.change_1m),
+        optional_f64s(|update| update.change_5m),
+        optional_f64s(|update| update.change_24h),
+        Arc::new(BooleanArray::from_iter(rows.iter().map(|update| Some(update.unchanged)))) as ArrayRef,
+    ];
+    Ok(arrow_array::RecordBatch::try_new(parquet_schema(), columns)?)
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::config::DeadmanConfig;
+use crate::events::MarketEvent;
+use crate::rpc;
+
+// A ping that takes longer than this counts as failed
+const PING_TIMEOUT_SECS: u64 = 10;
+
+// Dead-man's switch: every interval_secs, each URL gets a GET while price
+// updates are still flowing. Services like healthchecks.io page when the pings
+// stop, which covers what the watcher can't report itself: a wedged process, a
+// dead machine, or monitors that all went quiet. Pings are withheld as soon as
+// no price update has arrived for max_silence_secs.
+pub async fn run(config: DeadmanConfig, mut rx: broadcast::Receiver<MarketEvent>) {
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(PING_TIMEOUT_SECS)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!(error = %e, "dead-man's switch disabled, HTTP client failed");
+            return;
+        }
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // Startup counts as fresh, so the first ping doesn't wait for the slowest monitor
+    let mut last_update = Instant::now();
+    let mut withheld = false;
+    let mut failing = vec![false; config.urls.len()];
+
+    tracing::info!(urls = config.urls.len(), interval_secs = config.interval_secs.max(1), "dead-man's switch pinging");
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(MarketEvent::Price(_)) => last_update = Instant::now(),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {
+                let silence = last_update.elapsed();
+                if silence > Duration::from_secs(config.max_silence_secs) {
+                    if !withheld {
+                        withheld = true;
+                        tracing::warn!(silence_secs = silence.as_secs(), "no price update, withholding dead-man's switch pings");
+                    }
+                    continue;
+                }
+                if withheld {
+                    withheld = false;
+                    tracing::info!("price updates are back, dead-man's switch pings resumed");
+                }
+
+                for (url, failing) in config.urls.iter().zip(failing.iter_mut()) {
+                    let result = client.get(url).send().await.and_then(|response| response.error_for_status());
+                    match result {
+                        Ok(_) if *failing => {
+                            *failing = false;
+                            tracing::info!(host = %rpc::host(url), "dead-man's switch ping recovered");
+                        }
+                        Ok(_) => {}
+                        // Logged once per outage; the URL itself is a secret
+                        Err(e) if !*failing => {
+                            *failing = true;
+                            tracing::error!(host = %rpc::host(url), error = %e.without_url(), "dead-man's switch ping failed");
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+    }
+}
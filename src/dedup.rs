@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::SendError};
+
+use crate::config::{DedupConfig, DedupMode};
+use crate::events::MarketEvent;
+use crate::metrics;
+use crate::raydium::PriceUpdate;
+
+struct Published {
+    slot: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    price: u64,  // f64 bits, so NaN compares equal to itself
+    at: Instant,
+}
+
+static SETTINGS: OnceLock<DedupConfig> = OnceLock::new();
+
+// pool address -> its last published update
+static LAST: LazyLock<Mutex<HashMap<String, Published>>> = LazyLock::new(Mutex::default);
+
+pub fn init(config: &DedupConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+// Publish a monitor's price update, unless it repeats the pool's last one: no
+// newer slot, or the same reserves and price. A repeat is held back until the
+// pool's keep-alive is due, or published with `unchanged` set, as configured.
+pub fn publish(tx: &broadcast::Sender<MarketEvent>, mut update: PriceUpdate) -> Result<usize, SendError<MarketEvent>> {
+    let Some(config) = SETTINGS.get().filter(|config| config.enabled) else {
+        return tx.send(MarketEvent::Price(update));
+    };
+
+    let now = Instant::now();
+    {
+        let mut last = LAST.lock().unwrap();
+        // (slot, published at) of the last update, if this one repeats it
+        let repeat = last
+            .get(&update.pool)
+            .filter(|last| {
+                update.slot <= last.slot
+                    || (update.base_reserve == last.base_reserve
+                        && update.quote_reserve == last.quote_reserve
+                        && update.price.to_bits() == last.price)
+            })
+            .map(|last| (last.slot, last.at));
+        if let Some((_, at)) = repeat {
+            metrics::inc_duplicate(&update.source);
+            match config.mode {
+                DedupMode::Suppress if now.duration_since(at) < Duration::from_secs(config.keepalive_secs) => return Ok(0),
+                DedupMode::Suppress => {}
+                DedupMode::Mark => update.unchanged = true,
+            }
+        }
+        last.insert(update.pool.clone(), Published {
+            // A read from an endpoint lagging behind doesn't move the slot back
+            slot: update.slot.max(repeat.map_or(0, |(slot, _)| slot)),
+            base_reserve: update.base_reserve,
+            quote_reserve: update.quote_reserve,
+            price: update.price.to_bits(),
+            at: now,
+        });
+    }
+    tx.send(MarketEvent::Price(update))
+}
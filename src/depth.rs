@@ -0,0 +1,310 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_meteora_dlmm_decoder::accounts::{bin_array::BinArray, lb_pair::LbPair};
+use carbon_raydium_clmm_decoder::accounts::tick_array_state::TickArrayState;
+use orca_whirlpools_client::{TickArray, Whirlpool};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::{DepthConfig, PoolConfig};
+use crate::distribution;
+use crate::events::MarketEvent;
+use crate::programs::{DLMM_PROGRAM_ID, RAYDIUM_CLMM_PROGRAM_ID, WHIRLPOOL_PROGRAM_ID};
+use crate::rpc::RpcProvider;
+
+pub const TICKS_PER_ARRAY: i32 = 88;
+// Raydium CLMM tick arrays are smaller than Whirlpool ones
+pub const CLMM_TICKS_PER_ARRAY: i32 = 60;
+pub const BINS_PER_ARRAY: i64 = 70;
+
+// Liquidity within one distance of the current price, quote token UI units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthBand {
+    pub percent: f64,
+    pub bid_depth: f64,  // Quote tokens the pool pays out before the price falls this far
+    pub ask_depth: f64,  // Quote tokens it takes to push the price up this far
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthUpdate {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub price: f64,
+    pub bands: Vec<DepthBand>,
+    pub timestamp: u64,
+}
+
+// Walk the Whirlpool's active liquidity out from the current price, crossing
+// the initialized ticks of the tick arrays around it
+pub async fn fetch_whirlpool_depth(
+    rpc_client: &RpcClient,
+    pool: &PoolConfig,
+    bands_percent: &[f64],
+) -> anyhow::Result<DepthUpdate> {
+    let address = pool.pubkey()?;
+    let whirlpool = Whirlpool::from_bytes(&rpc_client.get_account_data(&address).await?)?;
+    let (decimals_a, decimals_b) =
+        distribution::fetch_mint_decimals(rpc_client, &whirlpool.token_mint_a, &whirlpool.token_mint_b).await?;
+
+    // Only the tick arrays spanning the widest band either side
+    let widest = bands_percent.iter().copied().fold(0.0, f64::max) / 100.0;
+    let tick_lower = whirlpool.tick_current_index + ((1.0 - widest).ln() / 1.0001_f64.ln()).floor() as i32;
+    let tick_upper = whirlpool.tick_current_index + ((1.0 + widest).ln() / 1.0001_f64.ln()).ceil() as i32;
+    let ticks = fetch_ticks(rpc_client, &address, whirlpool.tick_spacing, tick_lower, tick_upper).await?;
+
+    let sqrt_current = whirlpool.sqrt_price as f64 / (1u128 << 64) as f64;
+    let quote_unit = 10_f64.powi(decimals_b as i32);
+    let bands = bands_percent
+        .iter()
+        .map(|&percent| {
+            let band = percent / 100.0;
+            let (bid, ask) = whirlpool_band(&ticks, whirlpool.tick_current_index, sqrt_current, whirlpool.liquidity, band);
+            DepthBand {
+                percent,
+                bid_depth: bid / quote_unit,
+                ask_depth: ask / quote_unit,
+            }
+        })
+        .collect();
+
+    Ok(DepthUpdate {
+        symbol: pool.symbol.clone(),
+        source: "Orca".to_string(),
+        pool: pool.address.clone(),
+        price: sqrt_current.powi(2) * 10_f64.powi(decimals_a as i32 - decimals_b as i32),
+        bands,
+        timestamp: unix_now(),
+    })
+}
+
+// Initialized ticks (index, liquidity_net) of the tick arrays spanning
+// [tick_lower, tick_upper], in ascending order
+pub async fn fetch_ticks(
+    rpc_client: &RpcClient,
+    whirlpool: &Pubkey,
+    tick_spacing: u16,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> anyhow::Result<Vec<(i32, i128)>> {
+    let ticks_per_array = TICKS_PER_ARRAY * tick_spacing as i32;
+    let tick_arrays: Vec<Pubkey> = (tick_lower.div_euclid(ticks_per_array)..=tick_upper.div_euclid(ticks_per_array))
+        .map(|i| tick_array_address(whirlpool, i * ticks_per_array))
+        .collect();
+
+    let mut ticks: Vec<(i32, i128)> = Vec::new();
+    // Arrays nobody has initialized yet hold no liquidity and come back empty
+    for account in rpc_client.get_multiple_accounts(&tick_arrays).await?.into_iter().flatten() {
+        let Ok(tick_array) = TickArray::from_bytes(&account.data) else {
+            continue;
+        };
+        for (i, tick) in tick_array.ticks.iter().enumerate() {
+            if tick.initialized {
+                let tick_index = tick_array.start_tick_index + i as i32 * tick_spacing as i32;
+                ticks.push((tick_index, tick.liquidity_net));
+            }
+        }
+    }
+    ticks.sort_by_key(|(tick_index, _)| *tick_index);
+
+    Ok(ticks)
+}
+
+// Initialized ticks (index, liquidity_net) of the Raydium CLMM tick arrays
+// spanning [tick_lower, tick_upper], in ascending order
+pub async fn fetch_clmm_ticks(
+    rpc_client: &RpcClient,
+    pool: &Pubkey,
+    tick_spacing: u16,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> anyhow::Result<Vec<(i32, i128)>> {
+    let ticks_per_array = CLMM_TICKS_PER_ARRAY * tick_spacing as i32;
+    let tick_arrays: Vec<Pubkey> = (tick_lower.div_euclid(ticks_per_array)..=tick_upper.div_euclid(ticks_per_array))
+        .map(|i| clmm_tick_array_address(pool, i * ticks_per_array))
+        .collect();
+
+    let mut ticks: Vec<(i32, i128)> = Vec::new();
+    for account in rpc_client.get_multiple_accounts(&tick_arrays).await?.into_iter().flatten() {
+        let Some(tick_array) = <TickArrayState as CarbonDeserialize>::deserialize(&account.data) else {
+            continue;
+        };
+        // Each tick carries its own index; unused ones have no gross liquidity
+        for tick in tick_array.ticks.iter().filter(|tick| tick.liquidity_gross > 0) {
+            ticks.push((tick.tick, tick.liquidity_net));
+        }
+    }
+    ticks.sort_by_key(|(tick_index, _)| *tick_index);
+
+    Ok(ticks)
+}
+
+// Raw quote amounts out (price down) and in (price up) over one band. Between
+// ticks the liquidity is constant, so each stretch moves L * Δ√P of the quote.
+fn whirlpool_band(ticks: &[(i32, i128)], tick_current: i32, sqrt_current: f64, liquidity: u128, band: f64) -> (f64, f64) {
+    let sqrt_tick = |tick_index: i32| 1.0001_f64.powf(tick_index as f64 / 2.0);
+
+    let sqrt_target = sqrt_current * (1.0 + band).sqrt();
+    let (mut active, mut sqrt_price, mut ask) = (liquidity as f64, sqrt_current, 0.0);
+    for &(tick_index, liquidity_net) in ticks.iter().filter(|(tick_index, _)| *tick_index > tick_current) {
+        let sqrt_next = sqrt_tick(tick_index);
+        if sqrt_next >= sqrt_target {
+            break;
+        }
+        ask += active * (sqrt_next - sqrt_price);
+        active = (active + liquidity_net as f64).max(0.0);
+        sqrt_price = sqrt_next;
+    }
+    ask += active * (sqrt_target - sqrt_price);
+
+    // Crossing a tick downwards takes its liquidity_net back out
+    let sqrt_target = sqrt_current * (1.0 - band).sqrt();
+    let (mut active, mut sqrt_price, mut bid) = (liquidity as f64, sqrt_current, 0.0);
+    for &(tick_index, liquidity_net) in ticks.iter().rev().filter(|(tick_index, _)| *tick_index <= tick_current) {
+        let sqrt_next = sqrt_tick(tick_index);
+        if sqrt_next <= sqrt_target {
+            break;
+        }
+        bid += active * (sqrt_price - sqrt_next);
+        active = (active - liquidity_net as f64).max(0.0);
+        sqrt_price = sqrt_next;
+    }
+    bid += active * (sqrt_price - sqrt_target);
+
+    (bid, ask)
+}
+
+// Sum the DLMM bins within each band: bins above the active one hold only the
+// base token, bins below only the quote, the active bin both
+pub async fn fetch_dlmm_depth(
+    rpc_client: &RpcClient,
+    pool: &PoolConfig,
+    bands_percent: &[f64],
+) -> anyhow::Result<DepthUpdate> {
+    let address = pool.pubkey()?;
+    let lb_pair = <LbPair as CarbonDeserialize>::deserialize(&rpc_client.get_account_data(&address).await?)
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora DLMM data"))?;
+    let (decimals_x, decimals_y) =
+        distribution::fetch_mint_decimals(rpc_client, &lb_pair.token_x_mint, &lb_pair.token_y_mint).await?;
+
+    // Raw price (quote atoms per base atom) of a bin
+    let step = 1.0 + lb_pair.bin_step as f64 / 10_000.0;
+    let bin_price = |bin_id: i64| step.powf(bin_id as f64);
+    let active_id = lb_pair.active_id as i64;
+
+    let widest = bands_percent.iter().copied().fold(0.0, f64::max) / 100.0;
+    let bin_lower = active_id + ((1.0 - widest).ln() / step.ln()).floor() as i64;
+    let bin_upper = active_id + ((1.0 + widest).ln() / step.ln()).ceil() as i64;
+    let bins = fetch_bins(rpc_client, &address, bin_lower, bin_upper).await?;
+
+    let active_price = bin_price(active_id);
+    let quote_unit = 10_f64.powi(decimals_y as i32);
+    let bands = bands_percent
+        .iter()
+        .map(|&percent| {
+            let band = percent / 100.0;
+            let (mut bid, mut ask) = (0.0, 0.0);
+            for &(bin_id, amount_x, amount_y) in &bins {
+                let price = bin_price(bin_id);
+                if bin_id >= active_id && price <= active_price * (1.0 + band) {
+                    ask += amount_x as f64 * price;
+                }
+                if bin_id <= active_id && price >= active_price * (1.0 - band) {
+                    bid += amount_y as f64;
+                }
+            }
+            DepthBand {
+                percent,
+                bid_depth: bid / quote_unit,
+                ask_depth: ask / quote_unit,
+            }
+        })
+        .collect();
+
+    Ok(DepthUpdate {
+        symbol: pool.symbol.clone(),
+        source: "Meteora".to_string(),
+        pool: pool.address.clone(),
+        price: active_price * 10_f64.powi(decimals_x as i32 - decimals_y as i32),
+        bands,
+        timestamp: unix_now(),
+    })
+}
+
+// Bins (id, amount_x, amount_y) of the bin arrays spanning [bin_lower, bin_upper]
+pub async fn fetch_bins(
+    rpc_client: &RpcClient,
+    lb_pair: &Pubkey,
+    bin_lower: i64,
+    bin_upper: i64,
+) -> anyhow::Result<Vec<(i64, u64, u64)>> {
+    let bin_arrays: Vec<Pubkey> = (bin_lower.div_euclid(BINS_PER_ARRAY)..=bin_upper.div_euclid(BINS_PER_ARRAY))
+        .map(|index| bin_array_address(lb_pair, index))
+        .collect();
+
+    let mut bins: Vec<(i64, u64, u64)> = Vec::new();
+    for account in rpc_client.get_multiple_accounts(&bin_arrays).await?.into_iter().flatten() {
+        let Some(bin_array) = <BinArray as CarbonDeserialize>::deserialize(&account.data) else {
+            continue;
+        };
+        for (i, bin) in bin_array.bins.iter().enumerate() {
+            bins.push((bin_array.index * BINS_PER_ARRAY + i as i64, bin.amount_x, bin.amount_y));
+        }
+    }
+
+    Ok(bins)
+}
+
+// Periodically publish depth for every watched Orca/Meteora pool on the market bus
+pub async fn run(
+    config: DepthConfig,
+    orca_pools: Vec<PoolConfig>,
+    meteora_pools: Vec<PoolConfig>,
+    rpc: RpcProvider,
+    tx: Arc<broadcast::Sender<MarketEvent>>,
+) {
+    let rpc_client = rpc.client(CommitmentConfig::default());
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        for pool in &orca_pools {
+            match fetch_whirlpool_depth(&rpc_client, pool, &config.bands_percent).await {
+                Ok(update) => {
+                    let _ = tx.send(MarketEvent::Depth(update));
+                }
+                Err(e) => tracing::error!(source = "Orca", symbol = %pool.symbol, error = %e, "depth fetch failed"),
+            }
+        }
+
+        for pool in &meteora_pools {
+            match fetch_dlmm_depth(&rpc_client, pool, &config.bands_percent).await {
+                Ok(update) => {
+                    let _ = tx.send(MarketEvent::Depth(update));
+                }
+                Err(e) => tracing::error!(source = "Meteora", symbol = %pool.symbol, error = %e, "depth fetch failed"),
+            }
+        }
+    }
+}
+
+// PDA seeds: "tick_array", whirlpool, start tick index as a decimal string
+fn tick_array_address(whirlpool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    let start = start_tick_index.to_string();
+    Pubkey::find_program_address(&[b"tick_array", whirlpool.as_ref(), start.as_bytes()], &WHIRLPOOL_PROGRAM_ID).0
+}
+
+// PDA seeds: "tick_array", pool, start tick index as big-endian i32
+fn clmm_tick_array_address(pool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(&[b"tick_array", pool.as_ref(), &start_tick_index.to_be_bytes()], &RAYDIUM_CLMM_PROGRAM_ID).0
+}
+
+// PDA seeds: "bin_array", lb_pair, array index as little-endian i64
+fn bin_array_address(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    Pubkey::find_program_address(&[b"bin_array", lb_pair.as_ref(), &index.to_le_bytes()], &DLMM_PROGRAM_ID).0
+}
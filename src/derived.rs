@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::DerivedConfig;
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+
+// One evaluation of a user-defined series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedValue {
+    pub name: String,
+    pub symbol: String,
+    pub value: f64,
+    pub timestamp: u64,
+}
+
+// Arithmetic over the latest update of each source for one symbol, e.g.
+// `(orca.price - raydium.price) / raydium.price * 10000`. Operands are
+// numbers and `<source>.<field>` references; sources are lower case with
+// spaces and dots as underscores (`raydium_clmm`, `pump_fun`). abs, min and
+// max are built in.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(f64),
+    Field { source: String, field: Field },
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+// PriceUpdate fields an expression can read; reserves are in UI units
+#[derive(Debug, Clone, Copy)]
+pub enum Field {
+    Price,
+    ChangePercent,
+    BaseReserve,
+    QuoteReserve,
+    FeeBps,
+    SlotLag,
+    TvlUsd,
+    High24h,
+    Low24h,
+    Volume24h,
+    Bid,
+    Ask,
+    Microprice,
+    Imbalance,
+    Change1m,
+    Change5m,
+    Change24h,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "price" => Field::Price,
+            "change_percent" => Field::ChangePercent,
+            "base_reserve" => Field::BaseReserve,
+            "quote_reserve" => Field::QuoteReserve,
+            "fee_bps" => Field::FeeBps,
+            "slot_lag" => Field::SlotLag,
+            "tvl_usd" => Field::TvlUsd,
+            "high_24h" => Field::High24h,
+            "low_24h" => Field::Low24h,
+            "volume_24h" => Field::Volume24h,
+            "bid" => Field::Bid,
+            "ask" => Field::Ask,
+            "microprice" => Field::Microprice,
+            "imbalance" => Field::Imbalance,
+            "change_1m" => Field::Change1m,
+            "change_5m" => Field::Change5m,
+            "change_24h" => Field::Change24h,
+            _ => return None,
+        })
+    }
+
+    // None where the update doesn't carry the field (e.g. bid on an AMM)
+    fn read(self, update: &PriceUpdate) -> Option<f64> {
+        match self {
+            Field::Price => Some(update.price),
+            Field::ChangePercent => Some(update.change_percent),
+            Field::BaseReserve => Some(update.base_reserve as f64 / 10_f64.powi(update.base_decimals as i32)),
+            Field::QuoteReserve => Some(update.quote_reserve as f64 / 10_f64.powi(update.quote_decimals as i32)),
+            Field::FeeBps => Some(update.fee_bps),
+            Field::SlotLag => Some(update.slot_lag as f64),
+            Field::TvlUsd => update.tvl_usd,
+            Field::High24h => Some(update.high_24h),
+            Field::Low24h => Some(update.low_24h),
+            Field::Volume24h => update.volume_24h,
+            Field::Bid => update.bid,
+            Field::Ask => update.ask,
+            Field::Microprice => update.microprice,
+            Field::Imbalance => update.imbalance,
+            Field::Change1m => update.change_1m,
+            Field::Change5m => update.change_5m,
+            Field::Change24h => update.change_24h,
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens: &tokens, position: 0 };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(anyhow::anyhow!("Unexpected {:?} in expression", token)),
+        }
+    }
+
+    // Every source the expression reads from
+    fn sources(&self, sources: &mut Vec<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Field { source, .. } => {
+                if !sources.contains(source) {
+                    sources.push(source.clone());
+                }
+            }
+            Expr::Neg(inner) => inner.sources(sources),
+            Expr::Binary(left, _, right) => {
+                left.sources(sources);
+                right.sources(sources);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.sources(sources)),
+        }
+    }
+
+    // None while a referenced source has no update since `fresh_since` or lacks the field
+    fn eval(&self, latest: &HashMap<String, PriceUpdate>, fresh_since: u64) -> Option<f64> {
+        Some(match self {
+            Expr::Number(value) => *value,
+            Expr::Field { source, field } => {
+                field.read(latest.get(source).filter(|update| update.timestamp >= fresh_since)?)?
+            }
+            Expr::Neg(inner) => -inner.eval(latest, fresh_since)?,
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (left.eval(latest, fresh_since)?, right.eval(latest, fresh_since)?);
+                match op {
+                    Op::Add => left + right,
+                    Op::Sub => left - right,
+                    Op::Mul => left * right,
+                    Op::Div => left / right,
+                }
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<f64> = args.iter().map(|arg| arg.eval(latest, fresh_since)).collect::<Option<_>>()?;
+                match function {
+                    Function::Abs => args[0].abs(),
+                    Function::Min => args.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => args.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut end = start;
+                while let Some(&(index, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E') {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                let number = &input[start..end];
+                tokens.push(Token::Number(number.parse().map_err(|_| anyhow::anyhow!("Invalid number {}", number))?));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(index, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Ident(input[start..end].to_string()));
+            }
+            _ => {
+                tokens.push(match c {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => return Err(anyhow::anyhow!("Unexpected character '{}' in expression", c)),
+                });
+                chars.next();
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// Recursive descent, usual precedence: unary minus, then * /, then + -
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(anyhow::anyhow!("Expected {:?} in expression, found {:?}", expected, other)),
+        }
+    }
+
+    fn expr(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => Op::Add,
+                Some(Token::Minus) => Op::Sub,
+                _ => return Ok(left),
+            };
+            self.next();
+            left = Expr::Binary(Box::new(left), op, Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => Op::Mul,
+                Some(Token::Slash) => Op::Div,
+                _ => return Ok(left),
+            };
+            self.next();
+            left = Expr::Binary(Box::new(left), op, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        if self.peek() == Some(&Token::Minus) {
+            self.next();
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                let function = match name.as_str() {
+                    "abs" => Function::Abs,
+                    "min" => Function::Min,
+                    "max" => Function::Max,
+                    _ => return Err(anyhow::anyhow!("Unknown function {}", name)),
+                };
+                self.next();
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                match (function, args.len()) {
+                    (Function::Abs, 1) | (Function::Min | Function::Max, 2..) => Ok(Expr::Call(function, args)),
+                    _ => Err(anyhow::anyhow!("Wrong number of arguments to {}", name)),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                let (source, field) = name
+                    .split_once('.')
+                    .ok_or_else(|| anyhow::anyhow!("Expected <source>.<field>, found {}", name))?;
+                let field = Field::parse(field).ok_or_else(|| anyhow::anyhow!("Unknown field {} in {}", field, name))?;
+                Ok(Expr::Field { source: source.to_lowercase(), field })
+            }
+            other => Err(anyhow::anyhow!("Expected a number, field or '(' in expression, found {:?}", other)),
+        }
+    }
+}
+
+// Source name as written in expressions: "Raydium CLMM" -> "raydium_clmm",
+// "Pump.fun" -> "pump_fun". Anything but letters and digits becomes an
+// underscore, so the name can't clash with the '.' before the field.
+fn source_key(source: &str) -> String {
+    source
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+// Keep the latest update per source for each configured symbol and, whenever
+// one a series reads from arrives, re-evaluate that series and publish it.
+// Updates older than max_age_secs don't count, so a stalled source leaves the
+// series unpublished rather than computed from stale data.
+pub async fn run(
+    series: Vec<DerivedConfig>,
+    mut rx: broadcast::Receiver<MarketEvent>,
+    tx: Arc<broadcast::Sender<MarketEvent>>,
+) {
+    // Config validation already parsed every expression
+    let series: Vec<(DerivedConfig, Expr, Vec<String>)> = series
+        .into_iter()
+        .filter_map(|config| {
+            let expr = Expr::parse(&config.expr).ok()?;
+            let mut sources = Vec::new();
+            expr.sources(&mut sources);
+            Some((config, expr, sources))
+        })
+        .collect();
+    // symbol -> source key -> latest update
+    let mut latest: HashMap<String, HashMap<String, PriceUpdate>> = HashMap::new();
+
+    loop {
+        let update = match rx.recv().await {
+            Ok(MarketEvent::Price(update)) => update,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped = skipped, "derived series lagged, skipped events");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let source = source_key(&update.source);
+        let now = update.timestamp;
+        let symbol = update.symbol.clone();
+        let quotes = latest.entry(symbol.clone()).or_default();
+        quotes.insert(source.clone(), update);
+
+        for (config, expr, sources) in &series {
+            if !config.symbol.eq_ignore_ascii_case(&symbol) || !sources.contains(&source) {
+                continue;
+            }
+            let fresh_since = now.saturating_sub(config.max_age_secs);
+            let Some(value) = expr.eval(quotes, fresh_since).filter(|value| value.is_finite()) else {
+                continue;
+            };
+
+            let _ = tx.send(MarketEvent::Derived(DerivedValue {
+                name: config.name.clone(),
+                symbol: symbol.clone(),
+                value,
+                timestamp: now,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> f64 {
+        Expr::parse(input).unwrap().eval(&HashMap::new(), 0).unwrap()
+    }
+
+    #[test]
+    fn parse_follows_operator_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("8 / 4 / 2"), 1.0);
+        assert_eq!(eval("-2 * 3 + 1"), -5.0);
+        assert_eq!(eval("2 - -3"), 5.0);
+        assert_eq!(eval("max(1, 2 * 3) - abs(-4)"), 2.0);
+        assert_eq!(eval("min(3, 1, 2)"), 1.0);
+    }
+
+    #[test]
+    fn parse_reads_source_fields() {
+        let expr = Expr::parse("(orca.price - raydium_clmm.price) / raydium_clmm.price * 10000").unwrap();
+        let mut sources = Vec::new();
+        expr.sources(&mut sources);
+        assert_eq!(sources, ["orca", "raydium_clmm"]);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_expressions() {
+        for input in [
+            "",
+            "1 +",
+            "(1 + 2",
+            "1 2",
+            "1 $ 2",
+            "price",
+            "orca.depth",
+            "sqrt(4)",
+            "abs(1, 2)",
+            "min(1)",
+        ] {
+            assert!(Expr::parse(input).is_err(), "{:?} parsed", input);
+        }
+    }
+}
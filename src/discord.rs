@@ -0,0 +1,97 @@
+use serde_json::json;
+use std::collections::BTreeMap;
+use tokio::sync::broadcast;
+
+use crate::alerts::Alert;
+use crate::config::{DiscordConfig, DiscordMode};
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+
+// Discord rejects webhook messages longer than this
+const MAX_MESSAGE_LEN: usize = 2000;
+
+// Discord webhook sink: forwards alerts and, in updates mode, posts a throttled
+// digest of the latest price from every pool instead of one message per tick
+pub struct DiscordNotifier {
+    client: reqwest::Client,
+    config: DiscordConfig,
+    // pool address -> latest update since the last digest
+    pending: BTreeMap<String, PriceUpdate>,
+}
+
+impl DiscordNotifier {
+    pub fn new(config: DiscordConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub async fn run(mut self, mut alerts_rx: broadcast::Receiver<Alert>, mut updates_rx: broadcast::Receiver<MarketEvent>) {
+        let forward_updates = self.config.mode == DiscordMode::Updates;
+        let mut digest = tokio::time::interval(tokio::time::Duration::from_secs(self.config.throttle_secs.max(1)));
+
+        loop {
+            tokio::select! {
+                alert = alerts_rx.recv() => match alert {
+                    Ok(alert) => {
+                        if let Err(e) = self.send(&alert.text()).await {
+                            tracing::error!(error = %e, "Discord alert failed");
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "Discord sink lagged, dropped alerts");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                update = updates_rx.recv(), if forward_updates => match update {
+                    Ok(MarketEvent::Price(update)) => {
+                        self.pending.insert(update.pool.clone(), update);
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = digest.tick(), if forward_updates => {
+                    if let Some(text) = self.take_digest() {
+                        if let Err(e) = self.send(&text).await {
+                            tracing::error!(error = %e, "Discord update failed");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn take_digest(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let mut text = String::from("📊 Latest prices");
+        for update in std::mem::take(&mut self.pending).into_values() {
+            let line = format!(
+                "\n{} {}: ${:.4} ({:+.2}%)",
+                update.source, update.symbol, update.price, update.change_percent,
+            );
+            if text.len() + line.len() > MAX_MESSAGE_LEN {
+                break;
+            }
+            text.push_str(&line);
+        }
+
+        Some(text)
+    }
+
+    pub async fn send(&self, text: &str) -> anyhow::Result<()> {
+        self.client
+            .post(&self.config.webhook_url)
+            .json(&json!({ "content": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::OnceLock;
+
+use crate::cli::Cli;
+use crate::config::{DisplayConfig, OutputFormat};
+
+// Console output settings: emoji, ASCII-only and number formatting. The log
+// formatter passes every console line through render(), as do the commands
+// printing to stdout themselves; numbers go through number().
+
+static SETTINGS: OnceLock<DisplaySettings> = OnceLock::new();
+
+struct DisplaySettings {
+    emoji: bool,
+    ascii: bool,
+    // None keeps the plain `1234.5678` formatting
+    separators: Option<(&'static str, char)>,
+    output: OutputFormat,
+}
+
+// Apply the [display] section, with --no-emoji, --ascii, --locale and
+// --output on the command line taking precedence
+pub fn init(config: &DisplayConfig, cli: &Cli) {
+    let emoji = config.emoji && !cli.no_emoji;
+    let ascii = config.ascii || cli.ascii;
+    let locale = cli.locale.clone().or_else(|| config.locale.clone());
+    let output = cli.output.unwrap_or(config.output);
+
+    let separators = locale.as_deref().map(|tag| {
+        let tag = if tag == "auto" { system_locale() } else { tag.to_string() };
+        separators_for(&tag, ascii)
+    });
+
+    let _ = SETTINGS.set(DisplaySettings {
+        emoji: emoji && !ascii,
+        ascii,
+        separators,
+        output,
+    });
+}
+
+// Whether stdout carries market events as JSON lines rather than the text display
+pub fn json_output() -> bool {
+    settings().output == OutputFormat::Json
+}
+
+fn settings() -> &'static DisplaySettings {
+    SETTINGS.get_or_init(|| DisplaySettings {
+        emoji: true,
+        ascii: false,
+        separators: None,
+        output: OutputFormat::Text,
+    })
+}
+
+// Format a number with the configured thousands separator and decimal mark
+pub fn number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value);
+    let Some((thousands, decimal)) = settings().separators else {
+        return formatted;
+    };
+
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let mut grouped = String::with_capacity(formatted.len() + integer.len() / 3 * thousands.len());
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(thousands);
+        }
+        grouped.push(digit);
+    }
+
+    if fraction.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}{}{}", sign, grouped, decimal, fraction)
+    }
+}
+
+// Like number(), with an explicit sign for positive values as `{:+}` does
+pub fn signed(value: f64, decimals: usize) -> String {
+    let formatted = number(value, decimals);
+    if value >= 0.0 {
+        format!("+{}", formatted)
+    } else {
+        formatted
+    }
+}
+
+// Whether render() changes anything, i.e. emoji are off or output is ASCII-only
+pub fn plain() -> bool {
+    let settings = settings();
+    !settings.emoji || settings.ascii
+}
+
+// Strip emoji and, in ASCII mode, transliterate or drop everything non-ASCII
+pub fn render(text: impl Into<String>) -> String {
+    let text = text.into();
+    let settings = settings();
+    if !plain() {
+        return text;
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_emoji(c) {
+            continue;
+        }
+        if settings.ascii && !c.is_ascii() {
+            if let Some(replacement) = ascii_replacement(c) {
+                out.push_str(replacement);
+            }
+            continue;
+        }
+        out.push(c);
+    }
+
+    // Lines that started with an emoji keep its trailing space otherwise
+    if text.starts_with(is_emoji) && out.starts_with(' ') {
+        out.remove(0);
+    }
+    out
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF      // pictographs, emoticons, transport, supplemental symbols
+        | 0x2600..=0x27BF      // misc symbols and dingbats (⚠ ✅ ❌)
+        | 0x2300..=0x23FF      // misc technical (⏳ ⌛)
+        | 0x2B00..=0x2BFF      // arrows and stars used as emoji (⬆ ⭐)
+        | 0xFE0F | 0x200D      // emoji presentation selector, zero-width joiner
+    )
+}
+
+fn ascii_replacement(c: char) -> Option<&'static str> {
+    match c {
+        '→' => Some("->"),
+        '←' => Some("<-"),
+        '↔' => Some("<->"),
+        '≈' => Some("~"),
+        '—' | '–' => Some("-"),
+        '…' => Some("..."),
+        '×' => Some("x"),
+        '\u{a0}' | '\u{202f}' => Some(" "),
+        _ => None,
+    }
+}
+
+// (thousands separator, decimal mark) by language, with the region for the few
+// where it differs, e.g. "de-CH" vs "de-DE"
+fn separators_for(tag: &str, ascii: bool) -> (&'static str, char) {
+    let tag = tag.replace('_', "-").to_lowercase();
+    let language = tag.split(['-', '.']).next().unwrap_or("");
+
+    match language {
+        _ if tag.starts_with("de-ch") || tag.starts_with("it-ch") => ("'", '.'),
+        "de" | "nl" | "it" | "es" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl" => (".", ','),
+        "fr" | "pl" | "cs" | "sk" | "ru" | "uk" | "sv" | "nb" | "no" | "fi" | "hu" | "bg" => {
+            (if ascii { " " } else { "\u{202f}" }, ',')
+        }
+        _ => (",", '.'),
+    }
+}
+
+fn system_locale() -> String {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .unwrap_or_else(|| "en".to_string())
+}
@@ -0,0 +1,234 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_meteora_dlmm_decoder::accounts::{bin_array::BinArray, lb_pair::LbPair};
+use orca_whirlpools_client::{TickArray, Whirlpool};
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+
+use crate::clock::unix_now;
+use crate::config::{HeatmapConfig, PoolConfig};
+use crate::programs::{DLMM_PROGRAM_ID, WHIRLPOOL_PROGRAM_ID};
+use crate::rpc::RpcProvider;
+use crate::token;
+
+// Byte offset of the owning pool inside each account type, used as gPA filter
+// TickArray: discriminator (8) | start_tick_index (4) | ticks (88 * 113) | whirlpool
+const TICK_ARRAY_WHIRLPOOL_OFFSET: usize = 8 + 4 + 88 * 113;
+// BinArray: discriminator (8) | index (8) | version (1) | padding (7) | lb_pair
+const BIN_ARRAY_LB_PAIR_OFFSET: usize = 8 + 8 + 1 + 7;
+const BINS_PER_ARRAY: i64 = 70;
+
+// One price range of a pool and the tokens it holds (UI units)
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityBucket {
+    pub index: i64,           // Tick index (Orca) or bin id (Meteora)
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityDistribution {
+    pub source: String,
+    pub pool: String,
+    pub symbol: String,
+    pub timestamp: u64,
+    pub current_price: f64,
+    pub buckets: Vec<LiquidityBucket>,
+}
+
+// Reconstruct the active liquidity between every pair of initialized Whirlpool ticks
+pub async fn fetch_whirlpool_distribution(
+    rpc_client: &RpcClient,
+    pool: &PoolConfig,
+) -> anyhow::Result<LiquidityDistribution> {
+    let address = pool.pubkey()?;
+    let whirlpool = Whirlpool::from_bytes(&rpc_client.get_account_data(&address).await?)?;
+    let (decimals_a, decimals_b) =
+        fetch_mint_decimals(rpc_client, &whirlpool.token_mint_a, &whirlpool.token_mint_b).await?;
+    let decimal_adjustment = 10_f64.powi(decimals_a as i32 - decimals_b as i32);
+
+    let accounts = fetch_program_accounts(rpc_client, &WHIRLPOOL_PROGRAM_ID, TICK_ARRAY_WHIRLPOOL_OFFSET, &address).await?;
+
+    let mut ticks: Vec<(i32, i128)> = Vec::new();
+    for (_, account) in accounts {
+        let Ok(tick_array) = TickArray::from_bytes(&account.data) else {
+            continue;
+        };
+        for (i, tick) in tick_array.ticks.iter().enumerate() {
+            if tick.initialized {
+                let tick_index = tick_array.start_tick_index + i as i32 * whirlpool.tick_spacing as i32;
+                ticks.push((tick_index, tick.liquidity_net));
+            }
+        }
+    }
+    ticks.sort_by_key(|(tick_index, _)| *tick_index);
+
+    let sqrt_price_current = whirlpool.sqrt_price as f64 / (1u128 << 64) as f64;
+    let mut liquidity: f64 = 0.0;
+    let mut buckets = Vec::with_capacity(ticks.len());
+
+    for window in ticks.windows(2) {
+        let (lower_tick, liquidity_net) = window[0];
+        let (upper_tick, _) = window[1];
+        liquidity += liquidity_net as f64;
+        if liquidity <= 0.0 {
+            continue;
+        }
+
+        let sqrt_lower = 1.0001_f64.powf(lower_tick as f64 / 2.0);
+        let sqrt_upper = 1.0001_f64.powf(upper_tick as f64 / 2.0);
+
+        // Token amounts held by liquidity L in [lower, upper] at the current price
+        let sqrt_clamped = sqrt_price_current.clamp(sqrt_lower, sqrt_upper);
+        let amount_a = liquidity * (1.0 / sqrt_clamped - 1.0 / sqrt_upper);
+        let amount_b = liquidity * (sqrt_clamped - sqrt_lower);
+
+        buckets.push(LiquidityBucket {
+            index: lower_tick as i64,
+            price_lower: sqrt_lower.powi(2) * decimal_adjustment,
+            price_upper: sqrt_upper.powi(2) * decimal_adjustment,
+            base_amount: amount_a / 10_f64.powi(decimals_a as i32),
+            quote_amount: amount_b / 10_f64.powi(decimals_b as i32),
+        });
+    }
+
+    Ok(LiquidityDistribution {
+        source: "Orca".to_string(),
+        pool: pool.address.clone(),
+        symbol: pool.symbol.clone(),
+        timestamp: unix_now(),
+        current_price: sqrt_price_current.powi(2) * decimal_adjustment,
+        buckets,
+    })
+}
+
+// Read the token amounts of every non-empty DLMM bin
+pub async fn fetch_dlmm_distribution(
+    rpc_client: &RpcClient,
+    pool: &PoolConfig,
+) -> anyhow::Result<LiquidityDistribution> {
+    let address = pool.pubkey()?;
+    let lb_pair = <LbPair as CarbonDeserialize>::deserialize(&rpc_client.get_account_data(&address).await?)
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora DLMM data"))?;
+    let (decimals_x, decimals_y) =
+        fetch_mint_decimals(rpc_client, &lb_pair.token_x_mint, &lb_pair.token_y_mint).await?;
+
+    let bin_price = |bin_id: i64| {
+        (1.0 + lb_pair.bin_step as f64 / 10_000.0).powf(bin_id as f64)
+            * 10_f64.powi(decimals_x as i32 - decimals_y as i32)
+    };
+
+    let accounts = fetch_program_accounts(rpc_client, &DLMM_PROGRAM_ID, BIN_ARRAY_LB_PAIR_OFFSET, &address).await?;
+
+    let mut buckets = Vec::new();
+    for (_, account) in accounts {
+        let Some(bin_array) = <BinArray as CarbonDeserialize>::deserialize(&account.data) else {
+            continue;
+        };
+        for (i, bin) in bin_array.bins.iter().enumerate() {
+            if bin.amount_x == 0 && bin.amount_y == 0 {
+                continue;
+            }
+            let bin_id = bin_array.index * BINS_PER_ARRAY + i as i64;
+            buckets.push(LiquidityBucket {
+                index: bin_id,
+                price_lower: bin_price(bin_id),
+                price_upper: bin_price(bin_id + 1),
+                base_amount: bin.amount_x as f64 / 10_f64.powi(decimals_x as i32),
+                quote_amount: bin.amount_y as f64 / 10_f64.powi(decimals_y as i32),
+            });
+        }
+    }
+    buckets.sort_by_key(|bucket| bucket.index);
+
+    Ok(LiquidityDistribution {
+        source: "Meteora".to_string(),
+        pool: pool.address.clone(),
+        symbol: pool.symbol.clone(),
+        timestamp: unix_now(),
+        current_price: bin_price(lb_pair.active_id as i64),
+        buckets,
+    })
+}
+
+// Periodically write one compact JSON snapshot per watched Orca/Meteora pool
+pub async fn run_exporter(config: HeatmapConfig, orca_pools: Vec<PoolConfig>, meteora_pools: Vec<PoolConfig>, rpc: RpcProvider) {
+    let rpc_client = rpc.client(CommitmentConfig::default());
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+
+    if let Err(e) = tokio::fs::create_dir_all(&config.output_dir).await {
+        tracing::error!(error = %e, output_dir = %config.output_dir.display(), "failed to create heatmap directory");
+        return;
+    }
+
+    loop {
+        interval.tick().await;
+
+        for pool in &orca_pools {
+            match fetch_whirlpool_distribution(&rpc_client, pool).await {
+                Ok(distribution) => write_snapshot(&config.output_dir, &distribution).await,
+                Err(e) => tracing::error!(source = "Orca", symbol = %pool.symbol, error = %e, "heatmap fetch failed"),
+            }
+        }
+
+        for pool in &meteora_pools {
+            match fetch_dlmm_distribution(&rpc_client, pool).await {
+                Ok(distribution) => write_snapshot(&config.output_dir, &distribution).await,
+                Err(e) => tracing::error!(source = "Meteora", symbol = %pool.symbol, error = %e, "heatmap fetch failed"),
+            }
+        }
+    }
+}
+
+async fn write_snapshot(output_dir: &Path, distribution: &LiquidityDistribution) {
+    let path = output_dir.join(format!(
+        "{}-{}-{}.json",
+        distribution.source.to_lowercase(),
+        distribution.pool,
+        distribution.timestamp
+    ));
+
+    let result = match serde_json::to_vec(distribution) {
+        Ok(bytes) => tokio::fs::write(&path, bytes).await.map_err(anyhow::Error::from),
+        Err(e) => Err(e.into()),
+    };
+
+    match result {
+        Ok(()) => tracing::info!(source = %distribution.source, symbol = %distribution.symbol, buckets = distribution.buckets.len(), path = %path.display(), "wrote heatmap"),
+        Err(e) => tracing::error!(error = %e, path = %path.display(), "failed to write heatmap"),
+    }
+}
+
+async fn fetch_program_accounts(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    pool_offset: usize,
+    pool: &Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, solana_sdk::account::Account)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(pool_offset, pool.as_ref()))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    Ok(rpc_client.get_program_accounts_with_config(program_id, config).await?)
+}
+
+pub async fn fetch_mint_decimals(rpc_client: &RpcClient, mint_a: &Pubkey, mint_b: &Pubkey) -> anyhow::Result<(u8, u8)> {
+    let mints = rpc_client.get_multiple_accounts(&[*mint_a, *mint_b]).await?;
+    let [Some(mint_a), Some(mint_b)] = mints.as_slice() else {
+        return Err(anyhow::anyhow!("Mint accounts not found"));
+    };
+
+    Ok((token::mint_decimals(&mint_a.data)?, token::mint_decimals(&mint_b.data)?))
+}
@@ -0,0 +1,56 @@
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_request::RpcError;
+use thiserror::Error;
+
+use crate::rpc;
+
+pub type Result<T, E = WatcherError> = std::result::Result<T, E>;
+
+// What went wrong inside a monitor, by kind rather than by message, so callers
+// can tell failures worth retrying from ones that won't go away
+#[derive(Debug, Error)]
+pub enum WatcherError {
+    // A request that failed on every endpoint or that the node rejected, or a
+    // Geyser stream that closed
+    #[error("{0}")]
+    RpcError(String),
+    // Account data that doesn't have the layout the monitor reads
+    #[error("{0}")]
+    DecodeError(String),
+    // An account that was read fine but is too old to publish
+    #[error("{account} is stale: {reason}")]
+    StaleAccount { account: String, reason: String },
+    // Every endpoint answered 429; the provider's Retry-After says when to come back
+    #[error("{0}")]
+    RateLimited(String),
+    // A configured account that isn't what its section expects, e.g. a
+    // stable-swap pool under [meteora_amm]
+    #[error("{0}")]
+    ConfigError(String),
+}
+
+impl WatcherError {
+    pub fn decode(error: impl std::fmt::Display) -> Self {
+        WatcherError::DecodeError(error.to_string())
+    }
+
+    // Worth retrying after a backoff. Data that doesn't decode and accounts that
+    // don't belong in their section stay that way, so the monitor drops the pool.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            WatcherError::RpcError(_) | WatcherError::StaleAccount { .. } | WatcherError::RateLimited(_) => true,
+            WatcherError::DecodeError(_) | WatcherError::ConfigError(_) => false,
+        }
+    }
+}
+
+impl From<ClientError> for WatcherError {
+    fn from(error: ClientError) -> Self {
+        match error.kind() {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code: rpc::RATE_LIMITED, .. }) => {
+                WatcherError::RateLimited(error.to_string())
+            }
+            _ => WatcherError::RpcError(error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+use crate::alerts::{Alert, AlertKind};
+use crate::clock::unix_millis;
+use crate::config::EventLogConfig;
+use crate::health::HealthEvent;
+
+// Operational events (restarts, RPC failovers, pauses, fired alerts, exits) as
+// one JSON object per line with a stable dotted `event` name and a `severity`,
+// for log pipelines that alert on them centrally. The console output stays as
+// it is; this is a separate, machine-first stream.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+static SINK: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+// Open the event log if it's enabled; until then, and without it, emit() does nothing
+pub fn init(config: &EventLogConfig) -> anyhow::Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let writer: Box<dyn Write + Send> = match &config.path {
+        Some(path) => Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| anyhow::anyhow!("Failed to open event log {}: {}", path.display(), e))?,
+        ),
+        None => Box::new(std::io::stderr()),
+    };
+    let _ = SINK.set(Mutex::new(writer));
+    emit("process.started", Severity::Info, json!({ "version": env!("CARGO_PKG_VERSION") }));
+    Ok(())
+}
+
+// Write one event, `fields` (an object) merged in after the common keys
+pub fn emit(event: &str, severity: Severity, fields: Value) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+
+    let mut record = Map::new();
+    record.insert("ts".to_string(), json!(unix_millis()));
+    record.insert("service".to_string(), json!(env!("CARGO_PKG_NAME")));
+    record.insert("event".to_string(), json!(event));
+    record.insert("severity".to_string(), json!(severity));
+    if let Value::Object(fields) = fields {
+        record.extend(fields);
+    }
+    let Ok(line) = serde_json::to_string(&record) else {
+        return;
+    };
+
+    // Unbuffered, so the last events before a crash make it out
+    let mut sink = sink.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _ = writeln!(sink, "{}", line);
+    let _ = sink.flush();
+}
+
+// Log every health event and fired alert
+pub async fn run(mut health_rx: broadcast::Receiver<HealthEvent>, mut alerts_rx: broadcast::Receiver<Alert>) {
+    loop {
+        tokio::select! {
+            event = health_rx.recv() => match event {
+                Ok(event) => {
+                    let (name, severity) = health_event(&event);
+                    emit(name, severity, fields(&event));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    emit("event_log.lagged", Severity::Warning, json!({ "bus": "health", "skipped": skipped }));
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            alert = alerts_rx.recv() => match alert {
+                Ok(alert) => emit("alert.fired", alert_severity(alert.kind), fields(&alert)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    emit("event_log.lagged", Severity::Warning, json!({ "bus": "alerts", "skipped": skipped }));
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+// Event names are part of the log's contract: add new ones, never rename
+fn health_event(event: &HealthEvent) -> (&'static str, Severity) {
+    match event {
+        HealthEvent::Reconnecting { .. } => ("monitor.restarting", Severity::Warning),
+        HealthEvent::Degraded { .. } => ("monitor.degraded", Severity::Warning),
+        HealthEvent::PoolDropped { .. } => ("pool.dropped", Severity::Error),
+        HealthEvent::Stopped { .. } => ("monitor.stopped", Severity::Error),
+        HealthEvent::Stale { .. } => ("source.stale", Severity::Warning),
+        HealthEvent::Recovered { .. } => ("monitor.recovered", Severity::Info),
+        HealthEvent::Panicked { .. } => ("task.panicked", Severity::Error),
+        HealthEvent::Stalled { .. } => ("task.stalled", Severity::Error),
+        HealthEvent::Paused { .. } => ("source.paused", Severity::Info),
+        HealthEvent::Resumed { .. } => ("source.resumed", Severity::Info),
+        HealthEvent::Ready { .. } => ("watcher.ready", Severity::Info),
+        HealthEvent::DecoderMismatch { .. } => ("decoder.mismatch", Severity::Error),
+        HealthEvent::ProgramUpgraded { .. } => ("program.upgraded", Severity::Critical),
+    }
+}
+
+fn alert_severity(kind: AlertKind) -> Severity {
+    match kind {
+        AlertKind::ProgramUpgraded => Severity::Critical,
+        AlertKind::ReconnectStorm | AlertKind::DecoderMismatch => Severity::Error,
+        AlertKind::StaleData | AlertKind::OracleDeviation | AlertKind::LatencyBudget => Severity::Warning,
+        AlertKind::PriceMove | AlertKind::SpreadWidened | AlertKind::WalletActivity | AlertKind::HolderMoved => Severity::Info,
+    }
+}
+
+// The event's own fields, without the serde tag the event name replaces
+fn fields(event: &impl Serialize) -> Value {
+    let mut value = serde_json::to_value(event).unwrap_or(Value::Null);
+    if let Value::Object(fields) = &mut value {
+        fields.remove("type");
+    }
+    value
+}
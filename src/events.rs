@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::best_route::BestRoute;
+use crate::checkpoint::Checkpoint;
+use crate::depth::DepthUpdate;
+use crate::derived::DerivedValue;
+use crate::health::HealthEvent;
+use crate::jupiter::AggregatorQuote;
+use crate::liquidity::LiquidityChanged;
+use crate::migration::MigrationRate;
+use crate::raydium::PriceUpdate;
+use crate::sanity::PriceAnomaly;
+use crate::rewards::{IncentiveApr, RewardEmissions};
+use crate::schema;
+use crate::stats::StatsUpdate;
+use crate::swaps::SwapEvent;
+use crate::twap::TwapUpdate;
+
+// Everything published on the market bus. Consumers match the variants they
+// care about and skip the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum MarketEvent {
+    Price(PriceUpdate),
+    Liquidity(LiquidityChanged),
+    Swap(SwapEvent),
+    Depth(DepthUpdate),
+    Route(BestRoute),
+    Aggregator(AggregatorQuote),
+    Derived(DerivedValue),
+    Migration(MigrationRate),
+    Rewards(RewardEmissions),
+    Incentive(IncentiveApr),
+    Twap(TwapUpdate),
+    Checkpoint(Checkpoint),
+    Stats(StatsUpdate),
+    // Published by the sanity filter in place of, or next to, an outlying price
+    Anomaly(PriceAnomaly),
+    // Mirrored from the health bus, which the supervisors keep publishing to
+    Health(HealthEvent),
+}
+
+impl MarketEvent {
+    // The `event` tag the variant serializes with
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MarketEvent::Price(_) => "price",
+            MarketEvent::Liquidity(_) => "liquidity",
+            MarketEvent::Swap(_) => "swap",
+            MarketEvent::Depth(_) => "depth",
+            MarketEvent::Route(_) => "route",
+            MarketEvent::Aggregator(_) => "aggregator",
+            MarketEvent::Derived(_) => "derived",
+            MarketEvent::Migration(_) => "migration",
+            MarketEvent::Rewards(_) => "rewards",
+            MarketEvent::Incentive(_) => "incentive",
+            MarketEvent::Twap(_) => "twap",
+            MarketEvent::Checkpoint(_) => "checkpoint",
+            MarketEvent::Stats(_) => "stats",
+            MarketEvent::Anomaly(_) => "anomaly",
+            MarketEvent::Health(_) => "health",
+        }
+    }
+
+    // The event as JSON for external sinks, with price updates shaped as the
+    // given streaming schema version
+    pub fn to_json(&self, version: u32) -> serde_json::Result<String> {
+        match self {
+            MarketEvent::Price(update) => {
+                let mut value = schema::price_update(update, version)?;
+                if let serde_json::Value::Object(fields) = &mut value {
+                    fields.insert("event".to_string(), self.kind().into());
+                }
+                Ok(value.to_string())
+            }
+            event => serde_json::to_string(event),
+        }
+    }
+}
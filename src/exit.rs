@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use crate::clock::unix_now;
+use crate::event_log::{self, Severity};
+
+// Process exit codes, so orchestration tooling can tell failure modes apart
+// without parsing logs. Anything but a clean shutdown also writes a one-line
+// JSON report as the last line on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    Shutdown,            // 0: Ctrl+C, a recorded session ended or a one-shot command finished
+    ConfigError,         // 2: config file missing, unparsable or invalid, or a bad command line
+    RpcUnreachable,      // 3: no configured RPC endpoint answered at startup
+    MonitorsDead,        // 4: every monitor kept failing without producing data
+    RecordingFailed,     // 5: a session bundle couldn't be written
+    QueryFailed,         // 6: a `sql` query or `simulate` run failed, or `repl` couldn't reach the API
+    SnapshotIncomplete,  // 7: `snapshot` timed out before every pool reported a price
+    Panic,               // 101: same code Rust uses for an unhandled panic
+}
+
+impl ExitReason {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitReason::Shutdown => 0,
+            ExitReason::ConfigError => 2,
+            ExitReason::RpcUnreachable => 3,
+            ExitReason::MonitorsDead => 4,
+            ExitReason::RecordingFailed => 5,
+            ExitReason::QueryFailed => 6,
+            ExitReason::SnapshotIncomplete => 7,
+            ExitReason::Panic => 101,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FailureReport<'a> {
+    reason: ExitReason,
+    exit_code: i32,
+    message: &'a str,
+    timestamp: u64,
+}
+
+// Message and location of the most recent panic on any thread, picked up when
+// a task's JoinHandle reports that it panicked
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    // (message with location, backtrace) of the last panic on this thread, for
+    // the supervisor catching it on the same thread right after the unwind
+    static THREAD_PANIC: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+pub fn exit(reason: ExitReason, message: &str) -> ! {
+    let severity = if reason == ExitReason::Shutdown { Severity::Info } else { Severity::Critical };
+    event_log::emit("process.exited", severity, serde_json::json!({
+        "reason": reason,
+        "exit_code": reason.code(),
+        "message": message,
+    }));
+    if reason != ExitReason::Shutdown {
+        let report = FailureReport {
+            reason,
+            exit_code: reason.code(),
+            message,
+            timestamp: unix_now(),
+        };
+        // Bypasses the display settings: the report is for machines, byte for byte
+        if let Ok(json) = serde_json::to_string(&report) {
+            eprintln!("{}", json);
+        }
+    }
+    std::process::exit(reason.code())
+}
+
+// Keep the default panic output, remember the message for task panics, and
+// turn a panic on the main thread into a Panic exit with a report
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let message = info.to_string();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        THREAD_PANIC.with(|panic| *panic.borrow_mut() = Some((message.clone(), backtrace)));
+        *LAST_PANIC.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(message.clone());
+        if std::thread::current().name() == Some("main") {
+            exit(ExitReason::Panic, &message);
+        }
+    }));
+}
+
+// Message and backtrace of the panic that just unwound on this thread
+pub fn take_thread_panic() -> Option<(String, String)> {
+    THREAD_PANIC.with(|panic| panic.borrow_mut().take())
+}
+
+// Exit reason for a supervised task that should have run forever
+pub fn task_ended(name: &str, result: Result<(), tokio::task::JoinError>) -> (ExitReason, String) {
+    match result {
+        Err(e) if e.is_panic() => {
+            let message = LAST_PANIC
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .take()
+                .unwrap_or_else(|| e.to_string());
+            (ExitReason::Panic, format!("{} task panicked: {}", name, message))
+        }
+        Err(e) => (ExitReason::MonitorsDead, format!("{} task failed: {}", name, e)),
+        Ok(()) => (ExitReason::MonitorsDead, format!("{} task ended", name)),
+    }
+}
@@ -0,0 +1,58 @@
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::config::ScheduleConfig;
+use crate::metrics;
+
+// Hashes of the account data behind each pool as of the last fetch. At a 2s
+// poll most ticks return the same bytes for the pool and its vaults; those
+// pools are neither decoded again nor published, except once per keep-alive
+// so consumers watching for stale data don't mistake a quiet pool for a dead one.
+pub struct Fingerprints {
+    source: &'static str,
+    enabled: bool,
+    keepalive: Duration,
+    hashes: HashMap<Pubkey, u64>,
+    published: HashMap<Pubkey, Instant>,
+}
+
+impl Fingerprints {
+    pub fn new(source: &'static str, config: &ScheduleConfig) -> Self {
+        Self {
+            source,
+            enabled: config.skip_unchanged,
+            keepalive: Duration::from_secs(config.keepalive_secs),
+            hashes: HashMap::new(),
+            published: HashMap::new(),
+        }
+    }
+
+    // Record the account's data; true when it's the same as the last fetch returned
+    pub fn unchanged(&mut self, address: &Pubkey, data: &[u8]) -> bool {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let hash = hasher.finish();
+        let previous = self.hashes.insert(*address, hash);
+        self.enabled && previous == Some(hash)
+    }
+
+    // Whether to publish the pool this tick: whenever anything behind it
+    // changed, otherwise once per keep-alive
+    pub fn should_publish(&mut self, pool: &Pubkey, unchanged: bool) -> bool {
+        let now = Instant::now();
+        let recent = self.published.get(pool).is_some_and(|published| now.duration_since(*published) < self.keepalive);
+        if unchanged && recent {
+            metrics::inc_unchanged_skip(self.source);
+            return false;
+        }
+        self.published.insert(*pool, now);
+        true
+    }
+}
+
+// Data of an account that may be missing, empty if it is
+pub fn account_data(account: &Option<Account>) -> &[u8] {
+    account.as_ref().map_or(&[][..], |account| account.data.as_slice())
+}
@@ -0,0 +1,148 @@
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::config::GeyserConfig;
+use crate::error::{Result, WatcherError};
+use crate::metrics;
+
+// Pool account updates pushed by a Yellowstone gRPC (Geyser) endpoint, used by
+// monitors configured with `backend = "geyser"` instead of polling over RPC.
+// The gRPC client itself is only compiled with `--features geyser`.
+pub struct AccountUpdates {
+    // (address, account, slot it was written in)
+    rx: mpsc::Receiver<(Pubkey, Account, u64)>,
+    // Highest slot seen on the stream, i.e. the cluster tip as the endpoint sees it
+    tip: Arc<AtomicU64>,
+}
+
+impl AccountUpdates {
+    pub fn subscribe(config: GeyserConfig, source: &'static str, accounts: Vec<Pubkey>) -> Self {
+        let (tx, rx) = mpsc::channel(1024);
+        let tip = Arc::new(AtomicU64::new(0));
+
+        let stream_tip = tip.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = stream_accounts(&config, &accounts, &tx, &stream_tip).await {
+                    tracing::error!(source = %source, error = %e, "Geyser stream error");
+                }
+                if tx.is_closed() {
+                    break;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                metrics::inc_reconnect(source);
+                tracing::info!(source = %source, "reconnecting Geyser stream");
+            }
+        });
+
+        Self { rx, tip }
+    }
+
+    // Wait for the next change, then drain whatever else is already queued,
+    // keeping only the latest state of each account. Returns the newest slot
+    // in the batch along with the accounts.
+    pub async fn next_batch(&mut self) -> Result<(u64, Vec<(Pubkey, Account)>)> {
+        let Some((address, account, mut slot)) = self.rx.recv().await else {
+            return Err(WatcherError::RpcError("Geyser stream closed".to_string()));
+        };
+
+        let mut latest = HashMap::from([(address, account)]);
+        while let Ok((address, account, account_slot)) = self.rx.try_recv() {
+            latest.insert(address, account);
+            slot = slot.max(account_slot);
+        }
+
+        Ok((slot, latest.into_iter().collect()))
+    }
+
+    pub fn tip_slot(&self) -> u64 {
+        self.tip.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "geyser")]
+async fn stream_accounts(
+    config: &GeyserConfig,
+    accounts: &[Pubkey],
+    tx: &mpsc::Sender<(Pubkey, Account, u64)>,
+    tip: &AtomicU64,
+) -> anyhow::Result<()> {
+    use futures::{SinkExt, StreamExt};
+    use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+    use yellowstone_grpc_proto::prelude::{
+        subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterSlots, SubscribeRequestPing,
+    };
+
+    let mut client = GeyserGrpcClient::build_from_shared(config.endpoint.clone())?
+        .x_token(config.x_token.clone())?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "pools".to_string(),
+            SubscribeRequestFilterAccounts {
+                account: accounts.iter().map(ToString::to_string).collect(),
+                ..Default::default()
+            },
+        )]),
+        slots: HashMap::from([("tip".to_string(), SubscribeRequestFilterSlots::default())]),
+        commitment: Some(CommitmentLevel::Confirmed as i32),
+        ..Default::default()
+    };
+    let (mut subscribe_tx, mut stream) = client.subscribe_with_request(Some(request)).await?;
+
+    while let Some(message) = stream.next().await {
+        match message?.update_oneof {
+            Some(UpdateOneof::Account(update)) => {
+                let Some(info) = update.account else {
+                    continue;
+                };
+                let account = Account {
+                    lamports: info.lamports,
+                    data: info.data,
+                    owner: Pubkey::try_from(info.owner.as_slice())
+                        .map_err(|_| anyhow::anyhow!("Invalid account owner in Geyser update"))?,
+                    executable: info.executable,
+                    rent_epoch: info.rent_epoch,
+                };
+                let address = Pubkey::try_from(info.pubkey.as_slice())
+                    .map_err(|_| anyhow::anyhow!("Invalid account key in Geyser update"))?;
+
+                if tx.send((address, account, update.slot)).await.is_err() {
+                    return Ok(());  // Monitor dropped the subscription
+                }
+            }
+            Some(UpdateOneof::Slot(update)) => {
+                tip.fetch_max(update.slot, Ordering::Relaxed);
+            }
+            // Providers close idle streams unless pings are answered
+            Some(UpdateOneof::Ping(_)) => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+            _ => {}
+        }
+    }
+
+    Err(anyhow::anyhow!("Stream ended"))
+}
+
+#[cfg(not(feature = "geyser"))]
+async fn stream_accounts(
+    _config: &GeyserConfig,
+    _accounts: &[Pubkey],
+    _tx: &mpsc::Sender<(Pubkey, Account, u64)>,
+    _tip: &AtomicU64,
+) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("built without the `geyser` feature"))
+}
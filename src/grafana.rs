@@ -0,0 +1,156 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast;
+
+use crate::alerts::Alert;
+use crate::clock::unix_millis;
+use crate::config::GrafanaConfig;
+use crate::events::MarketEvent;
+use crate::health::HealthEvent;
+
+#[derive(Debug, Deserialize)]
+struct Created {
+    id: u64,
+}
+
+// Grafana annotations for notable events, so they show up as markers on the
+// price and metric dashboards: every fired alert, pools that only start
+// producing prices after warm-up, and source outages as a region from the
+// first failed fetch to the recovery
+pub struct GrafanaAnnotator {
+    client: reqwest::Client,
+    config: GrafanaConfig,
+    // Pools with a price so far; before warm-up ends they're just starting up
+    seen: HashSet<String>,
+    warmed_up: bool,
+    // source -> annotation of its ongoing outage, closed on recovery
+    outages: HashMap<String, u64>,
+}
+
+impl GrafanaAnnotator {
+    pub fn new(config: GrafanaConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+            seen: HashSet::new(),
+            warmed_up: false,
+            outages: HashMap::new(),
+        }
+    }
+
+    pub async fn run(
+        mut self,
+        mut alerts_rx: broadcast::Receiver<Alert>,
+        mut health_rx: broadcast::Receiver<HealthEvent>,
+        mut market_rx: broadcast::Receiver<MarketEvent>,
+    ) {
+        tracing::info!(url = %self.config.url, "annotating Grafana");
+
+        loop {
+            tokio::select! {
+                alert = alerts_rx.recv() => match alert {
+                    Ok(alert) => {
+                        let kind = serde_json::to_value(alert.kind).ok().and_then(|kind| kind.as_str().map(str::to_string)).unwrap_or_default();
+                        let mut tags = vec!["alert".to_string(), kind, alert.symbol.clone()];
+                        tags.extend(alert.source.clone());
+                        if let Err(e) = self.create(&alert.message, tags, alert.timestamp * 1000).await {
+                            tracing::error!(error = %e, "Grafana alert annotation failed");
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "Grafana annotator lagged, dropped alerts");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                event = health_rx.recv() => match event {
+                    Ok(event) => self.on_health(event).await,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                event = market_rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => {
+                        if self.seen.insert(update.pool.clone()) && self.warmed_up {
+                            let text = format!("🆕 {} on {} started producing prices ({})", update.symbol, update.source, update.pool);
+                            let tags = vec!["pool".to_string(), update.source.clone(), update.symbol.clone()];
+                            if let Err(e) = self.create(&text, tags, unix_millis()).await {
+                                tracing::error!(error = %e, "Grafana pool annotation failed");
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+
+    async fn on_health(&mut self, event: HealthEvent) {
+        match event {
+            HealthEvent::Ready { .. } => self.warmed_up = true,
+            HealthEvent::Degraded { source, error, .. } if !self.outages.contains_key(&source) => {
+                let text = format!("⚠️ {} outage: {}", source, error);
+                self.open_outage(source, &text).await;
+            }
+            HealthEvent::Stale { source, silent_secs, .. } if !self.outages.contains_key(&source) => {
+                let text = format!("🔇 {} outage: no price update for {}s", source, silent_secs);
+                self.open_outage(source, &text).await;
+            }
+            HealthEvent::Recovered { source, .. } => {
+                if let Some(id) = self.outages.remove(&source) {
+                    if let Err(e) = self.close(id, unix_millis()).await {
+                        tracing::error!(error = %e, "Grafana outage annotation failed");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Opens a region annotation, closed when the source recovers
+    async fn open_outage(&mut self, source: String, text: &str) {
+        match self.create(text, vec!["outage".to_string(), source.clone()], unix_millis()).await {
+            Ok(id) => {
+                self.outages.insert(source, id);
+            }
+            Err(e) => tracing::error!(error = %e, "Grafana outage annotation failed"),
+        }
+    }
+
+    // POST /api/annotations, on the configured dashboard or organization-wide
+    async fn create(&self, text: &str, tags: Vec<String>, time_ms: u64) -> anyhow::Result<u64> {
+        let mut body = json!({
+            "time": time_ms,
+            "tags": self.config.tags.iter().cloned().chain(tags).collect::<Vec<_>>(),
+            "text": text,
+        });
+        if let Some(dashboard_uid) = &self.config.dashboard_uid {
+            body["dashboardUID"] = json!(dashboard_uid);
+        }
+
+        let created: Created = self
+            .client
+            .post(format!("{}/api/annotations", self.config.url.trim_end_matches('/')))
+            .bearer_auth(&self.config.api_token)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(created.id)
+    }
+
+    // Turn a point annotation into a region ending now
+    async fn close(&self, id: u64, time_end_ms: u64) -> anyhow::Result<()> {
+        self.client
+            .patch(format!("{}/api/annotations/{}", self.config.url.trim_end_matches('/'), id))
+            .bearer_auth(&self.config.api_token)
+            .json(&json!({ "timeEnd": time_end_ms }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+
+use crate::clock::unix_now;
+use crate::config::PoolConfig;
+use crate::error::WatcherError;
+use crate::events::MarketEvent;
+
+// Operational events from the monitor supervisors, separate from market data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthEvent {
+    // A monitor failed and is about to be restarted
+    Reconnecting { source: String, error: String, timestamp: u64 },
+    // A monitor's fetches keep failing and it is backing off before the next one
+    Degraded { source: String, error: String, failures: u32, retry_in_ms: u64, timestamp: u64 },
+    // A pool whose account can never be read as configured was dropped; the rest of its source keeps running
+    PoolDropped { source: String, symbol: String, pool: String, error: String, timestamp: u64 },
+    // A monitor task ended for good, e.g. once all of its pools were dropped; the other sources keep running
+    Stopped { source: String, reason: String, timestamp: u64 },
+    // A supervised task panicked; `restarts` counts earlier ones within the policy window
+    Panicked { source: String, message: String, backtrace: String, restarts: u32, timestamp: u64 },
+    // The watchdog found a supervised task making no progress and restarted it
+    Stalled { source: String, diagnostic: String, restarts: u32, timestamp: u64 },
+    // A source went without a price update for longer than [supervisor] stale_source_secs
+    Stale { source: String, silent_secs: u64, timestamp: u64 },
+    // A degraded monitor fetched successfully again, or a stale source published again
+    Recovered { source: String, timestamp: u64 },
+    // Polling of a source was paused or resumed through the control API
+    Paused { source: String, timestamp: u64 },
+    Resumed { source: String, timestamp: u64 },
+    // Enough pools produced a first valid price at startup; `missing` lists the rest
+    Ready { pools_ready: usize, pools_total: usize, missing: Vec<String>, timestamp: u64 },
+    // The decoder self-check read a pool field differently from the RPC node's own parser
+    DecoderMismatch { source: String, symbol: String, pool: String, field: String, decoded: String, parsed: String, slot: u64, timestamp: u64 },
+    // A watched DEX program was redeployed; its account layouts may have changed
+    ProgramUpgraded { source: String, program: String, previous_slot: u64, slot: u64, timestamp: u64 },
+}
+
+impl HealthEvent {
+    pub fn reconnecting(source: &str, error: &WatcherError) -> Self {
+        HealthEvent::Reconnecting {
+            source: source.to_string(),
+            error: error.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn degraded(source: &str, error: &WatcherError, failures: u32, retry_in: Duration) -> Self {
+        HealthEvent::Degraded {
+            source: source.to_string(),
+            error: error.to_string(),
+            failures,
+            retry_in_ms: retry_in.as_millis() as u64,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn pool_dropped(source: &str, pool: &PoolConfig, error: &WatcherError) -> Self {
+        HealthEvent::PoolDropped {
+            source: source.to_string(),
+            symbol: pool.symbol.clone(),
+            pool: pool.address.clone(),
+            error: error.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn stopped(source: &str, reason: &str) -> Self {
+        HealthEvent::Stopped {
+            source: source.to_string(),
+            reason: reason.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn panicked(source: &str, message: &str, backtrace: &str, restarts: u32) -> Self {
+        HealthEvent::Panicked {
+            source: source.to_string(),
+            message: message.to_string(),
+            backtrace: backtrace.to_string(),
+            restarts,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn stalled(source: &str, diagnostic: &str, restarts: u32) -> Self {
+        HealthEvent::Stalled {
+            source: source.to_string(),
+            diagnostic: diagnostic.to_string(),
+            restarts,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn stale(source: &str, silent: Duration) -> Self {
+        HealthEvent::Stale {
+            source: source.to_string(),
+            silent_secs: silent.as_secs(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn recovered(source: &str) -> Self {
+        HealthEvent::Recovered {
+            source: source.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn paused(source: &str) -> Self {
+        HealthEvent::Paused {
+            source: source.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn resumed(source: &str) -> Self {
+        HealthEvent::Resumed {
+            source: source.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn ready(pools_ready: usize, pools_total: usize, missing: Vec<String>) -> Self {
+        HealthEvent::Ready {
+            pools_ready,
+            pools_total,
+            missing,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn decoder_mismatch(source: &str, pool: &PoolConfig, field: &str, decoded: &str, parsed: &str, slot: u64) -> Self {
+        HealthEvent::DecoderMismatch {
+            source: source.to_string(),
+            symbol: pool.symbol.clone(),
+            pool: pool.address.clone(),
+            field: field.to_string(),
+            decoded: decoded.to_string(),
+            parsed: parsed.to_string(),
+            slot,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn program_upgraded(source: &str, program: &Pubkey, previous_slot: u64, slot: u64) -> Self {
+        HealthEvent::ProgramUpgraded {
+            source: source.to_string(),
+            program: program.to_string(),
+            previous_slot,
+            slot,
+            timestamp: unix_now(),
+        }
+    }
+}
+
+// Stop watching a pool whose account can never be read as configured, so one
+// bad pool doesn't take the rest of its source down. Returns the event for the
+// monitor to publish once the fetch is done.
+pub fn drop_pool(source: &str, pools: &mut Vec<(Pubkey, PoolConfig)>, address: &Pubkey, error: &WatcherError) -> Option<HealthEvent> {
+    let index = pools.iter().position(|(pool_address, _)| pool_address == address)?;
+    let (_, pool) = pools.remove(index);
+    tracing::error!(source = %source, symbol = %pool.symbol, pool = %address, error = %error, "dropping pool, retrying won't help");
+    Some(HealthEvent::pool_dropped(source, &pool, error))
+}
+
+// Resolves once every one of `sources` has failed since its last price update
+// and stayed silent for `dead_after`, i.e. nothing produces data any more.
+// Returns the last error of each monitor.
+pub async fn all_monitors_dead(
+    sources: Vec<&'static str>,
+    mut updates_rx: broadcast::Receiver<MarketEvent>,
+    mut health_rx: broadcast::Receiver<HealthEvent>,
+    dead_after: Duration,
+) -> String {
+    if sources.is_empty() {
+        return std::future::pending().await;
+    }
+
+    // source -> (last price update, last error since then)
+    let started = Instant::now();
+    let mut state: HashMap<&'static str, (Instant, Option<String>)> =
+        sources.iter().map(|source| (*source, (started, None))).collect();
+    let mut check = tokio::time::interval(Duration::from_secs(5));
+
+    loop {
+        tokio::select! {
+            update = updates_rx.recv() => match update {
+                Ok(MarketEvent::Price(update)) => {
+                    if let Some(entry) = state.get_mut(update.source.as_str()) {
+                        *entry = (Instant::now(), None);
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return "Price channel closed".to_string(),
+            },
+            event = health_rx.recv() => match event {
+                Ok(HealthEvent::Reconnecting { source, error, .. } | HealthEvent::Degraded { source, error, .. }) => {
+                    if let Some(entry) = state.get_mut(source.as_str()) {
+                        entry.1 = Some(error);
+                    }
+                }
+                Ok(HealthEvent::Panicked { source, message, .. }
+                    | HealthEvent::Stalled { source, diagnostic: message, .. }
+                    | HealthEvent::Stopped { source, reason: message, .. }) => {
+                    if let Some(entry) = state.get_mut(source.as_str()) {
+                        entry.1 = Some(message);
+                    }
+                }
+                // A paused source is silent on purpose; its clock restarts on resume
+                Ok(HealthEvent::Paused { source, .. } | HealthEvent::Resumed { source, .. }) => {
+                    if let Some(entry) = state.get_mut(source.as_str()) {
+                        *entry = (Instant::now(), None);
+                    }
+                }
+                Ok(HealthEvent::Stale { .. } | HealthEvent::Recovered { .. } | HealthEvent::Ready { .. } | HealthEvent::DecoderMismatch { .. } | HealthEvent::ProgramUpgraded { .. } | HealthEvent::PoolDropped { .. }) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return "Health channel closed".to_string(),
+            },
+            _ = check.tick() => {
+                let dead = state
+                    .values()
+                    .all(|(last_update, error)| error.is_some() && last_update.elapsed() >= dead_after);
+                if dead {
+                    let mut errors: Vec<String> = state
+                        .iter()
+                        .map(|(source, (_, error))| format!("{}: {}", source, error.as_deref().unwrap_or("")))
+                        .collect();
+                    errors.sort();
+                    return errors.join("; ");
+                }
+            }
+        }
+    }
+}
+
+// Sources the watchdog finds stale, with the Unix time each went stale; read by GET /health
+pub type StaleSources = Arc<RwLock<BTreeMap<String, u64>>>;
+
+// Watchdog over what the monitors produce rather than over their tasks: a
+// monitor can keep looping while publishing nothing, e.g. on a hung RPC call
+// or an error its retry loop swallows. A source without a price update for
+// stale_after gets a Stale health event, and a Recovered one with its next
+// update. Paused sources are silent on purpose and aren't checked.
+pub async fn watch_sources(
+    sources: Vec<&'static str>,
+    stale_after: Duration,
+    mut updates_rx: broadcast::Receiver<MarketEvent>,
+    mut health_rx: broadcast::Receiver<HealthEvent>,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+    stale: StaleSources,
+) {
+    let started = Instant::now();
+    let mut last_update: HashMap<&'static str, Instant> = sources.iter().map(|source| (*source, started)).collect();
+    let mut paused = HashSet::new();
+    // Kept here as well, so price updates don't take the shared lock
+    let mut silent_sources: HashSet<&'static str> = HashSet::new();
+    let mut check = tokio::time::interval(Duration::from_secs(5));
+
+    loop {
+        tokio::select! {
+            update = updates_rx.recv() => match update {
+                Ok(MarketEvent::Price(update)) => {
+                    let Some(last) = last_update.get_mut(update.source.as_str()) else {
+                        continue;
+                    };
+                    *last = Instant::now();
+                    if silent_sources.remove(update.source.as_str()) {
+                        stale.write().await.remove(&update.source);
+                        tracing::info!(source = %update.source, "publishing prices again");
+                        let _ = health_tx.send(HealthEvent::recovered(&update.source));
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            event = health_rx.recv() => match event {
+                Ok(HealthEvent::Paused { source, .. }) => {
+                    paused.insert(source);
+                }
+                // Its clock restarts on resume
+                Ok(HealthEvent::Resumed { source, .. }) => {
+                    paused.remove(&source);
+                    if let Some(last) = last_update.get_mut(source.as_str()) {
+                        *last = Instant::now();
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = check.tick() => {
+                for (source, last) in &last_update {
+                    let silent = last.elapsed();
+                    if silent < stale_after || paused.contains(*source) || silent_sources.contains(source) {
+                        continue;
+                    }
+                    silent_sources.insert(*source);
+                    stale.write().await.insert(source.to_string(), unix_now());
+                    tracing::warn!(source = %source, silent_secs = silent.as_secs(), "no price update");
+                    let _ = health_tx.send(HealthEvent::stale(source, silent));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_lifinity_amm_v2_decoder::accounts::amm::Amm as LifinityAmm;
+use carbon_meteora_dlmm_decoder::accounts::lb_pair::LbPair;
+use carbon_raydium_amm_v4_decoder::accounts::amm_info::AmmInfo as RaydiumAmmInfo;
+use carbon_raydium_clmm_decoder::accounts::pool_state::PoolState as RaydiumClmmPool;
+use orca_whirlpools_client::Whirlpool;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::{HoldersConfig, PoolConfig};
+use crate::metrics;
+use crate::meteora_amm::PoolHeader as MeteoraAmmPool;
+use crate::openbook::MarketHeader as OpenBookMarket;
+use crate::phoenix::MarketHeader;
+use crate::pump::PoolHeader as PumpSwapPool;
+use crate::rpc::RpcProvider;
+use crate::saber::PoolHeader as SaberPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderMint {
+    Base,  // Base token of the pool
+    Lp,    // LP mint (Raydium AMM only; concentrated positions are NFTs)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holder {
+    pub address: String,       // Token account
+    pub amount: f64,           // UI units
+    pub share_percent: f64,    // Of total supply
+}
+
+// A dominant holder whose balance changed since the previous report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderMove {
+    pub address: String,
+    pub previous_amount: f64,
+    pub amount: f64,
+    pub change_percent: f64,
+    pub share_percent: f64,    // Share before the move
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderReport {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub mint: String,
+    pub mint_kind: HolderMint,
+    pub supply: f64,
+    pub top1_share_percent: f64,
+    pub top10_share_percent: f64,
+    pub hhi: f64,              // Herfindahl index over the largest accounts, 0..10000
+    pub largest: Vec<Holder>,
+    pub moves: Vec<HolderMove>,
+    pub timestamp: u64,
+}
+
+// Periodically snapshot the largest token accounts of each pool's base and LP
+// mints. getTokenLargestAccounts returns the top 20 accounts, which is what the
+// concentration figures are computed over.
+pub struct HoldersTracker {
+    rpc_client: RpcClient,
+    config: HoldersConfig,
+    // (source, pool config)
+    pools: Vec<(String, PoolConfig)>,
+    // pool address -> tracked mints, resolved once from the pool account
+    mints: HashMap<String, Vec<(HolderMint, Pubkey)>>,
+    // mint -> token account -> amount at the previous report
+    previous: HashMap<Pubkey, HashMap<String, f64>>,
+}
+
+impl HoldersTracker {
+    pub fn new(config: HoldersConfig, pools: Vec<(String, PoolConfig)>, rpc: &RpcProvider) -> Self {
+        Self {
+            rpc_client: rpc.client(CommitmentConfig::default()),
+            config,
+            pools,
+            mints: HashMap::new(),
+            previous: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self, tx: Arc<broadcast::Sender<HolderReport>>) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(self.config.interval_secs.max(1)));
+
+        loop {
+            interval.tick().await;
+
+            for (source, pool) in self.pools.clone() {
+                let mints = match self.pool_mints(&source, &pool).await {
+                    Ok(mints) => mints,
+                    Err(e) => {
+                        metrics::inc_rpc_error("Holders");
+                        tracing::error!(source = %source, symbol = %pool.symbol, error = %e, "failed to resolve mints");
+                        continue;
+                    }
+                };
+
+                for (mint_kind, mint) in mints {
+                    match self.report(&source, &pool, mint_kind, &mint).await {
+                        Ok(report) => {
+                            let _ = tx.send(report);
+                        }
+                        Err(e) => {
+                            metrics::inc_rpc_error("Holders");
+                            tracing::error!(source = %source, symbol = %pool.symbol, error = %e, "holder snapshot failed");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn pool_mints(&mut self, source: &str, pool: &PoolConfig) -> anyhow::Result<Vec<(HolderMint, Pubkey)>> {
+        if let Some(mints) = self.mints.get(&pool.address) {
+            return Ok(mints.clone());
+        }
+
+        let address = pool.pubkey()?;
+        let data = self.rpc_client.get_account_data(&address).await?;
+        let mints = pool_mints(source, &address, &data)?;
+
+        self.mints.insert(pool.address.clone(), mints.clone());
+        Ok(mints)
+    }
+
+    async fn report(&mut self, source: &str, pool: &PoolConfig, mint_kind: HolderMint, mint: &Pubkey) -> anyhow::Result<HolderReport> {
+        let supply = self.rpc_client.get_token_supply(mint).await?;
+        let supply = supply.ui_amount_string.parse::<f64>()?;
+        let accounts = self.rpc_client.get_token_largest_accounts(mint).await?;
+
+        let share = |amount: f64| if supply > 0.0 { amount / supply * 100.0 } else { 0.0 };
+        let largest: Vec<Holder> = accounts
+            .iter()
+            .map(|account| {
+                let amount = account.amount.ui_amount_string.parse::<f64>().unwrap_or(0.0);
+                Holder {
+                    address: account.address.clone(),
+                    amount,
+                    share_percent: share(amount),
+                }
+            })
+            .collect();
+
+        // A dominant holder "moves" when its balance changes by more than the
+        // threshold, including dropping out of the largest accounts entirely
+        let current: HashMap<String, f64> = largest.iter().map(|holder| (holder.address.clone(), holder.amount)).collect();
+        let mut moves = Vec::new();
+        if let Some(previous) = self.previous.get(mint) {
+            for (address, &previous_amount) in previous {
+                let previous_share = share(previous_amount);
+                if previous_share < self.config.dominant_share_percent || previous_amount <= 0.0 {
+                    continue;
+                }
+                let amount = current.get(address).copied().unwrap_or(0.0);
+                let change_percent = (amount - previous_amount) / previous_amount * 100.0;
+                if change_percent.abs() >= self.config.move_threshold_percent {
+                    moves.push(HolderMove {
+                        address: address.clone(),
+                        previous_amount,
+                        amount,
+                        change_percent,
+                        share_percent: previous_share,
+                    });
+                }
+            }
+        }
+        self.previous.insert(*mint, current);
+
+        Ok(HolderReport {
+            symbol: pool.symbol.clone(),
+            source: source.to_string(),
+            pool: pool.address.clone(),
+            mint: mint.to_string(),
+            mint_kind,
+            supply,
+            top1_share_percent: largest.first().map(|holder| holder.share_percent).unwrap_or(0.0),
+            top10_share_percent: largest.iter().take(10).map(|holder| holder.share_percent).sum(),
+            hhi: largest.iter().map(|holder| holder.share_percent.powi(2)).sum(),
+            largest,
+            moves,
+            timestamp: unix_now(),
+        })
+    }
+}
+
+// Base mint, plus the LP mint where the pool has one, decoded from the pool account
+pub fn pool_mints(source: &str, address: &Pubkey, data: &[u8]) -> anyhow::Result<Vec<(HolderMint, Pubkey)>> {
+    Ok(match source {
+        "Raydium" => {
+            let amm_info = <RaydiumAmmInfo as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium pool {}", address))?;
+            vec![(HolderMint::Base, amm_info.coin_mint), (HolderMint::Lp, amm_info.lp_mint)]
+        }
+        "Raydium CLMM" => {
+            let pool_state = <RaydiumClmmPool as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium CLMM pool {}", address))?;
+            vec![(HolderMint::Base, pool_state.token_mint0)]
+        }
+        "Orca" => vec![(HolderMint::Base, Whirlpool::from_bytes(data)?.token_mint_a)],
+        "Meteora" => {
+            let lb_pair = <LbPair as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora pool {}", address))?;
+            vec![(HolderMint::Base, lb_pair.token_x_mint)]
+        }
+        "Meteora AMM" => {
+            let pool = MeteoraAmmPool::parse(data)?;
+            vec![(HolderMint::Base, pool.token_a_mint), (HolderMint::Lp, pool.lp_mint)]
+        }
+        "Phoenix" => vec![(HolderMint::Base, MarketHeader::parse(data)?.base_mint)],
+        "OpenBook" => vec![(HolderMint::Base, OpenBookMarket::parse(data)?.base_mint)],
+        "Lifinity" => {
+            let amm = <LifinityAmm as CarbonDeserialize>::deserialize(data)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Lifinity pool {}", address))?;
+            vec![(HolderMint::Base, amm.token_a_mint)]
+        }
+        "PumpSwap" => {
+            let pool = PumpSwapPool::parse(data)?;
+            vec![(HolderMint::Base, pool.base_mint), (HolderMint::Lp, pool.lp_mint)]
+        }
+        "Saber" => {
+            let pool = SaberPool::parse(data)?;
+            vec![(HolderMint::Base, pool.token_a_mint), (HolderMint::Lp, pool.pool_mint)]
+        }
+        _ => return Err(anyhow::anyhow!("Unknown source {}", source)),
+    })
+}
@@ -0,0 +1,46 @@
+use iceoryx2::prelude::*;
+use tokio::sync::broadcast;
+
+use crate::events::MarketEvent;
+use crate::shm::TickRecord;
+
+// Zero-copy IPC transport built on iceoryx2 (enabled with `--features iceoryx2`).
+// Local processes open the same service name as publish-subscribe with
+// TickRecord as the payload type (its C layout is documented in shm.rs), and
+// receive samples straight from shared memory without serialization.
+
+pub fn spawn_publisher(service_name: String, rx: broadcast::Receiver<MarketEvent>) {
+    // iceoryx2 ports are not Send, so the publisher lives on its own thread
+    std::thread::spawn(move || {
+        if let Err(e) = run_publisher(&service_name, rx) {
+            tracing::error!(error = %e, "iceoryx2 publisher error");
+        }
+    });
+}
+
+fn run_publisher(service_name: &str, mut rx: broadcast::Receiver<MarketEvent>) -> anyhow::Result<()> {
+    let node = NodeBuilder::new().create::<ipc::Service>()?;
+
+    let service = node
+        .service_builder(&ServiceName::new(service_name)?)
+        .publish_subscribe::<TickRecord>()
+        .open_or_create()?;
+
+    let publisher = service.publisher_builder().create()?;
+    tracing::info!(service_name = %service_name, "publishing ticks over iceoryx2");
+
+    loop {
+        match rx.blocking_recv() {
+            Ok(MarketEvent::Price(price_update)) => {
+                let sample = publisher.loan_uninit()?;
+                let sample = sample.write_payload(TickRecord::from_update(&price_update));
+                sample.send()?;
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped = skipped, "iceoryx2 publisher lagged, skipped updates");
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
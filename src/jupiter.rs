@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::JupiterConfig;
+use crate::events::MarketEvent;
+use crate::quote;
+use crate::raydium::PriceUpdate;
+use crate::routing::Side;
+use crate::rpc::RpcProvider;
+
+// Jupiter's aggregated route for one trade, next to the best single pool
+// quoted with the watcher's own swap models
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatorQuote {
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,                      // Base tokens
+    pub route_price: f64,               // Quote per base over the whole route
+    pub route: Vec<String>,             // AMM of every leg, in route order
+    pub price_impact_pct: f64,          // As reported by Jupiter
+    pub best_pool_source: Option<String>,
+    pub best_pool_price: Option<f64>,   // Execution price on that pool, fee included
+    pub improvement_bps: Option<f64>,   // How much better the route fills; negative when the pool wins
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteResponse {
+    in_amount: String,
+    out_amount: String,
+    price_impact_pct: String,
+    route_plan: Vec<RoutePlanStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutePlanStep {
+    swap_info: SwapInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwapInfo {
+    label: Option<String>,
+}
+
+// Keep the latest update per pool from the bus and every interval quote the
+// configured size both ways through Jupiter and on the watched pools,
+// publishing the comparison on the bus
+pub async fn run(
+    config: JupiterConfig,
+    mut rx: broadcast::Receiver<MarketEvent>,
+    tx: Arc<broadcast::Sender<MarketEvent>>,
+    rpc: RpcProvider,
+) {
+    let rpc_client = rpc.client(CommitmentConfig::confirmed());
+    let http = reqwest::Client::new();
+    let mut latest: HashMap<String, PriceUpdate> = HashMap::new();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    tracing::info!(symbol = %config.symbol, size = %config.size, interval_secs = %config.interval_secs, "comparing Jupiter routes");
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(MarketEvent::Price(update)) if update.symbol.eq_ignore_ascii_case(&config.symbol) => {
+                    latest.insert(update.pool.clone(), update);
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {
+                // Token decimals come from the pools, so wait for the first quote
+                let Some((base_decimals, quote_decimals)) = latest.values().next().map(|update| (update.base_decimals, update.quote_decimals)) else {
+                    continue;
+                };
+                for side in [Side::Sell, Side::Buy] {
+                    let (route_price, route, price_impact_pct) = match route_quote(&http, &config, side, base_decimals, quote_decimals).await {
+                        Ok(route) => route,
+                        Err(e) => {
+                            tracing::error!(symbol = %config.symbol, error = %e, side = ?side, "Jupiter quote failed");
+                            continue;
+                        }
+                    };
+
+                    // Best pool that fills the whole size, like the best-route hints
+                    let venues = latest.values().cloned().collect();
+                    let best = quote::quote(&rpc_client, venues, &config.symbol, side, config.size)
+                        .await
+                        .and_then(|quote| quote.venues.into_iter().find(|venue| venue.filled >= config.size));
+                    let improvement_bps = best.as_ref().map(|best| match side {
+                        Side::Sell => (route_price - best.execution_price) / best.execution_price * 10_000.0,
+                        Side::Buy => (best.execution_price - route_price) / best.execution_price * 10_000.0,
+                    });
+
+                    let _ = tx.send(MarketEvent::Aggregator(AggregatorQuote {
+                        symbol: config.symbol.clone(),
+                        side,
+                        size: config.size,
+                        route_price,
+                        route,
+                        price_impact_pct,
+                        best_pool_source: best.as_ref().map(|best| best.source.clone()),
+                        best_pool_price: best.as_ref().map(|best| best.execution_price),
+                        improvement_bps,
+                        timestamp: unix_now(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+// Price of `size` base tokens through Jupiter: selling is an ExactIn quote of
+// base for quote, buying an ExactOut quote of base paid in quote
+async fn route_quote(
+    http: &reqwest::Client,
+    config: &JupiterConfig,
+    side: Side,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> anyhow::Result<(f64, Vec<String>, f64)> {
+    let base_unit = 10_f64.powi(base_decimals as i32);
+    let quote_unit = 10_f64.powi(quote_decimals as i32);
+    let amount = ((config.size * base_unit) as u64).to_string();
+    let slippage_bps = config.slippage_bps.to_string();
+    let (input_mint, output_mint, swap_mode) = match side {
+        Side::Sell => (&config.base_mint, &config.quote_mint, "ExactIn"),
+        Side::Buy => (&config.quote_mint, &config.base_mint, "ExactOut"),
+    };
+
+    let response: QuoteResponse = http
+        .get(&config.api_url)
+        .query(&[
+            ("inputMint", input_mint.as_str()),
+            ("outputMint", output_mint.as_str()),
+            ("amount", amount.as_str()),
+            ("swapMode", swap_mode),
+            ("slippageBps", slippage_bps.as_str()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let quote_atoms: f64 = match side {
+        Side::Sell => response.out_amount.parse()?,
+        Side::Buy => response.in_amount.parse()?,
+    };
+    let route_price = quote_atoms / quote_unit / config.size;
+    let route = response
+        .route_plan
+        .into_iter()
+        .map(|step| step.swap_info.label.unwrap_or_else(|| "Unknown".to_string()))
+        .collect();
+
+    Ok((route_price, route, response.price_impact_pct.parse().unwrap_or(0.0) * 100.0))
+}
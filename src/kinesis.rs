@@ -0,0 +1,147 @@
+use aws_sdk_kinesis::primitives::Blob;
+use aws_sdk_kinesis::types::PutRecordsRequestEntry;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::KinesisConfig;
+use crate::events::MarketEvent;
+use crate::latency::{self, Stage};
+
+// PutRecords takes at most this many records per call
+const MAX_BATCH: usize = 500;
+
+// Amazon Kinesis sink for the market bus. Events are batched into PutRecords
+// calls, flushed when a batch fills up or every flush_ms. Each record is the
+// event's JSON, keyed by pool where it has one so a pool's events stay ordered
+// within its shard.
+pub struct KinesisSink {
+    config: KinesisConfig,
+    client: aws_sdk_kinesis::Client,
+    batch: Vec<PutRecordsRequestEntry>,
+    // Logged once per outage rather than for every batch that fails
+    failing: bool,
+}
+
+impl KinesisSink {
+    // Credentials and region come from the standard AWS chain (environment,
+    // profile, instance role); `region` overrides the latter
+    pub async fn new(config: KinesisConfig) -> Self {
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+        if let Some(region) = config.region.clone() {
+            loader = loader.region(aws_config::Region::new(region));
+        }
+        let client = aws_sdk_kinesis::Client::new(&loader.load().await);
+
+        Self {
+            config,
+            client,
+            batch: Vec::new(),
+            failing: false,
+        }
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, shutdown: CancellationToken) {
+        let batch_size = self.config.batch_size.clamp(1, MAX_BATCH);
+        let mut flush = tokio::time::interval(tokio::time::Duration::from_millis(self.config.flush_ms.max(1)));
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(stream_name = %self.config.stream_name, "sending market events to Kinesis stream");
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) => {
+                        self.push(&event);
+                        if self.batch.len() >= batch_size {
+                            self.flush().await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "Kinesis sink lagged, dropped events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = flush.tick() => self.flush().await,
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        // The monitors have stopped by now; send what they published last too
+        while let Ok(event) = rx.try_recv() {
+            self.push(&event);
+            if self.batch.len() >= batch_size {
+                self.flush().await;
+            }
+        }
+        self.flush().await;
+    }
+
+    fn push(&mut self, event: &MarketEvent) {
+        let Ok(payload) = event.to_json(self.config.schema) else {
+            return;
+        };
+        let record = PutRecordsRequestEntry::builder()
+            .data(Blob::new(payload))
+            .partition_key(partition_key(event))
+            .build();
+        match record {
+            Ok(record) => self.batch.push(record),
+            Err(e) => tracing::warn!(error = %e, "failed to build Kinesis record"),
+        }
+    }
+
+    // Send the pending batch. Records Kinesis rejects (throttled shards,
+    // internal errors) and batches that fail outright are dropped, so a stalled
+    // stream can't grow the backlog without bound.
+    async fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let records = std::mem::take(&mut self.batch);
+        let count = records.len();
+
+        let started = std::time::Instant::now();
+        let result = self
+            .client
+            .put_records()
+            .stream_name(&self.config.stream_name)
+            .set_records(Some(records))
+            .send()
+            .await;
+        latency::observe(Stage::Sink, "Kinesis", started.elapsed());
+        match result {
+            Ok(output) => {
+                let failed = output.failed_record_count().unwrap_or(0);
+                if failed > 0 {
+                    tracing::warn!(failed = %failed, count = %count, "Kinesis rejected records");
+                } else if self.failing {
+                    self.failing = false;
+                    tracing::info!("Kinesis writes recovered");
+                }
+            }
+            Err(e) if !self.failing => {
+                self.failing = true;
+                tracing::error!(error = %e, stream_name = %self.config.stream_name, "Kinesis write failed, dropping events until it recovers");
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn partition_key(event: &MarketEvent) -> String {
+    match event {
+        MarketEvent::Price(update) => update.pool.clone(),
+        MarketEvent::Liquidity(change) => change.pool.clone(),
+        MarketEvent::Swap(swap) => swap.pool.clone(),
+        MarketEvent::Depth(depth) => depth.pool.clone(),
+        MarketEvent::Route(route) => route.symbol.clone(),
+        MarketEvent::Aggregator(quote) => quote.symbol.clone(),
+        MarketEvent::Derived(value) => value.symbol.clone(),
+        MarketEvent::Migration(rate) => rate.pool.clone(),
+        MarketEvent::Rewards(emissions) => emissions.pool.clone(),
+        MarketEvent::Incentive(incentive) => incentive.pool.clone(),
+        MarketEvent::Twap(twap) => twap.pool.clone(),
+        MarketEvent::Stats(stats) => stats.pool.clone(),
+        MarketEvent::Anomaly(anomaly) => anomaly.pool.clone(),
+        MarketEvent::Checkpoint(_) | MarketEvent::Health(_) => event.kind().to_string(),
+    }
+}
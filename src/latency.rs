@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+// Samples older than this don't count towards the percentiles
+const WINDOW: Duration = Duration::from_secs(300);
+// Fewer samples than this in a stage don't make a percentile worth alerting on
+const MIN_SAMPLES: usize = 20;
+// Nominal slot time, to turn slot lag into time
+const SLOT_MS: u64 = 400;
+
+// Where the time between a state change on chain and its update reaching the
+// sinks goes:
+// - chain: how far behind the tip the state read was, from slot_lag
+// - rpc: HTTP round trips of the fetch that read it
+// - decode: the rest of the fetch, decoding and pricing the accounts
+// - sink: writing it to Redis, Kinesis, the archive or dataset files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Chain,
+    Rpc,
+    Decode,
+    Sink,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Chain, Stage::Rpc, Stage::Decode, Stage::Sink];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Chain => "chain",
+            Stage::Rpc => "rpc",
+            Stage::Decode => "decode",
+            Stage::Sink => "sink",
+        }
+    }
+}
+
+tokio::task_local! {
+    // Time the current task spent in RPC requests since its last fetch was observed
+    static RPC_TIME: Cell<Duration>;
+}
+
+// (stage, source or sink name) -> (when, milliseconds) within the window
+static SAMPLES: LazyLock<Mutex<HashMap<(Stage, String), VecDeque<(Instant, f64)>>>> = LazyLock::new(Mutex::default);
+
+pub fn observe(stage: Stage, label: &str, elapsed: Duration) {
+    let now = Instant::now();
+    let mut samples = SAMPLES.lock().unwrap();
+    let window = samples.entry((stage, label.to_string())).or_default();
+    window.push_back((now, elapsed.as_secs_f64() * 1000.0));
+    while window.front().is_some_and(|(at, _)| now.duration_since(*at) > WINDOW) {
+        window.pop_front();
+    }
+}
+
+// Run a task with its own RPC time tally, so its fetches can be split into
+// RPC and decode time. Every supervised task runs inside one.
+pub fn track_rpc_time<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    RPC_TIME.scope(Cell::new(Duration::ZERO), future)
+}
+
+// Called for every RPC attempt; outside a tracked task it's dropped
+pub fn add_rpc_time(elapsed: Duration) {
+    let _ = RPC_TIME.try_with(|total| total.set(total.get() + elapsed));
+}
+
+// A monitor fetch of `elapsed` in total: the task's RPC time since its last
+// fetch is the rpc stage, the rest is decode
+pub fn observe_fetch(source: &str, elapsed: Duration) {
+    let rpc = RPC_TIME.try_with(|total| total.replace(Duration::ZERO)).unwrap_or_default().min(elapsed);
+    observe(Stage::Rpc, source, rpc);
+    observe(Stage::Decode, source, elapsed - rpc);
+}
+
+pub fn observe_slot_lag(source: &str, slot_lag: u64) {
+    observe(Stage::Chain, source, Duration::from_millis(slot_lag * SLOT_MS));
+}
+
+// A source whose pipeline went over budget
+#[derive(Debug, Clone)]
+pub struct Breach {
+    pub source: String,
+    pub total_ms: f64,
+    // Percentile of each stage with enough samples; the sink stage is the slowest sink's
+    pub stages: Vec<(Stage, f64)>,
+    // The stage furthest over its own budget, or else the largest one
+    pub culprit: Stage,
+}
+
+// Sources whose end-to-end latency, the sum of their stages' percentiles over
+// the last 5 minutes, exceeds `budget_ms`, or with a stage over its own budget
+pub fn breaches(percentile: f64, budget_ms: u64, stage_budgets_ms: &HashMap<Stage, u64>) -> Vec<Breach> {
+    let now = Instant::now();
+    let samples = SAMPLES.lock().unwrap();
+    let percentiles: HashMap<(Stage, &str), f64> = samples
+        .iter()
+        .filter_map(|((stage, label), window)| Some(((*stage, label.as_str()), percentile_of(window, percentile, now)?)))
+        .collect();
+
+    let sink_ms = percentiles
+        .iter()
+        .filter(|((stage, _), _)| *stage == Stage::Sink)
+        .map(|(_, ms)| *ms)
+        .reduce(f64::max);
+    let mut sources: Vec<&str> = percentiles
+        .keys()
+        .filter(|(stage, _)| *stage != Stage::Sink)
+        .map(|(_, source)| *source)
+        .collect();
+    sources.sort();
+    sources.dedup();
+
+    let mut breaches = Vec::new();
+    for source in sources {
+        let stages: Vec<(Stage, f64)> = Stage::ALL
+            .into_iter()
+            .filter_map(|stage| match stage {
+                Stage::Sink => sink_ms.map(|ms| (stage, ms)),
+                _ => percentiles.get(&(stage, source)).map(|ms| (stage, *ms)),
+            })
+            .collect();
+        let total_ms: f64 = stages.iter().map(|(_, ms)| ms).sum();
+        let overshoot = |(stage, ms): &(Stage, f64)| stage_budgets_ms.get(stage).map(|budget| ms - *budget as f64);
+
+        let over_stage = stages
+            .iter()
+            .filter_map(|stage| overshoot(stage).filter(|over| *over > 0.0).map(|over| (stage.0, over)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(stage, _)| stage);
+        if over_stage.is_none() && (budget_ms == 0 || total_ms <= budget_ms as f64) {
+            continue;
+        }
+        let Some(culprit) = over_stage.or_else(|| stages.iter().max_by(|a, b| a.1.total_cmp(&b.1)).map(|(stage, _)| *stage)) else {
+            continue;
+        };
+        breaches.push(Breach { source: source.to_string(), total_ms, stages, culprit });
+    }
+    breaches
+}
+
+// None for too few samples; a source that went quiet ages out of the window
+fn percentile_of(window: &VecDeque<(Instant, f64)>, percentile: f64, now: Instant) -> Option<f64> {
+    let mut values: Vec<f64> = window
+        .iter()
+        .filter(|(at, _)| now.duration_since(*at) <= WINDOW)
+        .map(|(_, ms)| *ms)
+        .collect();
+    if values.len() < MIN_SAMPLES {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = (percentile / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
@@ -0,0 +1,395 @@
+use async_trait::async_trait;
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_lifinity_amm_v2_decoder::accounts::amm::Amm;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "Lifinity";
+
+// Decoded state of one Lifinity pool for a single tick
+struct LifinityData {
+    price: f64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    fee_bps: f64,
+}
+
+pub struct LifinityMonitor {
+    rpc_client: RpcClient,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    // (token_a, token_b) decimals per pool, resolved once from the mints
+    decimals_cache: HashMap<Pubkey, (u8, u8)>,
+    // Pushed pool account changes when running on the Geyser backend
+    geyser: Option<AccountUpdates>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Geyser only pushes changes, so the first pass polls a full snapshot
+    snapshotted: bool,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl LifinityMonitor {
+    pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let geyser = geyser.map(|config| {
+            AccountUpdates::subscribe(config, SOURCE, pools.iter().map(|(address, _)| *address).collect())
+        });
+        let schedule = PollSchedule::new(SOURCE, &pools, schedule);
+
+        Self {
+            rpc_client,
+            pools,
+            last_snapshot: HashMap::new(),
+            decimals_cache: HashMap::new(),
+            geyser,
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            snapshotted: false,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+
+            // Changes pushed while paused may be long outdated, so re-read a full snapshot
+            if shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await == Some(true) {
+                self.snapshotted = false;
+            }
+
+            // On Geyser, wait for pool accounts to change instead of polling, once the initial snapshot is in
+            let pushed = match self.geyser.as_mut() {
+                Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                    Some(batch) => Some(batch?),
+                    None => return Ok(()),
+                },
+                _ => {
+                    shutdown.run_until_cancelled(interval.tick()).await;
+                    None
+                }
+            };
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = match pushed {
+                Some((slot, accounts)) => self.parse_pool_accounts(accounts).await.map(|pools| (slot, pools)),
+                None => self.fetch_pool_data().await,
+            };
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, pools)) => {
+                    self.snapshotted = true;
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in pools {
+                        let current_price = data.price;
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.base_decimals,
+                            data.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.base_decimals,
+                            quote_decimals: data.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: data.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    async fn fetch_pool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, LifinityData)>)> {
+        // Parked pools are only read once per keep-alive
+        let due = self.schedule.due(&self.pools);
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let mut found = Vec::with_capacity(response.value.len());
+        for ((address, pool), account) in due.into_iter().zip(response.value) {
+            match account {
+                Some(account) => found.push((*address, account)),
+                None => tracing::warn!(source = SOURCE, symbol = %pool.symbol, pool = %address, "pool account not found"),
+            }
+        }
+
+        Ok((slot, self.parse_pool_accounts(found).await?))
+    }
+
+    // Current cluster tip, to tell how far behind the data we publish is
+    async fn tip_slot(&self) -> Option<u64> {
+        match &self.geyser {
+            Some(updates) => Some(updates.tip_slot()),
+            None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
+        }
+    }
+
+    // Decode pool accounts, whether polled or pushed, then read all of their
+    // vaults in one round trip, concurrently with the mints of pools seen for
+    // the first time. Undecodable pools are logged and skipped.
+    async fn parse_pool_accounts(&mut self, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, LifinityData)>> {
+        let mut decoded = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            let Some(symbol) = self.pools.iter().find(|(pool_address, _)| *pool_address == address).map(|(_, pool)| pool.symbol.clone()) else {
+                continue;
+            };
+
+            match parse_amm(&account.data) {
+                Ok(amm) => decoded.push((address, symbol, amm)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.pools, &address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+                }
+            }
+        }
+
+        let vaults: Vec<Pubkey> = decoded
+            .iter()
+            .flat_map(|(_, _, amm)| [amm.token_a_account, amm.token_b_account])
+            .collect();
+        let unresolved: Vec<(Pubkey, Pubkey, Pubkey)> = decoded
+            .iter()
+            .filter(|(address, _, _)| !self.decimals_cache.contains_key(address))
+            .map(|(address, _, amm)| (*address, amm.token_a_mint, amm.token_b_mint))
+            .collect();
+        let (balances, decimals) = tokio::join!(
+            self.get_token_account_balances(&vaults),
+            self.fetch_mint_decimals(&unresolved),
+        );
+        let balances = balances?;
+        self.decimals_cache.extend(decimals?);
+
+        let mut results = Vec::with_capacity(decoded.len());
+        for ((address, symbol, amm), balances) in decoded.into_iter().zip(balances.chunks(2)) {
+            let Some(&(base_decimals, quote_decimals)) = self.decimals_cache.get(&address) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool mints");
+                continue;
+            };
+            let (Some(base_reserve), Some(quote_reserve)) = (balances[0], balances[1]) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool vaults");
+                continue;
+            };
+            let price = match lifinity_price(&amm) {
+                Ok(price) => price,
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "skipping pool");
+                    continue;
+                }
+            };
+
+            results.push((address, symbol, LifinityData {
+                price,
+                base_reserve,
+                quote_reserve,
+                base_decimals,
+                quote_decimals,
+                fee_bps: trade_fee_bps(&amm),
+            }));
+        }
+
+        Ok(results)
+    }
+
+    // Amounts of many token accounts in a single getMultipleAccounts call,
+    // None for accounts that are missing or not token accounts
+    async fn get_token_account_balances(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<u64>>> {
+        if token_accounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // At least as new as the pools read before them
+        let accounts = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?.value;
+        Ok(accounts
+            .iter()
+            .map(|account| account.as_ref().and_then(|account| token::token_account_amount(&account.data).ok()))
+            .collect())
+    }
+
+    // Token decimals never change, so the mints of each pool are read once,
+    // all new pools in one call. Pools whose mints can't be read are left out.
+    async fn fetch_mint_decimals(&self, pools: &[(Pubkey, Pubkey, Pubkey)]) -> Result<Vec<(Pubkey, (u8, u8))>> {
+        if pools.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mints: Vec<Pubkey> = pools.iter().flat_map(|(_, mint_a, mint_b)| [*mint_a, *mint_b]).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &mints).await?;
+        let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+        Ok(pools
+            .iter()
+            .zip(accounts.chunks(2))
+            .filter_map(|((address, _, _), mints)| Some((*address, (decimals(&mints[0])?, decimals(&mints[1])?))))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Monitor for LifinityMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
+
+fn parse_amm(data: &[u8]) -> Result<Amm> {
+    <Amm as CarbonDeserialize>::deserialize(data)
+        .ok_or_else(|| WatcherError::DecodeError("Failed to parse Lifinity pool data".to_string()))
+}
+
+// Lifinity prices off an oracle and re-centres its curve on every trade, so
+// the reserve ratio says little about where it quotes. The pool keeps its
+// last traded price, scaled by config_denominator, which is what it quotes around.
+fn lifinity_price(amm: &Amm) -> Result<f64> {
+    if amm.config.config_denominator == 0 || amm.config.last_price == 0 {
+        return Err(WatcherError::DecodeError("pool has no last price yet".to_string()));
+    }
+    Ok(amm.config.last_price as f64 / amm.config.config_denominator as f64)
+}
+
+fn trade_fee_bps(amm: &Amm) -> f64 {
+    if amm.fees.trade_fee_denominator == 0 {
+        return 0.0;
+    }
+    amm.fees.trade_fee_numerator as f64 / amm.fees.trade_fee_denominator as f64 * 10_000.0
+}
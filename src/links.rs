@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+// Explorer deep links attached to notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub label: String,
+    pub url: String,
+}
+
+pub fn birdeye_pool(pool: &str) -> Link {
+    Link {
+        label: "Birdeye".to_string(),
+        url: format!("https://birdeye.so/pair/{}?chain=solana", pool),
+    }
+}
+
+pub fn solscan_tx(signature: &str) -> Link {
+    Link {
+        label: "Solscan".to_string(),
+        url: format!("https://solscan.io/tx/{}", signature),
+    }
+}
+
+pub fn solanafm_account(address: &str) -> Link {
+    Link {
+        label: "Solana.fm".to_string(),
+        url: format!("https://solana.fm/address/{}", address),
+    }
+}
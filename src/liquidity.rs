@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::aggregate;
+use crate::raydium::PriceUpdate;
+use crate::events::MarketEvent;
+use crate::pairs::PairTable;
+use crate::snapshot::ChangeReason;
+use crate::tvl::USD_STABLES;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityDirection {
+    Add,
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityChanged {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub direction: LiquidityDirection,
+    pub base_delta: f64,        // UI units, signed
+    pub quote_delta: f64,       // UI units, signed
+    pub lp_supply_delta: Option<i128>,
+    pub usd_size: Option<f64>,  // None when the quote token can't be valued in USD yet
+    pub timestamp: u64,
+}
+
+pub struct LiquidityTracker {
+    // Previous reserves and LP supply of every pool
+    pairs: PairTable,
+    // token symbol -> latest USD price, learned from */USDC and */USDT pools
+    usd_prices: HashMap<String, f64>,
+}
+
+impl LiquidityTracker {
+    pub fn new() -> Self {
+        Self {
+            pairs: PairTable::new(),
+            usd_prices: HashMap::new(),
+        }
+    }
+
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+    ) {
+        loop {
+            match rx.recv().await {
+                Ok(MarketEvent::Price(price_update)) if !aggregate::is_aggregate(&price_update) => {
+                    if let Some(event) = self.on_price_update(price_update) {
+                        let _ = tx.send(MarketEvent::Liquidity(event));
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "liquidity tracker lagged, skipped updates");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub fn on_price_update(&mut self, update: PriceUpdate) -> Option<LiquidityChanged> {
+        if let Some((base, quote)) = update.symbol.split_once('/') {
+            if USD_STABLES.contains(&quote) && update.price > 0.0 {
+                self.usd_prices.insert(base.to_string(), update.price);
+            }
+        }
+
+        let previous = self.pairs.quoted_pair(&update.pool).map(|pair| {
+            (self.pairs.base_reserves[pair], self.pairs.quote_reserves[pair], self.pairs.lp_supplies[pair])
+        });
+        self.pairs.upsert(&update);
+        let (previous_base_reserve, previous_quote_reserve, previous_lp_supply) = previous?;
+
+        // LP mint supply is authoritative where the monitor reports it (Raydium);
+        // otherwise fall back to the reserve-diff classification
+        let lp_supply_delta = match (previous_lp_supply, update.lp_supply) {
+            (Some(before), Some(after)) => Some(after as i128 - before as i128),
+            _ => None,
+        };
+
+        let direction = match lp_supply_delta {
+            Some(delta) if delta > 0 => LiquidityDirection::Add,
+            Some(delta) if delta < 0 => LiquidityDirection::Remove,
+            Some(_) => return None,
+            None => match update.change_reason {
+                ChangeReason::LiquidityAdd => LiquidityDirection::Add,
+                ChangeReason::LiquidityRemove => LiquidityDirection::Remove,
+                _ => return None,
+            },
+        };
+
+        // Diff in i128 so large reserves keep every atom before scaling
+        let base_delta = (update.base_reserve as i128 - previous_base_reserve as i128) as f64
+            / 10_f64.powi(update.base_decimals as i32);
+        let quote_delta = (update.quote_reserve as i128 - previous_quote_reserve as i128) as f64
+            / 10_f64.powi(update.quote_decimals as i32);
+
+        // Value the change in quote terms, then convert the quote token to USD
+        let quote_size = quote_delta.abs() + base_delta.abs() * update.price;
+        let usd_size = update
+            .symbol
+            .split_once('/')
+            .and_then(|(_, quote)| self.usd_rate(quote))
+            .map(|rate| quote_size * rate);
+
+        Some(LiquidityChanged {
+            symbol: update.symbol,
+            source: update.source,
+            pool: update.pool,
+            direction,
+            base_delta,
+            quote_delta,
+            lp_supply_delta,
+            usd_size,
+            timestamp: update.timestamp,
+        })
+    }
+
+    fn usd_rate(&self, token: &str) -> Option<f64> {
+        if USD_STABLES.contains(&token) {
+            return Some(1.0);
+        }
+        self.usd_prices.get(token).copied()
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::io::IsTerminal;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::{Format, Writer};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+use crate::cli::Cli;
+use crate::config::{LogFormat, LoggingConfig};
+use crate::display;
+use crate::tui::LogLines;
+
+// Target of the text display's rows (prices, swaps, alerts, ...). The console
+// prints them as bare lines; JSON logs leave them out, as `--output json`
+// carries the same events as JSON.
+pub const DISPLAY: &str = "display";
+
+// Where console output goes
+pub enum Target {
+    Stdout,
+    // stdout carries JSON market events
+    Stderr,
+    // The dashboard owns the terminal and shows the lines in its log pane
+    Tui(LogLines),
+}
+
+// Install the tracing subscriber for all console output. RUST_LOG overrides
+// the configured filter and --log-format json|text the configured format, so
+// a unit file or pod spec can change either without touching the config.
+// Logs go to stdout, to stderr when stdout carries JSON market events, or to
+// the dashboard's log pane with --tui.
+pub fn init(config: &LoggingConfig, cli: &Cli, target: Target) {
+    let mut filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.filter));
+    let format = cli.log_format.unwrap_or(config.format);
+    if format == LogFormat::Json {
+        filter = filter.add_directive(format!("{}=off", DISPLAY).parse().expect("valid directive"));
+    }
+
+    let (writer, terminal) = match target {
+        Target::Stdout => (BoxMakeWriter::new(std::io::stdout), std::io::stdout().is_terminal()),
+        Target::Stderr => (BoxMakeWriter::new(std::io::stderr), std::io::stderr().is_terminal()),
+        // Escape codes would show up as text in the pane
+        Target::Tui(lines) => (BoxMakeWriter::new(move || lines.clone()), false),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_target(false).with_writer(writer);
+    let result = match format {
+        // Colors only for a terminal, not for journald or a pod's log file
+        LogFormat::Text => builder.with_ansi(terminal).event_format(Console(Format::default().with_target(false))).try_init(),
+        // One object per line: fields at the top level next to `message`, plus the task span
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).with_span_list(false).try_init(),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to install the log subscriber: {}", e);
+    }
+}
+
+// Text formatter: display rows as bare lines, everything else in the default
+// format, both passed through display::render() for --no-emoji and --ascii
+struct Console(Format);
+
+impl<S, N> FormatEvent<S, N> for Console
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        if event.metadata().target() == DISPLAY {
+            let mut message = Message::default();
+            event.record(&mut message);
+            return writeln!(writer, "{}", display::render(message.0));
+        }
+        if !display::plain() {
+            return self.0.format_event(ctx, writer, event);
+        }
+
+        // Rendering needs the whole line, which costs the colors
+        let mut line = String::new();
+        self.0.format_event(ctx, Writer::new(&mut line), event)?;
+        write!(writer, "{}", display::render(line))
+    }
+}
+
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
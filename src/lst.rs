@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::programs::MARINADE_PROGRAM_ID;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+
+const SOURCE: &str = "Stake Pool";
+
+// Rates move once per epoch, when the pool's crank runs; no need for the DEX cadence
+const POLL_INTERVAL_SECS: u64 = 30;
+
+// LSTs and SOL both have 9 decimals
+const DECIMALS: u8 = 9;
+
+// SPL stake pool account (JitoSOL, bSOL and Sanctum's LSTs, on the SPL program
+// or Sanctum's deployments of it), borsh encoded:
+// account_type (1) | manager (32) | staker (32) | stake_deposit_authority (32)
+//   | stake_withdraw_bump_seed (1) | validator_list (32) | reserve_stake (32)
+//   | pool_mint (32) | manager_fee_account (32) | token_program_id (32)
+//   | total_lamports (8) | pool_token_supply (8) | last_update_epoch (8)
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+const TOTAL_LAMPORTS_OFFSET: usize = 258;
+const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const STAKE_POOL_LEN: usize = POOL_TOKEN_SUPPLY_OFFSET + 8;
+
+// Marinade State account, after the 8-byte Anchor discriminator: mSOL mint and
+// authorities (128) | bump seeds (2) | rent_exempt_for_token_acc (8)
+//   | reward_fee (4) | stake_system (114) | validator_system (121)
+//   | liq_pool (111) | available_reserve_balance (8) | msol_supply (8)
+//   | msol_price (8, SOL per mSOL as a 32-bit fixed point fraction)
+const MSOL_SUPPLY_OFFSET: usize = 504;
+const MSOL_PRICE_OFFSET: usize = 512;
+const MARINADE_STATE_LEN: usize = MSOL_PRICE_OFFSET + 8;
+const MSOL_PRICE_DENOMINATOR: f64 = 4_294_967_296.0;
+
+// Exchange rate of one LST, in SOL per token, with the supply and the SOL
+// backing it
+struct PoolRate {
+    rate: f64,
+    supply: u64,
+    lamports: u64,
+}
+
+fn parse_rate(owner: &Pubkey, data: &[u8]) -> Result<PoolRate> {
+    if *owner == MARINADE_PROGRAM_ID {
+        if data.len() < MARINADE_STATE_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid Marinade state data size: {} bytes", data.len())));
+        }
+        let supply = read_u64(data, MSOL_SUPPLY_OFFSET);
+        let rate = read_u64(data, MSOL_PRICE_OFFSET) as f64 / MSOL_PRICE_DENOMINATOR;
+        // Marinade tracks the price rather than the total, so the backing is derived
+        return Ok(PoolRate {
+            rate,
+            supply,
+            lamports: (supply as f64 * rate) as u64,
+        });
+    }
+
+    if data.len() < STAKE_POOL_LEN || data[0] != STAKE_POOL_ACCOUNT_TYPE {
+        return Err(WatcherError::ConfigError("not an SPL stake pool or Marinade state account".to_string()));
+    }
+    let lamports = read_u64(data, TOTAL_LAMPORTS_OFFSET);
+    let supply = read_u64(data, POOL_TOKEN_SUPPLY_OFFSET);
+    if supply == 0 {
+        return Err(WatcherError::DecodeError("stake pool has no tokens minted".to_string()));
+    }
+    Ok(PoolRate {
+        rate: lamports as f64 / supply as f64,
+        supply,
+        lamports,
+    })
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+// Fair redemption rate of liquid staking tokens, read from their stake pool,
+// published as price updates next to the LST/SOL DEX pools
+pub struct LstMonitor {
+    rpc_client: RpcClient,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl LstMonitor {
+    pub fn new(pools: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let schedule = PollSchedule::new(SOURCE, &pools, schedule);
+
+        Self {
+            rpc_client,
+            pools,
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(POLL_INTERVAL_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_rates().await;
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, rates)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    for (address, symbol, rate) in rates {
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: rate.supply,
+                            quote_reserve: rate.lamports,
+                            price: rate.rate,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((rate.rate - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), rate.rate, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), rate.rate);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: rate.rate,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: rate.supply,
+                            quote_reserve: rate.lamports,
+                            base_decimals: DECIMALS,
+                            quote_decimals: DECIMALS,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: 0.0,
+                            slot,
+                            // Read at the tip and only worth as much as the last epoch update anyway
+                            slot_lag: 0,
+                            tvl_usd: None,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read every due stake pool in one getMultipleAccounts call; the owning
+    // program tells Marinade's state apart from SPL stake pools
+    async fn fetch_rates(&mut self) -> Result<(u64, Vec<(Pubkey, String, PoolRate)>)> {
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.pools)
+            .into_iter()
+            .map(|(address, pool)| (*address, pool.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let mut results = Vec::with_capacity(due.len());
+        for ((address, symbol), account) in due.into_iter().zip(response.value) {
+            let Some(account) = account else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "pool account not found");
+                continue;
+            };
+            match parse_rate(&account.owner, &account.data) {
+                Ok(rate) => results.push((address, symbol, rate)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.pools, &address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+                }
+            }
+        }
+
+        Ok((slot, results))
+    }
+}
+
+#[async_trait]
+impl Monitor for LstMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
@@ -1,120 +1,1177 @@
+mod aggregate;
+mod alerts;
+mod api;
+mod arbitrage;
+mod backoff;
+mod checkpoint;
+mod book;
+mod capabilities;
+mod best_route;
+mod cli;
+mod clock;
+mod config;
+mod crosscheck;
+mod dataset;
+mod deadman;
+mod dedup;
+mod depth;
+mod derived;
+mod discord;
+mod display;
+mod events;
+mod event_log;
+mod distribution;
+mod error;
+mod exit;
+mod fingerprint;
+mod geyser;
+mod grafana;
+mod health;
+mod holders;
+mod jupiter;
+mod latency;
+mod links;
+mod lifinity;
+mod liquidity;
+mod logging;
+mod metrics;
+mod migration;
+mod monitor;
+mod openbook;
+mod pairs;
+mod pause;
+mod phoenix;
+#[cfg(feature = "iceoryx2")]
+mod iceoryx;
+#[cfg(feature = "kinesis")]
+mod kinesis;
+#[cfg(feature = "archive")]
+mod archive;
 mod raydium;
+mod raydium_clmm;
+mod record;
+mod repl;
+mod rewards;
+mod redis_sink;
+mod routing;
 mod orca;
 mod meteora;
+mod meteora_amm;
+mod lst;
+mod pyth;
+mod programs;
+mod pump;
+mod quote;
+mod ratelimit;
+mod rpc;
+mod saber;
+mod sanity;
+mod sql;
+mod schedule;
+mod schema;
+mod shm;
+mod simulate;
+mod sla;
+mod snapshot;
+mod stats;
+mod supervisor;
+mod swaps;
+mod telegram;
+mod tui;
+mod token;
+mod tvl;
+mod twap;
+mod upgrades;
+mod volume;
+mod transactions;
+mod wallets;
+mod warmup;
 
-use raydium::RaydiumMonitor;
-use orca::OrcaMonitor;
-use meteora::MeteoraMonitor;
+use aggregate::PriceAggregator;
+use alerts::AlertEngine;
+use api::ApiState;
+use arbitrage::ArbitrageDetector;
+use best_route::BestRoute;
+use checkpoint::Checkpoint;
+use jupiter::AggregatorQuote;
+use config::{Cluster, Config, PoolConfig};
+use depth::DepthUpdate;
+use derived::DerivedValue;
+use cli::{Cli, Command};
+use discord::DiscordNotifier;
+use grafana::GrafanaAnnotator;
+use events::MarketEvent;
+use exit::ExitReason;
+use health::{HealthEvent, StaleSources};
+use holders::{HolderMint, HoldersTracker};
+use liquidity::{LiquidityChanged, LiquidityDirection, LiquidityTracker};
+use migration::{MigrationRate, MigrationTracker};
+use monitor::MonitorContext;
+use pause::Pause;
+use raydium::PriceUpdate;
+use record::RecordCommand;
+use redis_sink::RedisSink;
+use rewards::{IncentiveApr, IncentiveTracker};
+use rpc::RpcProvider;
+use sanity::PriceAnomaly;
+use simulate::SimulateCommand;
+use shm::{ShmRing, TickRecord};
+use stats::{RollingStats, StatsTracker, StatsUpdate};
+use snapshot::ChangeReason;
+use supervisor::Liveness;
+use swaps::{SwapDirection, SwapEvent, SwapMonitor};
+use sla::{SlaReports, SlaTracker};
+use telegram::TelegramNotifier;
+use tvl::TvlReporter;
+use twap::{TwapTracker, TwapUpdate};
+use volume::VolumeTracker;
+use wallets::{WalletActivity, WalletWatcher};
 
+use std::io::Write;
 use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+// Updates read more than this many slots behind the tip are flagged in the display
+const STALE_SLOT_LAG: u64 = 20;
+
+// Exit once every monitor has been failing without a price update for this long
+const MONITORS_DEAD_AFTER_SECS: u64 = 300;
+
+// How long monitors, sinks and a session recording get to wind down after Ctrl+C
+const SHUTDOWN_TIMEOUT_SECS: u64 = 10;
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
+    exit::install_panic_hook();
+    let cli = Cli::from_args();
+    
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            // No [logging] to go by, so report it with the defaults
+            logging::init(&config::LoggingConfig::default(), &cli, logging::Target::Stdout);
+            tracing::error!(error = %e, "config error");
+            exit::exit(ExitReason::ConfigError, &e.to_string());
+        }
+    };
+    display::init(&config.display, &cli);
+    // `--tui`: a live dashboard in place of the scrolling output; JSON output wins if both are given
+    let tui_logs = (cli.tui && !display::json_output()).then(tui::LogLines::default);
+    let log_target = match &tui_logs {
+        Some(lines) => logging::Target::Tui(lines.clone()),
+        None if display::json_output() => logging::Target::Stderr,
+        None => logging::Target::Stdout,
+    };
+    logging::init(&config.logging, &cli, log_target);
+    if let Err(e) = event_log::init(&config.event_log) {
+        tracing::error!(error = %e, "config error");
+        exit::exit(ExitReason::ConfigError, &e.to_string());
+    }
+    sanity::init(&config.sanity);
+    dedup::init(&config.dedup);
+    
+    let command = cli.command();
+    
+    // Migrated pools hand their symbols over to their successors before anything reads the pool lists
+    if let Err(e) = cli::apply_lifecycle(&mut config) {
+        tracing::error!(error = %e, "config error");
+        exit::exit(ExitReason::ConfigError, &e.to_string());
+    }
+
+    // `validate-config`: getting this far means the config loaded and checked out
+    if command == Command::ValidateConfig {
+        cli::validate_config(&config);
+        exit::exit(ExitReason::Shutdown, "");
+    }
     
-    // Create broadcast channel for price updates from all AMMs
+    // `--pair` and `--sources` narrow the pools before anything reads them
+    if matches!(command, Command::Watch | Command::Snapshot | Command::ListPools) {
+        if let Err(e) = cli::apply_filters(&mut config, &cli) {
+            tracing::error!(error = %e, "config error");
+            exit::exit(ExitReason::ConfigError, &e.to_string());
+        }
+    }
+    
+    // `--print-capabilities`: what this instance would provide, as JSON for orchestration
+    if cli.print_capabilities {
+        match serde_json::to_string_pretty(&capabilities::from_config(&config)) {
+            Ok(json) => println!("{}", json),
+            Err(e) => tracing::error!(error = %e, "failed to serialize capabilities"),
+        }
+        exit::exit(ExitReason::Shutdown, "");
+    }
+    
+    // `list-pools`: what would be watched, without connecting to anything
+    if command == Command::ListPools {
+        cli::list_pools(&config);
+        exit::exit(ExitReason::Shutdown, "");
+    }
+    
+    // `sql "<QUERY>"`: query the local history and exit without starting the watcher
+    if let Command::Sql { query } = &command {
+        match sql::run(&config, query) {
+            Ok(()) => exit::exit(ExitReason::Shutdown, ""),
+            Err(e) => {
+                tracing::error!(error = %e, "query failed");
+                exit::exit(ExitReason::QueryFailed, &e.to_string());
+            }
+        }
+    }
+    
+    // `simulate lp ...`: replay the local history against a hypothetical LP position and exit
+    if let Command::Simulate { command: SimulateCommand::Lp(options) } = &command {
+        match simulate::LpArgs::new(options).and_then(|args| simulate::run(&config, &args)) {
+            Ok(()) => exit::exit(ExitReason::Shutdown, ""),
+            Err(e) => {
+                tracing::error!(error = %e, "simulation failed");
+                exit::exit(ExitReason::QueryFailed, &e.to_string());
+            }
+        }
+    }
+    
+    // `repl`: an interactive prompt against a running watcher's API
+    if let Command::Repl(args) = &command {
+        match repl::Repl::new(&config, args).run().await {
+            Ok(()) => exit::exit(ExitReason::Shutdown, ""),
+            Err(e) => {
+                tracing::error!(error = %e, "command failed");
+                exit::exit(ExitReason::QueryFailed, &e.to_string());
+            }
+        }
+    }
+    
+    let session = match &command {
+        Command::Record { command: RecordCommand::Session(options) } => Some(record::SessionArgs::new(options)),
+        _ => None,
+    };
+    
+    // Every RPC client shares one rotation over the configured endpoints
+    let rpc = RpcProvider::new(&config.rpc);
+    if let Err(e) = rpc.probe().await {
+        tracing::error!(error = %e, "no RPC endpoint reachable");
+        exit::exit(ExitReason::RpcUnreachable, &e.to_string());
+    }
+    
+    // Market bus: price updates from all AMMs, liquidity changes, swaps and mirrored health events
     let (tx, mut rx) = broadcast::channel(1000);
     let tx = Arc::new(tx);
     
-    // Start all AMM monitors concurrently with join handles
-    let raydium_handle = {
-        let tx = tx.clone();
+    // Supervisor health events (reconnects, backoff), kept off the price channel
+    let (health_tx, _) = broadcast::channel::<HealthEvent>(100);
+    let health_tx = Arc::new(health_tx);
+    
+    // Swaps and liquidity changes of watched wallets
+    let (wallet_tx, _) = broadcast::channel(100);
+    let wallet_tx = Arc::new(wallet_tx);
+    
+    // Top-holder concentration reports
+    let (holder_tx, _) = broadcast::channel(100);
+    let holder_tx = Arc::new(holder_tx);
+    
+    // Alerts for the notification sinks
+    let (alert_tx, _) = broadcast::channel(100);
+    let alert_tx = Arc::new(alert_tx);
+    
+    // Restarts, failovers, pauses and alerts as JSON lines for log pipelines
+    if config.event_log.enabled {
+        tokio::spawn(event_log::run(health_tx.subscribe(), alert_tx.subscribe()));
+    }
+    
+    // Cancelled on shutdown: monitors stop between fetches, then the sinks
+    // write out what's still queued and flush
+    let shutdown = CancellationToken::new();
+    let sinks_shutdown = CancellationToken::new();
+    
+    // `record session`: capture this run into one bundle, then exit
+    let recording_session = session.is_some();
+    let recording = record::run(session, config.clone(), rpc.clone(), tx.subscribe(), alert_tx.subscribe(), health_tx.subscribe(), sinks_shutdown.clone());
+    tokio::pin!(recording);
+    
+    // Warn when market events pile up because a consumer stopped reading
+    tokio::spawn(supervisor::watch_bus("Market", tx.clone(), 1000, config.supervisor.clone()));
+    
+    // Every watched pool tagged with its DEX, for the warm-up barrier and the transaction-level watchers
+    let all_pools: Vec<(String, PoolConfig)> = monitor::sources(&config)
+        .iter()
+        .flat_map(|source| source.dex.pools.iter().map(|pool| (source.name.to_string(), pool.clone())))
+        .collect();
+    
+    // Consumers comparing pools hold off until every pool (or the quorum) has a first price
+    let (ready_tx, ready) = warmup::channel();
+    tokio::spawn(warmup::run(all_pools.clone(), config.warmup.clone(), tx.subscribe(), health_tx.clone(), ready_tx));
+    
+    // Global and per-source polling pause, driven through the REST API
+    let pause = Pause::new(health_tx.clone());
+    
+    // 24h high/low per pool, plus volume when swaps are decoded, attached to every price update
+    let stats = RollingStats::new(config.swaps.enabled);
+    
+    // Start all monitors concurrently with join handles, restarted if they panic
+    let context = MonitorContext {
+        tx: tx.clone(),
+        health_tx: health_tx.clone(),
+        rpc: rpc.clone(),
+        schedule: config.schedule.clone(),
+        pause: pause.clone(),
+        stats: stats.clone(),
+        shutdown: shutdown.clone(),
+    };
+    // A monitor task ending takes only its own source down; the process exits
+    // once no source produces data any more
+    let mut monitors: Vec<(&'static str, JoinHandle<()>)> = monitor::sources(&config)
+        .iter()
+        .map(|source| (source.name, monitor::spawn(source, &config, &context)))
+        .collect();
+    
+    // Mirror health events onto the market bus, for consumers of the unified stream
+    {
+        let (mut health_rx, tx) = (health_tx.subscribe(), tx.clone());
         tokio::spawn(async move {
-            let mut raydium = RaydiumMonitor::new();
             loop {
-                match raydium.start_monitoring(tx.clone()).await {
-                    Ok(_) => {
-                        println!("✅ Raydium monitoring ended normally");
-                    }
-                    Err(e) => {
-                        eprintln!("❌ Raydium error: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                        println!("🔄 Reconnecting to Raydium...");
+                match health_rx.recv().await {
+                    Ok(event) => {
+                        let _ = tx.send(MarketEvent::Health(event));
                     }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-        })
-    };
+        });
+    }
     
-    // Start Orca Whirlpool monitoring 
-    let orca_handle = {
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            let mut orca = OrcaMonitor::new();
+    // Demo: Print all price updates, liquidity changes, swaps, depth, routing hints, Jupiter comparisons and derived series from any AMM
+    let tui = tui_logs.is_some();
+    // `snapshot`: a comparison table of every pool's first price replaces the stream
+    let snapshot_pools = (command == Command::Snapshot).then(|| all_pools.clone());
+    let snapshot = snapshot_pools.is_some();
+    let snapshot_taken = cli::snapshot(snapshot_pools, tx.subscribe());
+    tokio::pin!(snapshot_taken);
+    let mut price_display_handle = tokio::spawn(async move {
+        if snapshot {
+            drop(rx);
+            return std::future::pending().await;
+        }
+        
+        if let Some(logs) = tui_logs {
+            tui::run(rx, logs).await;
+            return;
+        }
+        
+        // `--output json`: every event as one JSON line on stdout, for jq and friends
+        if display::json_output() {
             loop {
-                match orca.start_monitoring(tx.clone()).await {
-                    Ok(_) => {
-                        println!("✅ Orca monitoring ended normally");
+                match rx.recv().await {
+                    Ok(event) => {
+                        let Ok(line) = event.to_json(schema::LATEST) else {
+                            continue;
+                        };
+                        let mut stdout = std::io::stdout().lock();
+                        // The reader went away (e.g. `| head`); nothing left to do
+                        if writeln!(stdout, "{}", line).is_err() {
+                            exit::exit(ExitReason::Shutdown, "");
+                        }
                     }
-                    Err(e) => {
-                        eprintln!("❌ Orca error: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                        println!("🔄 Reconnecting to Orca...");
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "JSON output lagged, skipped events");
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-        })
-    };
+            return;
+        }
+        
+        loop {
+            match rx.recv().await {
+                Ok(MarketEvent::Price(price_update)) => print_price(&price_update),
+                Ok(MarketEvent::Liquidity(event)) => print_liquidity(&event),
+                Ok(MarketEvent::Swap(swap)) => print_swap(&swap),
+                Ok(MarketEvent::Depth(depth)) => print_depth(&depth),
+                Ok(MarketEvent::Route(route)) => print_route(&route),
+                Ok(MarketEvent::Aggregator(quote)) => print_aggregator(&quote),
+                Ok(MarketEvent::Derived(value)) => print_derived(&value),
+                Ok(MarketEvent::Migration(rate)) => print_migration(&rate),
+                Ok(MarketEvent::Incentive(incentive)) => print_incentive(&incentive),
+                Ok(MarketEvent::Twap(twap)) => print_twap(&twap),
+                Ok(MarketEvent::Checkpoint(checkpoint)) => print_checkpoint(&checkpoint),
+                Ok(MarketEvent::Stats(stats)) => print_stats(&stats),
+                Ok(MarketEvent::Anomaly(anomaly)) => print_anomaly(&anomaly),
+                // Summed up by the incentive APRs they turn into
+                Ok(MarketEvent::Rewards(_)) => {}
+                // Already logged where they're raised
+                Ok(MarketEvent::Health(_)) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "display lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    
+    // Optional shared-memory ring for co-located consumers (see shm.rs for the layout)
+    if let Ok(shm_path) = std::env::var("DEX_WATCHER_SHM_PATH") {
+        let slots = std::env::var("DEX_WATCHER_SHM_SLOTS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(4096);
 
-    // Start Meteora DLMM monitoring
-    let meteora_handle = {
-        let tx = tx.clone();
+        match ShmRing::create(std::path::Path::new(&shm_path), slots) {
+            Ok(mut ring) => {
+                let mut rx = tx.subscribe();
+                tokio::spawn(async move {
+                    loop {
+                        match rx.recv().await {
+                            Ok(MarketEvent::Price(price_update)) => ring.publish(&TickRecord::from_update(&price_update)),
+                            Ok(_) => {}
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                tracing::warn!(skipped = skipped, "shared-memory writer lagged, skipped updates");
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                });
+                tracing::info!(shm_path = %shm_path, slots = %slots, "publishing ticks to shared memory");
+            }
+            Err(e) => {
+                tracing::error!(error = %e, shm_path = %shm_path, "failed to create shared-memory ring");
+            }
+        }
+    }
+    
+    // Optional zero-copy IPC for local subscribers (requires the iceoryx2 feature)
+    #[cfg(feature = "iceoryx2")]
+    if let Ok(service_name) = std::env::var("DEX_WATCHER_IPC_SERVICE") {
+        iceoryx::spawn_publisher(service_name, tx.subscribe());
+    }
+    
+    // Sinks awaited on shutdown so they can flush
+    let mut sinks: Vec<(&'static str, JoinHandle<()>)> = Vec::new();
+    
+    // Market events to Redis pub/sub or a capped stream
+    if config.redis.enabled {
+        let (redis, tx, shutdown) = (config.redis.clone(), tx.clone(), sinks_shutdown.clone());
+        sinks.push(("Redis", supervisor::supervise("Redis", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+            RedisSink::new(redis.clone()).run(tx.subscribe(), shutdown.clone())
+        })));
+    }
+    
+    // Market events to Amazon Kinesis (requires the kinesis feature)
+    #[cfg(feature = "kinesis")]
+    if config.kinesis.enabled {
+        let (kinesis, tx, shutdown) = (config.kinesis.clone(), tx.clone(), sinks_shutdown.clone());
+        sinks.push(("Kinesis", supervisor::supervise("Kinesis", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+            let (kinesis, rx, shutdown) = (kinesis.clone(), tx.subscribe(), shutdown.clone());
+            async move { kinesis::KinesisSink::new(kinesis).await.run(rx, shutdown).await }
+        })));
+    }
+    
+    // Rolling market event files uploaded to S3/GCS (requires the archive feature)
+    #[cfg(feature = "archive")]
+    if config.archive.enabled {
+        let (archive, tx, shutdown) = (config.archive.clone(), tx.clone(), sinks_shutdown.clone());
+        sinks.push(("Archive", supervisor::supervise("Archive", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+            let (archive, rx, shutdown) = (archive.clone(), tx.subscribe(), shutdown.clone());
+            async move {
+                match archive::Archiver::new(archive) {
+                    Ok(archiver) => archiver.run(rx, shutdown).await,
+                    Err(e) => tracing::error!(error = %e, "failed to start archiver"),
+                }
+            }
+        })));
+    }
+    
+    // Price updates to local CSV or Parquet files
+    if config.dataset.enabled {
+        let (dataset, tx, shutdown) = (config.dataset.clone(), tx.clone(), sinks_shutdown.clone());
+        sinks.push(("Dataset", supervisor::supervise("Dataset", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+            let (dataset, rx, shutdown) = (dataset.clone(), tx.subscribe(), shutdown.clone());
+            async move {
+                match dataset::DatasetRecorder::new(dataset) {
+                    Ok(recorder) => recorder.run(rx, shutdown).await,
+                    Err(e) => tracing::error!(error = %e, "failed to start dataset recorder"),
+                }
+            }
+        })));
+    }
+    
+    // Cross-DEX arbitrage detection on top of the shared price stream
+    if config.arbitrage.enabled {
+        let (arb_tx, mut arb_rx) = broadcast::channel(100);
+        let detector = ArbitrageDetector::new(config.arbitrage.clone());
+        tokio::spawn(detector.run(tx.subscribe(), Arc::new(arb_tx), ready.clone()));
+        
         tokio::spawn(async move {
-            let mut meteora = MeteoraMonitor::new();
-            loop {
-                match meteora.start_monitoring(tx.clone()).await {
-                    Ok(_) => {
-                        println!("✅ Meteora monitoring ended normally");
+            while let Ok(opportunity) = arb_rx.recv().await {
+                tracing::info!(target: logging::DISPLAY, "💰 Arbitrage {}: buy {} ${} → sell {} ${} (gross {} bps, net {} bps)",
+                    opportunity.symbol,
+                    opportunity.buy_source,
+                    display::number(opportunity.buy_price, 4),
+                    opportunity.sell_source,
+                    display::number(opportunity.sell_price, 4),
+                    display::signed(opportunity.gross_spread_bps, 1),
+                    display::signed(opportunity.net_spread_bps, 1),
+                );
+            }
+        });
+    }
+    
+    // Liquidity add/remove events, derived from price updates and published back on the market bus
+    tokio::spawn(LiquidityTracker::new().run(tx.subscribe(), tx.clone()));
+    
+    // Active tick/bin migration rates of concentrated pools, published back on the market bus
+    tokio::spawn(MigrationTracker::new().run(tx.subscribe(), tx.clone()));
+    
+    // Incentive APRs of pools with reward emissions, published back on the market bus
+    tokio::spawn(IncentiveTracker::new(&config.rewards).run(tx.subscribe(), tx.clone()));
+    
+    // Cross-venue price per symbol, published back on the market bus as the "Aggregate" source
+    if config.aggregate.enabled {
+        tokio::spawn(PriceAggregator::new(config.aggregate.clone(), stats.clone()).run(tx.subscribe(), tx.clone()));
+    }
+    
+    // Checksums over every feed's latest price, for redundant instances to reconcile
+    if config.checkpoint.interval_secs > 0 {
+        tokio::spawn(checkpoint::run(config.checkpoint.interval_secs, tx.subscribe(), tx.clone()));
+    }
+    
+    // Moving averages and volatility per pool, published back on the market bus
+    if config.stats.enabled {
+        tokio::spawn(StatsTracker::new(config.stats.clone()).run(tx.subscribe(), tx.clone()));
+    }
+    
+    // Time-weighted average prices per pool, published back on the market bus
+    if config.twap.enabled {
+        tokio::spawn(TwapTracker::new(config.twap.clone()).run(tx.subscribe(), tx.clone()));
+    }
+    
+    // Periodic TVL comparison across the venues quoting each symbol
+    if config.tvl.summary_secs > 0 {
+        let (tvl_tx, mut tvl_rx) = broadcast::channel(100);
+        tokio::spawn(TvlReporter::new().run(config.tvl.summary_secs, tx.subscribe(), Arc::new(tvl_tx)));
+        
+        tokio::spawn(async move {
+            while let Ok(summary) = tvl_rx.recv().await {
+                let venues: Vec<String> = summary.venues
+                    .iter()
+                    .map(|venue| format!("{} ${} ({:.1}%)", venue.source, display::number(venue.tvl_usd, 0), venue.share_percent))
+                    .collect();
+                tracing::info!(target: logging::DISPLAY, "💵 TVL {}: ${} total, {}",
+                    summary.symbol,
+                    display::number(summary.total_usd, 0),
+                    venues.join(", "),
+                );
+            }
+        });
+    }
+    
+    // Per-feed availability, with a daily report to the console and the alert notifiers
+    let sla_reports: SlaReports = Default::default();
+    if config.sla.enabled {
+        let (sla_tx, mut sla_rx) = broadcast::channel(10);
+        tokio::spawn(SlaTracker::new(config.sla.clone(), &all_pools).run(
+            tx.subscribe(),
+            pause.clone(),
+            ready.clone(),
+            sla_reports.clone(),
+            Arc::new(sla_tx),
+        ));
+
+        let telegram = config.alerts.telegram.clone().filter(|_| config.alerts.enabled).map(TelegramNotifier::new);
+        let discord = config.alerts.discord.clone().filter(|_| config.alerts.enabled).map(DiscordNotifier::new);
+        tokio::spawn(async move {
+            while let Ok(report) = sla_rx.recv().await {
+                let text = report.text();
+                tracing::info!(target: logging::DISPLAY, "{}", text);
+                if let Some(telegram) = &telegram {
+                    if let Err(e) = telegram.send(&text).await {
+                        tracing::error!(error = %e, "Telegram SLA report failed");
                     }
-                    Err(e) => {
-                        eprintln!("❌ Meteora error: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                        println!("🔄 Reconnecting to Meteora...");
+                }
+                if let Some(discord) = &discord {
+                    if let Err(e) = discord.send(&text).await {
+                        tracing::error!(error = %e, "Discord SLA report failed");
                     }
                 }
             }
-        })
-    };
-    
-    // Demo: Print all price updates from any AMM
-    let price_display_handle = tokio::spawn(async move {
-        while let Ok(price_update) = rx.recv().await {
-            println!("📊 {} from {}: ${:.4} ({:+.2}%) (Reserves: {} SOL / {} USDC)", 
-                price_update.symbol,
-                price_update.source, 
-                price_update.price,
-                price_update.change_percent,
-                price_update.base_reserve as f64 / 1e9,
-                price_update.quote_reserve as f64 / 1e6,
-            );
-        }
-    });
+        });
+    }
     
-    println!("🚀 AMM Price Monitor started! Monitoring:");
-    println!("   - Raydium SOL/USDC (Classic AMM)");
-    println!("   - Orca Whirlpool SOL/USDC (Concentrated Liquidity)");
-    println!("   - Meteora DLMM SOL/USDC (Dynamic Bins)");
-    println!("Press Ctrl+C to exit");
+    // Alerts, late pools and outages as markers on Grafana dashboards
+    if config.grafana.enabled {
+        let (grafana, alert_tx, health_tx, tx) = (config.grafana.clone(), alert_tx.clone(), health_tx.clone(), tx.clone());
+        supervisor::supervise("Grafana", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+            GrafanaAnnotator::new(grafana.clone()).run(alert_tx.subscribe(), health_tx.subscribe(), tx.subscribe())
+        });
+    }
     
-    // Wait for shutdown signal or any task to complete
-    tokio::select! {
-        _ = tokio::signal::ctrl_c() => {
-            println!("🛑 Shutdown signal received...");
+    // Price-move, spread, reconnect-storm and stale-data alerts fanned out to notification sinks
+    if config.alerts.enabled {
+        let mut alert_rx = alert_tx.subscribe();
+        
+        if let Some(telegram) = config.alerts.telegram.clone() {
+            let alert_tx = alert_tx.clone();
+            supervisor::supervise("Telegram", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+                TelegramNotifier::new(telegram.clone()).run(alert_tx.subscribe())
+            });
         }
-        _ = raydium_handle => {
-            println!("🛑 Raydium task ended");
+        if let Some(discord) = config.alerts.discord.clone() {
+            let (alert_tx, tx) = (alert_tx.clone(), tx.clone());
+            supervisor::supervise("Discord", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+                DiscordNotifier::new(discord.clone()).run(alert_tx.subscribe(), tx.subscribe())
+            });
         }
-        _ = orca_handle => {
-            println!("🛑 Orca task ended");
+        
+        {
+            let alerts = config.alerts.clone();
+            let (tx, health_tx, wallet_tx, holder_tx, alert_tx, ready) =
+                (tx.clone(), health_tx.clone(), wallet_tx.clone(), holder_tx.clone(), alert_tx.clone(), ready.clone());
+            supervisor::supervise("Alerts", config.supervisor.clone(), health_tx.clone(), Liveness::Loop, move |heartbeat| {
+                AlertEngine::new(alerts.clone()).run(
+                    tx.subscribe(),
+                    health_tx.subscribe(),
+                    wallet_tx.subscribe(),
+                    holder_tx.subscribe(),
+                    alert_tx.clone(),
+                    heartbeat,
+                    ready.clone(),
+                )
+            });
         }
-        _ = meteora_handle => {
-            println!("🛑 Meteora task ended");
+        
+        tokio::spawn(async move {
+            while let Ok(alert) = alert_rx.recv().await {
+                tracing::info!(target: logging::DISPLAY, "🚨 {}", alert.message);
+            }
+        });
+    }
+    
+    // Swaps decoded from transactions touching the watched pools
+    if config.swaps.enabled {
+        // Order book vaults also move on maker deposits and withdrawals, so vault deltas aren't swaps there.
+        // Meteora AMM tokens sit in vaults shared with other pools and lent out to strategies, likewise.
+        // Bonding curves hold their SOL as lamports, which token balances don't show.
+        // Stake pools aren't traded against at all.
+        let swap_pools = all_pools
+            .iter()
+            .filter(|(source, _)| !["Phoenix", "OpenBook", "Meteora AMM", "Pump.fun", "Stake Pool", "Pyth"].contains(&source.as_str()))
+            .cloned()
+            .collect();
+        tokio::spawn(SwapMonitor::new(swap_pools, &rpc).run(tx.clone()));
+        tokio::spawn(stats.clone().run(tx.subscribe()));
+        
+        let (volume_tx, mut volume_rx) = broadcast::channel(100);
+        tokio::spawn(VolumeTracker::new().run(config.swaps.volume_interval_secs, tx.subscribe(), Arc::new(volume_tx)));
+        
+        tokio::spawn(async move {
+            while let Ok(update) = volume_rx.recv().await {
+                let (_, quote) = update.symbol.split_once('/').unwrap_or((update.symbol.as_str(), ""));
+                tracing::info!(target: logging::DISPLAY, "📦 Volume on {} {}: 1m {} {} ({} swaps), 5m {} {} ({} swaps), 24h {} {} ({} swaps)",
+                    update.source,
+                    update.symbol,
+                    display::number(update.volume_1m.quote_volume, 0), quote, update.volume_1m.swaps,
+                    display::number(update.volume_5m.quote_volume, 0), quote, update.volume_5m.swaps,
+                    display::number(update.volume_24h.quote_volume, 0), quote, update.volume_24h.swaps,
+                );
+            }
+        });
+    }
+    
+    // Largest holders of each pool's base and LP mints
+    if config.holders.enabled {
+        let mut holder_rx = holder_tx.subscribe();
+        {
+            // Bonding curves don't record their mint; stake pools aren't pools of holders' tokens
+            let pools: Vec<(String, PoolConfig)> = all_pools
+                .iter()
+                .filter(|(source, _)| !["Pump.fun", "Stake Pool", "Pyth"].contains(&source.as_str()))
+                .cloned()
+                .collect();
+            let (holders, rpc, holder_tx) = (config.holders.clone(), rpc.clone(), holder_tx.clone());
+            supervisor::supervise("Holders", config.supervisor.clone(), health_tx.clone(), Liveness::Unchecked, move |_| {
+                HoldersTracker::new(holders.clone(), pools.clone(), &rpc).run(holder_tx.clone())
+            });
         }
-        _ = price_display_handle => {
-            println!("🛑 Price display task ended");
+        
+        tokio::spawn(async move {
+            while let Ok(report) = holder_rx.recv().await {
+                let mint = match report.mint_kind {
+                    HolderMint::Base => "base",
+                    HolderMint::Lp => "LP",
+                };
+                tracing::info!(target: logging::DISPLAY, "🏦 {} {} {} holders: top1 {:.1}%, top10 {:.1}%, HHI {:.0}{}",
+                    report.source,
+                    report.symbol,
+                    mint,
+                    report.top1_share_percent,
+                    report.top10_share_percent,
+                    report.hhi,
+                    if report.moves.is_empty() { String::new() } else { format!(" ({} dominant holders moved)", report.moves.len()) },
+                );
+            }
+        });
+    }
+    
+    // Decoder self-check against the RPC node's parsed view of vaults and mints
+    if config.crosscheck.enabled {
+        // Meteora AMM pools hold vault LP tokens rather than token accounts of their own mints,
+        // and bonding curves record neither their mint nor a token account. Stake pools hold stake accounts.
+        let checked_pools = all_pools
+            .iter()
+            .filter(|(source, _)| !["Meteora AMM", "Pump.fun", "Stake Pool", "Pyth"].contains(&source.as_str()))
+            .cloned()
+            .collect();
+        tokio::spawn(crosscheck::run(config.crosscheck.clone(), checked_pools, rpc.clone(), health_tx.clone()));
+    }
+    
+    // Deployment slots of the watched DEX programs, to flag upgrades that may change account layouts
+    if config.upgrades.enabled {
+        let mut sources: Vec<String> = all_pools.iter().map(|(source, _)| source.clone()).collect();
+        sources.dedup();
+        tokio::spawn(upgrades::run(config.upgrades.clone(), sources, rpc.clone(), health_tx.clone()));
+    }
+    
+    // Activity of watched wallets on the monitored DEX programs
+    if config.wallets.enabled {
+        let mut wallet_rx = wallet_tx.subscribe();
+        tokio::spawn(WalletWatcher::new(config.wallets.clone(), all_pools.clone(), &rpc).run(wallet_tx.clone()));
+        
+        tokio::spawn(async move {
+            while let Ok(trade) = wallet_rx.recv().await {
+                let activity = match trade.activity {
+                    WalletActivity::Swap => "swap",
+                    WalletActivity::LiquidityAdd => "liquidity add",
+                    WalletActivity::LiquidityRemove => "liquidity remove",
+                };
+                let deltas: Vec<String> = trade.deltas
+                    .iter()
+                    .map(|delta| format!("{:+.4} {}", delta.amount, delta.mint))
+                    .collect();
+                tracing::info!(target: logging::DISPLAY, "👛 {} {} on {}{}: {} ({})",
+                    trade.wallet,
+                    activity,
+                    trade.source,
+                    trade.symbol.map(|symbol| format!(" {}", symbol)).unwrap_or_default(),
+                    deltas.join(", "),
+                    trade.signature,
+                );
+            }
+        });
+    }
+    
+    // Filled by the stale-source watchdog below, read by GET /health
+    let stale_sources: StaleSources = Default::default();
+
+    // REST API serving the latest price per pool
+    if config.api.enabled {
+        let state = ApiState {
+            prices: Default::default(),
+            twaps: Default::default(),
+            routes: Default::default(),
+            updates: tx.clone(),
+            ready: ready.clone(),
+            pause: pause.clone(),
+            control_token: config.api.control_token.clone(),
+            rpc: rpc.clone(),
+            sla: sla_reports.clone(),
+            capabilities: Arc::new(capabilities::from_config(&config)),
+            stale: stale_sources.clone(),
+        };
+        tokio::spawn(api::track_prices(tx.subscribe(), state.prices.clone(), state.twaps.clone()));
+        tokio::spawn(routing::run(config.routing.clone(), tx.subscribe(), state.routes.clone()));
+        
+        let listen = config.api.listen;
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(listen, state).await {
+                tracing::error!(error = %e, "REST API error");
+            }
+        });
+    }
+    
+    // Liquidity heatmap snapshots (Whirlpool ticks, DLMM bins)
+    if config.heatmap.enabled {
+        tokio::spawn(distribution::run_exporter(
+            config.heatmap.clone(),
+            config.orca.pools.clone(),
+            config.meteora.pools.clone(),
+            rpc.clone(),
+        ));
+    }
+    
+    // Best venue per trade size and direction, published on the market bus
+    if config.best_route.enabled {
+        tokio::spawn(best_route::run(config.best_route.clone(), tx.subscribe(), tx.clone(), rpc.clone()));
+    }
+    
+    // Jupiter's aggregated route next to the best single pool, published on the market bus
+    if config.jupiter.enabled {
+        tokio::spawn(jupiter::run(config.jupiter.clone(), tx.subscribe(), tx.clone(), rpc.clone()));
+    }
+    
+    // User-defined series over the latest prices, published on the market bus
+    if !config.derived.is_empty() {
+        tokio::spawn(derived::run(config.derived.clone(), tx.subscribe(), tx.clone()));
+    }
+    
+    // Liquidity within ±x% of the price of concentrated pools, published on the market bus
+    if config.depth.enabled {
+        tokio::spawn(depth::run(
+            config.depth.clone(),
+            config.orca.pools.clone(),
+            config.meteora.pools.clone(),
+            rpc.clone(),
+            tx.clone(),
+        ));
+    }
+    
+    // Dead-man's switch pings, withheld once price updates stop
+    if !config.deadman.urls.is_empty() {
+        tokio::spawn(deadman::run(config.deadman.clone(), tx.subscribe()));
+    }
+    
+    // Prometheus scrape endpoint
+    if config.metrics.enabled {
+        let listen = config.metrics.listen;
+        tokio::spawn(metrics::track_prices(tx.subscribe()));
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(listen).await {
+                tracing::error!(error = %e, "metrics server error");
+            }
+        });
+    }
+    
+    let capabilities = capabilities::from_config(&config);
+    tracing::info!(target: logging::DISPLAY, "🚀 AMM Price Monitor started! Monitoring {} pairs across {} sources:", capabilities.pairs.len(), capabilities.sources.len());
+    if capabilities.cluster != Cluster::Mainnet {
+        tracing::info!(target: logging::DISPLAY, "   Cluster: {:?}", capabilities.cluster);
+    }
+    for source in &capabilities.sources {
+        let symbols: Vec<&str> = source.pools.iter().map(|pool| pool.symbol.as_str()).collect();
+        tracing::info!(target: logging::DISPLAY, "   - {} ({}): {}", source.source, source.kind, symbols.join(", "));
+    }
+    tracing::info!(target: logging::DISPLAY, "Press Ctrl+C to exit");
+    
+    let monitored: Vec<&'static str> = monitor::sources(&config)
+        .into_iter()
+        .filter(|source| !source.dex.pools.is_empty())
+        .map(|source| source.name)
+        .collect();
+    // Sources still looping but no longer publishing prices
+    if config.supervisor.stale_source_secs > 0 {
+        tokio::spawn(health::watch_sources(
+            monitored.clone(),
+            std::time::Duration::from_secs(config.supervisor.stale_source_secs),
+            tx.subscribe(),
+            health_tx.subscribe(),
+            health_tx.clone(),
+            stale_sources.clone(),
+        ));
+    }
+    let monitors_dead = health::all_monitors_dead(
+        monitored,
+        tx.subscribe(),
+        health_tx.subscribe(),
+        std::time::Duration::from_secs(MONITORS_DEAD_AFTER_SECS),
+    );
+    
+    tokio::pin!(monitors_dead);
+    
+    // Wait for shutdown signal, the display or a one-shot task to complete, or every monitor to stall
+    let mut recorded = false;
+    let (reason, message) = loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("shutdown signal received");
+                break (ExitReason::Shutdown, String::new());
+            }
+            (name, result) = next_ended(&mut monitors) => {
+                let (_, message) = exit::task_ended(name, result);
+                tracing::warn!(message = %message, "task ended, the other sources keep running");
+                let _ = health_tx.send(HealthEvent::stopped(name, &message));
+            }
+            result = &mut price_display_handle => break match result {
+                // The dashboard was quit (raw mode turns Ctrl+C into a key press rather
+                // than a signal)
+                Ok(()) if tui => (ExitReason::Shutdown, String::new()),
+                result => {
+                    tracing::info!("price display task ended");
+                    exit::task_ended("Price display", result)
+                }
+            },
+            result = &mut snapshot_taken => break match result {
+                Ok(()) => (ExitReason::Shutdown, String::new()),
+                Err(e) => (ExitReason::SnapshotIncomplete, e.to_string()),
+            },
+            result = &mut recording => break match result {
+                Ok(path) => {
+                    recorded = true;
+                    tracing::info!(path = %path.display(), "session recorded");
+                    (ExitReason::Shutdown, String::new())
+                }
+                Err(e) => {
+                    recorded = true;
+                    tracing::error!(error = %e, "session recording failed");
+                    (ExitReason::RecordingFailed, e.to_string())
+                }
+            },
+            errors = &mut monitors_dead => {
+                tracing::error!(monitors_dead_after_secs = MONITORS_DEAD_AFTER_SECS, errors = %errors, "no monitor has produced data");
+                break (ExitReason::MonitorsDead, errors);
+            }
+        }
+    };
+    
+    tracing::info!("shutting down all monitors");
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(SHUTDOWN_TIMEOUT_SECS);
+    shutdown.cancel();
+    await_tasks(monitors, deadline).await;
+    
+    // Only once the monitors are done, so the sinks see their last updates
+    sinks_shutdown.cancel();
+    await_tasks(sinks, deadline).await;
+    if recording_session && !recorded {
+        match tokio::time::timeout_at(deadline, &mut recording).await {
+            Ok(Ok(path)) => tracing::info!(path = %path.display(), "session recorded"),
+            Ok(Err(e)) => tracing::error!(error = %e, "session recording failed"),
+            Err(_) => tracing::warn!(shutdown_timeout_secs = SHUTDOWN_TIMEOUT_SECS, "session recording didn't finish"),
         }
     }
     
-    println!("🛑 Shutting down all monitors...");
-}
\ No newline at end of file
+    exit::exit(reason, &message);
+}
+
+// The next of `tasks` to end, taken off the list with its result; pending
+// until one does, and for good once none are left
+async fn next_ended(tasks: &mut Vec<(&'static str, JoinHandle<()>)>) -> (&'static str, Result<(), tokio::task::JoinError>) {
+    if tasks.is_empty() {
+        return std::future::pending().await;
+    }
+    let (result, index, _) = futures::future::select_all(tasks.iter_mut().map(|(_, handle)| handle)).await;
+    let (name, _) = tasks.remove(index);
+    (name, result)
+}
+
+// Wait for tasks still running to wind down, up to the shutdown deadline
+async fn await_tasks(tasks: Vec<(&'static str, JoinHandle<()>)>, deadline: tokio::time::Instant) {
+    // Finished ones may already have been awaited by the select in main
+    for (name, handle) in tasks.into_iter().filter(|(_, handle)| !handle.is_finished()) {
+        if tokio::time::timeout_at(deadline, handle).await.is_err() {
+            tracing::warn!(task = name, shutdown_timeout_secs = SHUTDOWN_TIMEOUT_SECS, "task didn't stop, leaving it behind");
+        }
+    }
+}
+
+fn print_price(price_update: &PriceUpdate) {
+    let (base, quote) = price_update.symbol.split_once('/').unwrap_or((price_update.symbol.as_str(), ""));
+    let reason = match price_update.change_reason {
+        ChangeReason::Initial | ChangeReason::Unchanged => String::new(),
+        reason => format!(" [{}]", reason),
+    };
+    let stale = if price_update.slot_lag > STALE_SLOT_LAG {
+        format!(" ⏳ {} slots behind tip", price_update.slot_lag)
+    } else {
+        String::new()
+    };
+    let tvl = price_update.tvl_usd
+        .map(|tvl_usd| format!(" TVL ${}", display::number(tvl_usd, 0)))
+        .unwrap_or_default();
+    let book = match (price_update.bid, price_update.ask, price_update.microprice, price_update.imbalance) {
+        (Some(bid), Some(ask), Some(microprice), Some(imbalance)) => format!(" (bid ${} / ask ${}, micro ${}, imbalance {})",
+            display::number(bid, 4), display::number(ask, 4), display::number(microprice, 4), display::signed(imbalance, 2)),
+        (Some(bid), Some(ask), ..) => format!(" (bid ${} / ask ${})", display::number(bid, 4), display::number(ask, 4)),
+        _ => String::new(),
+    };
+    // Windowed changes once the history covers them; until then the change since the last update
+    let windows: Vec<String> = [("1m", price_update.change_1m), ("5m", price_update.change_5m), ("24h", price_update.change_24h)]
+        .into_iter()
+        .filter_map(|(window, change)| change.map(|change| format!("{} {}%", window, display::signed(change, 2))))
+        .collect();
+    let change = if windows.is_empty() {
+        format!("{}%", display::signed(price_update.change_percent, 2))
+    } else {
+        windows.join(", ")
+    };
+    let line = format!("📊 {} from {}: ${} ({}){} (24h ${}-${}) (Reserves: {} {} / {} {}){}{}{}", 
+        price_update.symbol,
+        price_update.source, 
+        display::number(price_update.price, 4),
+        change,
+        book,
+        display::number(price_update.low_24h, 4),
+        display::number(price_update.high_24h, 4),
+        display::number(price_update.base_reserve as f64 / 10_f64.powi(price_update.base_decimals as i32), 2),
+        base,
+        display::number(price_update.quote_reserve as f64 / 10_f64.powi(price_update.quote_decimals as i32), 2),
+        quote,
+        tvl,
+        reason,
+        stale,
+    );
+    tracing::info!(target: logging::DISPLAY, "{}", line);
+}
+
+fn print_liquidity(event: &LiquidityChanged) {
+    let direction = match event.direction {
+        LiquidityDirection::Add => "added to",
+        LiquidityDirection::Remove => "removed from",
+    };
+    let usd_size = event.usd_size.map(|usd| format!(" (~${})", display::number(usd, 0))).unwrap_or_default();
+    tracing::info!(target: logging::DISPLAY, "💧 Liquidity {} {} {}: {} / {}{}",
+        direction,
+        event.source,
+        event.symbol,
+        display::signed(event.base_delta, 4),
+        display::signed(event.quote_delta, 4),
+        usd_size,
+    );
+}
+
+fn print_swap(swap: &SwapEvent) {
+    let (base, quote) = swap.symbol.split_once('/').unwrap_or((swap.symbol.as_str(), ""));
+    let (amount_in, token_in, amount_out, token_out) = match swap.direction {
+        SwapDirection::Buy => (swap.amount_in, quote, swap.amount_out, base),
+        SwapDirection::Sell => (swap.amount_in, base, swap.amount_out, quote),
+    };
+    tracing::info!(target: logging::DISPLAY, "🔁 Swap on {} {}: {} {} → {} {} @ ${} by {} ({})",
+        swap.source,
+        swap.symbol,
+        display::number(amount_in, 4),
+        token_in,
+        display::number(amount_out, 4),
+        token_out,
+        display::number(swap.price, 4),
+        swap.signer,
+        swap.signature,
+    );
+}
+
+fn print_depth(depth: &DepthUpdate) {
+    let (_, quote) = depth.symbol.split_once('/').unwrap_or((depth.symbol.as_str(), ""));
+    let bands: Vec<String> = depth.bands
+        .iter()
+        .map(|band| format!("±{}%: bid {} / ask {} {}",
+            band.percent,
+            display::number(band.bid_depth, 0),
+            display::number(band.ask_depth, 0),
+            quote,
+        ))
+        .collect();
+    tracing::info!(target: logging::DISPLAY, "📏 Depth on {} {} @ ${}: {}",
+        depth.source,
+        depth.symbol,
+        display::number(depth.price, 4),
+        bands.join(", "),
+    );
+}
+
+fn print_derived(value: &DerivedValue) {
+    tracing::info!(target: logging::DISPLAY, "🧮 {} {}: {}", value.name, value.symbol, display::number(value.value, 4));
+}
+
+// Only actual moves; every other update of the pool just refreshes the rates
+fn print_migration(rate: &MigrationRate) {
+    if rate.moved == 0 {
+        return;
+    }
+    tracing::info!(target: logging::DISPLAY, "🔀 {} {} active {} {} → {} ({} crossed/min, {} changes/min)",
+        rate.source,
+        rate.symbol,
+        if rate.source == "Meteora" { "bin" } else { "tick" },
+        display::signed(rate.moved as f64, 0),
+        rate.active_index,
+        display::number(rate.crossed_per_minute, 1),
+        display::number(rate.changes_per_minute, 1),
+    );
+}
+
+fn print_incentive(incentive: &IncentiveApr) {
+    let percent = |apr: Option<f64>| apr.map_or("n/a".to_string(), |apr| format!("{}%", display::number(apr, 2)));
+    let rewards: Vec<String> = incentive.rewards
+        .iter()
+        .map(|reward| format!("{} {}", reward.token.as_deref().unwrap_or(&reward.mint), percent(reward.apr_percent)))
+        .collect();
+    // Emissions that stopped leave the pool with nothing to list
+    let rewards = if rewards.is_empty() { "rewards ended".to_string() } else { rewards.join(", ") };
+    tracing::info!(target: logging::DISPLAY, "🎁 {} {} incentives {} APR ({}), fees {} APR",
+        incentive.source,
+        incentive.symbol,
+        percent(incentive.reward_apr_percent),
+        rewards,
+        percent(incentive.fee_apr_percent),
+    );
+}
+
+fn print_twap(twap: &TwapUpdate) {
+    let windows: Vec<String> = twap.windows
+        .iter()
+        .map(|window| {
+            let span = if window.window_secs % 60 == 0 { format!("{}m", window.window_secs / 60) } else { format!("{}s", window.window_secs) };
+            format!("{} ${}", span, display::number(window.price, 4))
+        })
+        .collect();
+    tracing::info!(target: logging::DISPLAY, "⏱️ TWAP {} on {}: {}", twap.symbol, twap.source, windows.join(", "));
+}
+
+fn print_stats(stats: &StatsUpdate) {
+    let windows: Vec<String> = stats.windows
+        .iter()
+        .map(|window| {
+            let span = if window.window_secs % 60 == 0 { format!("{}m", window.window_secs / 60) } else { format!("{}s", window.window_secs) };
+            format!("{} EMA ${} σ {} vol {}%",
+                span,
+                display::number(window.ema, 4),
+                display::number(window.stddev, 4),
+                display::number(window.realized_volatility_percent, 1),
+            )
+        })
+        .collect();
+    tracing::info!(target: logging::DISPLAY, "📐 {} on {}: {}", stats.symbol, stats.source, windows.join(", "));
+}
+
+fn print_anomaly(anomaly: &PriceAnomaly) {
+    tracing::info!(target: logging::DISPLAY, "🚫 {} {} on {}: ${} is {}% off the recent median ${}{}",
+        if anomaly.rejected { "Rejected" } else { "Suspicious" },
+        anomaly.symbol,
+        anomaly.source,
+        display::number(anomaly.price, 4),
+        display::number(anomaly.deviation_percent, 1),
+        display::number(anomaly.median, 4),
+        if anomaly.rejected { ", not published" } else { "" },
+    );
+}
+
+fn print_checkpoint(checkpoint: &Checkpoint) {
+    tracing::info!(target: logging::DISPLAY, "🧾 Checkpoint {} over {} feeds", &checkpoint.checksum[..16], checkpoint.feeds);
+}
+
+fn print_aggregator(quote: &AggregatorQuote) {
+    let comparison = match (&quote.best_pool_source, quote.best_pool_price, quote.improvement_bps) {
+        (Some(source), Some(price), Some(bps)) => format!(" vs {} @ ${} ({} bps)", source, display::number(price, 4), display::number(bps, 1)),
+        _ => " (no single pool fills it)".to_string(),
+    };
+    tracing::info!(target: logging::DISPLAY, "🪐 Jupiter {:?} {} {}: ${} via {}{}",
+        quote.side,
+        display::number(quote.size, 4),
+        quote.symbol,
+        display::number(quote.route_price, 4),
+        quote.route.join(" → "),
+        comparison,
+    );
+}
+
+fn print_route(route: &BestRoute) {
+    let runner_up = match (&route.runner_up, route.runner_up_bps) {
+        (Some(source), Some(bps)) => format!(", {} {} bps behind", source, display::number(bps, 1)),
+        _ => String::new(),
+    };
+    tracing::info!(target: logging::DISPLAY, "🧭 Best route {} {} → {} {}: {} @ ${} ({} bps slippage{})",
+        display::number(route.amount_in, 4),
+        route.input_token,
+        display::number(route.amount_out, 4),
+        route.output_token,
+        route.source,
+        display::number(route.execution_price, 4),
+        display::number(route.slippage_bps, 1),
+        runner_up,
+    );
+}
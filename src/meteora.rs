@@ -1,138 +1,485 @@
+use async_trait::async_trait;
 use carbon_meteora_dlmm_decoder::accounts::lb_pair::LbPair;
 use carbon_core::deserialize::CarbonDeserialize;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{ pubkey::Pubkey};
-use std::str::FromStr;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
 use tokio::sync::broadcast;
-use anyhow::Result;
+use tokio_util::sync::CancellationToken;
 use std::sync::Arc;
 
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::fingerprint::{account_data, Fingerprints};
+use crate::backoff::Backoff;
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
 use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+// Decoded state of one DLMM pool for a single tick
+struct DlmmData {
+   price: f64,
+   base_reserve: u64,
+   quote_reserve: u64,
+   base_decimals: u8,
+   quote_decimals: u8,
+   fee_bps: f64,
+   active_id: i64,
+}
 
 pub struct MeteoraMonitor {
    rpc_client: RpcClient,
-   dlmm_pool_address: Pubkey,
-   price_cache: Option<f64>,
+   dlmm_pools: Vec<(Pubkey, PoolConfig)>,
+   last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+   // (token_x, token_y) decimals per pool, resolved once from the mints
+   decimals_cache: HashMap<Pubkey, (u8, u8)>,
+   // Pushed pool account changes when running on the Geyser backend
+   geyser: Option<AccountUpdates>,
+   rpc: RpcProvider,
+   backoff: Backoff,
+   // Highest slot read so far; later reads are pinned at or after it
+   last_slot: u64,
+   // Geyser only pushes changes, so the first pass polls a full snapshot
+   snapshotted: bool,
+   // Which pools each poll reads, per their active hours
+   schedule: PollSchedule,
+   // Pool and reserve data as of the last read, and the decode it gave
+   fingerprints: Fingerprints,
+   decoded: HashMap<Pubkey, LbPair>,
+   // Pools dropped during the current fetch, reported once it is done
+   dropped: Vec<HealthEvent>,
 }
 
 impl MeteoraMonitor {
-   pub fn new() -> Self {
-       let rpc_client = RpcClient::new("https://api.mainnet-beta.solana.com".to_string());
+   pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+       let rpc_client = rpc.client(CommitmentConfig::default());
        
-       let dlmm_pool_address = Pubkey::from_str("5rCf1DM8LjKTw4YqhnoLcngyZYeNnQqztScTogYHAS6").unwrap();
+       let dlmm_pools: Vec<(Pubkey, PoolConfig)> = pools
+           .into_iter()
+           .map(|pool| (pool.pubkey().unwrap(), pool))
+           .collect();
+       let geyser = geyser.map(|config| {
+           AccountUpdates::subscribe(config, "Meteora", dlmm_pools.iter().map(|(address, _)| *address).collect())
+       });
+       let fingerprints = Fingerprints::new("Meteora", schedule);
+       let schedule = PollSchedule::new("Meteora", &dlmm_pools, schedule);
        
        Self {
            rpc_client,
-           dlmm_pool_address,
-           price_cache: None,
+           dlmm_pools,
+           last_snapshot: HashMap::new(),
+           decimals_cache: HashMap::new(),
+           geyser,
+           rpc: rpc.clone(),
+           backoff: Backoff::new(),
+           last_slot: 0,
+           snapshotted: false,
+           schedule,
+           fingerprints,
+           decoded: HashMap::new(),
+           dropped: Vec::new(),
        }
    }
 
-   pub async fn start_monitoring(&mut self, tx: Arc<broadcast::Sender<PriceUpdate>>) -> Result<()> {
+   pub async fn start_monitoring(
+       &mut self,
+       tx: Arc<broadcast::Sender<MarketEvent>>,
+       health_tx: Arc<broadcast::Sender<HealthEvent>>,
+       heartbeat: &Heartbeat,
+       pause: &Pause,
+       stats: &RollingStats,
+       shutdown: &CancellationToken,
+   ) -> Result<()> {
        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+       // Resume the 2s cadence after a backoff instead of bursting missed ticks
+       interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
        
        loop {
-           interval.tick().await;
+           heartbeat.beat();
+           
+           // Changes pushed while paused may be long outdated, so re-read a full snapshot
+           if shutdown.run_until_cancelled(pause.wait_resumed("Meteora", heartbeat)).await == Some(true) {
+               self.snapshotted = false;
+           }
            
-           match self.fetch_dlmm_data().await {
-               Ok((current_price, base_reserve, quote_reserve)) => {
-                   let change_percent = if let Some(cached) = self.price_cache {
-                       ((current_price - cached) / cached) * 100.0
+           // On Geyser, wait for pool accounts to change instead of polling, once the initial snapshot is in
+           let pushed = match self.geyser.as_mut() {
+               Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                   Some(batch) => Some(batch?),
+                   None => return Ok(()),
+               },
+               _ => {
+                   shutdown.run_until_cancelled(interval.tick()).await;
+                   None
+               }
+           };
+           
+           // Stop between fetches, never in the middle of one
+           if shutdown.is_cancelled() {
+               return Ok(());
+           }
+
+           let fetch_started = std::time::Instant::now();
+           let result = match pushed {
+               Some((slot, accounts)) => self.parse_dlmm_accounts(accounts).await.map(|pools| (slot, pools)),
+               None => self.fetch_dlmm_data().await,
+           };
+           metrics::observe_fetch("Meteora", fetch_started);
+
+           // Dropped pools are gone for good; once none are left there's nothing to watch
+           if !self.dropped.is_empty() {
+               for event in self.dropped.drain(..) {
+                   let _ = health_tx.send(event);
+               }
+               if self.dlmm_pools.is_empty() {
+                   tracing::error!(source = "Meteora", "no pools left to watch");
+                   return Ok(());
+               }
+           }
+           
+           match result {
+               Ok((slot, pools)) => {
+                   self.snapshotted = true;
+                   if self.backoff.reset() {
+                       tracing::info!(source = "Meteora", "fetches recovered");
+                       let _ = health_tx.send(HealthEvent::recovered("Meteora"));
+                   }
+                   // No tip lookup while every pool is parked between keep-alives
+                   let slot_lag = if pools.is_empty() {
+                       0
                    } else {
-                       0.0
-                   };
-                   
-                   let update = PriceUpdate {
-                       symbol: "SOL/USDC".to_string(),
-                       source: "Meteora".to_string(),
-                       price: current_price,
-                       change_percent,
-                       timestamp: std::time::SystemTime::now()
-                           .duration_since(std::time::UNIX_EPOCH)
-                           .unwrap()
-                           .as_secs(),
-                       base_reserve,
-                       quote_reserve,
+                       self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
                    };
-                   
-                   if tx.send(update).is_err() {
-                       println!("No receivers for Meteora price updates");
+                   for (address, symbol, data) in pools {
+                       let current_price = data.price;
+                       let snapshot = ReserveSnapshot {
+                           base_reserve: data.base_reserve,
+                           quote_reserve: data.quote_reserve,
+                           price: current_price,
+                       };
+                       let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                           ((current_price - cached.price) / cached.price) * 100.0
+                       } else {
+                           0.0
+                       };
+                       
+                       let tvl_usd = tvl::pool_tvl_usd(
+                           &symbol,
+                           data.base_reserve,
+                           data.quote_reserve,
+                           data.base_decimals,
+                           data.quote_decimals,
+                           current_price,
+                       );
+                       
+                       if sanity::reject(&tx, "Meteora", &symbol, &address.to_string(), current_price, slot) {
+                           continue;
+                       }
+                       let rolling = stats.record(&address.to_string(), current_price);
+                       let update = PriceUpdate {
+                           symbol,
+                           source: "Meteora".to_string(),
+                           pool: address.to_string(),
+                           price: current_price,
+                           change_percent,
+                           timestamp: unix_now(),
+                           base_reserve: data.base_reserve,
+                           quote_reserve: data.quote_reserve,
+                           base_decimals: data.base_decimals,
+                           quote_decimals: data.quote_decimals,
+                           change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                           lp_supply: None,
+                           fee_bps: data.fee_bps,
+                           slot,
+                           slot_lag,
+                           tvl_usd,
+                           high_24h: rolling.high,
+                           low_24h: rolling.low,
+                           volume_24h: rolling.volume,
+                           bid: None,
+                           ask: None,
+                           active_index: Some(data.active_id),
+                           microprice: None,
+                           imbalance: None,
+                           change_1m: rolling.change_1m,
+                           change_5m: rolling.change_5m,
+                           change_24h: rolling.change_24h,
+                           unchanged: false,
+                       };
+                       
+                       if dedup::publish(&tx, update).is_err() {
+                           tracing::info!(source = "Meteora", "no receivers for price updates");
+                       }
+                       
+                       self.last_snapshot.insert(address, snapshot);
                    }
-                   
-                   self.price_cache = Some(current_price);
                }
                Err(e) => {
-                   eprintln!("Failed to fetch Meteora DLMM price: {}", e);
+                   metrics::inc_rpc_error("Meteora");
+                   let delay = self.backoff.next_delay(self.rpc.retry_after());
+                   tracing::warn!(source = "Meteora", failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                   let _ = health_tx.send(HealthEvent::degraded("Meteora", &e, self.backoff.failures(), delay));
+                   shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
                }
            }
        }
    }
 
-   async fn fetch_dlmm_data(&self) -> Result<(f64, u64, u64)> {
-       // Get DLMM pool account data
-       let account = self.rpc_client.get_account(&self.dlmm_pool_address)?;
+   async fn fetch_dlmm_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, DlmmData)>)> {
+       // Get all DLMM pool accounts in one round trip, with the slot they were read at
+       // Parked pools are only read once per keep-alive
+       let due = self.schedule.due(&self.dlmm_pools);
+       if due.is_empty() {
+           return Ok((0, Vec::new()));
+       }
+       let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+       let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+       let slot = response.context.slot;
+       self.last_slot = self.last_slot.max(slot);
+       let accounts = response.value;
        
-       // Parse account data with Carbon decoder (same pattern as Raydium)
-       let data = &account.data;
+       let mut found = Vec::with_capacity(accounts.len());
+       for ((address, pool), account) in due.into_iter().zip(accounts) {
+           match account {
+               Some(account) => found.push((*address, account)),
+               None => tracing::warn!(source = "Meteora", symbol = %pool.symbol, pool = %address, "pool account not found"),
+           }
+       }
+       
+       Ok((slot, self.parse_dlmm_accounts(found).await?))
+   }
+
+   // Current cluster tip, to tell how far behind the data we publish is
+   async fn tip_slot(&self) -> Option<u64> {
+       match &self.geyser {
+           Some(updates) => Some(updates.tip_slot()),
+           None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
+       }
+   }
+
+   // Decode pool accounts, whether polled or pushed, then read all of their
+   // reserves in one round trip, concurrently with the mints of pools seen for
+   // the first time. Undecodable pools are logged and skipped, and pools whose
+   // pool and reserve data are unchanged since the last read are left out
+   // between keep-alives.
+   async fn parse_dlmm_accounts(&mut self, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, DlmmData)>> {
+       let mut decoded = Vec::with_capacity(accounts.len());
+       for (address, account) in accounts {
+           let Some(symbol) = self.dlmm_pools.iter().find(|(pool_address, _)| *pool_address == address).map(|(_, pool)| pool.symbol.clone()) else {
+               continue;
+           };
+           
+           // Same bytes as last time decode to the same pool, so reuse that
+           let unchanged = self.fingerprints.unchanged(&address, &account.data);
+           let parsed = match self.decoded.get(&address) {
+               Some(lb_pair) if unchanged => Ok(lb_pair.clone()),
+               _ => self.parse_dlmm_pool(&account.data),
+           };
+           match parsed {
+               Ok(lb_pair) => {
+                   self.decoded.insert(address, lb_pair.clone());
+                   decoded.push((address, symbol, lb_pair, unchanged));
+               }
+               Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool("Meteora", &mut self.dlmm_pools, &address, &e)),
+               Err(e) => {
+                   metrics::inc_decode_error("Meteora");
+                   tracing::warn!(source = "Meteora", symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+               }
+           }
+       }
+       
+       let reserves: Vec<Pubkey> = decoded
+           .iter()
+           .flat_map(|(_, _, lb_pair, _)| [lb_pair.reserve_x, lb_pair.reserve_y])
+           .collect();
+       let unresolved: Vec<(Pubkey, Pubkey, Pubkey)> = decoded
+           .iter()
+           .filter(|(address, _, _, _)| !self.decimals_cache.contains_key(address))
+           .map(|(address, _, lb_pair, _)| (*address, lb_pair.token_x_mint, lb_pair.token_y_mint))
+           .collect();
+       let (reserve_accounts, decimals) = tokio::join!(
+           self.get_reserve_accounts(&reserves),
+           self.fetch_mint_decimals(&unresolved),
+       );
+       let reserve_accounts = reserve_accounts?;
+       self.decimals_cache.extend(decimals?);
+       
+       let mut results = Vec::with_capacity(decoded.len());
+       for ((address, symbol, lb_pair, pool_unchanged), (reserves, accounts)) in decoded
+           .into_iter()
+           .zip(reserves.chunks(2).zip(reserve_accounts.chunks(2)))
+       {
+           let Some(&(base_decimals, quote_decimals)) = self.decimals_cache.get(&address) else {
+               metrics::inc_decode_error("Meteora");
+               tracing::warn!(source = "Meteora", symbol = %symbol, pool = %address, "failed to read pool mints");
+               continue;
+           };
+           let amount = |reserve: &Option<Account>| reserve.as_ref().and_then(|reserve| token::token_account_amount(&reserve.data).ok());
+           let (Some(base_reserve), Some(quote_reserve)) = (amount(&accounts[0]), amount(&accounts[1])) else {
+               metrics::inc_decode_error("Meteora");
+               tracing::warn!(source = "Meteora", symbol = %symbol, pool = %address, "failed to read pool reserves");
+               continue;
+           };
+           
+           // Both reserves are recorded even when the first already changed
+           let reserves_unchanged = [
+               self.fingerprints.unchanged(&reserves[0], account_data(&accounts[0])),
+               self.fingerprints.unchanged(&reserves[1], account_data(&accounts[1])),
+           ];
+           if !self.fingerprints.should_publish(&address, pool_unchanged && reserves_unchanged == [true, true]) {
+               continue;
+           }
+           
+           // Get active bin price
+           let price = self.calculate_price_from_active_bin(
+                lb_pair.active_id,
+                lb_pair.bin_step,
+                base_decimals,
+                quote_decimals,
+            );
+           
+           results.push((address, symbol, DlmmData {
+               price,
+               base_reserve,
+               quote_reserve,
+               base_decimals,
+               quote_decimals,
+               fee_bps: swap_fee_bps(&lb_pair, unix_now()),
+               active_id: lb_pair.active_id as i64,
+           }));
+       }
        
+       Ok(results)
+   }
+
+   fn parse_dlmm_pool(&self, data: &[u8]) -> Result<LbPair> {
+       // Parse account data with Carbon decoder (same pattern as Raydium)
        if data.len() < 100 {  
-           return Err(anyhow::anyhow!("Invalid DLMM account data size"));
+           return Err(WatcherError::DecodeError("Invalid DLMM account data size".to_string()));
        }
        
        // 🔥 PROPER PARSING WITH CARBON DECODER! 🔥
        match <LbPair as CarbonDeserialize>::deserialize(data) {
-           Some(lb_pair) => {
-               // Get actual token vault balances
-               let base_reserve = self.get_token_account_balance(&lb_pair.reserve_x).await?;
-               let quote_reserve = self.get_token_account_balance(&lb_pair.reserve_y).await?;
-
-               // Get active bin price
-               let price = self.calculate_price_from_active_bin(
-                    lb_pair.active_id,
-                    lb_pair.bin_step
-                );
-               
-               Ok((price, base_reserve, quote_reserve))
-           }
+           Some(lb_pair) => Ok(lb_pair),
            None => {
-               eprintln!("❌ Failed to parse Meteora DLMM data");
-               println!("🔍 Raw data length: {} bytes", data.len());
-               println!("🔍 First 64 bytes: {}", hex::encode(&data[..64.min(data.len())]));
+               tracing::error!(source = "Meteora", "failed to parse data");
+               tracing::info!(source = "Meteora", bytes = data.len(), "raw data length");
+               tracing::info!(source = "Meteora", data = %hex::encode(&data[..64.min(data.len())]), "first 64 bytes");
                
-               Err(anyhow::anyhow!("Failed to parse Meteora DLMM data"))
+               Err(WatcherError::DecodeError("Failed to parse Meteora DLMM data".to_string()))
            }
        }
    }
 
+   // Token decimals never change, so the mints of each pool are read once, all
+   // new pools in one call. Pools whose mints can't be read are left out.
+   async fn fetch_mint_decimals(&self, pools: &[(Pubkey, Pubkey, Pubkey)]) -> Result<Vec<(Pubkey, (u8, u8))>> {
+       if pools.is_empty() {
+           return Ok(Vec::new());
+       }
+
+       let mints: Vec<Pubkey> = pools.iter().flat_map(|(_, mint_x, mint_y)| [*mint_x, *mint_y]).collect();
+       let accounts = rpc::get_multiple_accounts(&self.rpc_client, &mints).await?;
+       let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+       Ok(pools
+           .iter()
+           .zip(accounts.chunks(2))
+           .filter_map(|((address, _, _), mints)| Some((*address, (decimals(&mints[0])?, decimals(&mints[1])?))))
+           .collect())
+   }
+
    // METEORA DLMM PRICE FORMULA:
     // ===========================
-    // active_bin_price = base_price × (1 + bin_step/10000)^active_id
+    // price = (1 + bin_step/10000)^active_id × 10^(decimals_x - decimals_y)
     //
     // Where:
-    // - base_price = pool's "center" reference price
     // - bin_step = percentage difference between bins (e.g. 10 = 0.1%)
-    // - active_id = current active bin identifier (negative = higher price)
+    // - active_id = current active bin identifier, relative to a bin price of 1
+    // - the bin price is in raw units of Y per raw unit of X, so the decimal
+    //   adjustment turns it into whole tokens (10^(9-6) = 1000 for SOL/USDC)
     //
-    // Example: active_id = -1963, bin_step = 10
-    // → Current price is 1963 bins HIGHER than base_price
-    // → Each bin differs by 0.1% → ~19.6% total deviation
-
-   fn calculate_price_from_active_bin(&self, active_id: i32, bin_step: u16) -> f64 {
-        // Meteora DLMM exact formula: price = (1 + bin_step/10000)^active_id
-        let bin_step_decimal = bin_step as f64 / 10000.0;
-        let base_multiplier = 1.0 + bin_step_decimal;
-        
-        // Calculate the actual price using the DLMM formula
+    // Example: SOL/USDC, active_id = -1963, bin_step = 10
+    // → 1.001^-1963 ≈ 0.1406 raw USDC per lamport
+    // → × 1000 ≈ $140.6 per SOL
+
+   fn calculate_price_from_active_bin(&self, active_id: i32, bin_step: u16, decimals_x: u8, decimals_y: u8) -> f64 {
+        let base_multiplier = 1.0 + bin_step as f64 / 10000.0;
         let active_bin_price = base_multiplier.powi(active_id);
 
-        let final_price = active_bin_price * 1000.0;
-        return final_price;
+        active_bin_price * 10_f64.powi(decimals_x as i32 - decimals_y as i32)
     }
 
-   async fn get_token_account_balance(&self, token_account: &Pubkey) -> anyhow::Result<u64> {
-       let balance = self.rpc_client
-           .get_token_account_balance(token_account)?;
-       
-       Ok(balance.amount.parse::<u64>()?)
+   // Many token accounts in a single getMultipleAccounts call, None for
+   // accounts that are missing
+   async fn get_reserve_accounts(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+       if token_accounts.is_empty() {
+           return Ok(Vec::new());
+       }
+
+       // At least as new as the pools read before them
+       let response = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+       Ok(response.value)
    }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl Monitor for MeteoraMonitor {
+   async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+      self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+   }
+}
+
+// DLMM FEE FORMULA:
+// =================
+// fee = base fee + variable fee, with 1e9 precision and capped at 10%
+//   base fee     = base_factor × bin_step × 10
+//   variable fee = variable_fee_control × (volatility_accumulator × bin_step)² / 1e11
+//
+// The volatility accumulator grows with every bin a swap crosses and decays
+// between swaps, so the fee swings with recent activity. What the next swap
+// pays is the accumulator as the program would reset it now:
+// - within filter_period of the last update, it keeps the reference plus the
+//   bins moved away from index_reference
+// - within decay_period, the reference shrinks to accumulator × reduction_factor / 10000
+// - after that it's back to zero and only the base fee is left
+// A swap crossing bins raises the fee further as it goes; quotes use this
+// starting fee throughout.
+fn swap_fee_bps(lb_pair: &LbPair, now: u64) -> f64 {
+    const FEE_PRECISION: f64 = 1e9;
+    const MAX_FEE_RATE: f64 = 1e8;
+    const BASIS_POINT_MAX: u64 = 10_000;
+
+    let parameters = &lb_pair.parameters;
+    let variable = &lb_pair.v_parameters;
+    let elapsed = (now as i64).saturating_sub(variable.last_update_timestamp).max(0) as u64;
+
+    let reference = if elapsed < parameters.filter_period as u64 {
+        variable.volatility_reference as u64
+    } else if elapsed < parameters.decay_period as u64 {
+        variable.volatility_accumulator as u64 * parameters.reduction_factor as u64 / BASIS_POINT_MAX
+    } else {
+        0
+    };
+    let index_reference = if elapsed < parameters.filter_period as u64 { variable.index_reference } else { lb_pair.active_id };
+    let moved = (index_reference as i64 - lb_pair.active_id as i64).unsigned_abs();
+    let accumulator = (reference + moved * BASIS_POINT_MAX).min(parameters.max_volatility_accumulator as u64);
+
+    let base_fee = parameters.base_factor as f64 * lb_pair.bin_step as f64 * 10.0;
+    let volatility = accumulator as f64 * lb_pair.bin_step as f64;
+    let variable_fee = parameters.variable_fee_control as f64 * volatility * volatility / 1e11;
+    (base_fee + variable_fee).min(MAX_FEE_RATE) / FEE_PRECISION * 10_000.0
+}
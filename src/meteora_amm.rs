@@ -0,0 +1,515 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "Meteora AMM";
+
+// Dynamic AMM Pool account, after the 8-byte Anchor discriminator:
+// lp_mint (32) | token_a_mint (32) | token_b_mint (32) | a_vault (32) | b_vault (32)
+//   | a_vault_lp (32) | b_vault_lp (32) | a_vault_lp_bump (1) | enabled (1)
+//   | protocol_token_a_fee (32) | protocol_token_b_fee (32) | fee_last_updated_at (8)
+//   | padding (24) | trade_fee_numerator (8) | trade_fee_denominator (8)
+//   | protocol_trade_fee_numerator (8) | protocol_trade_fee_denominator (8) | pool_type (1)
+//   | stake (32) | total_locked_lp (8) | bootstrapping (73) | partner_info (56)
+//   | padding (342) | curve_type (tag, then the curve's parameters)
+const LP_MINT_OFFSET: usize = 8;
+const TOKEN_A_MINT_OFFSET: usize = 8 + 32;
+const TOKEN_B_MINT_OFFSET: usize = 8 + 64;
+const A_VAULT_OFFSET: usize = 8 + 96;
+const B_VAULT_OFFSET: usize = 8 + 128;
+const A_VAULT_LP_OFFSET: usize = 8 + 160;
+const B_VAULT_LP_OFFSET: usize = 8 + 192;
+const ENABLED_OFFSET: usize = 8 + 225;
+const TRADE_FEE_NUMERATOR_OFFSET: usize = 8 + 322;
+const TRADE_FEE_DENOMINATOR_OFFSET: usize = 8 + 330;
+const CURVE_TYPE_OFFSET: usize = 8 + 866;
+const POOL_LEN: usize = CURVE_TYPE_OFFSET + 1;
+const CONSTANT_PRODUCT_CURVE: u8 = 0;
+
+// Vault account of Meteora's dynamic vault program, after the discriminator:
+// enabled (1) | bumps (2) | total_amount (8) | token_vault (32) | fee_vault (32)
+//   | token_mint (32) | lp_mint (32) | strategies (32 × 30) | base (32) | admin (32)
+//   | operator (32) | last_updated_locked_profit (8) | last_report (8)
+//   | locked_profit_degradation (8)
+const TOTAL_AMOUNT_OFFSET: usize = 8 + 3;
+const VAULT_LP_MINT_OFFSET: usize = 8 + 107;
+const LOCKED_PROFIT_OFFSET: usize = 8 + 1195;
+const LAST_REPORT_OFFSET: usize = 8 + 1203;
+const LOCKED_PROFIT_DEGRADATION_OFFSET: usize = 8 + 1211;
+const VAULT_LEN: usize = LOCKED_PROFIT_DEGRADATION_OFFSET + 8;
+// Degradation is the share of locked profit released per second, out of this
+const LOCKED_PROFIT_DEGRADATION_DENOMINATOR: u128 = 1_000_000_000_000;
+
+// Accounts a pool is made of, fixed at its creation
+#[derive(Debug, Clone, Copy)]
+pub struct PoolHeader {
+    pub lp_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub a_vault: Pubkey,
+    pub b_vault: Pubkey,
+    pub a_vault_lp: Pubkey,   // The pool's share of vault A, a token account of its LP mint
+    pub b_vault_lp: Pubkey,
+}
+
+impl PoolHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < POOL_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid Meteora AMM pool data size: {} bytes", data.len())));
+        }
+        // Stable pools price off an amplified curve, which the reserve ratio doesn't give
+        if data[CURVE_TYPE_OFFSET] != CONSTANT_PRODUCT_CURVE {
+            return Err(WatcherError::ConfigError("only constant-product pools are supported, not stable-swap ones".to_string()));
+        }
+
+        Ok(Self {
+            lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+            token_a_mint: read_pubkey(data, TOKEN_A_MINT_OFFSET),
+            token_b_mint: read_pubkey(data, TOKEN_B_MINT_OFFSET),
+            a_vault: read_pubkey(data, A_VAULT_OFFSET),
+            b_vault: read_pubkey(data, B_VAULT_OFFSET),
+            a_vault_lp: read_pubkey(data, A_VAULT_LP_OFFSET),
+            b_vault_lp: read_pubkey(data, B_VAULT_LP_OFFSET),
+        })
+    }
+}
+
+// Trade fee and whether the pool takes swaps; both can change after creation
+fn pool_state(data: &[u8]) -> Option<(f64, bool)> {
+    if data.len() < POOL_LEN {
+        return None;
+    }
+    let (numerator, denominator) = (read_u64(data, TRADE_FEE_NUMERATOR_OFFSET), read_u64(data, TRADE_FEE_DENOMINATOR_OFFSET));
+    let fee_bps = if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 * 10_000.0 };
+    Some((fee_bps, data[ENABLED_OFFSET] != 0))
+}
+
+// A dynamic vault lends its tokens out to strategies and holds back profit it
+// has just reported, releasing it linearly over time. Vault LP tokens are worth
+// their share of the unlocked amount only.
+struct Vault {
+    total_amount: u64,
+    lp_mint: Pubkey,
+    last_updated_locked_profit: u64,
+    last_report: u64,
+    locked_profit_degradation: u64,
+}
+
+impl Vault {
+    fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < VAULT_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid Meteora vault data size: {} bytes", data.len())));
+        }
+
+        Ok(Self {
+            total_amount: read_u64(data, TOTAL_AMOUNT_OFFSET),
+            lp_mint: read_pubkey(data, VAULT_LP_MINT_OFFSET),
+            last_updated_locked_profit: read_u64(data, LOCKED_PROFIT_OFFSET),
+            last_report: read_u64(data, LAST_REPORT_OFFSET),
+            locked_profit_degradation: read_u64(data, LOCKED_PROFIT_DEGRADATION_OFFSET),
+        })
+    }
+
+    fn unlocked_amount(&self, now: u64) -> u64 {
+        let ratio = now.saturating_sub(self.last_report) as u128 * self.locked_profit_degradation as u128;
+        let locked_profit = if ratio > LOCKED_PROFIT_DEGRADATION_DENOMINATOR {
+            0
+        } else {
+            (self.last_updated_locked_profit as u128 * (LOCKED_PROFIT_DEGRADATION_DENOMINATOR - ratio)
+                / LOCKED_PROFIT_DEGRADATION_DENOMINATOR) as u64
+        };
+        self.total_amount.saturating_sub(locked_profit)
+    }
+
+    // Tokens a holding of `lp_amount` vault LP tokens redeems for
+    fn amount_by_share(&self, lp_amount: u64, lp_supply: u64, now: u64) -> u64 {
+        if lp_supply == 0 {
+            return 0;
+        }
+        (self.unlocked_amount(now) as u128 * lp_amount as u128 / lp_supply as u128) as u64
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// A pool's accounts together with those of its vaults, resolved once
+#[derive(Debug, Clone, Copy)]
+struct ResolvedPool {
+    header: PoolHeader,
+    a_vault_lp_mint: Pubkey,
+    b_vault_lp_mint: Pubkey,
+    base_decimals: u8,
+    quote_decimals: u8,
+}
+
+// Decoded state of one pool for a single tick
+struct PoolData {
+    pool: ResolvedPool,
+    base_reserve: u64,
+    quote_reserve: u64,
+    lp_supply: u64,
+    fee_bps: f64,
+}
+
+pub struct MeteoraAmmMonitor {
+    rpc_client: RpcClient,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    resolved: HashMap<Pubkey, ResolvedPool>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl MeteoraAmmMonitor {
+    pub fn new(pools: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let schedule = PollSchedule::new(SOURCE, &pools, schedule);
+
+        Self {
+            rpc_client,
+            pools,
+            resolved: HashMap::new(),
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_pool_data().await;
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, pools)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.rpc_client
+                            .get_slot_with_commitment(CommitmentConfig::processed())
+                            .await
+                            .unwrap_or(slot)
+                            .saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in pools {
+                        let base_amount = data.base_reserve as f64 / 10_f64.powi(data.pool.base_decimals as i32);
+                        let quote_amount = data.quote_reserve as f64 / 10_f64.powi(data.pool.quote_decimals as i32);
+                        let current_price = quote_amount / base_amount;
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.pool.base_decimals,
+                            data.pool.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.pool.base_decimals,
+                            quote_decimals: data.pool.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: Some(data.lp_supply),
+                            fee_bps: data.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read every due pool with both vaults, the pool's vault LP balances and
+    // the LP mints in one getMultipleAccounts call, then value the pool's vault
+    // LP tokens at their share of what each vault has unlocked
+    async fn fetch_pool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, PoolData)>)> {
+        // Parked pools are only read once per keep-alive
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.pools)
+            .into_iter()
+            .map(|(address, pool)| (*address, pool.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        self.resolve_pools(&due).await?;
+
+        let due: Vec<(Pubkey, String, ResolvedPool)> = due
+            .into_iter()
+            .filter_map(|(address, symbol)| self.resolved.get(&address).map(|pool| (address, symbol, *pool)))
+            .collect();
+        let accounts: Vec<Pubkey> = due
+            .iter()
+            .flat_map(|(address, _, pool)| [
+                *address,
+                pool.header.a_vault,
+                pool.header.b_vault,
+                pool.header.a_vault_lp,
+                pool.header.b_vault_lp,
+                pool.a_vault_lp_mint,
+                pool.b_vault_lp_mint,
+                pool.header.lp_mint,
+            ])
+            .collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let now = unix_now();
+
+        let mut results = Vec::with_capacity(due.len());
+        for ((address, symbol, pool), accounts) in due.into_iter().zip(response.value.chunks(8)) {
+            let [pool_account, a_vault, b_vault, a_vault_lp, b_vault_lp, a_vault_lp_mint, b_vault_lp_mint, lp_mint] = accounts else {
+                continue;
+            };
+            let Some((fee_bps, enabled)) = pool_account.as_ref().and_then(|account| pool_state(&account.data)) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool");
+                continue;
+            };
+            if !enabled {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "pool is disabled, skipping");
+                continue;
+            }
+
+            let vault = |account: &Option<Account>| account.as_ref().and_then(|account| Vault::parse(&account.data).ok());
+            let amount = |account: &Option<Account>| account.as_ref().and_then(|account| token::token_account_amount(&account.data).ok());
+            let supply = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_supply(&account.data).ok());
+            let (
+                Some(a_vault),
+                Some(b_vault),
+                Some(a_vault_lp),
+                Some(b_vault_lp),
+                Some(a_vault_lp_supply),
+                Some(b_vault_lp_supply),
+                Some(lp_supply),
+            ) = (
+                vault(a_vault),
+                vault(b_vault),
+                amount(a_vault_lp),
+                amount(b_vault_lp),
+                supply(a_vault_lp_mint),
+                supply(b_vault_lp_mint),
+                supply(lp_mint),
+            ) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool vaults");
+                continue;
+            };
+
+            let base_reserve = a_vault.amount_by_share(a_vault_lp, a_vault_lp_supply, now);
+            let quote_reserve = b_vault.amount_by_share(b_vault_lp, b_vault_lp_supply, now);
+            if base_reserve == 0 {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, mint = %pool.header.token_a_mint, "pool holds no token A, no price");
+                continue;
+            }
+
+            results.push((address, symbol, PoolData {
+                pool,
+                base_reserve,
+                quote_reserve,
+                lp_supply,
+                fee_bps,
+            }));
+        }
+
+        Ok((slot, results))
+    }
+
+    // Resolve the pools not seen yet: one call for the pool accounts, then one
+    // for their vaults (for the vault LP mints) and token mints (for decimals)
+    async fn resolve_pools(&mut self, due: &[(Pubkey, String)]) -> Result<()> {
+        let missing: Vec<&(Pubkey, String)> = due.iter().filter(|(address, _)| !self.resolved.contains_key(address)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = missing.iter().map(|(address, _)| *address).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        let mut headers = Vec::with_capacity(missing.len());
+        for ((address, symbol), account) in missing.into_iter().zip(accounts) {
+            let Some(account) = account else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "pool account not found");
+                continue;
+            };
+            match PoolHeader::parse(&account.data) {
+                Ok(header) => headers.push((*address, symbol, header)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.pools, address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+                }
+            }
+        }
+        if headers.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = headers
+            .iter()
+            .flat_map(|(_, _, header)| [header.a_vault, header.b_vault, header.token_a_mint, header.token_b_mint])
+            .collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        for ((address, symbol, header), accounts) in headers.into_iter().zip(accounts.chunks(4)) {
+            let [a_vault, b_vault, token_a_mint, token_b_mint] = accounts else {
+                continue;
+            };
+            let vault = |account: &Option<Account>| account.as_ref().and_then(|account| Vault::parse(&account.data).ok());
+            let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+            let (Some(a_vault), Some(b_vault), Some(base_decimals), Some(quote_decimals)) =
+                (vault(a_vault), vault(b_vault), decimals(token_a_mint), decimals(token_b_mint))
+            else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool vaults or mints");
+                continue;
+            };
+
+            self.resolved.insert(address, ResolvedPool {
+                header,
+                a_vault_lp_mint: a_vault.lp_mint,
+                b_vault_lp_mint: b_vault.lp_mint,
+                base_decimals,
+                quote_decimals,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Monitor for MeteoraAmmMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
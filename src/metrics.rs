@@ -0,0 +1,286 @@
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+use prometheus::{
+    register_gauge_vec, register_histogram_vec, register_int_counter_vec, Encoder, GaugeVec,
+    HistogramVec, IntCounterVec, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::LazyLock;
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+use crate::events::MarketEvent;
+use crate::latency;
+
+pub static LAST_PRICE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_last_price",
+        "Last observed price per DEX pool",
+        &["source", "symbol", "pool"]
+    )
+    .unwrap()
+});
+
+pub static POOL_TVL: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_pool_tvl_usd",
+        "Pool value in USD, for pools quoted in a USD stable",
+        &["source", "symbol", "pool"]
+    )
+    .unwrap()
+});
+
+pub static POOL_FEE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_pool_fee_bps",
+        "Swap fee per pool in bps; dynamic on Meteora DLMM",
+        &["source", "symbol", "pool"]
+    )
+    .unwrap()
+});
+
+pub static DERIVED: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_derived",
+        "Latest value of each configured derived series",
+        &["name", "symbol"]
+    )
+    .unwrap()
+});
+
+pub static MIGRATION_RATE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_active_index_crossed_per_minute",
+        "Ticks or bins the active price crossed per minute over the last 5 minutes, for concentrated pools",
+        &["source", "symbol", "pool"]
+    )
+    .unwrap()
+});
+
+pub static INCENTIVE_APR: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_incentive_apr_percent",
+        "Reward emissions per year relative to pool TVL, over the rewards that could be priced",
+        &["source", "symbol", "pool"]
+    )
+    .unwrap()
+});
+
+pub static TWAP: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_twap",
+        "Time-weighted average price per pool over each configured window",
+        &["source", "symbol", "pool", "window_secs"]
+    )
+    .unwrap()
+});
+
+pub static EMA: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_ema",
+        "Time-decayed moving average price per pool over each configured window",
+        &["source", "symbol", "pool", "window_secs"]
+    )
+    .unwrap()
+});
+
+pub static PRICE_STDDEV: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_price_stddev",
+        "Standard deviation of the prices per pool within each configured window",
+        &["source", "symbol", "pool", "window_secs"]
+    )
+    .unwrap()
+});
+
+pub static REALIZED_VOLATILITY: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "dex_watcher_realized_volatility_percent",
+        "Annualized realized volatility per pool from the log returns within each configured window",
+        &["source", "symbol", "pool", "window_secs"]
+    )
+    .unwrap()
+});
+
+pub static RPC_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_rpc_errors_total",
+        "Failed pool fetches per DEX",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static DECODE_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_decode_errors_total",
+        "Fetched pools that didn't decode or price: bad account data, missing vaults or mints",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static RECONNECTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_reconnects_total",
+        "Monitor restarts after an error per DEX",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static RPC_FAILOVERS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_rpc_failovers_total",
+        "Requests moved to another RPC endpoint after a rate limit, timeout or server error",
+        &["endpoint"]
+    )
+    .unwrap()
+});
+
+pub static UNCHANGED_SKIPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_unchanged_skips_total",
+        "Pool reads skipped because the pool and vault data matched the previous fetch",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static ANOMALIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_anomalies_total",
+        "Prices that deviated too far from their pool's recent median",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static DUPLICATES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "dex_watcher_duplicate_updates_total",
+        "Price updates that repeated their pool's last one, suppressed or marked",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static FETCH_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "dex_watcher_fetch_latency_seconds",
+        "Time to fetch and decode all pools of a DEX",
+        &["source"],
+        vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    )
+    .unwrap()
+});
+
+pub fn observe_fetch(source: &str, started: Instant) {
+    let elapsed = started.elapsed();
+    FETCH_LATENCY
+        .with_label_values(&[source])
+        .observe(elapsed.as_secs_f64());
+    latency::observe_fetch(source, elapsed);
+    tracing::debug!(source, latency_ms = elapsed.as_millis() as u64, "fetch done");
+}
+
+pub fn inc_rpc_error(source: &str) {
+    RPC_ERRORS.with_label_values(&[source]).inc();
+}
+
+pub fn inc_decode_error(source: &str) {
+    DECODE_ERRORS.with_label_values(&[source]).inc();
+}
+
+pub fn inc_reconnect(source: &str) {
+    RECONNECTS.with_label_values(&[source]).inc();
+}
+
+pub fn inc_rpc_failover(endpoint: &str) {
+    RPC_FAILOVERS.with_label_values(&[endpoint]).inc();
+}
+
+pub fn inc_unchanged_skip(source: &str) {
+    UNCHANGED_SKIPS.with_label_values(&[source]).inc();
+}
+
+pub fn inc_anomaly(source: &str) {
+    ANOMALIES.with_label_values(&[source]).inc();
+}
+
+pub fn inc_duplicate(source: &str) {
+    DUPLICATES.with_label_values(&[source]).inc();
+}
+
+// Keep the last-price, fee, TVL, derived-series, migration, incentive, TWAP and volatility gauges in sync with the broadcast stream
+pub async fn track_prices(mut rx: broadcast::Receiver<MarketEvent>) {
+    loop {
+        match rx.recv().await {
+            Ok(MarketEvent::Price(update)) => {
+                LAST_PRICE
+                    .with_label_values(&[&update.source, &update.symbol, &update.pool])
+                    .set(update.price);
+                POOL_FEE
+                    .with_label_values(&[&update.source, &update.symbol, &update.pool])
+                    .set(update.fee_bps);
+                if let Some(tvl_usd) = update.tvl_usd {
+                    POOL_TVL
+                        .with_label_values(&[&update.source, &update.symbol, &update.pool])
+                        .set(tvl_usd);
+                }
+            }
+            Ok(MarketEvent::Derived(value)) => {
+                DERIVED.with_label_values(&[&value.name, &value.symbol]).set(value.value);
+            }
+            Ok(MarketEvent::Migration(rate)) => {
+                MIGRATION_RATE
+                    .with_label_values(&[&rate.source, &rate.symbol, &rate.pool])
+                    .set(rate.crossed_per_minute);
+            }
+            Ok(MarketEvent::Incentive(incentive)) => {
+                if let Some(apr) = incentive.reward_apr_percent {
+                    INCENTIVE_APR
+                        .with_label_values(&[&incentive.source, &incentive.symbol, &incentive.pool])
+                        .set(apr);
+                }
+            }
+            Ok(MarketEvent::Twap(twap)) => {
+                for window in &twap.windows {
+                    TWAP
+                        .with_label_values(&[&twap.source, &twap.symbol, &twap.pool, &window.window_secs.to_string()])
+                        .set(window.price);
+                }
+            }
+            Ok(MarketEvent::Stats(stats)) => {
+                for window in &stats.windows {
+                    let window_secs = window.window_secs.to_string();
+                    let labels = [stats.source.as_str(), &stats.symbol, &stats.pool, &window_secs];
+                    EMA.with_label_values(&labels).set(window.ema);
+                    PRICE_STDDEV.with_label_values(&labels).set(window.stddev);
+                    REALIZED_VOLATILITY.with_label_values(&labels).set(window.realized_volatility_percent);
+                }
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
+    let app = Router::new().route("/metrics", get(metrics_handler));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(addr = %addr, "Prometheus metrics listening");
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        tracing::error!(error = %e, "failed to encode metrics");
+    }
+
+    ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], buffer)
+}
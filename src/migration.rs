@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+
+// Rolling window the rates are taken over
+const WINDOW_SECS: u64 = 300;
+// Rates over less history than this would swing wildly after startup
+const MIN_SPAN_SECS: u64 = 60;
+
+// How fast the active tick or bin of a concentrated pool moves, as a proxy for
+// volatility and turnover. Published for every update of such a pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationRate {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub active_index: i64,
+    pub moved: i64,                // Ticks or bins since the previous update, signed
+    pub changes_per_minute: f64,   // Updates in the window with a different active index
+    pub crossed_per_minute: f64,   // Ticks or bins crossed in the window
+    pub window_secs: u64,
+    pub timestamp: u64,
+}
+
+struct PoolHistory {
+    first_seen: u64,
+    active_index: i64,
+    // (timestamp, ticks or bins moved) of every change in the window
+    moves: VecDeque<(u64, i64)>,
+}
+
+// Tracks the active tick (Orca, Raydium CLMM) or bin (Meteora) of every pool
+// that reports one. Polling collapses several moves between two reads into
+// one change, so crossed_per_minute is the steadier of the two rates.
+pub struct MigrationTracker {
+    pools: HashMap<String, PoolHistory>,
+}
+
+impl MigrationTracker {
+    pub fn new() -> Self {
+        Self { pools: HashMap::new() }
+    }
+
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+    ) {
+        loop {
+            match rx.recv().await {
+                Ok(MarketEvent::Price(update)) => {
+                    if let Some(rate) = self.on_price_update(&update) {
+                        let _ = tx.send(MarketEvent::Migration(rate));
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "migration tracker lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    fn on_price_update(&mut self, update: &PriceUpdate) -> Option<MigrationRate> {
+        let active_index = update.active_index?;
+        let now = update.timestamp;
+        let history = self.pools.entry(update.pool.clone()).or_insert_with(|| PoolHistory {
+            first_seen: now,
+            active_index,
+            moves: VecDeque::new(),
+        });
+
+        let moved = active_index - history.active_index;
+        history.active_index = active_index;
+        if moved != 0 {
+            history.moves.push_back((now, moved));
+        }
+        while history.moves.front().is_some_and(|(timestamp, _)| *timestamp + WINDOW_SECS < now) {
+            history.moves.pop_front();
+        }
+
+        let span_minutes = now.saturating_sub(history.first_seen).clamp(MIN_SPAN_SECS, WINDOW_SECS) as f64 / 60.0;
+        let crossed: i64 = history.moves.iter().map(|(_, moved)| moved.abs()).sum();
+        Some(MigrationRate {
+            symbol: update.symbol.clone(),
+            source: update.source.clone(),
+            pool: update.pool.clone(),
+            active_index,
+            moved,
+            changes_per_minute: history.moves.len() as f64 / span_minutes,
+            crossed_per_minute: crossed as f64 / span_minutes,
+            window_secs: WINDOW_SECS,
+            timestamp: now,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::config::{Backend, Config, DexConfig, GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::error::Result;
+use crate::events::MarketEvent;
+use crate::health::HealthEvent;
+use crate::lifinity::LifinityMonitor;
+use crate::lst::LstMonitor;
+use crate::meteora::MeteoraMonitor;
+use crate::meteora_amm::MeteoraAmmMonitor;
+use crate::metrics;
+use crate::openbook::OpenBookMonitor;
+use crate::orca::OrcaMonitor;
+use crate::pause::Pause;
+use crate::phoenix::PhoenixMonitor;
+use crate::pump::{PumpKind, PumpMonitor};
+use crate::pyth::PythMonitor;
+use crate::raydium::RaydiumMonitor;
+use crate::raydium_clmm::RaydiumClmmMonitor;
+use crate::rpc::RpcProvider;
+use crate::saber::SaberMonitor;
+use crate::stats::RollingStats;
+use crate::supervisor::{self, Heartbeat, Liveness};
+
+// Seconds between a monitor failing and reconnecting it
+const RECONNECT_DELAY_SECS: u64 = 5;
+
+// A source's poll or subscription loop. run() returns Ok once shutdown is
+// requested and an error when the monitor has to reconnect.
+#[async_trait]
+pub trait Monitor: Send {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()>;
+}
+
+// What every monitor runs with, shared by all sources
+#[derive(Clone)]
+pub struct MonitorContext {
+    pub tx: Arc<broadcast::Sender<MarketEvent>>,
+    pub health_tx: Arc<broadcast::Sender<HealthEvent>>,
+    pub rpc: RpcProvider,
+    pub schedule: ScheduleConfig,
+    pub pause: Pause,
+    pub stats: RollingStats,
+    pub shutdown: CancellationToken,
+}
+
+type Build = fn(Vec<PoolConfig>, Option<GeyserConfig>, &ScheduleConfig, &RpcProvider) -> Box<dyn Monitor>;
+
+// One watched source: its name on events and metrics, what kind of venue it
+// is, its config section and how to build its monitor
+pub struct Source<'a> {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub dex: &'a DexConfig,
+    build: Build,
+}
+
+// Every source, in display order. Monitors that only poll ignore the Geyser
+// config; validation rejects backend = "geyser" for their sections.
+pub fn sources(config: &Config) -> [Source<'_>; 13] {
+    [
+        Source { name: "Raydium", kind: "Classic AMM", dex: &config.raydium, build: |pools, geyser, schedule, rpc| Box::new(RaydiumMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "Raydium CLMM", kind: "Concentrated Liquidity", dex: &config.raydium_clmm, build: |pools, geyser, schedule, rpc| Box::new(RaydiumClmmMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "Orca", kind: "Concentrated Liquidity", dex: &config.orca, build: |pools, geyser, schedule, rpc| Box::new(OrcaMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "Meteora", kind: "Dynamic Bins", dex: &config.meteora, build: |pools, geyser, schedule, rpc| Box::new(MeteoraMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "Meteora AMM", kind: "Constant Product", dex: &config.meteora_amm, build: |pools, _, schedule, rpc| Box::new(MeteoraAmmMonitor::new(pools, schedule, rpc)) },
+        Source { name: "Phoenix", kind: "Order Book", dex: &config.phoenix, build: |pools, geyser, schedule, rpc| Box::new(PhoenixMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "OpenBook", kind: "Order Book", dex: &config.openbook, build: |pools, _, schedule, rpc| Box::new(OpenBookMonitor::new(pools, schedule, rpc)) },
+        Source { name: "Lifinity", kind: "Oracle-Anchored", dex: &config.lifinity, build: |pools, geyser, schedule, rpc| Box::new(LifinityMonitor::new(pools, geyser, schedule, rpc)) },
+        Source { name: "Pump.fun", kind: "Bonding Curve", dex: &config.pump_fun, build: |pools, _, schedule, rpc| Box::new(PumpMonitor::new(PumpKind::BondingCurve, pools, schedule, rpc)) },
+        Source { name: "PumpSwap", kind: "Constant Product", dex: &config.pumpswap, build: |pools, _, schedule, rpc| Box::new(PumpMonitor::new(PumpKind::PumpSwap, pools, schedule, rpc)) },
+        Source { name: "Saber", kind: "StableSwap", dex: &config.saber, build: |pools, _, schedule, rpc| Box::new(SaberMonitor::new(pools, schedule, rpc)) },
+        Source { name: "Stake Pool", kind: "LST Exchange Rate", dex: &config.lst, build: |pools, _, schedule, rpc| Box::new(LstMonitor::new(pools, schedule, rpc)) },
+        Source { name: "Pyth", kind: "Oracle Reference", dex: &config.pyth, build: |pools, _, schedule, rpc| Box::new(PythMonitor::new(pools, schedule, rpc)) },
+    ]
+}
+
+// Run a source's monitor under the supervisor, restarted if it panics or
+// stalls and reconnected after errors until shutdown
+pub fn spawn(source: &Source, config: &Config, context: &MonitorContext) -> JoinHandle<()> {
+    let (name, build, context) = (source.name, source.build, context.clone());
+    let pools = source.dex.pools.clone();
+    let geyser = (source.dex.backend == Backend::Geyser).then(|| config.geyser.clone());
+    // Geyser-fed monitors legitimately idle while their pools don't change
+    let liveness = if geyser.is_some() { Liveness::Unchecked } else { Liveness::Loop };
+    supervisor::supervise(name, config.supervisor.clone(), context.health_tx.clone(), liveness, move |heartbeat| {
+        let (context, pools, geyser) = (context.clone(), pools.clone(), geyser.clone());
+        let span = tracing::info_span!("monitor", source = name, pools = pools.len());
+        async move {
+            let mut monitor = build(pools, geyser, &context.schedule, &context.rpc);
+            loop {
+                match monitor.run(&context, &heartbeat).await {
+                    Ok(()) => {
+                        tracing::info!(source = name, "monitoring stopped");
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::error!(source = name, error = %e, "monitor error");
+                        let _ = context.health_tx.send(HealthEvent::reconnecting(name, &e));
+                        let delay = tokio::time::sleep(tokio::time::Duration::from_secs(RECONNECT_DELAY_SECS));
+                        if context.shutdown.run_until_cancelled(delay).await.is_none() {
+                            break;
+                        }
+                        metrics::inc_reconnect(name);
+                        tracing::info!(source = name, "reconnecting");
+                    }
+                }
+            }
+        }
+        .instrument(span)
+    })
+}
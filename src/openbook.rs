@@ -0,0 +1,459 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::book::{self, Level};
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "OpenBook";
+
+// OpenBook v2 Market account, after the 8-byte Anchor discriminator:
+// bump (1) | base_decimals (1) | quote_decimals (1) | padding (5) | market_authority (32)
+//   | time_expiry (8) | collect_fee_admin (32) | open_orders_admin (32) | consume_events_admin (32)
+//   | close_market_admin (32) | name (16) | bids (32) | asks (32) | event_heap (32) | oracle_a (32)
+//   | oracle_b (32) | oracle_config (88) | quote_lot_size (8) | base_lot_size (8) | seq_num (8)
+//   | registration_time (8) | maker_fee (8) | taker_fee (8) | fee and volume counters (96)
+//   | base_mint (32) | quote_mint (32) | market_base_vault (32) | base_deposit_total (8)
+//   | market_quote_vault (32) | quote_deposit_total (8) | reserved (128)
+const MARKET_LEN: usize = 8 + 840;
+const BASE_DECIMALS_OFFSET: usize = 8 + 1;
+const QUOTE_DECIMALS_OFFSET: usize = 8 + 2;
+const BIDS_OFFSET: usize = 8 + 192;
+const ASKS_OFFSET: usize = 8 + 224;
+const QUOTE_LOT_SIZE_OFFSET: usize = 8 + 440;
+const BASE_LOT_SIZE_OFFSET: usize = 8 + 448;
+const TAKER_FEE_OFFSET: usize = 8 + 480;
+const BASE_MINT_OFFSET: usize = 8 + 568;
+const QUOTE_MINT_OFFSET: usize = 8 + 600;
+const BASE_VAULT_OFFSET: usize = 8 + 632;
+const QUOTE_VAULT_OFFSET: usize = 8 + 672;
+// Fees are in millionths of the notional
+const FEES_SCALE_FACTOR: f64 = 1_000_000.0;
+
+// BookSide account, after the discriminator: roots [fixed, oracle_pegged] (u32 node,
+// u32 leaf_count each) | reserved roots (32) | reserved (256) | order_tree_type (1)
+// | padding (3) | bump_index (4) | free_list_len (4) | free_list_head (4) | reserved (512)
+// | nodes [88 bytes; 1024]. Node indices are 0-based.
+const FIXED_ROOT_OFFSET: usize = 8;
+const NODES_OFFSET: usize = 8 + 832;
+const NODE_LEN: usize = 88;
+const MAX_NODES: usize = 1024;
+const BOOK_SIDE_LEN: usize = NODES_OFFSET + MAX_NODES * NODE_LEN;
+
+// Node tags. Inner nodes keep their children at +24; leaves are
+// tag (1) | owner_slot (1) | time_in_force (u16) | padding (4) | key (u128) | owner (32)
+// | quantity (i64) | timestamp (u64) | peg_limit (i64) | client_order_id (u64).
+// The upper 64 bits of a key are the price in lots.
+const INNER_NODE: u8 = 1;
+const LEAF_NODE: u8 = 2;
+
+// Book sides, vaults and price scale of a market, fixed at its creation
+#[derive(Debug, Clone, Copy)]
+pub struct MarketHeader {
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub quote_lot_size: u64,
+    pub base_lot_size: u64,
+    pub taker_fee_bps: f64,
+}
+
+impl MarketHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < MARKET_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid OpenBook market data size: {} bytes", data.len())));
+        }
+
+        let (quote_lot_size, base_lot_size) = (read_u64(data, QUOTE_LOT_SIZE_OFFSET), read_u64(data, BASE_LOT_SIZE_OFFSET));
+        if quote_lot_size == 0 || base_lot_size == 0 {
+            return Err(WatcherError::DecodeError("OpenBook market has a zero lot size".to_string()));
+        }
+
+        Ok(Self {
+            base_decimals: data[BASE_DECIMALS_OFFSET],
+            quote_decimals: data[QUOTE_DECIMALS_OFFSET],
+            bids: read_pubkey(data, BIDS_OFFSET),
+            asks: read_pubkey(data, ASKS_OFFSET),
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            base_vault: read_pubkey(data, BASE_VAULT_OFFSET),
+            quote_vault: read_pubkey(data, QUOTE_VAULT_OFFSET),
+            quote_lot_size,
+            base_lot_size,
+            taker_fee_bps: read_u64(data, TAKER_FEE_OFFSET) as i64 as f64 / FEES_SCALE_FACTOR * 10_000.0,
+        })
+    }
+
+    // Quote per base in UI units
+    fn price(&self, price_lots: u64) -> f64 {
+        (price_lots as f64 * self.quote_lot_size as f64 / self.base_lot_size as f64)
+            * 10_f64.powi(self.base_decimals as i32 - self.quote_decimals as i32)
+    }
+}
+
+// Best price in lots on one book side and the base lots resting at it, from
+// the fixed-price tree. Every leaf reachable from the root is visited, skipping
+// orders past their time in force. Oracle-pegged orders need the oracle price
+// and are left out.
+fn best_level(data: &[u8], best_of: fn(u64, u64) -> u64, now: u64) -> Option<(u64, u64)> {
+    if data.len() < BOOK_SIDE_LEN {
+        return None;
+    }
+    let (root, leaf_count) = (read_u32(data, FIXED_ROOT_OFFSET), read_u32(data, FIXED_ROOT_OFFSET + 4));
+    if leaf_count == 0 {
+        return None;
+    }
+
+    let mut best: Option<(u64, u64)> = None;
+    let mut stack = vec![root];
+    let mut visited = 0;
+    // A well-formed tree has at most MAX_NODES nodes; the bound also stops a torn read from looping
+    while let Some(index) = stack.pop() {
+        if index as usize >= MAX_NODES {
+            continue;
+        }
+        visited += 1;
+        if visited > MAX_NODES {
+            break;
+        }
+
+        let node = NODES_OFFSET + index as usize * NODE_LEN;
+        match data[node] {
+            INNER_NODE => {
+                stack.push(read_u32(data, node + 24));
+                stack.push(read_u32(data, node + 28));
+            }
+            LEAF_NODE => {
+                let time_in_force = u16::from_le_bytes([data[node + 2], data[node + 3]]) as u64;
+                let price_lots = read_u64(data, node + 16);
+                let quantity = read_u64(data, node + 56) as i64;
+                let timestamp = read_u64(data, node + 64);
+                let expired = time_in_force != 0 && timestamp.saturating_add(time_in_force) < now;
+                if quantity > 0 && !expired {
+                    best = match best {
+                        Some((price, size)) if price == price_lots => Some((price, size + quantity as u64)),
+                        Some((price, size)) if best_of(price, price_lots) == price => Some((price, size)),
+                        _ => Some((price_lots, quantity as u64)),
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+    best
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// Decoded state of one market for a single tick
+struct MarketData {
+    header: MarketHeader,
+    bid: Level,
+    ask: Level,
+    base_reserve: u64,
+    quote_reserve: u64,
+}
+
+pub struct OpenBookMonitor {
+    rpc_client: RpcClient,
+    markets: Vec<(Pubkey, PoolConfig)>,
+    // Market accounts don't change once created, so each is read once
+    headers: HashMap<Pubkey, MarketHeader>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which markets each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl OpenBookMonitor {
+    pub fn new(pools: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let markets: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid market address"), pool))
+            .collect();
+        let schedule = PollSchedule::new(SOURCE, &markets, schedule);
+
+        Self {
+            rpc_client,
+            markets,
+            headers: HashMap::new(),
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, markets = self.markets.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_market_data().await;
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.markets.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, markets)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every market is parked between keep-alives
+                    let slot_lag = if markets.is_empty() {
+                        0
+                    } else {
+                        self.rpc_client
+                            .get_slot_with_commitment(CommitmentConfig::processed())
+                            .await
+                            .unwrap_or(slot)
+                            .saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in markets {
+                        let current_price = (data.bid.price + data.ask.price) / 2.0;
+                        let pressure = book::pressure(data.bid, data.ask);
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.header.base_decimals,
+                            data.header.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.header.base_decimals,
+                            quote_decimals: data.header.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: data.header.taker_fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: Some(data.bid.price),
+                            ask: Some(data.ask.price),
+                            active_index: None,
+                            microprice: pressure.map(|pressure| pressure.microprice),
+                            imbalance: pressure.map(|pressure| pressure.imbalance),
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read the bids, asks and both vaults of every due market in one
+    // getMultipleAccounts call. Markets without both a bid and an ask have no
+    // mid price and are skipped until they do.
+    async fn fetch_market_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, MarketData)>)> {
+        // Parked markets are only read once per keep-alive
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.markets)
+            .into_iter()
+            .map(|(address, market)| (*address, market.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        self.load_headers(&due).await?;
+
+        let due: Vec<(Pubkey, String, MarketHeader)> = due
+            .into_iter()
+            .filter_map(|(address, symbol)| self.headers.get(&address).map(|header| (address, symbol, *header)))
+            .collect();
+        let accounts: Vec<Pubkey> = due
+            .iter()
+            .flat_map(|(_, _, header)| [header.bids, header.asks, header.base_vault, header.quote_vault])
+            .collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let now = unix_now();
+
+        let mut results = Vec::with_capacity(due.len());
+        for ((address, symbol, header), accounts) in due.into_iter().zip(response.value.chunks(4)) {
+            let [bids, asks, base_vault, quote_vault] = accounts else {
+                continue;
+            };
+            let (Some(base_reserve), Some(quote_reserve)) = (
+                base_vault.as_ref().and_then(|account| token::token_account_amount(&account.data).ok()),
+                quote_vault.as_ref().and_then(|account| token::token_account_amount(&account.data).ok()),
+            ) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read market vaults");
+                continue;
+            };
+
+            let best_bid = bids.as_ref().and_then(|account| best_level(&account.data, u64::max, now));
+            let best_ask = asks.as_ref().and_then(|account| best_level(&account.data, u64::min, now));
+            let (Some((bid_lots, bid_size)), Some((ask_lots, ask_size))) = (best_bid, best_ask) else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "market has an empty side, no mid price");
+                continue;
+            };
+
+            results.push((address, symbol, MarketData {
+                header,
+                bid: Level { price: header.price(bid_lots), size: bid_size },
+                ask: Level { price: header.price(ask_lots), size: ask_size },
+                base_reserve,
+                quote_reserve,
+            }));
+        }
+
+        Ok((slot, results))
+    }
+
+    // Read the market accounts not seen yet, in one getMultipleAccounts call
+    async fn load_headers(&mut self, due: &[(Pubkey, String)]) -> Result<()> {
+        let missing: Vec<&(Pubkey, String)> = due.iter().filter(|(address, _)| !self.headers.contains_key(address)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = missing.iter().map(|(address, _)| *address).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        for ((address, symbol), account) in missing.into_iter().zip(accounts) {
+            let Some(account) = account else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "market account not found");
+                continue;
+            };
+            match MarketHeader::parse(&account.data) {
+                Ok(header) => {
+                    self.headers.insert(*address, header);
+                }
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.markets, address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse market");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Monitor for OpenBookMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
@@ -1,80 +1,470 @@
+use async_trait::async_trait;
 use orca_whirlpools_client::Whirlpool;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{account_info::AccountInfo, pubkey::Pubkey};
-use std::str::FromStr;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, account_info::AccountInfo, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
 use tokio::sync::broadcast;
-use anyhow::Result;
+use tokio_util::sync::CancellationToken;
 use std::sync::Arc;
 
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::fingerprint::{account_data, Fingerprints};
+use crate::backoff::Backoff;
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
 use crate::raydium::PriceUpdate;
+use crate::rewards::{RewardEmission, RewardEmissions};
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+// Decoded state of one whirlpool for a single tick
+struct WhirlpoolData {
+    price: f64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    fee_bps: f64,
+    tick: i64,
+    // (mint, emissions_per_second_x64) of every reward still emitting
+    rewards: Vec<(Pubkey, u128)>,
+}
 
 pub struct OrcaMonitor {
     rpc_client: RpcClient,
-    whirlpool_address: Pubkey,
-    price_cache: Option<f64>,
+    whirlpools: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    // (token_a, token_b) decimals per whirlpool, resolved once from the mints
+    decimals_cache: HashMap<Pubkey, (u8, u8)>,
+    // Pushed whirlpool account changes when running on the Geyser backend
+    geyser: Option<AccountUpdates>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Geyser only pushes changes, so the first pass polls a full snapshot
+    snapshotted: bool,
+    // Which whirlpools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Whirlpool and vault data as of the last read, and the decode it gave
+    fingerprints: Fingerprints,
+    decoded: HashMap<Pubkey, Whirlpool>,
+    // Reward emissions last published per whirlpool, and reward mint decimals
+    published_rewards: HashMap<Pubkey, Vec<(Pubkey, u128)>>,
+    reward_decimals: HashMap<Pubkey, u8>,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
 }
 
 impl OrcaMonitor {
-    pub fn new() -> Self {
-        let rpc_client = RpcClient::new("https://api.mainnet-beta.solana.com".to_string());
+    pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::default());
         
-        let whirlpool_address = Pubkey::from_str("Czfq3xZZDmsdGdUyrNLtRhGc47cXcZtLG4crryfu44zE").unwrap();
+        let whirlpools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().unwrap(), pool))
+            .collect();
+        let geyser = geyser.map(|config| {
+            AccountUpdates::subscribe(config, "Orca", whirlpools.iter().map(|(address, _)| *address).collect())
+        });
+        let fingerprints = Fingerprints::new("Orca", schedule);
+        let schedule = PollSchedule::new("Orca", &whirlpools, schedule);
         
         Self {
             rpc_client,
-            whirlpool_address,
-            price_cache: None,
+            whirlpools,
+            last_snapshot: HashMap::new(),
+            decimals_cache: HashMap::new(),
+            geyser,
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            snapshotted: false,
+            schedule,
+            fingerprints,
+            decoded: HashMap::new(),
+            published_rewards: HashMap::new(),
+            reward_decimals: HashMap::new(),
+            dropped: Vec::new(),
         }
     }
 
-    pub async fn start_monitoring(&mut self, tx: Arc<broadcast::Sender<PriceUpdate>>) -> Result<()> {
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         
         loop {
-            interval.tick().await;
+            heartbeat.beat();
+            
+            // Changes pushed while paused may be long outdated, so re-read a full snapshot
+            if shutdown.run_until_cancelled(pause.wait_resumed("Orca", heartbeat)).await == Some(true) {
+                self.snapshotted = false;
+            }
+            
+            // On Geyser, wait for whirlpool accounts to change instead of polling, once the initial snapshot is in
+            let pushed = match self.geyser.as_mut() {
+                Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                    Some(batch) => Some(batch?),
+                    None => return Ok(()),
+                },
+                _ => {
+                    shutdown.run_until_cancelled(interval.tick()).await;
+                    None
+                }
+            };
+            
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = match pushed {
+                Some((slot, accounts)) => self.parse_whirlpool_accounts(accounts).await.map(|whirlpools| (slot, whirlpools)),
+                None => self.fetch_whirlpool_data().await,
+            };
+            metrics::observe_fetch("Orca", fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.whirlpools.is_empty() {
+                    tracing::error!(source = "Orca", "no pools left to watch");
+                    return Ok(());
+                }
+            }
             
-            match self.fetch_whirlpool_data().await {
-                Ok((current_price, base_reserve, quote_reserve)) => {
-                    let change_percent = if let Some(cached) = self.price_cache {
-                        ((current_price - cached) / cached) * 100.0
+            match result {
+                Ok((slot, whirlpools)) => {
+                    self.snapshotted = true;
+                    if self.backoff.reset() {
+                        tracing::info!(source = "Orca", "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered("Orca"));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if whirlpools.is_empty() {
+                        0
                     } else {
-                        0.0
+                        self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
                     };
-                    
-                    let update = PriceUpdate {
-                        symbol: "SOL/USDC".to_string(),
-                        source: "Orca".to_string(),
-                        price: current_price,
-                        change_percent,
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        base_reserve,
-                        quote_reserve,
-                    };
-                    
-                    if tx.send(update).is_err() {
-                        println!("No receivers for Orca price updates");
+                    for (address, symbol, data) in whirlpools {
+                        // Emissions rarely change, so they're only published when they do
+                        let rewards_changed = self.published_rewards
+                            .get(&address)
+                            .map_or(!data.rewards.is_empty(), |published| *published != data.rewards);
+                        if rewards_changed {
+                            match self.reward_emissions(&data.rewards).await {
+                                Ok(rewards) => {
+                                    let _ = tx.send(MarketEvent::Rewards(RewardEmissions {
+                                        symbol: symbol.clone(),
+                                        source: "Orca".to_string(),
+                                        pool: address.to_string(),
+                                        rewards,
+                                        timestamp: unix_now(),
+                                    }));
+                                    self.published_rewards.insert(address, data.rewards.clone());
+                                }
+                                Err(e) => tracing::warn!(source = "Orca", symbol = %symbol, pool = %address, error = %e, "failed to read whirlpool reward mints"),
+                            }
+                        }
+                        
+                        let current_price = data.price;
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+                        
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.base_decimals,
+                            data.quote_decimals,
+                            current_price,
+                        );
+                        
+                        if sanity::reject(&tx, "Orca", &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: "Orca".to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.base_decimals,
+                            quote_decimals: data.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: data.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: Some(data.tick),
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+                        
+                        if dedup::publish(&tx, update).is_err() {
+                            tracing::info!(source = "Orca", "no receivers for price updates");
+                        }
+                        
+                        self.last_snapshot.insert(address, snapshot);
                     }
-                    
-                    self.price_cache = Some(current_price);
                 }
                 Err(e) => {
-                    eprintln!("Failed to fetch Orca price: {}", e);
+                    metrics::inc_rpc_error("Orca");
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = "Orca", failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded("Orca", &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
                 }
             }
         }
     }
 
-    async fn fetch_whirlpool_data(&self) -> Result<(f64, u64, u64)> {
-    let account = self.rpc_client.get_account(&self.whirlpool_address)?;
+    async fn fetch_whirlpool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, WhirlpoolData)>)> {
+    // Parked whirlpools are only read once per keep-alive
+    let due = self.schedule.due(&self.whirlpools);
+    if due.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+    let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+    let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+    let slot = response.context.slot;
+    self.last_slot = self.last_slot.max(slot);
+    let accounts = response.value;
+    
+    let mut found = Vec::with_capacity(accounts.len());
+    for ((address, pool), account) in due.into_iter().zip(accounts) {
+        match account {
+            Some(account) => found.push((*address, account)),
+            None => tracing::warn!(source = "Orca", symbol = %pool.symbol, pool = %address, "pool account not found"),
+        }
+    }
+    
+    Ok((slot, self.parse_whirlpool_accounts(found).await?))
+    }
+
+    // Current cluster tip, to tell how far behind the data we publish is
+    async fn tip_slot(&self) -> Option<u64> {
+        match &self.geyser {
+            Some(updates) => Some(updates.tip_slot()),
+            None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
+        }
+    }
+
+    // Decode whirlpool accounts, whether polled or pushed, then read all of
+    // their vaults in one round trip, concurrently with the mints of whirlpools
+    // seen for the first time. Undecodable whirlpools are logged and skipped,
+    // and whirlpools whose account and vault data are unchanged since the last
+    // read are left out between keep-alives.
+    async fn parse_whirlpool_accounts(&mut self, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, WhirlpoolData)>> {
+    let mut decoded = Vec::with_capacity(accounts.len());
+    for (address, account) in accounts {
+        let Some(symbol) = self.whirlpools.iter().find(|(pool_address, _)| *pool_address == address).map(|(_, pool)| pool.symbol.clone()) else {
+            continue;
+        };
+        
+        // Same bytes as last time decode to the same whirlpool, so reuse that
+        let unchanged = self.fingerprints.unchanged(&address, &account.data);
+        let parsed = match self.decoded.get(&address) {
+            Some(whirlpool) if unchanged => Ok(whirlpool.clone()),
+            _ => parse_whirlpool(&address, account),
+        };
+        match parsed {
+            Ok(whirlpool) => {
+                self.decoded.insert(address, whirlpool.clone());
+                decoded.push((address, symbol, whirlpool, unchanged));
+            }
+            Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool("Orca", &mut self.whirlpools, &address, &e)),
+            Err(e) => {
+                metrics::inc_decode_error("Orca");
+                tracing::warn!(source = "Orca", symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+            }
+        }
+    }
+    
+    let vaults: Vec<Pubkey> = decoded
+        .iter()
+        .flat_map(|(_, _, whirlpool, _)| [whirlpool.token_vault_a, whirlpool.token_vault_b])
+        .collect();
+    let unresolved: Vec<(Pubkey, Pubkey, Pubkey)> = decoded
+        .iter()
+        .filter(|(address, _, _, _)| !self.decimals_cache.contains_key(address))
+        .map(|(address, _, whirlpool, _)| (*address, whirlpool.token_mint_a, whirlpool.token_mint_b))
+        .collect();
+    let (vault_accounts, decimals) = tokio::join!(
+        self.get_vault_accounts(&vaults),
+        self.fetch_mint_decimals(&unresolved),
+    );
+    let vault_accounts = vault_accounts?;
+    self.decimals_cache.extend(decimals?);
     
+    let mut results = Vec::with_capacity(decoded.len());
+    for ((address, symbol, whirlpool, whirlpool_unchanged), (vaults, accounts)) in decoded
+        .into_iter()
+        .zip(vaults.chunks(2).zip(vault_accounts.chunks(2)))
+    {
+        let Some(&(base_decimals, quote_decimals)) = self.decimals_cache.get(&address) else {
+            metrics::inc_decode_error("Orca");
+            tracing::warn!(source = "Orca", symbol = %symbol, pool = %address, "failed to read pool mints");
+            continue;
+        };
+        let amount = |vault: &Option<Account>| vault.as_ref().and_then(|vault| token::token_account_amount(&vault.data).ok());
+        let (Some(base_reserve), Some(quote_reserve)) = (amount(&accounts[0]), amount(&accounts[1])) else {
+            metrics::inc_decode_error("Orca");
+            tracing::warn!(source = "Orca", symbol = %symbol, pool = %address, "failed to read pool vaults");
+            continue;
+        };
+        
+        // Both vaults are recorded even when the first already changed
+        let vaults_unchanged = [
+            self.fingerprints.unchanged(&vaults[0], account_data(&accounts[0])),
+            self.fingerprints.unchanged(&vaults[1], account_data(&accounts[1])),
+        ];
+        if !self.fingerprints.should_publish(&address, whirlpool_unchanged && vaults_unchanged == [true, true]) {
+            continue;
+        }
+        
+        let price = whirlpool_price_from_sqrt_price(
+            whirlpool.sqrt_price,
+            base_decimals,
+            quote_decimals,
+        );
+        
+        results.push((address, symbol, WhirlpoolData {
+            price,
+            base_reserve,
+            quote_reserve,
+            base_decimals,
+            quote_decimals,
+            // fee_rate is in hundredths of a basis point
+            fee_bps: whirlpool.fee_rate as f64 / 100.0,
+            tick: whirlpool.tick_current_index as i64,
+            rewards: whirlpool.reward_infos
+                .iter()
+                .filter(|reward| reward.mint != Pubkey::default() && reward.emissions_per_second_x64 > 0)
+                .map(|reward| (reward.mint, reward.emissions_per_second_x64))
+                .collect(),
+        }));
+    }
+    
+    Ok(results)
+    }
+
+    // Many token accounts in a single getMultipleAccounts call, None for
+    // accounts that are missing
+    async fn get_vault_accounts(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        if token_accounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // At least as new as the pools read before them
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        Ok(response.value)
+    }
+
+    // Emissions in reward tokens per second. emissions_per_second_x64 is a Q64.64
+    // amount of the mint's smallest unit, whose decimals are read once per mint.
+    async fn reward_emissions(&mut self, rewards: &[(Pubkey, u128)]) -> Result<Vec<RewardEmission>> {
+        let unresolved: Vec<Pubkey> = rewards
+            .iter()
+            .map(|(mint, _)| *mint)
+            .filter(|mint| !self.reward_decimals.contains_key(mint))
+            .collect();
+        if !unresolved.is_empty() {
+            let accounts = rpc::get_multiple_accounts(&self.rpc_client, &unresolved).await?;
+            for (mint, account) in unresolved.iter().zip(accounts) {
+                let account = account.ok_or_else(|| WatcherError::RpcError(format!("reward mint {} not found", mint)))?;
+                self.reward_decimals.insert(*mint, token::mint_decimals(&account.data)?);
+            }
+        }
+
+        let q64 = (1u128 << 64) as f64;
+        Ok(rewards
+            .iter()
+            .map(|(mint, emissions_x64)| RewardEmission {
+                mint: mint.to_string(),
+                emissions_per_second: *emissions_x64 as f64 / q64 / 10_f64.powi(self.reward_decimals[mint] as i32),
+            })
+            .collect())
+    }
+
+    // Token decimals never change, so the mints of each whirlpool are read once,
+    // all new whirlpools in one call. Whirlpools whose mints can't be read are left out.
+    async fn fetch_mint_decimals(&self, whirlpools: &[(Pubkey, Pubkey, Pubkey)]) -> Result<Vec<(Pubkey, (u8, u8))>> {
+        if whirlpools.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mints: Vec<Pubkey> = whirlpools.iter().flat_map(|(_, mint_a, mint_b)| [*mint_a, *mint_b]).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &mints).await?;
+        let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+        Ok(whirlpools
+            .iter()
+            .zip(accounts.chunks(2))
+            .filter_map(|((address, _, _), mints)| Some((*address, (decimals(&mints[0])?, decimals(&mints[1])?))))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Monitor for OrcaMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
+
+fn parse_whirlpool(address: &Pubkey, account: Account) -> Result<Whirlpool> {
     let mut lamports = account.lamports;
     let mut data = account.data;
 
     let account_info = AccountInfo::new(
-        &self.whirlpool_address,
+        address,
         false,
         false, 
         &mut lamports,
@@ -84,31 +474,16 @@ impl OrcaMonitor {
         account.rent_epoch,
     );
     
-    let whirlpool = Whirlpool::try_from(&account_info)?;
-
-    let base_balance = self.rpc_client.get_token_account_balance(&whirlpool.token_vault_a)?;
-    let quote_balance = self.rpc_client.get_token_account_balance(&whirlpool.token_vault_b)?;
-    
-    let base_reserve = base_balance.amount.parse::<u64>()?;
-    let quote_reserve = quote_balance.amount.parse::<u64>()?;
-    
-    let price = whirlpool_price_from_sqrt_price(
-        whirlpool.sqrt_price,
-        6, // SOL decimals  
-        9, // USDC decimals
-    );
-    
-    Ok((price, base_reserve, quote_reserve))
-    }
+    Whirlpool::try_from(&account_info).map_err(WatcherError::decode)
 }
 
 // Orca Whirlpool sqrt_price -> price conversion
 fn whirlpool_price_from_sqrt_price(sqrt_price: u128, token_a_decimals: u8, token_b_decimals: u8) -> f64 {
-    // Orca formula: price = (sqrt_price / 2^64)^2 * 10^(decimals_b - decimals_a)
+    // Orca formula: price = (sqrt_price / 2^64)^2 * 10^(decimals_a - decimals_b)
     let sqrt_price_f64 = sqrt_price as f64;
     let q64 = (1u128 << 64) as f64;
     let price_raw = (sqrt_price_f64 / q64).powi(2);
-    let decimal_adjustment = 10_f64.powi(token_b_decimals as i32 - token_a_decimals as i32);
+    let decimal_adjustment = 10_f64.powi(token_a_decimals as i32 - token_b_decimals as i32);
     
     price_raw * decimal_adjustment
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::raydium::PriceUpdate;
+
+// Latest state of every watched pool in pair-indexed columns. Each pool gets a
+// pair id on its first update and keeps it; later updates overwrite its slot in
+// place, so a steady stream allocates nothing, and passes over all pairs of a
+// symbol (spreads, staleness) walk a few dense arrays instead of hash maps of
+// full updates. With hundreds of pairs that keeps the hot loops in cache.
+#[derive(Debug, Default)]
+pub struct PairTable {
+    // pool address -> pair id
+    index: HashMap<String, usize>,
+    // symbol -> pair ids quoting it
+    by_symbol: HashMap<String, Vec<usize>>,
+    pub pools: Vec<String>,
+    pub symbols: Vec<String>,
+    pub sources: Vec<String>,
+    pub prices: Vec<f64>,
+    // 0 until the pair's first update, and again after clear_source
+    pub timestamps: Vec<u64>,
+    pub slots: Vec<u64>,
+    pub base_reserves: Vec<u64>,
+    pub quote_reserves: Vec<u64>,
+    pub lp_supplies: Vec<Option<u64>>,
+    pub fees_bps: Vec<f64>,
+}
+
+impl PairTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Write the update into its pair's slot, registering the pool on first sight
+    pub fn upsert(&mut self, update: &PriceUpdate) -> usize {
+        let pair = match self.index.get(&update.pool) {
+            Some(&pair) => pair,
+            None => {
+                let pair = self.pools.len();
+                self.index.insert(update.pool.clone(), pair);
+                self.by_symbol.entry(update.symbol.clone()).or_default().push(pair);
+                self.pools.push(update.pool.clone());
+                self.symbols.push(update.symbol.clone());
+                self.sources.push(update.source.clone());
+                self.prices.push(0.0);
+                self.timestamps.push(0);
+                self.slots.push(0);
+                self.base_reserves.push(0);
+                self.quote_reserves.push(0);
+                self.lp_supplies.push(None);
+                self.fees_bps.push(0.0);
+                pair
+            }
+        };
+
+        self.prices[pair] = update.price;
+        self.timestamps[pair] = update.timestamp;
+        self.slots[pair] = update.slot;
+        self.base_reserves[pair] = update.base_reserve;
+        self.quote_reserves[pair] = update.quote_reserve;
+        self.lp_supplies[pair] = update.lp_supply;
+        self.fees_bps[pair] = update.fee_bps;
+        pair
+    }
+
+    // Pair id of a pool that has a quote
+    pub fn quoted_pair(&self, pool: &str) -> Option<usize> {
+        self.index.get(pool).copied().filter(|&pair| self.timestamps[pair] > 0)
+    }
+
+    // Pair ids quoting a symbol, in order of first update
+    pub fn symbol_pairs(&self, symbol: &str) -> &[usize] {
+        self.by_symbol.get(symbol).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Pair ids with a quote (at least one update since registration or the last clear)
+    pub fn quoted(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.pools.len()).filter(|&pair| self.timestamps[pair] > 0)
+    }
+
+    // Forget the quotes of a source without giving up its pair ids
+    pub fn clear_source(&mut self, source: &str) {
+        for pair in 0..self.pools.len() {
+            if self.sources[pair] == source {
+                self.timestamps[pair] = 0;
+            }
+        }
+    }
+}
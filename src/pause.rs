@@ -0,0 +1,98 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch};
+
+use crate::health::HealthEvent;
+use crate::supervisor::Heartbeat;
+
+pub const SOURCES: [&str; 13] = [
+    "Raydium", "Raydium CLMM", "Orca", "Meteora", "Meteora AMM", "Phoenix",
+    "OpenBook", "Lifinity", "Pump.fun", "PumpSwap", "Saber", "Stake Pool", "Pyth",
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PauseState {
+    pub global: bool,
+    pub sources: BTreeSet<String>,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, source: &str) -> bool {
+        self.global || self.sources.contains(source)
+    }
+}
+
+// Polling switches for RPC maintenance: a paused monitor stops fetching but
+// keeps its caches and last snapshots, so resuming doesn't start cold
+#[derive(Clone)]
+pub struct Pause {
+    state: Arc<watch::Sender<PauseState>>,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+}
+
+impl Pause {
+    pub fn new(health_tx: Arc<broadcast::Sender<HealthEvent>>) -> Self {
+        let (state, _) = watch::channel(PauseState::default());
+        Self { state: Arc::new(state), health_tx }
+    }
+
+    // Canonical name of a monitored source, matched case-insensitively
+    pub fn source(name: &str) -> Option<&'static str> {
+        SOURCES.into_iter().find(|source| source.eq_ignore_ascii_case(name))
+    }
+
+    pub fn state(&self) -> PauseState {
+        self.state.borrow().clone()
+    }
+
+    // Pause or resume one source, or everything when `source` is None. Sources
+    // whose effective state flips get a paused/resumed health event.
+    pub fn set(&self, source: Option<&str>, paused: bool) {
+        let mut flipped = Vec::new();
+        self.state.send_modify(|state| {
+            let before = state.clone();
+            match source {
+                None => state.global = paused,
+                Some(source) if paused => {
+                    state.sources.insert(source.to_string());
+                }
+                Some(source) => {
+                    state.sources.remove(source);
+                }
+            }
+            flipped.extend(SOURCES.into_iter().filter(|source| before.is_paused(source) != state.is_paused(source)));
+        });
+
+        for source in flipped {
+            if paused {
+                tracing::info!(source = %source, "source paused");
+                let _ = self.health_tx.send(HealthEvent::paused(source));
+            } else {
+                tracing::info!(source = %source, "source resumed");
+                let _ = self.health_tx.send(HealthEvent::resumed(source));
+            }
+        }
+    }
+
+    // Hold a monitor while its source is paused, still beating so the watchdog
+    // doesn't take the wait for a stall. Returns whether it had to wait.
+    pub async fn wait_resumed(&self, source: &str, heartbeat: &Heartbeat) -> bool {
+        let mut rx = self.state.subscribe();
+        let mut beat = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        let mut waited = false;
+
+        while rx.borrow_and_update().is_paused(source) {
+            waited = true;
+            tokio::select! {
+                changed = rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+                _ = beat.tick() => heartbeat.beat(),
+            }
+        }
+        waited
+    }
+}
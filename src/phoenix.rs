@@ -0,0 +1,484 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::book::{self, Level};
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "Phoenix";
+
+// Phoenix v1 market account: a 576-byte MarketHeader followed by the FIFOMarket.
+// Header: discriminant (8) | status (8) | bids_size (8) | asks_size (8) | num_seats (8)
+//   | base params: decimals (u32), vault_bump (u32), mint (32), vault (32) | base_lot_size (8)
+//   | quote params, same layout | quote_lot_size (8) | tick_size_in_quote_atoms_per_base_unit (8)
+//   | authority (32) | fee_recipient (32) | market_sequence_number (8) | successor (32)
+//   | raw_base_units_per_base_unit (u32) | padding
+const HEADER_LEN: usize = 576;
+const BIDS_SIZE_OFFSET: usize = 16;
+const ASKS_SIZE_OFFSET: usize = 24;
+const BASE_PARAMS_OFFSET: usize = 40;
+const QUOTE_PARAMS_OFFSET: usize = 120;
+const TICK_SIZE_OFFSET: usize = 200;
+const RAW_BASE_UNITS_OFFSET: usize = 312;
+
+// FIFOMarket: padding (256) | base_lots_per_base_unit (8) | tick_size_in_quote_lots_per_base_unit (8)
+//   | order_sequence_number (8) | taker_fee_bps (8) | fee counters (16) | bids | asks | traders
+const TAKER_FEE_OFFSET: usize = HEADER_LEN + 256 + 24;
+const BIDS_OFFSET: usize = HEADER_LEN + 256 + 48;
+
+// Sokoban red-black tree: root (u32) | padding (12) | size (8) | bump_index (u32) | free_list_head (u32)
+// | nodes. Each node is registers [left, right, parent, color] (4 x u32) followed by the
+// FIFOOrderId key (price_in_ticks, order_sequence_number) and the FIFORestingOrder
+// (trader_index, num_base_lots, last_valid_slot, last_valid_unix_timestamp_in_seconds).
+// Node indices are 1-based, 0 is the empty sentinel.
+const TREE_HEADER_LEN: usize = 32;
+const NODE_LEN: usize = 64;
+
+// Mints, vaults and price scale of a market
+#[derive(Debug, Clone, Copy)]
+pub struct MarketHeader {
+    pub bids_size: usize,
+    pub asks_size: usize,
+    pub base_decimals: u8,
+    pub base_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_decimals: u8,
+    pub quote_mint: Pubkey,
+    pub quote_vault: Pubkey,
+    pub tick_size: u64,  // Quote atoms per base unit per tick
+    pub raw_base_units_per_base_unit: u32,
+}
+
+impl MarketHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < BIDS_OFFSET {
+            return Err(WatcherError::DecodeError(format!("Invalid Phoenix market data size: {} bytes", data.len())));
+        }
+
+        let bids_size = read_u64(data, BIDS_SIZE_OFFSET) as usize;
+        let asks_size = read_u64(data, ASKS_SIZE_OFFSET) as usize;
+        let books_end = bids_size
+            .checked_add(asks_size)
+            .and_then(|nodes| nodes.checked_mul(NODE_LEN))
+            .and_then(|nodes| nodes.checked_add(BIDS_OFFSET + 2 * TREE_HEADER_LEN));
+        if books_end.is_none_or(|books_end| data.len() < books_end) {
+            return Err(WatcherError::DecodeError(format!("Phoenix market of {} bytes is too small for its books", data.len())));
+        }
+
+        Ok(Self {
+            bids_size,
+            asks_size,
+            base_decimals: read_u32(data, BASE_PARAMS_OFFSET) as u8,
+            base_mint: read_pubkey(data, BASE_PARAMS_OFFSET + 8),
+            base_vault: read_pubkey(data, BASE_PARAMS_OFFSET + 40),
+            quote_decimals: read_u32(data, QUOTE_PARAMS_OFFSET) as u8,
+            quote_mint: read_pubkey(data, QUOTE_PARAMS_OFFSET + 8),
+            quote_vault: read_pubkey(data, QUOTE_PARAMS_OFFSET + 40),
+            tick_size: read_u64(data, TICK_SIZE_OFFSET),
+            raw_base_units_per_base_unit: read_u32(data, RAW_BASE_UNITS_OFFSET).max(1),
+        })
+    }
+
+    fn price(&self, price_in_ticks: u64) -> f64 {
+        (price_in_ticks as f64 * self.tick_size as f64)
+            / 10_f64.powi(self.quote_decimals as i32)
+            / self.raw_base_units_per_base_unit as f64
+    }
+}
+
+// Top of the book in UI units, with the base lots resting at each side's best price
+#[derive(Debug, Clone, Copy)]
+pub struct TopOfBook {
+    pub bid: Level,
+    pub ask: Level,
+    pub taker_fee_bps: f64,
+}
+
+// Best bid and ask, skipping orders that expired by slot or time. Every node
+// reachable from each root is visited rather than trusting the key order.
+pub fn top_of_book(header: &MarketHeader, data: &[u8], slot: u64, now: u64) -> Option<TopOfBook> {
+    let asks_offset = BIDS_OFFSET + TREE_HEADER_LEN + header.bids_size * NODE_LEN;
+    let bids = live_orders(data, BIDS_OFFSET, header.bids_size, slot, now);
+    let asks = live_orders(data, asks_offset, header.asks_size, slot, now);
+
+    let best_bid = bids.iter().map(|(ticks, _)| *ticks).max()?;
+    let best_ask = asks.iter().map(|(ticks, _)| *ticks).min()?;
+    let size_at = |orders: &[(u64, u64)], ticks: u64| orders.iter().filter(|(price, _)| *price == ticks).map(|(_, lots)| lots).sum();
+
+    Some(TopOfBook {
+        bid: Level { price: header.price(best_bid), size: size_at(&bids, best_bid) },
+        ask: Level { price: header.price(best_ask), size: size_at(&asks, best_ask) },
+        taker_fee_bps: read_u64(data, TAKER_FEE_OFFSET) as f64,
+    })
+}
+
+// (price_in_ticks, num_base_lots) of every unexpired, unfilled order in one tree
+fn live_orders(data: &[u8], tree_offset: usize, capacity: usize, slot: u64, now: u64) -> Vec<(u64, u64)> {
+    let nodes = tree_offset + TREE_HEADER_LEN;
+    let mut orders = Vec::new();
+    let mut stack = vec![read_u32(data, tree_offset)];
+    let mut visited = 0;
+
+    // A well-formed tree has at most `capacity` nodes; the bound also stops a torn read from looping
+    while let Some(index) = stack.pop() {
+        if index == 0 || index as usize > capacity {
+            continue;
+        }
+        visited += 1;
+        if visited > capacity {
+            break;
+        }
+        let node = nodes + (index as usize - 1) * NODE_LEN;
+        stack.push(read_u32(data, node));
+        stack.push(read_u32(data, node + 4));
+
+        let (price_in_ticks, num_base_lots) = (read_u64(data, node + 16), read_u64(data, node + 40));
+        let (last_valid_slot, last_valid_timestamp) = (read_u64(data, node + 48), read_u64(data, node + 56));
+        let expired = (last_valid_slot != 0 && last_valid_slot < slot)
+            || (last_valid_timestamp != 0 && last_valid_timestamp < now);
+        if num_base_lots > 0 && !expired {
+            orders.push((price_in_ticks, num_base_lots));
+        }
+    }
+    orders
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// Decoded state of one market for a single tick
+struct MarketData {
+    header: MarketHeader,
+    book: TopOfBook,
+    base_reserve: u64,
+    quote_reserve: u64,
+}
+
+pub struct PhoenixMonitor {
+    rpc_client: RpcClient,
+    markets: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    // Pushed market account changes when running on the Geyser backend
+    geyser: Option<AccountUpdates>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Geyser only pushes changes, so the first pass polls a full snapshot
+    snapshotted: bool,
+    // Which markets each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl PhoenixMonitor {
+    pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let markets: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid market address"), pool))
+            .collect();
+        let geyser = geyser.map(|config| {
+            AccountUpdates::subscribe(config, SOURCE, markets.iter().map(|(address, _)| *address).collect())
+        });
+        let schedule = PollSchedule::new(SOURCE, &markets, schedule);
+
+        Self {
+            rpc_client,
+            markets,
+            last_snapshot: HashMap::new(),
+            geyser,
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            snapshotted: false,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, markets = self.markets.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+
+            // Changes pushed while paused may be long outdated, so re-read a full snapshot
+            if shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await == Some(true) {
+                self.snapshotted = false;
+            }
+
+            // On Geyser, wait for market accounts to change instead of polling, once the initial snapshot is in
+            let pushed = match self.geyser.as_mut() {
+                Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                    Some(batch) => Some(batch?),
+                    None => return Ok(()),
+                },
+                _ => {
+                    shutdown.run_until_cancelled(interval.tick()).await;
+                    None
+                }
+            };
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = match pushed {
+                Some((slot, accounts)) => self.parse_market_accounts(slot, accounts).await.map(|markets| (slot, markets)),
+                None => self.fetch_market_data().await,
+            };
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.markets.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, markets)) => {
+                    self.snapshotted = true;
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every market is parked between keep-alives
+                    let slot_lag = if markets.is_empty() {
+                        0
+                    } else {
+                        self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in markets {
+                        let current_price = (data.book.bid.price + data.book.ask.price) / 2.0;
+                        let pressure = book::pressure(data.book.bid, data.book.ask);
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.header.base_decimals,
+                            data.header.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.header.base_decimals,
+                            quote_decimals: data.header.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: data.book.taker_fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: Some(data.book.bid.price),
+                            ask: Some(data.book.ask.price),
+                            active_index: None,
+                            microprice: pressure.map(|pressure| pressure.microprice),
+                            imbalance: pressure.map(|pressure| pressure.imbalance),
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    async fn fetch_market_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, MarketData)>)> {
+        // Parked markets are only read once per keep-alive
+        let due = self.schedule.due(&self.markets);
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let mut found = Vec::with_capacity(response.value.len());
+        for ((address, market), account) in due.into_iter().zip(response.value) {
+            match account {
+                Some(account) => found.push((*address, account)),
+                None => tracing::warn!(source = SOURCE, symbol = %market.symbol, pool = %address, "market account not found"),
+            }
+        }
+
+        Ok((slot, self.parse_market_accounts(slot, found).await?))
+    }
+
+    // Current cluster tip, to tell how far behind the data we publish is
+    async fn tip_slot(&self) -> Option<u64> {
+        match &self.geyser {
+            Some(updates) => Some(updates.tip_slot()),
+            None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
+        }
+    }
+
+    // Read the top of each market's book, whether polled or pushed, then all of
+    // their vaults in one round trip. Markets without both a bid and an ask have
+    // no mid price and are skipped until they do.
+    async fn parse_market_accounts(&mut self, slot: u64, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, MarketData)>> {
+        let now = unix_now();
+
+        let mut decoded = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            let Some(symbol) = self.markets.iter().find(|(market_address, _)| *market_address == address).map(|(_, market)| market.symbol.clone()) else {
+                continue;
+            };
+
+            let header = match MarketHeader::parse(&account.data) {
+                Ok(header) => header,
+                Err(e) if !e.is_transient() => {
+                    self.dropped.extend(health::drop_pool(SOURCE, &mut self.markets, &address, &e));
+                    continue;
+                }
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse market");
+                    continue;
+                }
+            };
+            match top_of_book(&header, &account.data, slot, now) {
+                Some(book) => decoded.push((address, symbol, header, book)),
+                None => tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "market has an empty side, no mid price"),
+            }
+        }
+
+        let vaults: Vec<Pubkey> = decoded
+            .iter()
+            .flat_map(|(_, _, header, _)| [header.base_vault, header.quote_vault])
+            .collect();
+        let balances = self.get_token_account_balances(&vaults).await?;
+
+        let mut results = Vec::with_capacity(decoded.len());
+        for ((address, symbol, header, book), balances) in decoded.into_iter().zip(balances.chunks(2)) {
+            let (Some(base_reserve), Some(quote_reserve)) = (balances[0], balances[1]) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read market vaults");
+                continue;
+            };
+
+            results.push((address, symbol, MarketData {
+                header,
+                book,
+                base_reserve,
+                quote_reserve,
+            }));
+        }
+
+        Ok(results)
+    }
+
+    // Amounts of many token accounts in a single getMultipleAccounts call,
+    // None for accounts that are missing or not token accounts
+    async fn get_token_account_balances(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<u64>>> {
+        if token_accounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // At least as new as the pools read before them
+        let accounts = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?.value;
+        Ok(accounts
+            .iter()
+            .map(|account| account.as_ref().and_then(|account| token::token_account_amount(&account.data).ok()))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Monitor for PhoenixMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
@@ -0,0 +1,56 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+// On-chain programs of the monitored DEXes
+pub const RAYDIUM_AMM_PROGRAM_ID: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey = pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+pub const WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+pub const DLMM_PROGRAM_ID: Pubkey = pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+pub const METEORA_AMM_PROGRAM_ID: Pubkey = pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB");
+pub const PHOENIX_PROGRAM_ID: Pubkey = pubkey!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+pub const OPENBOOK_V2_PROGRAM_ID: Pubkey = pubkey!("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb");
+pub const LIFINITY_V2_PROGRAM_ID: Pubkey = pubkey!("2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c");
+pub const PUMP_FUN_PROGRAM_ID: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+pub const PUMPSWAP_PROGRAM_ID: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+pub const SABER_PROGRAM_ID: Pubkey = pubkey!("SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ");
+
+// Liquid staking program whose state isn't an SPL stake pool
+pub const MARINADE_PROGRAM_ID: Pubkey = pubkey!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+
+// Loader owning programs that can be upgraded in place
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+// Source name used in PriceUpdate for a DEX program, if it is one we monitor
+pub fn dex_name(program_id: &Pubkey) -> Option<&'static str> {
+    match *program_id {
+        RAYDIUM_AMM_PROGRAM_ID => Some("Raydium"),
+        RAYDIUM_CLMM_PROGRAM_ID => Some("Raydium CLMM"),
+        WHIRLPOOL_PROGRAM_ID => Some("Orca"),
+        DLMM_PROGRAM_ID => Some("Meteora"),
+        METEORA_AMM_PROGRAM_ID => Some("Meteora AMM"),
+        PHOENIX_PROGRAM_ID => Some("Phoenix"),
+        OPENBOOK_V2_PROGRAM_ID => Some("OpenBook"),
+        LIFINITY_V2_PROGRAM_ID => Some("Lifinity"),
+        PUMP_FUN_PROGRAM_ID => Some("Pump.fun"),
+        PUMPSWAP_PROGRAM_ID => Some("PumpSwap"),
+        SABER_PROGRAM_ID => Some("Saber"),
+        _ => None,
+    }
+}
+
+// Program behind a PriceUpdate source name
+pub fn program_id(source: &str) -> Option<Pubkey> {
+    match source {
+        "Raydium" => Some(RAYDIUM_AMM_PROGRAM_ID),
+        "Raydium CLMM" => Some(RAYDIUM_CLMM_PROGRAM_ID),
+        "Orca" => Some(WHIRLPOOL_PROGRAM_ID),
+        "Meteora" => Some(DLMM_PROGRAM_ID),
+        "Meteora AMM" => Some(METEORA_AMM_PROGRAM_ID),
+        "Phoenix" => Some(PHOENIX_PROGRAM_ID),
+        "OpenBook" => Some(OPENBOOK_V2_PROGRAM_ID),
+        "Lifinity" => Some(LIFINITY_V2_PROGRAM_ID),
+        "Pump.fun" => Some(PUMP_FUN_PROGRAM_ID),
+        "PumpSwap" => Some(PUMPSWAP_PROGRAM_ID),
+        "Saber" => Some(SABER_PROGRAM_ID),
+        _ => None,
+    }
+}
@@ -0,0 +1,528 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::programs;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+// BondingCurve account, after the 8-byte Anchor discriminator:
+// virtual_token_reserves (8) | virtual_sol_reserves (8) | real_token_reserves (8)
+//   | real_sol_reserves (8) | token_total_supply (8) | complete (1) | creator (32)
+// The curve prices off its virtual reserves; the real ones are what it holds.
+const VIRTUAL_TOKEN_RESERVES_OFFSET: usize = 8;
+const VIRTUAL_SOL_RESERVES_OFFSET: usize = 8 + 8;
+const REAL_TOKEN_RESERVES_OFFSET: usize = 8 + 16;
+const REAL_SOL_RESERVES_OFFSET: usize = 8 + 24;
+const COMPLETE_OFFSET: usize = 8 + 40;
+const CURVE_LEN: usize = COMPLETE_OFFSET + 1;
+// Every pump.fun mint has 6 decimals, and the curve trades against native SOL
+const CURVE_TOKEN_DECIMALS: u8 = 6;
+const SOL_DECIMALS: u8 = 9;
+
+// pump.fun Global account: initialized (1) | authority (32) | fee_recipient (32)
+//   | initial_virtual_token_reserves (8) | initial_virtual_sol_reserves (8)
+//   | initial_real_token_reserves (8) | token_total_supply (8) | fee_basis_points (8)
+const CURVE_FEE_OFFSET: usize = 8 + 97;
+
+// PumpSwap Pool account: pool_bump (1) | index (2) | creator (32) | base_mint (32)
+//   | quote_mint (32) | lp_mint (32) | pool_base_token_account (32)
+//   | pool_quote_token_account (32) | lp_supply (8) | coin_creator (32)
+const BASE_MINT_OFFSET: usize = 8 + 35;
+const QUOTE_MINT_OFFSET: usize = 8 + 67;
+const LP_MINT_OFFSET: usize = 8 + 99;
+const BASE_VAULT_OFFSET: usize = 8 + 131;
+const QUOTE_VAULT_OFFSET: usize = 8 + 163;
+const LP_SUPPLY_OFFSET: usize = 8 + 195;
+const POOL_LEN: usize = LP_SUPPLY_OFFSET + 8;
+
+// PumpSwap GlobalConfig account: admin (32) | lp_fee_basis_points (8)
+//   | protocol_fee_basis_points (8) | disable_flags (1) | protocol_fee_recipients (32 × 8)
+//   | coin_creator_fee_basis_points (8)
+const LP_FEE_OFFSET: usize = 8 + 32;
+const PROTOCOL_FEE_OFFSET: usize = 8 + 40;
+const COIN_CREATOR_FEE_OFFSET: usize = 8 + 305;
+
+// Which pump.fun accounts a monitor reads. A token trades on its bonding curve
+// until the curve completes, then migrates to a PumpSwap pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpKind {
+    BondingCurve,
+    PumpSwap,
+}
+
+impl PumpKind {
+    pub fn source(self) -> &'static str {
+        match self {
+            PumpKind::BondingCurve => "Pump.fun",
+            PumpKind::PumpSwap => "PumpSwap",
+        }
+    }
+
+    // The program's singleton holding its fee settings
+    fn global_account(self) -> Pubkey {
+        match self {
+            PumpKind::BondingCurve => Pubkey::find_program_address(&[b"global"], &programs::PUMP_FUN_PROGRAM_ID).0,
+            PumpKind::PumpSwap => Pubkey::find_program_address(&[b"global_config"], &programs::PUMPSWAP_PROGRAM_ID).0,
+        }
+    }
+
+    // Taker fee, the LP, protocol and coin creator shares together on PumpSwap
+    fn fee_bps(self, data: &[u8]) -> Option<f64> {
+        match self {
+            PumpKind::BondingCurve => (data.len() >= CURVE_FEE_OFFSET + 8).then(|| read_u64(data, CURVE_FEE_OFFSET) as f64),
+            PumpKind::PumpSwap => {
+                if data.len() < PROTOCOL_FEE_OFFSET + 8 {
+                    return None;
+                }
+                let coin_creator_fee = if data.len() >= COIN_CREATOR_FEE_OFFSET + 8 { read_u64(data, COIN_CREATOR_FEE_OFFSET) } else { 0 };
+                Some((read_u64(data, LP_FEE_OFFSET) + read_u64(data, PROTOCOL_FEE_OFFSET) + coin_creator_fee) as f64)
+            }
+        }
+    }
+}
+
+// Mints and token accounts of a PumpSwap pool, fixed at its creation
+#[derive(Debug, Clone, Copy)]
+pub struct PoolHeader {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl PoolHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < POOL_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid PumpSwap pool data size: {} bytes", data.len())));
+        }
+
+        Ok(Self {
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+            base_vault: read_pubkey(data, BASE_VAULT_OFFSET),
+            quote_vault: read_pubkey(data, QUOTE_VAULT_OFFSET),
+        })
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// A PumpSwap pool's accounts with its mint decimals, resolved once
+#[derive(Debug, Clone, Copy)]
+struct ResolvedPool {
+    header: PoolHeader,
+    base_decimals: u8,
+    quote_decimals: u8,
+}
+
+// Decoded state of one curve or pool for a single tick
+struct PoolData {
+    price: f64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    lp_supply: Option<u64>,
+}
+
+enum CurveState {
+    Trading(PoolData),
+    Complete,
+}
+
+pub struct PumpMonitor {
+    rpc_client: RpcClient,
+    kind: PumpKind,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    global: Pubkey,
+    // PumpSwap pools only; bonding curves are read whole every poll
+    resolved: HashMap<Pubkey, ResolvedPool>,
+    // Curves that migrated, so that is reported once rather than every poll
+    completed: HashSet<Pubkey>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl PumpMonitor {
+    pub fn new(kind: PumpKind, pools: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let schedule = PollSchedule::new(kind.source(), &pools, schedule);
+
+        Self {
+            rpc_client,
+            kind,
+            pools,
+            global: kind.global_account(),
+            resolved: HashMap::new(),
+            completed: HashSet::new(),
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let source = self.kind.source();
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = %source, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(source, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_pool_data().await;
+            metrics::observe_fetch(source, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = %source, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, fee_bps, pools)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = %source, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(source));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.rpc_client
+                            .get_slot_with_commitment(CommitmentConfig::processed())
+                            .await
+                            .unwrap_or(slot)
+                            .saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in pools {
+                        let current_price = data.price;
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.base_decimals,
+                            data.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, source, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: source.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.base_decimals,
+                            quote_decimals: data.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: data.lp_supply,
+                            fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(source);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(source, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read the program's fee settings and every due curve, or every due pool
+    // with its two token accounts, in one getMultipleAccounts call
+    async fn fetch_pool_data(&mut self) -> Result<(u64, f64, Vec<(Pubkey, String, PoolData)>)> {
+        let source = self.kind.source();
+        // Parked pools are only read once per keep-alive
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.pools)
+            .into_iter()
+            .filter(|(address, _)| !self.completed.contains(address))
+            .map(|(address, pool)| (*address, pool.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, 0.0, Vec::new()));
+        }
+        if self.kind == PumpKind::PumpSwap {
+            self.resolve_pools(&due).await?;
+        }
+
+        let mut accounts = vec![self.global];
+        for (address, _) in &due {
+            match self.kind {
+                PumpKind::BondingCurve => accounts.push(*address),
+                PumpKind::PumpSwap => {
+                    if let Some(pool) = self.resolved.get(address) {
+                        accounts.extend([*address, pool.header.base_vault, pool.header.quote_vault]);
+                    }
+                }
+            }
+        }
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+        let Some((global, accounts)) = response.value.split_first() else {
+            return Err(WatcherError::RpcError("Empty getMultipleAccounts response".to_string()));
+        };
+        let Some(global) = global else {
+            return Err(WatcherError::RpcError(format!("{} fee settings account not found", source)));
+        };
+        let fee_bps = self
+            .kind
+            .fee_bps(&global.data)
+            .ok_or_else(|| WatcherError::DecodeError(format!("Failed to read the {} fee settings", source)))?;
+
+        let mut results = Vec::with_capacity(due.len());
+        match self.kind {
+            PumpKind::BondingCurve => {
+                for ((address, symbol), account) in due.into_iter().zip(accounts) {
+                    let Some(account) = account else {
+                        tracing::warn!(source = %source, symbol = %symbol, pool = %address, "bonding curve account not found");
+                        continue;
+                    };
+                    match parse_curve(&account.data) {
+                        Ok(CurveState::Trading(data)) => results.push((address, symbol, data)),
+                        Ok(CurveState::Complete) => {
+                            tracing::info!(source = %source, symbol = %symbol, pool = %address, "bonding curve completed and migrated, list its PumpSwap pool to keep watching it");
+                            self.completed.insert(address);
+                        }
+                        Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(source, &mut self.pools, &address, &e)),
+                        Err(e) => {
+                            metrics::inc_decode_error(source);
+                            tracing::warn!(source = %source, symbol = %symbol, pool = %address, error = %e, "failed to parse bonding curve");
+                        }
+                    }
+                }
+            }
+            PumpKind::PumpSwap => {
+                let due: Vec<(Pubkey, String, ResolvedPool)> = due
+                    .into_iter()
+                    .filter_map(|(address, symbol)| self.resolved.get(&address).map(|pool| (address, symbol, *pool)))
+                    .collect();
+                for ((address, symbol, pool), accounts) in due.into_iter().zip(accounts.chunks(3)) {
+                    let [pool_account, base_vault, quote_vault] = accounts else {
+                        continue;
+                    };
+                    match parse_pool(&pool, pool_account, base_vault, quote_vault) {
+                        Some(data) => results.push((address, symbol, data)),
+                        None => {
+                            metrics::inc_decode_error(source);
+                            tracing::warn!(source = %source, symbol = %symbol, pool = %address, "failed to read pool or its token accounts");
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((slot, fee_bps, results))
+    }
+
+    // Read the PumpSwap pools not seen yet, then their mints, one call each
+    async fn resolve_pools(&mut self, due: &[(Pubkey, String)]) -> Result<()> {
+        let source = self.kind.source();
+        let missing: Vec<&(Pubkey, String)> = due.iter().filter(|(address, _)| !self.resolved.contains_key(address)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = missing.iter().map(|(address, _)| *address).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        let mut headers = Vec::with_capacity(missing.len());
+        for ((address, symbol), account) in missing.into_iter().zip(accounts) {
+            let Some(account) = account else {
+                tracing::warn!(source = %source, symbol = %symbol, pool = %address, "pool account not found");
+                continue;
+            };
+            match PoolHeader::parse(&account.data) {
+                Ok(header) => headers.push((*address, symbol, header)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(source, &mut self.pools, address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(source);
+                    tracing::warn!(source = %source, symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+                }
+            }
+        }
+        if headers.is_empty() {
+            return Ok(());
+        }
+
+        let mints: Vec<Pubkey> = headers.iter().flat_map(|(_, _, header)| [header.base_mint, header.quote_mint]).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &mints).await?;
+        let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+        for ((address, symbol, header), mints) in headers.into_iter().zip(accounts.chunks(2)) {
+            let (Some(base_decimals), Some(quote_decimals)) = (decimals(&mints[0]), decimals(&mints[1])) else {
+                metrics::inc_decode_error(source);
+                tracing::warn!(source = %source, symbol = %symbol, pool = %address, "failed to read pool mints");
+                continue;
+            };
+            self.resolved.insert(address, ResolvedPool { header, base_decimals, quote_decimals });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Monitor for PumpMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
+
+// Price in SOL per token from the virtual reserves. Reserves are the real ones,
+// the tokens left to sell and the SOL paid in so far.
+fn parse_curve(data: &[u8]) -> Result<CurveState> {
+    if data.len() < CURVE_LEN {
+        return Err(WatcherError::DecodeError(format!("Invalid bonding curve data size: {} bytes", data.len())));
+    }
+    if data[COMPLETE_OFFSET] != 0 {
+        return Ok(CurveState::Complete);
+    }
+
+    let virtual_token_reserves = read_u64(data, VIRTUAL_TOKEN_RESERVES_OFFSET);
+    let virtual_sol_reserves = read_u64(data, VIRTUAL_SOL_RESERVES_OFFSET);
+    if virtual_token_reserves == 0 {
+        return Err(WatcherError::DecodeError("bonding curve has no virtual token reserves".to_string()));
+    }
+    let price = (virtual_sol_reserves as f64 / 10_f64.powi(SOL_DECIMALS as i32))
+        / (virtual_token_reserves as f64 / 10_f64.powi(CURVE_TOKEN_DECIMALS as i32));
+
+    Ok(CurveState::Trading(PoolData {
+        price,
+        base_reserve: read_u64(data, REAL_TOKEN_RESERVES_OFFSET),
+        quote_reserve: read_u64(data, REAL_SOL_RESERVES_OFFSET),
+        base_decimals: CURVE_TOKEN_DECIMALS,
+        quote_decimals: SOL_DECIMALS,
+        lp_supply: None,
+    }))
+}
+
+// Constant-product price from the pool's token account balances
+fn parse_pool(pool: &ResolvedPool, pool_account: &Option<Account>, base_vault: &Option<Account>, quote_vault: &Option<Account>) -> Option<PoolData> {
+    let pool_data = &pool_account.as_ref()?.data;
+    if pool_data.len() < POOL_LEN {
+        return None;
+    }
+    let base_reserve = token::token_account_amount(&base_vault.as_ref()?.data).ok()?;
+    let quote_reserve = token::token_account_amount(&quote_vault.as_ref()?.data).ok()?;
+    if base_reserve == 0 {
+        return None;
+    }
+
+    let price = (quote_reserve as f64 / 10_f64.powi(pool.quote_decimals as i32))
+        / (base_reserve as f64 / 10_f64.powi(pool.base_decimals as i32));
+    Some(PoolData {
+        price,
+        base_reserve,
+        quote_reserve,
+        base_decimals: pool.base_decimals,
+        quote_decimals: pool.quote_decimals,
+        lp_supply: Some(read_u64(pool_data, LP_SUPPLY_OFFSET)),
+    })
+}
@@ -0,0 +1,271 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+
+pub const SOURCE: &str = "Pyth";
+
+// Prices published longer ago than this are left out rather than passed off as current
+const MAX_PUBLISH_AGE_SECS: i64 = 60;
+
+// PriceUpdateV2 account of the Pyth receiver, after the 8-byte Anchor
+// discriminator: write_authority (32) | verification_level (tag, plus a
+// signature count (1) when Partial) | price message: feed_id (32) | price (8)
+//   | conf (8) | exponent (4) | publish_time (8) | prev_publish_time (8)
+//   | ema_price (8) | ema_conf (8) | posted_slot (8)
+const VERIFICATION_LEVEL_OFFSET: usize = 8 + 32;
+const VERIFICATION_PARTIAL: u8 = 0;
+const PRICE_OFFSET: usize = 32;
+const CONF_OFFSET: usize = 40;
+const EXPONENT_OFFSET: usize = 48;
+const PUBLISH_TIME_OFFSET: usize = 52;
+const MESSAGE_LEN: usize = 84;
+
+struct FeedPrice {
+    price: f64,
+    conf: f64,
+    publish_time: i64,
+}
+
+fn parse_price(data: &[u8]) -> Result<FeedPrice> {
+    let tag = *data
+        .get(VERIFICATION_LEVEL_OFFSET)
+        .ok_or_else(|| WatcherError::DecodeError(format!("Invalid Pyth price update data size: {} bytes", data.len())))?;
+    let message = VERIFICATION_LEVEL_OFFSET + if tag == VERIFICATION_PARTIAL { 2 } else { 1 };
+    if data.len() < message + MESSAGE_LEN {
+        return Err(WatcherError::DecodeError(format!("Invalid Pyth price update data size: {} bytes", data.len())));
+    }
+
+    let read_i64 = |offset: usize| i64::from_le_bytes(data[message + offset..message + offset + 8].try_into().unwrap());
+    let exponent = i32::from_le_bytes(data[message + EXPONENT_OFFSET..message + EXPONENT_OFFSET + 4].try_into().unwrap());
+    let scale = 10_f64.powi(exponent);
+    Ok(FeedPrice {
+        price: read_i64(PRICE_OFFSET) as f64 * scale,
+        conf: read_i64(CONF_OFFSET) as u64 as f64 * scale,
+        publish_time: read_i64(PUBLISH_TIME_OFFSET),
+    })
+}
+
+// Pyth price feeds as a reference source next to the DEXes. The feed's symbol
+// is whatever it's listed under, e.g. SOL/USD; the alert engine compares DEX
+// pools quoted in USD stablecoins against it.
+pub struct PythMonitor {
+    rpc_client: RpcClient,
+    feeds: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which feeds each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl PythMonitor {
+    pub fn new(feeds: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let feeds: Vec<(Pubkey, PoolConfig)> = feeds
+            .into_iter()
+            .map(|feed| (feed.pubkey().expect("Invalid feed address"), feed))
+            .collect();
+        let schedule = PollSchedule::new(SOURCE, &feeds, schedule);
+
+        Self {
+            rpc_client,
+            feeds,
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, feeds = self.feeds.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_prices().await;
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.feeds.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, prices)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    for (address, symbol, feed) in prices {
+                        // No reserves behind an oracle price; only the price feeds the snapshot
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: 0,
+                            quote_reserve: 0,
+                            price: feed.price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((feed.price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), feed.price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), feed.price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: feed.price,
+                            change_percent,
+                            timestamp: feed.publish_time as u64,
+                            base_reserve: 0,
+                            quote_reserve: 0,
+                            base_decimals: 0,
+                            quote_decimals: 0,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: 0.0,
+                            slot,
+                            slot_lag: 0,
+                            tvl_usd: None,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            // The confidence interval around the aggregate price
+                            bid: Some(feed.price - feed.conf),
+                            ask: Some(feed.price + feed.conf),
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read every due feed account in one getMultipleAccounts call
+    async fn fetch_prices(&mut self) -> Result<(u64, Vec<(Pubkey, String, FeedPrice)>)> {
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.feeds)
+            .into_iter()
+            .map(|(address, feed)| (*address, feed.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+        let now = unix_now() as i64;
+
+        let mut results = Vec::with_capacity(due.len());
+        for ((address, symbol), account) in due.into_iter().zip(response.value) {
+            let Some(account) = account else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "feed account not found");
+                continue;
+            };
+            let fresh = parse_price(&account.data).and_then(|feed| match now - feed.publish_time {
+                age if age > MAX_PUBLISH_AGE_SECS => Err(WatcherError::StaleAccount {
+                    account: format!("Pyth feed {} ({})", address, symbol),
+                    reason: format!("last published {}s ago", age),
+                }),
+                _ => Ok(feed),
+            });
+            match fresh {
+                Ok(feed) if feed.price <= 0.0 => {}
+                Ok(feed) => results.push((address, symbol, feed)),
+                Err(e @ WatcherError::StaleAccount { .. }) => tracing::warn!(source = SOURCE, error = %e, "stale account, skipping"),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.feeds, &address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse feed");
+                }
+            }
+        }
+
+        Ok((slot, results))
+    }
+}
+
+#[async_trait]
+impl Monitor for PythMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
@@ -0,0 +1,526 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_meteora_dlmm_decoder::accounts::lb_pair::LbPair;
+use carbon_raydium_clmm_decoder::accounts::pool_state::PoolState;
+use futures::future::join_all;
+use orca_whirlpools_client::Whirlpool;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::depth::{self, BINS_PER_ARRAY, CLMM_TICKS_PER_ARRAY, TICKS_PER_ARRAY};
+use crate::raydium::PriceUpdate;
+use crate::routing::Side;
+
+// A swap crosses at most this many tick arrays (the Whirlpool program's own
+// limit) or bin arrays, so that is all a quote loads in the trade's direction
+const ARRAYS_AHEAD: i64 = 3;
+
+// Fee rates are applied in hundredths of a basis point, the unit the Whirlpool
+// and CLMM programs store theirs in
+const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+// Pools that really are x * y = k over their reserves
+const CONSTANT_PRODUCT: [&str; 3] = ["Raydium", "Meteora AMM", "PumpSwap"];
+// Sources with a swap model; order books, oracles and the rest have none
+const SWAP_MODELS: [&str; 6] = ["Raydium", "Meteora AMM", "PumpSwap", "Orca", "Raydium CLMM", "Meteora"];
+
+pub fn is_constant_product(source: &str) -> bool {
+    CONSTANT_PRODUCT.contains(&source)
+}
+
+pub fn has_swap_model(source: &str) -> bool {
+    SWAP_MODELS.contains(&source)
+}
+
+// Expected fill of one trade on one venue, UI units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueQuote {
+    pub source: String,
+    pub pool: String,
+    pub mid_price: f64,
+    pub execution_price: f64,  // Average over the filled part, fee included
+    pub filled: f64,           // Base tokens; below `size` when the venue runs out of liquidity
+    pub quote_amount: f64,     // Quote received (sell) or spent (buy)
+    pub fee_bps: f64,
+    pub slippage_bps: f64,     // Execution vs. mid price, fee included
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+    pub venues: Vec<VenueQuote>,  // Best execution price first
+}
+
+// Quote `size` base tokens on every venue of a symbol with that venue's own
+// curve. Raydium, Meteora AMM and PumpSwap are quoted from the latest reserves;
+// Orca, Raydium CLMM and Meteora read the pool and its tick/bin arrays fresh.
+// Venues without a swap model are skipped, and those that fail to load are logged and left out.
+pub async fn quote(rpc_client: &RpcClient, venues: Vec<PriceUpdate>, symbol: &str, side: Side, size: f64) -> Option<Quote> {
+    let venues: Vec<PriceUpdate> = venues
+        .into_iter()
+        .filter(|update| update.symbol.eq_ignore_ascii_case(symbol) && update.price > 0.0)
+        .filter(|update| has_swap_model(&update.source))
+        .collect();
+    if venues.is_empty() || size <= 0.0 {
+        return None;
+    }
+
+    let quotes = join_all(venues.iter().map(|update| quote_venue(rpc_client, update, side, size))).await;
+    let mut quotes: Vec<VenueQuote> = venues
+        .iter()
+        .zip(quotes)
+        .filter_map(|(update, quote)| {
+            quote
+                .map_err(|e| tracing::error!(source = %update.source, symbol = %update.symbol, error = %e, "quote failed"))
+                .ok()
+        })
+        .collect();
+
+    // Fuller fills first, then the better price for the side
+    quotes.sort_by(|a, b| {
+        b.filled.total_cmp(&a.filled).then(match side {
+            Side::Sell => b.execution_price.total_cmp(&a.execution_price),
+            Side::Buy => a.execution_price.total_cmp(&b.execution_price),
+        })
+    });
+
+    Some(Quote {
+        symbol: symbol.to_string(),
+        side,
+        size,
+        venues: quotes,
+    })
+}
+
+pub async fn quote_venue(rpc_client: &RpcClient, update: &PriceUpdate, side: Side, size: f64) -> anyhow::Result<VenueQuote> {
+    let curve = load_curve(rpc_client, update, side).await?;
+    let (filled, quote_amount) = curve.fill(size)?;
+    let mid_price = curve.mid_price;
+    let execution_price = if filled > 0.0 { quote_amount / filled } else { 0.0 };
+    let slippage_bps = match side {
+        Side::Sell => (mid_price - execution_price) / mid_price * 10_000.0,
+        Side::Buy => (execution_price - mid_price) / mid_price * 10_000.0,
+    };
+
+    Ok(VenueQuote {
+        source: update.source.clone(),
+        pool: update.pool.clone(),
+        mid_price,
+        execution_price,
+        filled,
+        quote_amount,
+        fee_bps: update.fee_bps,
+        slippage_bps,
+    })
+}
+
+// One venue's swap curve in the trade's direction, loaded once and then
+// filled for any size
+pub struct VenueCurve {
+    pub source: String,
+    pub pool: String,
+    pub mid_price: f64,
+    side: Side,
+    base_unit: f64,
+    quote_unit: f64,
+    model: SwapModel,
+}
+
+enum SwapModel {
+    ConstantProduct { base_reserve: u64, quote_reserve: u64, fee_rate: u128 },
+    Ticks { curve: TickCurve, fee: f64 },
+    Bins { bins: Vec<(i64, u64, u64)>, active_id: i64, step: f64, fee: f64 },
+}
+
+// Concentrated liquidity around the current price. Whirlpools and Raydium CLMM
+// pools share the layout: Q64.64 √P, ticks at powers of 1.0001.
+struct TickCurve {
+    ticks: Vec<(i32, i128)>,  // Initialized ticks (index, liquidity_net), ascending
+    liquidity: u128,
+    sqrt_price: f64,          // √(quote atoms per base atom)
+    tick_current: i32,
+    tick_lower: i32,          // The loaded range; a walk stops at its ends
+    tick_upper: i32,
+}
+
+impl TickCurve {
+    // Tick range a trade in `side` can reach within ARRAYS_AHEAD arrays
+    fn range(tick_current: i32, ticks_ahead: i32, side: Side) -> (i32, i32) {
+        match side {
+            Side::Sell => (tick_current - ticks_ahead, tick_current),
+            Side::Buy => (tick_current, tick_current + ticks_ahead),
+        }
+    }
+}
+
+// Read what a venue's swap model needs: the latest reserves for constant-product
+// pools, the pool and its tick or bin arrays ahead of the price otherwise
+pub async fn load_curve(rpc_client: &RpcClient, update: &PriceUpdate, side: Side) -> anyhow::Result<VenueCurve> {
+    let base_unit = 10_f64.powi(update.base_decimals as i32);
+    let quote_unit = 10_f64.powi(update.quote_decimals as i32);
+    if !(0.0..10_000.0).contains(&update.fee_bps) {
+        return Err(anyhow::anyhow!("Fee of {} bps is out of range", update.fee_bps));
+    }
+    let fee_rate = (update.fee_bps * 100.0).round() as u128;
+    let fee = fee_rate as f64 / FEE_RATE_DENOMINATOR as f64;
+    let address = Pubkey::from_str(&update.pool)?;
+
+    let (mid_price, model) = match update.source.as_str() {
+        source if is_constant_product(source) => (
+            update.price,
+            SwapModel::ConstantProduct { base_reserve: update.base_reserve, quote_reserve: update.quote_reserve, fee_rate },
+        ),
+        "Orca" => {
+            let whirlpool = Whirlpool::from_bytes(&rpc_client.get_account_data(&address).await?)?;
+            let ticks_ahead = ARRAYS_AHEAD as i32 * TICKS_PER_ARRAY * whirlpool.tick_spacing as i32;
+            let (tick_lower, tick_upper) = TickCurve::range(whirlpool.tick_current_index, ticks_ahead, side);
+            let ticks = depth::fetch_ticks(rpc_client, &address, whirlpool.tick_spacing, tick_lower, tick_upper).await?;
+
+            let sqrt_price = whirlpool.sqrt_price as f64 / (1u128 << 64) as f64;
+            let curve = TickCurve {
+                ticks,
+                liquidity: whirlpool.liquidity,
+                sqrt_price,
+                tick_current: whirlpool.tick_current_index,
+                tick_lower,
+                tick_upper,
+            };
+            (sqrt_price.powi(2) * base_unit / quote_unit, SwapModel::Ticks { curve, fee })
+        }
+        "Raydium CLMM" => {
+            let pool_state = <PoolState as CarbonDeserialize>::deserialize(&rpc_client.get_account_data(&address).await?)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium CLMM data"))?;
+            let ticks_ahead = ARRAYS_AHEAD as i32 * CLMM_TICKS_PER_ARRAY * pool_state.tick_spacing as i32;
+            let (tick_lower, tick_upper) = TickCurve::range(pool_state.tick_current, ticks_ahead, side);
+            let ticks = depth::fetch_clmm_ticks(rpc_client, &address, pool_state.tick_spacing, tick_lower, tick_upper).await?;
+
+            let sqrt_price = pool_state.sqrt_price_x64 as f64 / (1u128 << 64) as f64;
+            let curve = TickCurve {
+                ticks,
+                liquidity: pool_state.liquidity,
+                sqrt_price,
+                tick_current: pool_state.tick_current,
+                tick_lower,
+                tick_upper,
+            };
+            (sqrt_price.powi(2) * base_unit / quote_unit, SwapModel::Ticks { curve, fee })
+        }
+        "Meteora" => {
+            let lb_pair = <LbPair as CarbonDeserialize>::deserialize(&rpc_client.get_account_data(&address).await?)
+                .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora DLMM data"))?;
+            let active_id = lb_pair.active_id as i64;
+            let (bin_lower, bin_upper) = match side {
+                Side::Sell => (active_id - ARRAYS_AHEAD * BINS_PER_ARRAY, active_id),
+                Side::Buy => (active_id, active_id + ARRAYS_AHEAD * BINS_PER_ARRAY),
+            };
+            let bins = depth::fetch_bins(rpc_client, &address, bin_lower, bin_upper).await?;
+
+            let step = 1.0 + lb_pair.bin_step as f64 / 10_000.0;
+            (step.powf(active_id as f64) * base_unit / quote_unit, SwapModel::Bins { bins, active_id, step, fee })
+        }
+        source => return Err(anyhow::anyhow!("No swap model for {}", source)),
+    };
+    if !mid_price.is_finite() || mid_price <= 0.0 {
+        return Err(anyhow::anyhow!("{} mid price is not a positive amount", update.source));
+    }
+
+    Ok(VenueCurve {
+        source: update.source.clone(),
+        pool: update.pool.clone(),
+        mid_price,
+        side,
+        base_unit,
+        quote_unit,
+        model,
+    })
+}
+
+impl VenueCurve {
+    // (base filled, quote out/in) for `size` base tokens, UI units
+    pub fn fill(&self, size: f64) -> anyhow::Result<(f64, f64)> {
+        let size = size * self.base_unit;
+        let (filled, quote_amount) = match &self.model {
+            SwapModel::ConstantProduct { base_reserve, quote_reserve, fee_rate } => {
+                constant_product(*base_reserve, *quote_reserve, self.side, size, *fee_rate)?
+            }
+            SwapModel::Ticks { curve, fee } => tick_walk(curve, self.side, size, *fee),
+            SwapModel::Bins { bins, active_id, step, fee } => bin_walk(bins, *active_id, *step, self.side, size, *fee),
+        };
+
+        // The tick and bin walks stay in f64, so their result is checked instead
+        if !filled.is_finite() || !quote_amount.is_finite() {
+            return Err(anyhow::anyhow!("{} quote is not a finite amount", self.source));
+        }
+        Ok((filled / self.base_unit, quote_amount / self.quote_unit))
+    }
+
+    // Quote out/in for all of `size`, None when the venue can't fill it
+    pub fn fill_all(&self, size: f64) -> Option<f64> {
+        let (filled, quote_amount) = self.fill(size).ok()?;
+        // An atom lost to rounding isn't a shortfall
+        ((size - filled) * self.base_unit <= 1.0).then_some(quote_amount)
+    }
+}
+
+// x * y = k on raw atoms in u128 math, rounding in the pool's favour like the
+// program does; the fee, in hundredths of a basis point, is taken from the
+// input. Returns (base filled, quote out/in).
+fn constant_product(base_reserve: u64, quote_reserve: u64, side: Side, size: f64, fee_rate: u128) -> anyhow::Result<(f64, f64)> {
+    if base_reserve == 0 || quote_reserve == 0 {
+        return Err(anyhow::anyhow!("Pool has an empty reserve"));
+    }
+    if !size.is_finite() || size < 0.0 || size >= u64::MAX as f64 {
+        return Err(anyhow::anyhow!("Trade size of {} atoms does not fit in u64", size));
+    }
+    // A product of two values under 2^64 always fits in u128
+    let (base_reserve, quote_reserve, size) = (base_reserve as u128, quote_reserve as u128, size as u128);
+    let after_fee = FEE_RATE_DENOMINATOR - fee_rate;
+
+    match side {
+        Side::Sell => {
+            let base_in = size * after_fee / FEE_RATE_DENOMINATOR;
+            let quote_out = quote_reserve * base_in / (base_reserve + base_in);
+            Ok((size as f64, quote_out as f64))
+        }
+        Side::Buy => {
+            // The curve never gives up its whole base reserve
+            if size >= base_reserve {
+                return Ok((0.0, 0.0));
+            }
+            // Up to quote_reserve * size when the trade leaves a single atom behind,
+            // so grossing it up for the fee is the step that can overflow
+            let quote_in = (quote_reserve * size).div_ceil(base_reserve - size);
+            let quote_in = quote_in
+                .checked_mul(FEE_RATE_DENOMINATOR)
+                .ok_or_else(|| anyhow::anyhow!("Constant-product quote overflows u128"))?
+                .div_ceil(after_fee);
+            Ok((size as f64, quote_in as f64))
+        }
+    }
+}
+
+// Walk a concentrated-liquidity curve tick by tick. Between initialized ticks
+// liquidity L is constant: moving √P trades L * Δ(1/√P) base against L * Δ√P quote.
+// Returns (base filled, quote out/in); the fill stops at the loaded tick range.
+// Kept in f64: this is an estimate over √P, not the programs' Q64.64 math.
+fn tick_walk(curve: &TickCurve, side: Side, size: f64, fee: f64) -> (f64, f64) {
+    let sqrt_tick = |tick_index: i32| 1.0001_f64.powf(tick_index as f64 / 2.0);
+    let mut liquidity = curve.liquidity as f64;
+    let mut sqrt_price = curve.sqrt_price;
+    let (ticks, tick_current, tick_lower, tick_upper) = (&curve.ticks, curve.tick_current, curve.tick_lower, curve.tick_upper);
+
+    match side {
+        // Base in, price down; crossing a tick downwards takes its liquidity_net back out
+        Side::Sell => {
+            let mut remaining = size * (1.0 - fee);
+            let mut quote_out = 0.0;
+            let crossings = ticks
+                .iter()
+                .rev()
+                .filter(|(tick_index, _)| (tick_lower..=tick_current).contains(tick_index))
+                .map(|&(tick_index, liquidity_net)| (sqrt_tick(tick_index), liquidity_net))
+                .chain(std::iter::once((sqrt_tick(tick_lower), 0)));
+            for (sqrt_next, liquidity_net) in crossings {
+                let base_to_next = liquidity * (1.0 / sqrt_next - 1.0 / sqrt_price);
+                if remaining <= base_to_next {
+                    let sqrt_end = 1.0 / (1.0 / sqrt_price + remaining / liquidity);
+                    quote_out += liquidity * (sqrt_price - sqrt_end);
+                    return (size, quote_out);
+                }
+                quote_out += liquidity * (sqrt_price - sqrt_next);
+                remaining -= base_to_next;
+                liquidity = (liquidity - liquidity_net as f64).max(0.0);
+                sqrt_price = sqrt_next;
+            }
+            (size - remaining / (1.0 - fee), quote_out)
+        }
+        // Base out, price up
+        Side::Buy => {
+            let mut remaining = size;
+            let mut quote_in = 0.0;
+            let crossings = ticks
+                .iter()
+                .filter(|(tick_index, _)| *tick_index > tick_current && *tick_index <= tick_upper)
+                .map(|&(tick_index, liquidity_net)| (sqrt_tick(tick_index), liquidity_net))
+                .chain(std::iter::once((sqrt_tick(tick_upper), 0)));
+            for (sqrt_next, liquidity_net) in crossings {
+                let base_to_next = liquidity * (1.0 / sqrt_price - 1.0 / sqrt_next);
+                if remaining <= base_to_next {
+                    let sqrt_end = 1.0 / (1.0 / sqrt_price - remaining / liquidity);
+                    quote_in += liquidity * (sqrt_end - sqrt_price);
+                    return (size, quote_in / (1.0 - fee));
+                }
+                quote_in += liquidity * (sqrt_next - sqrt_price);
+                remaining -= base_to_next;
+                liquidity = (liquidity + liquidity_net as f64).max(0.0);
+                sqrt_price = sqrt_next;
+            }
+            (size - remaining, quote_in / (1.0 - fee))
+        }
+    }
+}
+
+// Walk DLMM bins away from the active one at each bin's fixed price: selling
+// base drains the quote of the active and lower bins, buying drains the base of
+// the active and higher bins. Returns (base filled, quote out/in).
+fn bin_walk(bins: &[(i64, u64, u64)], active_id: i64, step: f64, side: Side, size: f64, fee: f64) -> (f64, f64) {
+    let bins: HashMap<i64, (u64, u64)> = bins.iter().map(|&(bin_id, amount_x, amount_y)| (bin_id, (amount_x, amount_y))).collect();
+    let (lowest, highest) = (
+        bins.keys().copied().min().unwrap_or(active_id),
+        bins.keys().copied().max().unwrap_or(active_id),
+    );
+
+    match side {
+        Side::Sell => {
+            let mut remaining = size * (1.0 - fee);
+            let mut quote_out = 0.0;
+            for bin_id in (lowest..=active_id).rev() {
+                let Some(&(_, amount_y)) = bins.get(&bin_id) else {
+                    continue;
+                };
+                let price = step.powf(bin_id as f64);
+                let base_to_drain = amount_y as f64 / price;
+                if remaining <= base_to_drain {
+                    quote_out += remaining * price;
+                    return (size, quote_out);
+                }
+                quote_out += amount_y as f64;
+                remaining -= base_to_drain;
+            }
+            (size - remaining / (1.0 - fee), quote_out)
+        }
+        Side::Buy => {
+            let mut remaining = size;
+            let mut quote_in = 0.0;
+            for bin_id in active_id..=highest {
+                let Some(&(amount_x, _)) = bins.get(&bin_id) else {
+                    continue;
+                };
+                let price = step.powf(bin_id as f64);
+                let take = remaining.min(amount_x as f64);
+                quote_in += take * price;
+                remaining -= take;
+                if remaining <= 0.0 {
+                    break;
+                }
+            }
+            (size - remaining, quote_in / (1.0 - fee))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected.abs() * 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn constant_product_sell_rounds_down() {
+        // 0.25% off 1000 in leaves 997.5, floored to 997; 2e6 * 997 / 1_000_997 = 1992.01
+        let (filled, quote_out) = constant_product(1_000_000, 2_000_000, Side::Sell, 1000.0, 2500).unwrap();
+        assert_eq!((filled, quote_out), (1000.0, 1992.0));
+    }
+
+    #[test]
+    fn constant_product_buy_rounds_fee_gross_up_up() {
+        // 2e6 * 1000 / 999_000 = 2002.002 -> 2003 before the fee, 2003 / 0.9975 = 2008.02 -> 2009
+        let (filled, quote_in) = constant_product(1_000_000, 2_000_000, Side::Buy, 1000.0, 2500).unwrap();
+        assert_eq!((filled, quote_in), (1000.0, 2009.0));
+    }
+
+    #[test]
+    fn constant_product_buy_cannot_drain_the_base_reserve() {
+        assert_eq!(constant_product(1000, 1000, Side::Buy, 1000.0, 0).unwrap(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn constant_product_rejects_empty_pools_and_overflow() {
+        assert!(constant_product(0, 1000, Side::Sell, 1.0, 0).is_err());
+        assert!(constant_product(1000, 1000, Side::Sell, -1.0, 0).is_err());
+        // Leaving one atom of base behind costs quote_reserve * size, which the fee overflows
+        let size = (1u64 << 63) as f64;
+        assert!(constant_product((1 << 63) + 1, u64::MAX, Side::Buy, size, 2500).is_err());
+    }
+
+    // 1e6 liquidity at tick 0, with an initialized tick 10 ticks away on either side
+    fn tick_curve(liquidity_net_below: i128, liquidity_net_above: i128) -> TickCurve {
+        TickCurve {
+            ticks: vec![(-10, liquidity_net_below), (10, liquidity_net_above)],
+            liquidity: 1_000_000,
+            sqrt_price: 1.0,
+            tick_current: 0,
+            tick_lower: -100,
+            tick_upper: 100,
+        }
+    }
+
+    #[test]
+    fn tick_walk_sell_crosses_a_tick() {
+        let curve = tick_curve(400_000, 0);
+        let sqrt_boundary = 1.0001_f64.powf(-5.0);
+        let base_to_boundary = 1_000_000.0 * (1.0 / sqrt_boundary - 1.0);
+        // Past tick -10 the remaining 100 base trade against 600k liquidity
+        let sqrt_end = 1.0 / (1.0 / sqrt_boundary + 100.0 / 600_000.0);
+        let expected = 1_000_000.0 * (1.0 - sqrt_boundary) + 600_000.0 * (sqrt_boundary - sqrt_end);
+
+        let (filled, quote_out) = tick_walk(&curve, Side::Sell, base_to_boundary + 100.0, 0.0);
+        assert_close(filled, base_to_boundary + 100.0);
+        assert_close(quote_out, expected);
+    }
+
+    #[test]
+    fn tick_walk_buy_crosses_a_tick() {
+        let curve = tick_curve(0, 500_000);
+        let sqrt_boundary = 1.0001_f64.powf(5.0);
+        let base_to_boundary = 1_000_000.0 * (1.0 - 1.0 / sqrt_boundary);
+        // Past tick 10 the remaining 100 base come out of 1.5M liquidity
+        let sqrt_end = 1.0 / (1.0 / sqrt_boundary - 100.0 / 1_500_000.0);
+        let expected = 1_000_000.0 * (sqrt_boundary - 1.0) + 1_500_000.0 * (sqrt_end - sqrt_boundary);
+
+        let (filled, quote_in) = tick_walk(&curve, Side::Buy, base_to_boundary + 100.0, 0.003);
+        assert_close(filled, base_to_boundary + 100.0);
+        assert_close(quote_in, expected / (1.0 - 0.003));
+    }
+
+    #[test]
+    fn tick_walk_stops_at_the_loaded_range() {
+        let curve = tick_curve(0, 0);
+        let base_in_range = 1_000_000.0 * (1.0 / 1.0001_f64.powf(-50.0) - 1.0);
+        let (filled, quote_out) = tick_walk(&curve, Side::Sell, 1e9, 0.0);
+        assert_close(filled, base_in_range);
+        assert_close(quote_out, 1_000_000.0 * (1.0 - 1.0001_f64.powf(-50.0)));
+    }
+
+    // Bin -1 holds quote only, bin 0 (active) both, bin 1 base only
+    const BINS: [(i64, u64, u64); 3] = [(-1, 0, 1000), (0, 500, 1000), (1, 500, 0)];
+
+    #[test]
+    fn bin_walk_sell_crosses_a_bin() {
+        // 1000 base drain bin 0 at price 1, the other 500 sell into bin -1 at 1 / 1.01
+        let (filled, quote_out) = bin_walk(&BINS, 0, 1.01, Side::Sell, 1500.0, 0.0);
+        assert_close(filled, 1500.0);
+        assert_close(quote_out, 1000.0 + 500.0 / 1.01);
+    }
+
+    #[test]
+    fn bin_walk_buy_crosses_a_bin() {
+        // 500 base out of bin 0 at price 1, 300 out of bin 1 at 1.01, grossed up for the fee
+        let (filled, quote_in) = bin_walk(&BINS, 0, 1.01, Side::Buy, 800.0, 0.01);
+        assert_close(filled, 800.0);
+        assert_close(quote_in, (500.0 + 300.0 * 1.01) / 0.99);
+    }
+
+    #[test]
+    fn bin_walk_fills_what_the_bins_hold() {
+        let (filled, quote_in) = bin_walk(&BINS, 0, 1.01, Side::Buy, 2000.0, 0.0);
+        assert_close(filled, 1000.0);
+        assert_close(quote_in, 500.0 + 500.0 * 1.01);
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Token bucket shared by every RPC call, so concurrent monitors queue up behind
+// one request budget instead of each running into the provider's limit
+pub struct RateLimiter {
+    rate: f64,     // Tokens added per second
+    burst: f64,    // Bucket size
+    // (tokens, last refill); tokens go negative while callers are queued
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate: requests_per_second,
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    // Wait for a token. Each caller reserves its token up front and sleeps
+    // until it would have been refilled, so waiters are served in order.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let (tokens, last) = &mut *state;
+            let now = Instant::now();
+            *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.rate).min(self.burst);
+            *last = now;
+            *tokens -= 1.0;
+            if *tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-*tokens / self.rate)
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
@@ -1,14 +1,37 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use solana_client::{
     nonblocking::rpc_client::RpcClient
 };
 use solana_sdk::{account::Account, pubkey::Pubkey, commitment_config::CommitmentConfig};
-use std::str::FromStr;
+use std::collections::HashMap;
 use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 use std::sync::Arc;
 use carbon_raydium_amm_v4_decoder::accounts::amm_info::AmmInfo as RaydiumAmmInfo;
 use carbon_core::deserialize::CarbonDeserialize;
 
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::fingerprint::{account_data, Fingerprints};
+use crate::backoff::Backoff;
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::snapshot::{classify_change, ChangeReason, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AmmInfo {
     pub pool_id: String,
@@ -16,6 +39,10 @@ pub struct AmmInfo {
     pub quote_mint: String,   // USDC mint  
     pub base_reserve: u64,    // SOL amount in pool
     pub quote_reserve: u64,   // USDC amount in pool
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub lp_supply: u64,       // LP tokens outstanding
+    pub fee_bps: f64,         // Swap fee charged to traders
     pub price: f64,           // Calculated price
 }
 
@@ -26,124 +53,365 @@ pub struct PriceUpdate {
     pub change_percent: f64,
     pub timestamp: u64,
     pub source: String,
+    pub pool: String,
     pub base_reserve: u64,
     pub quote_reserve: u64,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub change_reason: ChangeReason,
+    pub lp_supply: Option<u64>,   // LP mint supply, where the pool tracks one
+    pub fee_bps: f64,             // Swap fee tier of the pool
+    pub slot: u64,                // Slot the pool state was read at
+    pub slot_lag: u64,            // Slots behind the cluster tip when published; large = stale source
+    pub tvl_usd: Option<f64>,     // Both reserves valued in USD; None unless quoted in a USD stable
+    pub high_24h: f64,            // Range over the last 24h the watcher has seen, this update included
+    pub low_24h: f64,
+    pub volume_24h: Option<f64>,  // Quote volume over the last 24h; None unless swaps are decoded
+    pub bid: Option<f64>,         // Top of book on order book venues; None on AMMs
+    pub ask: Option<f64>,
+    pub active_index: Option<i64>,  // Current tick (Orca, Raydium CLMM) or active bin (Meteora); None elsewhere
+    pub microprice: Option<f64>,    // Top of book mid weighted by the size on each side; order book venues only
+    pub imbalance: Option<f64>,     // (bid size - ask size) / (bid size + ask size) at the top of book, -1 to 1
+    pub change_1m: Option<f64>,     // Percent change over the window, from the rolling history; None until it covers it
+    pub change_5m: Option<f64>,
+    pub change_24h: Option<f64>,
+    #[serde(default)]
+    pub unchanged: bool,            // Same reserves and price as the pool's last update; set by [dedup] mode = "mark"
 }
 
 pub struct RaydiumMonitor {
     rpc_client: RpcClient,
-    sol_usdc_pool: Pubkey,
-    price_cache: Option<f64>,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    // Pushed pool account changes when running on the Geyser backend
+    geyser: Option<AccountUpdates>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Geyser only pushes changes, so the first pass polls a full snapshot
+    snapshotted: bool,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pool and vault data as of the last read, and the decode it gave
+    fingerprints: Fingerprints,
+    decoded: HashMap<Pubkey, RaydiumAmmInfo>,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
 }
 
 impl RaydiumMonitor {
-    pub fn new() -> Self {
-        // Solana Mainnet RPC (free)
-        let rpc_client = RpcClient::new_with_commitment(
-            "https://api.mainnet-beta.solana.com".to_string(),
-            CommitmentConfig::confirmed(),
-        );
+    pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
         
-        let sol_usdc_pool = Pubkey::from_str("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2")
-            .expect("Invalid pool address");
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let geyser = geyser.map(|config| {
+            AccountUpdates::subscribe(config, "Raydium", pools.iter().map(|(address, _)| *address).collect())
+        });
+        let fingerprints = Fingerprints::new("Raydium", schedule);
+        let schedule = PollSchedule::new("Raydium", &pools, schedule);
             
         Self {
             rpc_client,
-            sol_usdc_pool,
-            price_cache: None,
+            pools,
+            last_snapshot: HashMap::new(),
+            geyser,
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            snapshotted: false,
+            schedule,
+            fingerprints,
+            decoded: HashMap::new(),
+            dropped: Vec::new(),
         }
     }
     
-    // Start monitoring the pool account for changes
+    // Start monitoring the pool accounts for changes
     pub async fn start_monitoring(
         &mut self, 
-        tx: Arc<broadcast::Sender<PriceUpdate>>
-    ) -> anyhow::Result<()> {
-        println!("🚀 Starting Raydium SOL/USDC pool monitoring...");
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        tracing::info!(source = "Raydium", pools = self.pools.len(), "starting monitoring");
         
         loop {
-            match self.fetch_pool_data().await {
-                Ok(amm_info) => {
-                    // Calculate price from reserves
-                    let current_price = self.calculate_price(&amm_info);
-                    
-                    let price_update = PriceUpdate {
-                        symbol: "SOL/USDC".to_string(),
-                        price: current_price,
-                        change_percent: self.calculate_change_percent(current_price),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        source: "Raydium".to_string(),
-                        base_reserve: amm_info.base_reserve,
-                        quote_reserve: amm_info.quote_reserve,
+            heartbeat.beat();
+            
+            // Changes pushed while paused may be long outdated, so re-read a full snapshot
+            if shutdown.run_until_cancelled(pause.wait_resumed("Raydium", heartbeat)).await == Some(true) {
+                self.snapshotted = false;
+            }
+            
+            // On Geyser, wait for pool accounts to change instead of polling, once the initial snapshot is in
+            let pushed = match self.geyser.as_mut() {
+                Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                    Some(batch) => Some(batch?),
+                    None => return Ok(()),
+                },
+                _ => None,
+            };
+            
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = match pushed {
+                Some((slot, accounts)) => self.parse_pool_accounts(accounts).await.map(|pools| (slot, pools)),
+                None => self.fetch_pool_data().await,
+            };
+            metrics::observe_fetch("Raydium", fetch_started);
+            
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = "Raydium", "no pools left to watch");
+                    return Ok(());
+                }
+            }
+            
+            match result {
+                Ok((slot, pools)) => {
+                    self.snapshotted = true;
+                    if self.backoff.reset() {
+                        tracing::info!(source = "Raydium", "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered("Raydium"));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
                     };
-                    
-                    let _ = tx.send(price_update);
-                    self.price_cache = Some(current_price);
+                    for (address, symbol, amm_info) in pools {
+                        // Calculate price from reserves
+                        let current_price = self.calculate_price(&amm_info);
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: amm_info.base_reserve,
+                            quote_reserve: amm_info.quote_reserve,
+                            price: current_price,
+                        };
+                        
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            amm_info.base_reserve,
+                            amm_info.quote_reserve,
+                            amm_info.base_decimals,
+                            amm_info.quote_decimals,
+                            current_price,
+                        );
+                        
+                        if sanity::reject(&tx, "Raydium", &symbol, &amm_info.pool_id, current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&amm_info.pool_id, current_price);
+                        let price_update = PriceUpdate {
+                            symbol,
+                            price: current_price,
+                            change_percent: self.calculate_change_percent(&address, current_price),
+                            timestamp: unix_now(),
+                            source: "Raydium".to_string(),
+                            pool: amm_info.pool_id.clone(),
+                            base_reserve: amm_info.base_reserve,
+                            quote_reserve: amm_info.quote_reserve,
+                            base_decimals: amm_info.base_decimals,
+                            quote_decimals: amm_info.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: Some(amm_info.lp_supply),
+                            fee_bps: amm_info.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+                        
+                        let _ = dedup::publish(&tx, price_update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
                 }
                 Err(e) => {
-                    eprintln!("❌ Raydium fetch error: {}", e);
+                    metrics::inc_rpc_error("Raydium");
+                    // Back off instead of hammering a rate-limited endpoint every 2 seconds
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = "Raydium", failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded("Raydium", &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                    continue;
                 }
             }
             
             // Poll every 2 seconds (much faster than API polling)
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            if self.geyser.is_none() {
+                shutdown.run_until_cancelled(tokio::time::sleep(tokio::time::Duration::from_secs(2))).await;
+            }
+        }
+    }
+    
+    // Fetch all pool accounts from Solana blockchain in a single round trip,
+    // along with the slot they were read at
+    async fn fetch_pool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, AmmInfo)>)> {
+        // Parked pools are only read once per keep-alive
+        let due = self.schedule.due(&self.pools);
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+        let accounts = response.value;
+        
+        let mut found = Vec::with_capacity(accounts.len());
+        for ((address, pool), account) in due.into_iter().zip(accounts) {
+            match account {
+                Some(account) => found.push((*address, account)),
+                None => tracing::error!(source = "Raydium", symbol = %pool.symbol, pool = %address, "pool account not found"),
+            }
+        }
+        
+        Ok((slot, self.parse_pool_accounts(found).await?))
+    }
+    
+    // Current cluster tip, to tell how far behind the data we publish is
+    async fn tip_slot(&self) -> Option<u64> {
+        match &self.geyser {
+            Some(updates) => Some(updates.tip_slot()),
+            None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
         }
     }
     
-    // Fetch pool account data from Solana blockchain
-    async fn fetch_pool_data(&self) -> anyhow::Result<AmmInfo> {
-        // Get the pool account data directly from blockchain
-        let account = self.rpc_client
-            .get_account_with_commitment(&self.sol_usdc_pool, CommitmentConfig::confirmed())
-            .await?
-            .value
-            .ok_or_else(|| anyhow::anyhow!("Pool account not found"))?;
+    // Decode pool accounts, whether polled or pushed, then read all of their
+    // vaults in one round trip. Undecodable pools are logged and skipped, and
+    // pools whose pool and vault data are unchanged since the last read are
+    // left out between keep-alives.
+    async fn parse_pool_accounts(&mut self, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, AmmInfo)>> {
+        let mut decoded = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            let Some(symbol) = self.pools.iter().find(|(pool_address, _)| *pool_address == address).map(|(_, pool)| pool.symbol.clone()) else {
+                continue;
+            };
+            
+            // Same bytes as last time decode to the same pool, so reuse that
+            let unchanged = self.fingerprints.unchanged(&address, &account.data);
+            let parsed = match self.decoded.get(&address) {
+                Some(raydium_info) if unchanged => Ok(raydium_info.clone()),
+                // Parse the account data (this is where AMM-specific parsing happens)
+                _ => self.parse_raydium_pool_data(&account),
+            };
+            match parsed {
+                Ok(raydium_info) => {
+                    self.decoded.insert(address, raydium_info.clone());
+                    decoded.push((address, symbol, raydium_info, unchanged));
+                }
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool("Raydium", &mut self.pools, &address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error("Raydium");
+                    tracing::error!(source = "Raydium", symbol = %symbol, pool = %address, error = %e, "failed to decode pool");
+                }
+            }
+        }
+        
+        // Get the actual reserves from token vault accounts
+        let vaults: Vec<Pubkey> = decoded
+            .iter()
+            .flat_map(|(_, _, raydium_info, _)| [raydium_info.token_coin, raydium_info.token_pc])
+            .collect();
+        let vault_accounts = self.get_vault_accounts(&vaults).await?;
+        
+        let mut pools = Vec::with_capacity(decoded.len());
+        for ((address, symbol, raydium_info, pool_unchanged), (vaults, accounts)) in decoded
+            .into_iter()
+            .zip(vaults.chunks(2).zip(vault_accounts.chunks(2)))
+        {
+            let amount = |vault: &Option<Account>| vault.as_ref().and_then(|vault| token::token_account_amount(&vault.data).ok());
+            let (Some(base_vault_amount), Some(quote_vault_amount)) = (amount(&accounts[0]), amount(&accounts[1])) else {
+                metrics::inc_decode_error("Raydium");
+                tracing::error!(source = "Raydium", symbol = %symbol, pool = %address, "vault account missing or invalid");
+                continue;
+            };
             
-        // Parse the account data (this is where AMM-specific parsing happens)
-        self.parse_raydium_pool_data(&account).await
+            // Both vaults are recorded even when the first already changed
+            let vaults_unchanged = [
+                self.fingerprints.unchanged(&vaults[0], account_data(&accounts[0])),
+                self.fingerprints.unchanged(&vaults[1], account_data(&accounts[1])),
+            ];
+            if !self.fingerprints.should_publish(&address, pool_unchanged && vaults_unchanged == [true, true]) {
+                continue;
+            }
+            
+            let amm_info = AmmInfo {
+                pool_id: address.to_string(),
+                base_mint: raydium_info.coin_mint.to_string(),
+                quote_mint: raydium_info.pc_mint.to_string(),
+                base_reserve: base_vault_amount,
+                quote_reserve: quote_vault_amount,
+                base_decimals: raydium_info.coin_decimals as u8,
+                quote_decimals: raydium_info.pc_decimals as u8,
+                lp_supply: raydium_info.lp_amount,
+                fee_bps: raydium_info.fees.trade_fee_numerator as f64
+                    / raydium_info.fees.trade_fee_denominator.max(1) as f64
+                    * 10_000.0,
+                price: 0.0,
+            };
+            pools.push((address, symbol, amm_info));
+        }
+        
+        Ok(pools)
     }
 
-    async fn get_token_account_balance(&self, token_account: &Pubkey) -> anyhow::Result<u64> {
-        let balance = self.rpc_client
-            .get_token_account_balance(token_account)
-            .await?;
+    // Many token accounts in a single getMultipleAccounts call, None for
+    // accounts that are missing
+    async fn get_vault_accounts(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        if token_accounts.is_empty() {
+            return Ok(Vec::new());
+        }
         
-        Ok(balance.amount.parse::<u64>()?)
+        // At least as new as the pools read before them
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        Ok(response.value)
     }
     
-    // Parse raw Raydium account data into structured info
-    async fn parse_raydium_pool_data(&self, account: &Account) -> anyhow::Result<AmmInfo> {
+    // Parse raw Raydium account data
+    fn parse_raydium_pool_data(&self, account: &Account) -> Result<RaydiumAmmInfo> {
         let data = &account.data;
         
         if data.len() < 656 {  
-            return Err(anyhow::anyhow!("Invalid pool account data size"));
+            return Err(WatcherError::DecodeError(format!("Invalid pool account data size: {} bytes", data.len())));
         }
         
         // 🔥 PROPER PARSING WITH CARBON DECODER! 🔥
         match <RaydiumAmmInfo as CarbonDeserialize>::deserialize(data) {
-            Some(raydium_info) => {
-                // Get the actual reserves from token vault accounts
-                let base_vault_amount = self.get_token_account_balance(&raydium_info.token_coin).await?;
-                let quote_vault_amount = self.get_token_account_balance(&raydium_info.token_pc).await?;
-                
-                Ok(AmmInfo {
-                    pool_id: self.sol_usdc_pool.to_string(),
-                    base_mint: raydium_info.coin_mint.to_string(),
-                    quote_mint: raydium_info.pc_mint.to_string(),
-                    base_reserve: base_vault_amount,
-                    quote_reserve: quote_vault_amount,
-                    price: 0.0,
-                })
-            }
+            Some(raydium_info) => Ok(raydium_info),
             None => {
-                eprintln!("❌ Failed to parse Raydium data");
-                println!("🔍 Raw data length: {} bytes", data.len());
-                println!("🔍 First 64 bytes: {}", hex::encode(&data[..64.min(data.len())]));
+                tracing::error!(source = "Raydium", "failed to parse data");
+                tracing::info!(source = "Raydium", bytes = data.len(), "raw data length");
+                tracing::info!(source = "Raydium", data = %hex::encode(&data[..64.min(data.len())]), "first 64 bytes");
                 
-                Err(anyhow::anyhow!("Failed to parse Raydium AMM data"))
+                Err(WatcherError::DecodeError("Failed to parse Raydium AMM data".to_string()))
             }
         }
     }
@@ -155,20 +423,27 @@ impl RaydiumMonitor {
         }
         
         // Price = quote_reserve / base_reserve (adjusted for decimals)
-        // SOL has 9 decimals, USDC has 6 decimals
-        let sol_amount = amm_info.base_reserve as f64 / 1e9;    // Convert lamports to SOL
-        let usdc_amount = amm_info.quote_reserve as f64 / 1e6;  // Convert micro-USDC to USDC
+        // e.g. SOL has 9 decimals, USDC has 6 decimals
+        let base_amount = amm_info.base_reserve as f64 / 10_f64.powi(amm_info.base_decimals as i32);
+        let quote_amount = amm_info.quote_reserve as f64 / 10_f64.powi(amm_info.quote_decimals as i32);
         
-        return usdc_amount / sol_amount;  // Price of SOL in USDC
+        return quote_amount / base_amount;  // Price of base token in quote token
     }
     
-    // Calculate percentage change from cached price
-    fn calculate_change_percent(&self, current_price: f64) -> f64 {
-        match self.price_cache {
-            Some(cached_price) => {
-                ((current_price - cached_price) / cached_price) * 100.0
+    // Calculate percentage change from the pool's cached price
+    fn calculate_change_percent(&self, pool: &Pubkey, current_price: f64) -> f64 {
+        match self.last_snapshot.get(pool) {
+            Some(cached) => {
+                ((current_price - cached.price) / cached.price) * 100.0
             }
             None => 0.0,
         }
     }
+}
+
+#[async_trait]
+impl Monitor for RaydiumMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,406 @@
+use async_trait::async_trait;
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_raydium_clmm_decoder::accounts::{amm_config::AmmConfig, pool_state::PoolState};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{GeyserConfig, PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::geyser::AccountUpdates;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "Raydium CLMM";
+
+// Decoded state of one CLMM pool for a single tick
+struct ClmmData {
+    price: f64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    fee_bps: f64,
+    tick: i64,
+}
+
+pub struct RaydiumClmmMonitor {
+    rpc_client: RpcClient,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    // Trade fee per AMM config (fee tier) account, resolved once
+    fee_cache: HashMap<Pubkey, f64>,
+    // Pushed pool account changes when running on the Geyser backend
+    geyser: Option<AccountUpdates>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Geyser only pushes changes, so the first pass polls a full snapshot
+    snapshotted: bool,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl RaydiumClmmMonitor {
+    pub fn new(pools: Vec<PoolConfig>, geyser: Option<GeyserConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let geyser = geyser.map(|config| {
+            AccountUpdates::subscribe(config, SOURCE, pools.iter().map(|(address, _)| *address).collect())
+        });
+        let schedule = PollSchedule::new(SOURCE, &pools, schedule);
+
+        Self {
+            rpc_client,
+            pools,
+            last_snapshot: HashMap::new(),
+            fee_cache: HashMap::new(),
+            geyser,
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            snapshotted: false,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+
+            // Changes pushed while paused may be long outdated, so re-read a full snapshot
+            if shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await == Some(true) {
+                self.snapshotted = false;
+            }
+
+            // On Geyser, wait for pool accounts to change instead of polling, once the initial snapshot is in
+            let pushed = match self.geyser.as_mut() {
+                Some(updates) if self.snapshotted => match shutdown.run_until_cancelled(updates.next_batch()).await {
+                    Some(batch) => Some(batch?),
+                    None => return Ok(()),
+                },
+                _ => {
+                    shutdown.run_until_cancelled(interval.tick()).await;
+                    None
+                }
+            };
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = match pushed {
+                Some((slot, accounts)) => self.parse_pool_accounts(accounts).await.map(|pools| (slot, pools)),
+                None => self.fetch_pool_data().await,
+            };
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, pools)) => {
+                    self.snapshotted = true;
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.tip_slot().await.unwrap_or(slot).saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in pools {
+                        let current_price = data.price;
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.base_decimals,
+                            data.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.base_decimals,
+                            quote_decimals: data.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: None,
+                            fee_bps: data.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: Some(data.tick),
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    async fn fetch_pool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, ClmmData)>)> {
+        // Parked pools are only read once per keep-alive
+        let due = self.schedule.due(&self.pools);
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let addresses: Vec<Pubkey> = due.iter().map(|(address, _)| *address).collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &addresses, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let mut found = Vec::with_capacity(response.value.len());
+        for ((address, pool), account) in due.into_iter().zip(response.value) {
+            match account {
+                Some(account) => found.push((*address, account)),
+                None => tracing::warn!(source = SOURCE, symbol = %pool.symbol, pool = %address, "pool account not found"),
+            }
+        }
+
+        Ok((slot, self.parse_pool_accounts(found).await?))
+    }
+
+    // Current cluster tip, to tell how far behind the data we publish is
+    async fn tip_slot(&self) -> Option<u64> {
+        match &self.geyser {
+            Some(updates) => Some(updates.tip_slot()),
+            None => self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await.ok(),
+        }
+    }
+
+    // Decode pool accounts, whether polled or pushed, then read all of their
+    // vaults in one round trip, concurrently with the fee tiers not seen yet.
+    // Decimals are stored on the pool itself. Undecodable pools are logged and skipped.
+    async fn parse_pool_accounts(&mut self, accounts: Vec<(Pubkey, Account)>) -> Result<Vec<(Pubkey, String, ClmmData)>> {
+        let mut decoded = Vec::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            let Some(symbol) = self.pools.iter().find(|(pool_address, _)| *pool_address == address).map(|(_, pool)| pool.symbol.clone()) else {
+                continue;
+            };
+
+            match parse_pool_state(&account.data) {
+                Ok(pool_state) => decoded.push((address, symbol, pool_state)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.pools, &address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse pool");
+                }
+            }
+        }
+
+        let vaults: Vec<Pubkey> = decoded
+            .iter()
+            .flat_map(|(_, _, pool_state)| [pool_state.token_vault0, pool_state.token_vault1])
+            .collect();
+        let mut unresolved: Vec<Pubkey> = decoded
+            .iter()
+            .map(|(_, _, pool_state)| pool_state.amm_config)
+            .filter(|amm_config| !self.fee_cache.contains_key(amm_config))
+            .collect();
+        unresolved.sort();
+        unresolved.dedup();
+        let (balances, fees) = tokio::join!(
+            self.get_token_account_balances(&vaults),
+            self.fetch_trade_fees(&unresolved),
+        );
+        let balances = balances?;
+        self.fee_cache.extend(fees?);
+
+        let mut results = Vec::with_capacity(decoded.len());
+        for ((address, symbol, pool_state), balances) in decoded.into_iter().zip(balances.chunks(2)) {
+            let Some(&fee_bps) = self.fee_cache.get(&pool_state.amm_config) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool fee tier");
+                continue;
+            };
+            let (Some(base_reserve), Some(quote_reserve)) = (balances[0], balances[1]) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read pool vaults");
+                continue;
+            };
+            let price = match clmm_price(&pool_state) {
+                Ok(price) => price,
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "skipping pool");
+                    continue;
+                }
+            };
+
+            results.push((address, symbol, ClmmData {
+                price,
+                base_reserve,
+                quote_reserve,
+                base_decimals: pool_state.mint_decimals0,
+                quote_decimals: pool_state.mint_decimals1,
+                fee_bps,
+                tick: pool_state.tick_current as i64,
+            }));
+        }
+
+        Ok(results)
+    }
+
+    // Amounts of many token accounts in a single getMultipleAccounts call,
+    // None for accounts that are missing or not token accounts
+    async fn get_token_account_balances(&self, token_accounts: &[Pubkey]) -> Result<Vec<Option<u64>>> {
+        if token_accounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // At least as new as the pools read before them
+        let accounts = rpc::get_multiple_accounts_since(&self.rpc_client, token_accounts, CommitmentConfig::confirmed(), self.last_slot).await?.value;
+        Ok(accounts
+            .iter()
+            .map(|account| account.as_ref().and_then(|account| token::token_account_amount(&account.data).ok()))
+            .collect())
+    }
+
+    // A pool's fee tier lives in its AMM config account, shared by every pool of
+    // that tier, so each one is read once. Configs that can't be read are left out.
+    async fn fetch_trade_fees(&self, amm_configs: &[Pubkey]) -> Result<Vec<(Pubkey, f64)>> {
+        if amm_configs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, amm_configs).await?;
+        Ok(amm_configs
+            .iter()
+            .zip(accounts)
+            .filter_map(|(address, account)| {
+                let amm_config = <AmmConfig as CarbonDeserialize>::deserialize(&account?.data)?;
+                // trade_fee_rate is in hundredths of a basis point
+                Some((*address, amm_config.trade_fee_rate as f64 / 100.0))
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Monitor for RaydiumClmmMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
+
+fn parse_pool_state(data: &[u8]) -> Result<PoolState> {
+    <PoolState as CarbonDeserialize>::deserialize(data)
+        .ok_or_else(|| WatcherError::DecodeError("Failed to parse Raydium CLMM pool data".to_string()))
+}
+
+// Price of token0 in token1 from the Q64.64 sqrt_price_x64, checked against the
+// pool's current tick: tick_current is floor(log_1.0001(price)), so a sqrt price
+// more than a tick away from it means the account doesn't hold a consistent state
+fn clmm_price(pool_state: &PoolState) -> Result<f64> {
+    if pool_state.sqrt_price_x64 == 0 {
+        return Err(WatcherError::DecodeError("pool is not initialized".to_string()));
+    }
+
+    let price_raw = (pool_state.sqrt_price_x64 as f64 / (1u128 << 64) as f64).powi(2);
+    let tick_from_sqrt = price_raw.log(1.0001).floor() as i32;
+    if (tick_from_sqrt - pool_state.tick_current).abs() > 1 {
+        return Err(WatcherError::DecodeError(format!(
+            "sqrt_price_x64 points at tick {} but tick_current is {}",
+            tick_from_sqrt,
+            pool_state.tick_current,
+        )));
+    }
+
+    let decimal_adjustment = 10_f64.powi(pool_state.mint_decimals0 as i32 - pool_state.mint_decimals1 as i32);
+    Ok(price_raw * decimal_adjustment)
+}
@@ -0,0 +1,280 @@
+use clap::{Args, Subcommand};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::alerts::Alert;
+use crate::best_route::BestRoute;
+use crate::checkpoint::Checkpoint;
+use crate::clock::unix_now;
+use crate::jupiter::AggregatorQuote;
+use crate::config::Config;
+use crate::depth::DepthUpdate;
+use crate::derived::DerivedValue;
+use crate::events::MarketEvent;
+use crate::health::HealthEvent;
+use crate::liquidity::LiquidityChanged;
+use crate::migration::MigrationRate;
+use crate::monitor;
+use crate::raydium::PriceUpdate;
+use crate::rewards::{IncentiveApr, RewardEmissions};
+use crate::rpc::{self, RpcProvider};
+use crate::sanity::PriceAnomaly;
+use crate::stats::StatsUpdate;
+use crate::swaps::SwapEvent;
+use crate::twap::TwapUpdate;
+
+const DEFAULT_DURATION_SECS: u64 = 300;
+const DEFAULT_SNAPSHOT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum RecordCommand {
+    #[command(about = "Run the watcher as usual, capture everything for the window, then exit")]
+    Session(SessionOptions),
+}
+
+// `record session [--duration=SECS] [--out=PATH] [--snapshot-secs=SECS]`
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct SessionOptions {
+    #[arg(long, default_value_t = DEFAULT_DURATION_SECS, help = "Length of the window in seconds")]
+    pub duration: u64,
+    #[arg(long, help = "Bundle path, session-<unix time>.jsonl.gz by default")]
+    pub out: Option<PathBuf>,
+    #[arg(long, default_value_t = DEFAULT_SNAPSHOT_SECS, help = "Seconds between raw pool account snapshots")]
+    pub snapshot_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionArgs {
+    pub duration: Duration,
+    pub out: PathBuf,
+    pub snapshot_interval: Duration,
+}
+
+impl SessionArgs {
+    pub fn new(options: &SessionOptions) -> Self {
+        Self {
+            duration: Duration::from_secs(options.duration),
+            out: options.out.clone().unwrap_or_else(|| PathBuf::from(format!("session-{}.jsonl.gz", unix_now()))),
+            snapshot_interval: Duration::from_secs(options.snapshot_secs.max(1)),
+        }
+    }
+}
+
+// One line of a session bundle. The bundle is gzipped JSON Lines: a header
+// first, then everything in the order it happened, then a summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionRecord {
+    Header { version: String, started_at: u64, duration_secs: u64, config: serde_json::Value },
+    // Raw pool accounts as read from RPC, hex encoded
+    Accounts { source: String, slot: u64, timestamp: u64, accounts: Vec<RawAccount> },
+    Update(PriceUpdate),
+    Liquidity(LiquidityChanged),
+    Swap(SwapEvent),
+    Depth(DepthUpdate),
+    Route(BestRoute),
+    Aggregator(AggregatorQuote),
+    Derived(DerivedValue),
+    Migration(MigrationRate),
+    Rewards(RewardEmissions),
+    Incentive(IncentiveApr),
+    Twap(TwapUpdate),
+    Checkpoint(Checkpoint),
+    Stats(StatsUpdate),
+    Anomaly(PriceAnomaly),
+    Alert(Alert),
+    Health(HealthEvent),
+    Summary { ended_at: u64, snapshots: u64, market_events: u64, alerts: u64, health_events: u64, lagged: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawAccount {
+    pub pool: String,
+    pub symbol: String,
+    pub owner: String,
+    pub data: String,
+}
+
+// Record a session if one was asked for, resolving with the bundle path once
+// the window is over or shutdown cuts it short. Without a session this never resolves.
+pub async fn run(
+    session: Option<SessionArgs>,
+    config: Config,
+    rpc: RpcProvider,
+    mut market_rx: broadcast::Receiver<MarketEvent>,
+    mut alerts_rx: broadcast::Receiver<Alert>,
+    mut health_rx: broadcast::Receiver<HealthEvent>,
+    shutdown: CancellationToken,
+) -> anyhow::Result<PathBuf> {
+    let Some(session) = session else {
+        return std::future::pending().await;
+    };
+
+    let file = std::fs::File::create(&session.out)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", session.out.display(), e))?;
+    let mut bundle = GzEncoder::new(BufWriter::new(file), Compression::default());
+    tracing::info!(out = %session.out.display(), duration_secs = session.duration.as_secs(), "recording session");
+
+    write_record(&mut bundle, &SessionRecord::Header {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: unix_now(),
+        duration_secs: session.duration.as_secs(),
+        config: redacted_config(&config)?,
+    })?;
+
+    let rpc_client = rpc.client(CommitmentConfig::confirmed());
+    let sources = monitor::sources(&config);
+    let mut snapshot = tokio::time::interval(session.snapshot_interval);
+    snapshot.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let window = tokio::time::sleep(session.duration);
+    tokio::pin!(window);
+
+    let (mut snapshots, mut market_events, mut alerts, mut health_events, mut lagged) = (0, 0, 0, 0, 0);
+    loop {
+        let record = tokio::select! {
+            _ = &mut window => break,
+            // Ctrl+C ends the window early; the bundle is still finished
+            _ = shutdown.cancelled() => break,
+            event = market_rx.recv() => match event {
+                Ok(event) => {
+                    let record = match event {
+                        MarketEvent::Price(update) => SessionRecord::Update(update),
+                        MarketEvent::Liquidity(event) => SessionRecord::Liquidity(event),
+                        MarketEvent::Swap(swap) => SessionRecord::Swap(swap),
+                        MarketEvent::Depth(depth) => SessionRecord::Depth(depth),
+                        MarketEvent::Route(route) => SessionRecord::Route(route),
+                        MarketEvent::Aggregator(quote) => SessionRecord::Aggregator(quote),
+                        MarketEvent::Derived(value) => SessionRecord::Derived(value),
+                        MarketEvent::Migration(rate) => SessionRecord::Migration(rate),
+                        MarketEvent::Rewards(emissions) => SessionRecord::Rewards(emissions),
+                        MarketEvent::Incentive(incentive) => SessionRecord::Incentive(incentive),
+                        MarketEvent::Twap(twap) => SessionRecord::Twap(twap),
+                        MarketEvent::Checkpoint(checkpoint) => SessionRecord::Checkpoint(checkpoint),
+                        MarketEvent::Stats(stats) => SessionRecord::Stats(stats),
+                        MarketEvent::Anomaly(anomaly) => SessionRecord::Anomaly(anomaly),
+                        // Recorded from the health bus itself
+                        MarketEvent::Health(_) => continue,
+                    };
+                    market_events += 1;
+                    record
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    lagged += skipped;
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            alert = alerts_rx.recv() => match alert {
+                Ok(alert) => {
+                    alerts += 1;
+                    SessionRecord::Alert(alert)
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    lagged += skipped;
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            event = health_rx.recv() => match event {
+                Ok(event) => {
+                    health_events += 1;
+                    SessionRecord::Health(event)
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    lagged += skipped;
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = snapshot.tick() => {
+                for (source, dex) in sources.iter().map(|source| (source.name, source.dex)) {
+                    if dex.pools.is_empty() {
+                        continue;
+                    }
+                    let addresses = dex.pools.iter().map(|pool| pool.pubkey()).collect::<anyhow::Result<Vec<_>>>()?;
+                    let response = match rpc_client.get_multiple_accounts_with_commitment(&addresses, CommitmentConfig::confirmed()).await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            tracing::warn!(source = %source, error = %e, "session pool snapshot failed");
+                            continue;
+                        }
+                    };
+                    let accounts = dex.pools
+                        .iter()
+                        .zip(response.value)
+                        .filter_map(|(pool, account)| {
+                            let account = account?;
+                            Some(RawAccount {
+                                pool: pool.address.clone(),
+                                symbol: pool.symbol.clone(),
+                                owner: account.owner.to_string(),
+                                data: hex::encode(&account.data),
+                            })
+                        })
+                        .collect();
+                    snapshots += 1;
+                    write_record(&mut bundle, &SessionRecord::Accounts {
+                        source: source.to_string(),
+                        slot: response.context.slot,
+                        timestamp: unix_now(),
+                        accounts,
+                    })?;
+                }
+                continue;
+            }
+        };
+        write_record(&mut bundle, &record)?;
+    }
+
+    write_record(&mut bundle, &SessionRecord::Summary {
+        ended_at: unix_now(),
+        snapshots,
+        market_events,
+        alerts,
+        health_events,
+        lagged,
+    })?;
+    bundle.finish()?.flush()?;
+
+    Ok(session.out)
+}
+
+fn write_record(bundle: &mut impl Write, record: &SessionRecord) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *bundle, record)?;
+    bundle.write_all(b"\n")?;
+    Ok(())
+}
+
+// The effective config with credentials stripped, so bundles can be shared:
+// RPC endpoints and dead-man's switch URLs keep only their host, tokens and
+// webhooks are masked
+fn redacted_config(config: &Config) -> anyhow::Result<serde_json::Value> {
+    let mut value = serde_json::to_value(config)?;
+    if let Some(endpoints) = value.pointer_mut("/rpc/endpoints").and_then(|endpoints| endpoints.as_array_mut()) {
+        for endpoint in endpoints {
+            if let Some(url) = endpoint.as_str() {
+                *endpoint = serde_json::Value::String(rpc::host(url).to_string());
+            }
+        }
+    }
+    if let Some(urls) = value.pointer_mut("/deadman/urls").and_then(|urls| urls.as_array_mut()) {
+        for url in urls {
+            if let Some(host) = url.as_str().map(rpc::host) {
+                *url = serde_json::Value::String(host.to_string());
+            }
+        }
+    }
+    for path in ["/geyser/x_token", "/api/control_token", "/alerts/telegram/bot_token", "/alerts/discord/webhook_url", "/grafana/api_token"] {
+        if let Some(secret) = value.pointer_mut(path).filter(|secret| secret.is_string()) {
+            *secret = serde_json::Value::String("<redacted>".to_string());
+        }
+    }
+    Ok(value)
+}
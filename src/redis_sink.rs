@@ -0,0 +1,131 @@
+use redis::aio::ConnectionManager;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::backoff::Backoff;
+use crate::config::{RedisConfig, RedisMode};
+use crate::events::MarketEvent;
+use crate::latency::{self, Stage};
+
+// Redis sink for the market bus. Every event goes out as its JSON, tagged with
+// `event` like the bus serializes it; in stream mode the entry also carries the
+// tag as its own field so consumers can skip kinds without parsing the payload.
+pub struct RedisSink {
+    config: RedisConfig,
+}
+
+impl RedisSink {
+    pub fn new(config: RedisConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn run(self, mut rx: broadcast::Receiver<MarketEvent>, shutdown: CancellationToken) {
+        let mut connection = self.connect().await;
+        // Logged once per outage rather than for every event that fails
+        let mut failing = false;
+
+        loop {
+            let event = tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "Redis sink lagged, dropped events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                // The monitors have stopped by now; write what's still queued, then stop
+                _ = shutdown.cancelled() => match rx.try_recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+            };
+            let Ok(payload) = event.to_json(self.config.schema) else {
+                continue;
+            };
+
+            let started = std::time::Instant::now();
+            let result = match self.config.mode {
+                RedisMode::Pubsub => redis::cmd("PUBLISH")
+                    .arg(&self.config.key)
+                    .arg(&payload)
+                    .query_async::<i64>(&mut connection)
+                    .await
+                    .map(|_| ()),
+                RedisMode::Stream => redis::cmd("XADD")
+                    .arg(&self.config.key)
+                    .arg("MAXLEN")
+                    .arg("~")
+                    .arg(self.config.maxlen)
+                    .arg("*")
+                    .arg("event")
+                    .arg(event.kind())
+                    .arg("data")
+                    .arg(&payload)
+                    .query_async::<String>(&mut connection)
+                    .await
+                    .map(|_| ()),
+            };
+            latency::observe(Stage::Sink, "Redis", started.elapsed());
+
+            match result {
+                Ok(()) if failing => {
+                    failing = false;
+                    tracing::info!("Redis writes recovered");
+                }
+                Ok(()) => {}
+                Err(e) if !failing => {
+                    failing = true;
+                    tracing::error!(error = %e, key = %self.config.key, "Redis write failed, dropping events until it recovers");
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    // Retry until Redis is reachable; the connection manager reconnects on its own after that
+    async fn connect(&self) -> ConnectionManager {
+        let mut backoff = Backoff::new();
+        loop {
+            let connected = match redis::Client::open(self.config.url.as_str()) {
+                Ok(client) => ConnectionManager::new(client).await,
+                Err(e) => Err(e),
+            };
+            match connected {
+                Ok(mut connection) => {
+                    if let Some(group) = &self.config.group {
+                        self.create_group(&mut connection, group).await;
+                    }
+                    match self.config.mode {
+                        RedisMode::Pubsub => tracing::info!(key = %self.config.key, "publishing market events to Redis channel"),
+                        RedisMode::Stream => tracing::info!(key = %self.config.key, maxlen = %self.config.maxlen, "appending market events to Redis stream"),
+                    }
+                    return connection;
+                }
+                Err(e) => {
+                    let delay = backoff.next_delay(None);
+                    tracing::error!(error = %e, url = %self.config.url, delay_secs = delay.as_secs_f64(), "Redis connection failed, retrying");
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    // Create the consumer group at the stream's end, creating the stream too if
+    // needed. A group that already exists keeps its position.
+    async fn create_group(&self, connection: &mut ConnectionManager, group: &str) {
+        let created = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(&self.config.key)
+            .arg(group)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async::<()>(connection)
+            .await;
+        match created {
+            Ok(()) => tracing::info!(group = %group, key = %self.config.key, "created Redis consumer group"),
+            Err(e) if e.code() == Some("BUSYGROUP") => {}
+            Err(e) => tracing::warn!(error = %e, group = %group, key = %self.config.key, "failed to create Redis consumer group"),
+        }
+    }
+}
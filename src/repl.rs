@@ -0,0 +1,312 @@
+use clap::Args;
+use futures_util::StreamExt;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::api::Spread;
+use crate::config::Config;
+use crate::display;
+use crate::quote::Quote;
+use crate::raydium::PriceUpdate;
+use crate::routing::Side;
+use crate::tvl::USD_STABLES;
+
+const HELP: &str = "\
+price <symbol>                  latest price on every venue
+price <source> <symbol>         latest price on one venue, e.g. price orca sol/usdc
+spread [symbol]                 cheapest vs. most expensive venue
+spread <source> <source>        price difference between two venues per symbol
+quote <size> <token|symbol> [buy|sell]
+                                expected fill on every venue, e.g. quote 100 sol
+watch add <pool|symbol>         print live updates for a pool or symbol
+watch remove <pool|symbol>
+watch list | watch clear
+pause [source] | resume [source]
+ready                           whether warm-up has passed
+help | quit";
+
+// Pools and symbols whose live updates are printed as they arrive
+type Watches = Arc<Mutex<BTreeSet<String>>>;
+
+// `repl [--api=URL] [--token=TOKEN]`: an interactive prompt over the REST API
+// of a running watcher. Defaults to the [api] port on localhost and the
+// configured control_token.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct ReplArgs {
+    #[arg(long, help = "Watcher API, http://127.0.0.1:<[api] port> by default")]
+    pub api: Option<String>,
+    #[arg(long, help = "Control token for pause/resume, the configured one by default")]
+    pub token: Option<String>,
+}
+
+pub struct Repl {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    watches: Watches,
+    // Live update stream behind `watch`, opened on the first watch
+    stream: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Repl {
+    pub fn new(config: &Config, args: &ReplArgs) -> Self {
+        let url = match &args.api {
+            Some(api) => api.trim_end_matches('/').to_string(),
+            None => format!("http://127.0.0.1:{}", config.api.listen.port()),
+        };
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            token: args.token.clone().or_else(|| config.api.control_token.clone()),
+            watches: Arc::default(),
+            stream: None,
+        }
+    }
+
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        // Fail early rather than on the first command
+        self.client
+            .get(format!("{}/ready", self.url))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("No watcher API at {}: {}", self.url, e))?;
+
+        // Results are the command's output, not log lines
+        println!("Connected to {}. Type `help` for commands.", self.url);
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            print!("> ");
+            std::io::stdout().flush()?;
+
+            let Some(line) = lines.next_line().await? else {
+                break;
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => continue,
+                ["quit" | "exit"] => break,
+                words => {
+                    if let Err(e) = self.execute(words).await {
+                        eprintln!("{}", display::render(format!("❌ {}", e)));
+                    }
+                }
+            }
+        }
+
+        if let Some(stream) = self.stream.take() {
+            stream.abort();
+        }
+        Ok(())
+    }
+
+    async fn execute(&mut self, words: &[&str]) -> anyhow::Result<()> {
+        match words {
+            ["help"] => println!("{}", HELP),
+            ["price", symbol] => {
+                let updates: Vec<PriceUpdate> = self.get("/prices").await?;
+                print_prices(updates.iter().filter(|update| update.symbol.eq_ignore_ascii_case(symbol)));
+            }
+            ["price", source, symbol] => {
+                let updates: Vec<PriceUpdate> = self.get(&format!("/prices/{}", source_path(source))).await?;
+                print_prices(updates.iter().filter(|update| update.symbol.eq_ignore_ascii_case(symbol)));
+            }
+            ["spread"] => print_spreads(&self.get::<Vec<Spread>>("/spread").await?),
+            ["spread", symbol] if symbol.contains('/') => {
+                print_spreads(&self.get::<Vec<Spread>>(&format!("/spread?symbol={}", symbol)).await?);
+            }
+            ["spread", first, second] => {
+                let updates: Vec<PriceUpdate> = self.get("/prices").await?;
+                print_venue_spreads(&updates, first, second);
+            }
+            ["quote", size, token] => self.quote(size, token, Side::Sell).await?,
+            ["quote", size, token, "sell"] => self.quote(size, token, Side::Sell).await?,
+            ["quote", size, token, "buy"] => self.quote(size, token, Side::Buy).await?,
+            ["watch", "add", target] => {
+                self.watches.lock().unwrap().insert(target.to_string());
+                if self.stream.as_ref().is_none_or(|stream| stream.is_finished()) {
+                    self.stream = Some(tokio::spawn(stream_watches(self.url.clone(), self.watches.clone())));
+                }
+                println!("Watching {}", target);
+            }
+            ["watch", "remove", target] => {
+                let removed = self.watches.lock().unwrap().remove(*target);
+                println!("{}", if removed { "Removed" } else { "Not watched" });
+            }
+            ["watch", "list"] => {
+                for target in self.watches.lock().unwrap().iter() {
+                    println!("{}", target);
+                }
+            }
+            ["watch", "clear"] => self.watches.lock().unwrap().clear(),
+            ["pause"] => self.control("/pause").await?,
+            ["pause", source] => self.control(&format!("/pause/{}", source_path(source))).await?,
+            ["resume"] => self.control("/resume").await?,
+            ["resume", source] => self.control(&format!("/resume/{}", source_path(source))).await?,
+            ["ready"] => {
+                let status = self.client.get(format!("{}/ready", self.url)).send().await?.status();
+                println!("{}", if status.is_success() { "Ready" } else { "Warming up" });
+            }
+            _ => return Err(anyhow::anyhow!("Unknown command, type `help` for the list")),
+        }
+        Ok(())
+    }
+
+    // `quote 100 sol` quotes SOL against a USD stable if one is watched,
+    // otherwise against the first quote token found
+    async fn quote(&self, size: &str, token: &str, side: Side) -> anyhow::Result<()> {
+        let size: f64 = size.parse().map_err(|_| anyhow::anyhow!("Invalid size: {}", size))?;
+        let symbol = if token.contains('/') {
+            token.to_uppercase()
+        } else {
+            let updates: Vec<PriceUpdate> = self.get("/prices").await?;
+            let mut symbols: Vec<&str> = updates
+                .iter()
+                .map(|update| update.symbol.as_str())
+                .filter(|symbol| symbol.split_once('/').is_some_and(|(base, _)| base.eq_ignore_ascii_case(token)))
+                .collect();
+            symbols.sort_by_key(|symbol| !symbol.split_once('/').is_some_and(|(_, quote)| USD_STABLES.contains(&quote)));
+            symbols.first().map(|symbol| symbol.to_string()).ok_or_else(|| anyhow::anyhow!("No watched pool trades {}", token))?
+        };
+
+        let side_name = match side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        };
+        let quote: Quote = self.get(&format!("/quote?symbol={}&side={}&size={}", symbol, side_name, size)).await?;
+        println!("{} {} {}", side_name, display::number(quote.size, 4), quote.symbol);
+        for venue in &quote.venues {
+            println!(
+                "  {:<14} {:>14}  slippage {:>8} bps  filled {}",
+                venue.source,
+                display::number(venue.execution_price, 6),
+                display::number(venue.slippage_bps, 1),
+                display::number(venue.filled, 4),
+            );
+        }
+        Ok(())
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let response = self.client.get(format!("{}{}", self.url, path)).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(anyhow::anyhow!("Nothing found"));
+        }
+        Ok(response.error_for_status()?.json().await?)
+    }
+
+    // Pause and resume need the API's control_token
+    async fn control(&self, path: &str) -> anyhow::Result<()> {
+        let token = self.token.as_deref().ok_or_else(|| anyhow::anyhow!("Needs --token or [api] control_token"))?;
+        let state: serde_json::Value = self
+            .client
+            .post(format!("{}{}", self.url, path))
+            .bearer_auth(token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        println!("{}", state);
+        Ok(())
+    }
+}
+
+// Sources are typed as one word: raydium_clmm for "Raydium CLMM"
+fn source_path(source: &str) -> String {
+    source.replace(['_', '-'], "%20")
+}
+
+fn print_prices<'a>(updates: impl Iterator<Item = &'a PriceUpdate>) {
+    let mut found = false;
+    for update in updates {
+        found = true;
+        println!(
+            "{:<14} {:<12} {:>14}  slot {}  {}",
+            update.source,
+            update.symbol,
+            display::number(update.price, 6),
+            update.slot,
+            update.pool,
+        );
+    }
+    if !found {
+        println!("No matching pools");
+    }
+}
+
+fn print_spreads(spreads: &[Spread]) {
+    for spread in spreads {
+        println!(
+            "{:<12} {:>8} bps  {} {} -> {} {}  ({} venues)",
+            spread.symbol,
+            display::number(spread.spread_bps, 1),
+            spread.low_source,
+            display::number(spread.low_price, 6),
+            spread.high_source,
+            display::number(spread.high_price, 6),
+            spread.venues,
+        );
+    }
+}
+
+// Every symbol both venues quote, with the second venue's premium over the first
+fn print_venue_spreads(updates: &[PriceUpdate], first: &str, second: &str) {
+    let matches = |update: &PriceUpdate, source: &str| update.price > 0.0 && update.source.replace(' ', "_").eq_ignore_ascii_case(source);
+    let mut found = false;
+    for low in updates.iter().filter(|update| matches(update, first)) {
+        for high in updates.iter().filter(|update| matches(update, second) && update.symbol == low.symbol) {
+            found = true;
+            println!(
+                "{:<12} {} {} vs {} {}: {} bps",
+                low.symbol,
+                low.source,
+                display::number(low.price, 6),
+                high.source,
+                display::number(high.price, 6),
+                display::signed((high.price - low.price) / low.price * 10_000.0, 1),
+            );
+        }
+    }
+    if !found {
+        println!("No symbol quoted on both {} and {}", first, second);
+    }
+}
+
+// Print updates for watched pools and symbols from the API's WebSocket stream
+async fn stream_watches(url: String, watches: Watches) {
+    let ws_url = format!("{}/ws", url.replacen("http", "ws", 1));
+    let mut socket = match tokio_tungstenite::connect_async(ws_url.as_str()).await {
+        Ok((socket, _)) => socket,
+        Err(e) => {
+            eprintln!("{}", display::render(format!("❌ Update stream failed: {}", e)));
+            return;
+        }
+    };
+
+    while let Some(Ok(message)) = socket.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(update) = serde_json::from_str::<PriceUpdate>(&text) else {
+            continue;
+        };
+        let watched = watches
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|target| target == &update.pool || target.eq_ignore_ascii_case(&update.symbol));
+        if watched {
+            println!(
+                "\n👀 {} {} {} (slot {})",
+                update.source,
+                update.symbol,
+                display::number(update.price, 6),
+                update.slot,
+            );
+        }
+    }
+    eprintln!("{}", display::render("⚠️ Update stream closed, `watch add` reconnects"));
+}
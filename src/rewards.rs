@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::RewardsConfig;
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+use crate::tvl::USD_STABLES;
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+// An APR moving less than this, relative to the last one published, isn't republished
+const MIN_CHANGE_RATIO: f64 = 0.01;
+
+// Reward tokens priced without any [rewards.tokens] entry
+const KNOWN_TOKENS: [(&str, &str); 7] = [
+    ("orcaEKTdK7LKz57vaAYr9QeNsVEPfiu6QeMU1kektZE", "ORCA"),
+    ("So11111111111111111111111111111111111111112", "SOL"),
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC"),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT"),
+    ("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", "mSOL"),
+    ("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", "JitoSOL"),
+    ("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", "BONK"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardEmission {
+    pub mint: String,
+    pub emissions_per_second: f64,  // In reward tokens
+}
+
+// The reward emissions of one pool, published by its monitor when they first
+// show up and whenever the pool's reward infos change. Only Orca whirlpools
+// carry them for now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardEmissions {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub rewards: Vec<RewardEmission>,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardApr {
+    pub mint: String,
+    pub token: Option<String>,      // Symbol the reward is priced by; None for unknown mints
+    pub emissions_per_second: f64,
+    pub usd_per_day: Option<f64>,
+    pub apr_percent: Option<f64>,
+}
+
+// Incentive APR of one pool: what its reward emissions pay a year relative to
+// its TVL, next to the fee APR its volume earns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncentiveApr {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub tvl_usd: Option<f64>,
+    pub rewards: Vec<RewardApr>,
+    pub reward_apr_percent: Option<f64>,  // Sum over the rewards that could be priced
+    pub fee_apr_percent: Option<f64>,     // None unless swaps are decoded
+    pub timestamp: u64,
+}
+
+struct PoolState {
+    emissions: RewardEmissions,
+    tvl_usd: Option<f64>,
+    fee_apr_percent: Option<f64>,
+    // Total APR last published, None until one was
+    published: Option<Option<f64>>,
+}
+
+// Turns reward emissions into APRs. TVL and volume come from the pool's own
+// price updates, and reward tokens are priced by the latest update of any
+// pool quoting them in a USD stable (e.g. ORCA/USDC for ORCA rewards).
+pub struct IncentiveTracker {
+    // mint -> symbol the reward token trades under
+    tokens: HashMap<String, String>,
+    // token symbol -> latest USD price
+    usd_prices: HashMap<String, f64>,
+    pools: HashMap<String, PoolState>,
+}
+
+impl IncentiveTracker {
+    pub fn new(config: &RewardsConfig) -> Self {
+        let mut tokens: HashMap<String, String> = KNOWN_TOKENS
+            .iter()
+            .map(|(mint, symbol)| (mint.to_string(), symbol.to_string()))
+            .collect();
+        tokens.extend(config.tokens.clone());
+        let usd_prices = USD_STABLES.iter().map(|stable| (stable.to_string(), 1.0)).collect();
+
+        Self { tokens, usd_prices, pools: HashMap::new() }
+    }
+
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+    ) {
+        loop {
+            let incentive = match rx.recv().await {
+                Ok(MarketEvent::Price(update)) => self.on_price_update(&update),
+                Ok(MarketEvent::Rewards(emissions)) => self.on_emissions(emissions),
+                Ok(_) => None,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "incentive tracker lagged, skipped events");
+                    None
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if let Some(incentive) = incentive {
+                let _ = tx.send(MarketEvent::Incentive(incentive));
+            }
+        }
+    }
+
+    fn on_emissions(&mut self, emissions: RewardEmissions) -> Option<IncentiveApr> {
+        let timestamp = emissions.timestamp;
+        let pool = emissions.pool.clone();
+        match self.pools.get_mut(&pool) {
+            Some(state) => {
+                state.emissions = emissions;
+                state.published = None;
+            }
+            None => {
+                self.pools.insert(pool.clone(), PoolState { emissions, tvl_usd: None, fee_apr_percent: None, published: None });
+            }
+        }
+        self.refresh(&pool, timestamp)
+    }
+
+    fn on_price_update(&mut self, update: &PriceUpdate) -> Option<IncentiveApr> {
+        if let Some((base, quote)) = update.symbol.split_once('/') {
+            if USD_STABLES.contains(&quote) && update.price > 0.0 {
+                self.usd_prices.insert(base.to_string(), update.price);
+            }
+        }
+
+        let state = self.pools.get_mut(&update.pool)?;
+        state.tvl_usd = update.tvl_usd;
+        state.fee_apr_percent = match (update.volume_24h, update.tvl_usd) {
+            (Some(volume), Some(tvl_usd)) if tvl_usd > 0.0 => Some(volume * update.fee_bps / 10_000.0 * 365.0 / tvl_usd * 100.0),
+            _ => None,
+        };
+        self.refresh(&update.pool, update.timestamp)
+    }
+
+    // The pool's APR, if it's new or moved enough since it was last published
+    fn refresh(&mut self, pool: &str, now: u64) -> Option<IncentiveApr> {
+        let state = self.pools.get(pool)?;
+        let tvl_usd = state.tvl_usd.filter(|tvl_usd| *tvl_usd > 0.0);
+        let rewards: Vec<RewardApr> = state.emissions.rewards
+            .iter()
+            .map(|reward| {
+                let token = self.tokens.get(&reward.mint).cloned();
+                let usd_per_day = token
+                    .as_ref()
+                    .and_then(|token| self.usd_prices.get(token))
+                    .map(|price| reward.emissions_per_second * 86_400.0 * price);
+                RewardApr {
+                    mint: reward.mint.clone(),
+                    token,
+                    emissions_per_second: reward.emissions_per_second,
+                    usd_per_day,
+                    apr_percent: usd_per_day.zip(tvl_usd).map(|(usd_per_day, tvl_usd)| usd_per_day / 86_400.0 * SECONDS_PER_YEAR / tvl_usd * 100.0),
+                }
+            })
+            .collect();
+        let priced: Vec<f64> = rewards.iter().filter_map(|reward| reward.apr_percent).collect();
+        let reward_apr_percent = if priced.is_empty() { None } else { Some(priced.iter().sum()) };
+
+        let changed = match state.published {
+            None => true,
+            Some(None) => reward_apr_percent.is_some(),
+            Some(Some(published)) => match reward_apr_percent {
+                Some(apr) => (apr - published).abs() > published.abs() * MIN_CHANGE_RATIO,
+                None => false,
+            },
+        };
+        if !changed {
+            return None;
+        }
+
+        let incentive = IncentiveApr {
+            symbol: state.emissions.symbol.clone(),
+            source: state.emissions.source.clone(),
+            pool: pool.to_string(),
+            tvl_usd: state.tvl_usd,
+            rewards,
+            reward_apr_percent,
+            fee_apr_percent: state.fee_apr_percent,
+            timestamp: now,
+        };
+        self.pools.get_mut(pool)?.published = Some(reward_apr_percent);
+        Some(incentive)
+    }
+}
@@ -0,0 +1,222 @@
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::aggregate;
+use crate::config::RoutingConfig;
+use crate::depth::DepthUpdate;
+use crate::events::MarketEvent;
+use crate::quote::{self, VenueCurve};
+use crate::raydium::PriceUpdate;
+use crate::snapshot::ChangeReason;
+
+// Latest route score per pool address, read by the REST API
+pub type RouteScores = Arc<RwLock<HashMap<String, RouteScore>>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteScore {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub fee_bps: f64,
+    pub depth_1pct: f64,          // Quote tokens needed to move the price up 1%
+    pub slippage_bps: f64,        // Rolling impact of a reference-size trade
+    pub contention: f64,          // Rolling share of ticks with pool activity (0..1)
+    pub expected_cost_bps: f64,   // fee + slippage + contention penalty
+    pub score: f64,               // 0..100, higher is better
+    pub timestamp: u64,
+}
+
+// Score every venue continuously from the shared price stream.
+//
+// Depth near mid comes from the reserves only for constant-product pools. Orca
+// and Meteora take it from the tick/bin depth the [depth] task publishes, and
+// stay unscored until it has; venues with neither are not scored at all.
+// Contention is approximated by how often the pool's reserves change between
+// polls: busy pools mean more transactions competing for the same accounts.
+pub async fn run(config: RoutingConfig, mut rx: broadcast::Receiver<MarketEvent>, scores: RouteScores) {
+    // Latest depth_1pct of each concentrated-liquidity pool, by address
+    let mut depths: HashMap<String, f64> = HashMap::new();
+    loop {
+        match rx.recv().await {
+            Ok(MarketEvent::Depth(update)) => {
+                if let Some(depth) = band_depth(&update) {
+                    depths.insert(update.pool.clone(), depth);
+                }
+            }
+            Ok(MarketEvent::Price(update)) if !aggregate::is_aggregate(&update) => {
+                let depth_1pct = if quote::is_constant_product(&update.source) {
+                    reserve_depth(&update)
+                } else {
+                    match depths.get(&update.pool) {
+                        Some(depth) => *depth,
+                        None => continue,
+                    }
+                };
+                let mut scores = scores.write().await;
+                let previous = scores.get(&update.pool);
+                let score = score_update(&config, previous, &update, depth_1pct);
+                scores.insert(update.pool.clone(), score);
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+// x * y = k: raising the price by 1% takes y * (sqrt(1.01) - 1) quote tokens
+fn reserve_depth(update: &PriceUpdate) -> f64 {
+    let quote_reserve = update.quote_reserve as f64 / 10_f64.powi(update.quote_decimals as i32);
+    quote_reserve * (1.01_f64.sqrt() - 1.0)
+}
+
+// Ask depth of the band closest to 1%, scaled to 1%: liquidity that close to
+// mid is near enough to even
+fn band_depth(update: &DepthUpdate) -> Option<f64> {
+    let band = update.bands.iter().min_by(|a, b| (a.percent - 1.0).abs().total_cmp(&(b.percent - 1.0).abs()))?;
+    Some(band.ask_depth / band.percent).filter(|depth| depth.is_finite())
+}
+
+fn score_update(config: &RoutingConfig, previous: Option<&RouteScore>, update: &PriceUpdate, depth_1pct: f64) -> RouteScore {
+    // Spending depth_1pct moves the marginal price 1% and the average price
+    // about half that, so a reference-size trade costs size / depth * 50 bps
+    let impact_bps = if depth_1pct > 0.0 {
+        (config.reference_size / depth_1pct * 50.0).min(10_000.0)
+    } else {
+        10_000.0
+    };
+    let active = match update.change_reason {
+        ChangeReason::Initial | ChangeReason::Unchanged => 0.0,
+        _ => 1.0,
+    };
+
+    let alpha = config.ema_alpha;
+    let (slippage_bps, contention) = match previous {
+        Some(previous) => (
+            previous.slippage_bps + alpha * (impact_bps - previous.slippage_bps),
+            previous.contention + alpha * (active - previous.contention),
+        ),
+        None => (impact_bps, active),
+    };
+
+    let expected_cost_bps = update.fee_bps + slippage_bps + contention * config.contention_penalty_bps;
+
+    RouteScore {
+        symbol: update.symbol.clone(),
+        source: update.source.clone(),
+        pool: update.pool.clone(),
+        fee_bps: update.fee_bps,
+        depth_1pct,
+        slippage_bps,
+        contention,
+        expected_cost_bps,
+        score: (100.0 - expected_cost_bps).clamp(0.0, 100.0),
+        timestamp: update.timestamp,
+    }
+}
+
+// Number of equal slices an order is cut into when splitting across venues
+const SPLIT_CHUNKS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Buy,   // Buy `size` base tokens with quote
+    Sell,  // Sell `size` base tokens for quote
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteLeg {
+    pub source: String,
+    pub pool: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub average_price: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteSplit {
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+    pub legs: Vec<RouteLeg>,
+    pub quote_amount: f64,    // Total quote received (sell) or spent (buy)
+    pub blended_price: f64,
+}
+
+// Split an order across venues by handing each slice to the venue with the best
+// marginal price on its own curve (see quote::load_curve). Every curve gets
+// costlier as it fills, so the greedy fill converges on the optimal split as
+// the slices shrink. Venues without a swap model are left out, and those whose
+// curve fails to load are logged and skipped.
+pub async fn split_order(rpc_client: &RpcClient, venues: Vec<PriceUpdate>, symbol: &str, side: Side, size: f64) -> Option<RouteSplit> {
+    let venues: Vec<PriceUpdate> = venues
+        .into_iter()
+        .filter(|update| update.symbol.eq_ignore_ascii_case(symbol) && quote::has_swap_model(&update.source))
+        .collect();
+    if venues.is_empty() || size <= 0.0 {
+        return None;
+    }
+
+    let curves = join_all(venues.iter().map(|update| quote::load_curve(rpc_client, update, side))).await;
+    let curves: Vec<VenueCurve> = venues
+        .iter()
+        .zip(curves)
+        .filter_map(|(update, curve)| {
+            curve
+                .map_err(|e| tracing::error!(source = %update.source, symbol = %update.symbol, error = %e, "failed to load swap curve"))
+                .ok()
+        })
+        .collect();
+
+    let chunk = size / SPLIT_CHUNKS as f64;
+    let mut filled = vec![0.0; curves.len()];
+    let mut quote_amounts = vec![0.0; curves.len()];
+
+    for _ in 0..SPLIT_CHUNKS {
+        // Quote for the next slice on each venue that can still fill it
+        let (best, quote) = curves
+            .iter()
+            .enumerate()
+            .filter_map(|(i, curve)| {
+                let before = curve.fill_all(filled[i])?;
+                let after = curve.fill_all(filled[i] + chunk)?;
+                Some((i, after - before))
+            })
+            .reduce(|best, candidate| match side {
+                Side::Sell if candidate.1 > best.1 => candidate,
+                Side::Buy if candidate.1 < best.1 => candidate,
+                _ => best,
+            })?;  // Not enough liquidity across all venues
+
+        filled[best] += chunk;
+        quote_amounts[best] += quote;
+    }
+
+    let legs: Vec<RouteLeg> = curves
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| filled[*i] > 0.0)
+        .map(|(i, curve)| RouteLeg {
+            source: curve.source.clone(),
+            pool: curve.pool.clone(),
+            base_amount: filled[i],
+            quote_amount: quote_amounts[i],
+            average_price: quote_amounts[i] / filled[i],
+        })
+        .collect();
+    let quote_amount: f64 = quote_amounts.iter().sum();
+
+    Some(RouteSplit {
+        symbol: symbol.to_string(),
+        side,
+        size,
+        legs,
+        quote_amount,
+        blended_price: quote_amount / size,
+    })
+}
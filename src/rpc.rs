@@ -0,0 +1,336 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde_json::Value;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_client::rpc_response::Response;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::RpcConfig;
+use crate::event_log::{self, Severity};
+use crate::latency;
+use crate::metrics;
+use crate::ratelimit::RateLimiter;
+
+// JSON-RPC "node is behind" error; another endpoint may well be in sync
+const NODE_UNHEALTHY: i64 = -32005;
+// The node hasn't reached the request's minContextSlot yet. Endpoints routinely
+// trail each other by a slot or two, so it's only benched briefly.
+const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+const MIN_CONTEXT_SLOT_BENCH: Duration = Duration::from_secs(2);
+// Code of the error a request fails with when every endpoint answered 429. No
+// node sends it; it lets callers tell rate limits from other failures.
+pub const RATE_LIMITED: i64 = 429;
+// getMultipleAccounts takes at most this many addresses per call
+const MAX_ACCOUNTS_PER_CALL: usize = 100;
+// Chunks of one large read in flight at once; the rate limiter still paces them
+const CHUNK_CONCURRENCY: usize = 8;
+
+struct Endpoint {
+    url: String,
+    // Host only, for logs and metrics; the full URL usually embeds an API key
+    label: String,
+    cooling_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_cooling(&self, now: Instant) -> bool {
+        self.cooling_until.lock().unwrap().is_some_and(|until| until > now)
+    }
+
+    fn bench(&self, duration: Duration) {
+        *self.cooling_until.lock().unwrap() = Some(Instant::now() + duration);
+    }
+}
+
+// Result of sending a request to one endpoint
+enum Attempt {
+    Done(ClientResult<Value>),
+    // Worth retrying elsewhere; bench this endpoint for the given time
+    FailOver(String, Duration),
+    // An HTTP 429, failed over like the others
+    RateLimited(String, Duration),
+}
+
+// Set of JSON-RPC endpoints behind a single RpcSender. Each request goes to the
+// next endpoint in round-robin order; a rate limit, timeout or server error
+// moves it on to the following one and benches the failing endpoint, for as
+// long as its Retry-After asks or the cooldown otherwise. Cheap to clone, and
+// every clone shares the rotation.
+#[derive(Clone)]
+pub struct RpcProvider {
+    http: reqwest::Client,
+    endpoints: Arc<Vec<Endpoint>>,
+    next: Arc<AtomicUsize>,
+    request_id: Arc<AtomicU64>,
+    stats: Arc<Mutex<RpcTransportStats>>,
+    cooldown: Duration,
+    // None when requests_per_second = 0
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl RpcProvider {
+    pub fn new(config: &RpcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .expect("Failed to build RPC HTTP client");
+        let endpoints = config
+            .endpoints
+            .iter()
+            .map(|url| Endpoint {
+                url: url.clone(),
+                label: host(url).to_string(),
+                cooling_until: Mutex::new(None),
+            })
+            .collect();
+
+        Self {
+            http,
+            endpoints: Arc::new(endpoints),
+            next: Arc::new(AtomicUsize::new(0)),
+            request_id: Arc::new(AtomicU64::new(1)),
+            stats: Arc::new(Mutex::new(RpcTransportStats::default())),
+            cooldown: Duration::from_secs(config.cooldown_secs),
+            limiter: (config.requests_per_second > 0.0)
+                .then(|| Arc::new(RateLimiter::new(config.requests_per_second, config.burst))),
+        }
+    }
+    pub fn client(&self, commitment: CommitmentConfig) -> solana_client::nonblocking::rpc_client::RpcClient {
+        solana_client::nonblocking::rpc_client::RpcClient::new_sender(
+            self.clone(),
+            RpcClientConfig::with_commitment(commitment),
+        )
+    }
+
+    // Fails only if none of the endpoints answers
+    pub async fn probe(&self) -> anyhow::Result<u64> {
+        Ok(self.client(CommitmentConfig::processed()).get_slot().await?)
+    }
+
+    // While every endpoint is benched, how long until the first comes back.
+    // Callers backing off use it to honor the providers' Retry-After.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.cooling_until.lock().unwrap().map(|until| until.saturating_duration_since(now)))
+            .collect::<Option<Vec<Duration>>>()?
+            .into_iter()
+            .min()
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    // Websocket URL for subscriptions, rotating like requests do so that a
+    // resubscribe after an error lands on the next endpoint
+    pub fn ws_url(&self) -> String {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.endpoints.len();
+        let url = &self.endpoints[index].url;
+        match url.split_once("://") {
+            Some(("http", rest)) => format!("ws://{}", rest),
+            Some((_, rest)) => format!("wss://{}", rest),
+            None => url.clone(),
+        }
+    }
+
+    // Endpoints in the order this request should try them: round-robin start,
+    // benched endpoints last
+    fn rotation(&self) -> Vec<&Endpoint> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let (mut ready, cooling): (Vec<&Endpoint>, Vec<&Endpoint>) = (0..self.endpoints.len())
+            .map(|offset| &self.endpoints[(start + offset) % self.endpoints.len()])
+            .partition(|endpoint| !endpoint.is_cooling(now));
+        ready.extend(cooling);
+        ready
+    }
+
+    async fn send_with_failover(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let rotation = self.rotation();
+        let mut last_error = String::new();
+        let mut rate_limited = true;
+
+        for (attempt, endpoint) in rotation.iter().enumerate() {
+            let (error, bench) = match self.attempt(endpoint, request, params.clone()).await {
+                Attempt::Done(result) => return result,
+                Attempt::RateLimited(error, bench) => (error, bench),
+                Attempt::FailOver(error, bench) => {
+                    rate_limited = false;
+                    (error, bench)
+                }
+            };
+            endpoint.bench(bench);
+            metrics::inc_rpc_failover(&endpoint.label);
+            event_log::emit("rpc.failover", Severity::Warning, serde_json::json!({
+                "endpoint": endpoint.label,
+                "error": error,
+                "benched_ms": bench.as_millis() as u64,
+            }));
+            if attempt + 1 < rotation.len() {
+                tracing::warn!(label = %endpoint.label, error = %error, "RPC endpoint failed, failing over");
+            }
+            last_error = format!("{}: {}", endpoint.label, error);
+        }
+
+        event_log::emit("rpc.exhausted", Severity::Error, serde_json::json!({ "error": last_error }));
+        if rate_limited {
+            return Err(RpcError::RpcResponseError {
+                code: RATE_LIMITED,
+                message: format!("All RPC endpoints rate limited, last {}", last_error),
+                data: RpcResponseErrorData::Empty,
+            }
+            .into());
+        }
+        Err(ClientError::from(ClientErrorKind::Custom(format!(
+            "All RPC endpoints failed, last {}",
+            last_error
+        ))))
+    }
+
+    async fn attempt(&self, endpoint: &Endpoint, request: RpcRequest, params: Value) -> Attempt {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire().await;
+        }
+        let id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+        let response = self.http.post(&endpoint.url).json(&request.build_request_json(id, params)).send().await;
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.request_count += 1;
+            stats.elapsed_time += started.elapsed();
+        }
+        latency::add_rpc_time(started.elapsed());
+
+        // Timeouts and unreachable hosts
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => return Attempt::FailOver(e.to_string(), self.cooldown),
+        };
+
+        // Rate limits, server errors, and keys the provider rejects
+        let status = response.status();
+        if !status.is_success() {
+            let bench = retry_after(&response).unwrap_or(self.cooldown);
+            if status.as_u16() == 429 {
+                self.stats.lock().unwrap().rate_limited_time += bench;
+                return Attempt::RateLimited(format!("HTTP {}", status), bench);
+            }
+            return Attempt::FailOver(format!("HTTP {}", status), bench);
+        }
+
+        let mut json: Value = match response.json().await {
+            Ok(json) => json,
+            Err(e) => return Attempt::FailOver(format!("invalid response: {}", e), self.cooldown),
+        };
+        if let Some(error) = json.get("error") {
+            let code = error["code"].as_i64().unwrap_or_default();
+            let message = error["message"].as_str().unwrap_or_default().to_string();
+            if code == MIN_CONTEXT_SLOT_NOT_REACHED {
+                return Attempt::FailOver(message, MIN_CONTEXT_SLOT_BENCH);
+            }
+            if code == NODE_UNHEALTHY {
+                return Attempt::FailOver(message, self.cooldown);
+            }
+            // The request itself was rejected; another endpoint won't do better
+            return Attempt::Done(Err(RpcError::RpcResponseError {
+                code,
+                message,
+                data: RpcResponseErrorData::Empty,
+            }
+            .into()));
+        }
+
+        Attempt::Done(Ok(json["result"].take()))
+    }
+}
+
+#[async_trait]
+impl RpcSender for RpcProvider {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        self.send_with_failover(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn url(&self) -> String {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.label.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+// getMultipleAccounts pinned at or after `min_context_slot`, so a node that fell
+// behind fails over instead of answering with state older than what was
+// already read. 0 leaves the read unpinned, for the first one.
+//
+// More addresses than one call takes are split into chunks fetched
+// CHUNK_CONCURRENCY at a time. The accounts come back in the order asked for,
+// under the oldest slot any chunk was read at.
+pub async fn get_multiple_accounts_since(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+    commitment: CommitmentConfig,
+    min_context_slot: u64,
+) -> ClientResult<Response<Vec<Option<Account>>>> {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64Zstd),
+        commitment: Some(commitment),
+        min_context_slot: (min_context_slot > 0).then_some(min_context_slot),
+        ..Default::default()
+    };
+    if addresses.len() <= MAX_ACCOUNTS_PER_CALL {
+        return rpc_client.get_multiple_accounts_with_config(addresses, config).await;
+    }
+
+    let responses: Vec<Response<Vec<Option<Account>>>> = stream::iter(addresses.chunks(MAX_ACCOUNTS_PER_CALL))
+        .map(|chunk| rpc_client.get_multiple_accounts_with_config(chunk, config.clone()))
+        .buffered(CHUNK_CONCURRENCY)
+        .try_collect()
+        .await?;
+    let mut context = responses[0].context.clone();
+    context.slot = responses.iter().map(|response| response.context.slot).min().unwrap_or(context.slot);
+    Ok(Response {
+        context,
+        value: responses.into_iter().flat_map(|response| response.value).collect(),
+    })
+}
+
+// Plain getMultipleAccounts at the client's commitment, for metadata such as
+// mints and configs that's resolved once. Chunked like get_multiple_accounts_since,
+// so resolving hundreds of new pools at startup takes a few round trips in parallel.
+pub async fn get_multiple_accounts(rpc_client: &RpcClient, addresses: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+    if addresses.len() <= MAX_ACCOUNTS_PER_CALL {
+        return rpc_client.get_multiple_accounts(addresses).await;
+    }
+
+    let chunks: Vec<Vec<Option<Account>>> = stream::iter(addresses.chunks(MAX_ACCOUNTS_PER_CALL))
+        .map(|chunk| rpc_client.get_multiple_accounts(chunk))
+        .buffered(CHUNK_CONCURRENCY)
+        .try_collect()
+        .await?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+// Retry-After in seconds; the HTTP-date form is rare enough from RPC providers to ignore
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?;
+    value.to_str().ok()?.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+// Host part of an endpoint URL, safe to log: API keys live in the path or query
+pub fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    rest.split(['/', '?']).next().unwrap_or(rest)
+}
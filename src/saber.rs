@@ -0,0 +1,519 @@
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use std::sync::Arc;
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+use crate::dedup;
+use crate::error::{Result, WatcherError};
+use crate::events::MarketEvent;
+use crate::backoff::Backoff;
+use crate::health::{self, HealthEvent};
+use crate::metrics;
+use crate::monitor::{Monitor, MonitorContext};
+use crate::pause::Pause;
+use crate::rpc::{self, RpcProvider};
+use crate::sanity;
+use crate::schedule::PollSchedule;
+use crate::stats::RollingStats;
+use crate::raydium::PriceUpdate;
+use crate::snapshot::{classify_change, ReserveSnapshot};
+use crate::supervisor::Heartbeat;
+use crate::token;
+use crate::tvl;
+
+const SOURCE: &str = "Saber";
+
+// SwapInfo account of the stable-swap program (packed, no discriminator):
+// is_initialized (1) | is_paused (1) | nonce (1) | initial_amp_factor (8)
+//   | target_amp_factor (8) | start_ramp_ts (8) | stop_ramp_ts (8)
+//   | future_admin_deadline (8) | future_admin_key (32) | admin_key (32)
+//   | token_a (reserves 32 | mint 32 | admin_fees 32 | index 1)
+//   | token_b (same) | pool_mint (32)
+//   | fees (admin trade, admin withdraw, trade, withdraw; numerator and denominator u64 each)
+const IS_INITIALIZED_OFFSET: usize = 0;
+const IS_PAUSED_OFFSET: usize = 1;
+const INITIAL_AMP_OFFSET: usize = 3;
+const TARGET_AMP_OFFSET: usize = 11;
+const START_RAMP_OFFSET: usize = 19;
+const STOP_RAMP_OFFSET: usize = 27;
+const TOKEN_A_RESERVES_OFFSET: usize = 107;
+const TOKEN_A_MINT_OFFSET: usize = 107 + 32;
+const TOKEN_B_RESERVES_OFFSET: usize = 204;
+const TOKEN_B_MINT_OFFSET: usize = 204 + 32;
+const POOL_MINT_OFFSET: usize = 301;
+const TRADE_FEE_NUMERATOR_OFFSET: usize = 333 + 32;
+const TRADE_FEE_DENOMINATOR_OFFSET: usize = 333 + 40;
+const SWAP_INFO_LEN: usize = 395;
+
+// Newton's method on D converges in a handful of rounds; the program stops at 256
+const MAX_ITERATIONS: usize = 256;
+
+// Accounts a pool is made of, fixed at its creation
+#[derive(Debug, Clone, Copy)]
+pub struct PoolHeader {
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_reserves: Pubkey,
+    pub token_b_reserves: Pubkey,
+    pub pool_mint: Pubkey,
+}
+
+impl PoolHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < SWAP_INFO_LEN {
+            return Err(WatcherError::DecodeError(format!("Invalid Saber swap data size: {} bytes", data.len())));
+        }
+        if data[IS_INITIALIZED_OFFSET] == 0 {
+            return Err(WatcherError::DecodeError("Saber swap is not initialized".to_string()));
+        }
+
+        Ok(Self {
+            token_a_mint: read_pubkey(data, TOKEN_A_MINT_OFFSET),
+            token_b_mint: read_pubkey(data, TOKEN_B_MINT_OFFSET),
+            token_a_reserves: read_pubkey(data, TOKEN_A_RESERVES_OFFSET),
+            token_b_reserves: read_pubkey(data, TOKEN_B_RESERVES_OFFSET),
+            pool_mint: read_pubkey(data, POOL_MINT_OFFSET),
+        })
+    }
+}
+
+// Parameters of the curve that can change after creation: the admin ramps the
+// amplification coefficient and can pause swaps or change fees
+struct SwapState {
+    amp_factor: f64,
+    fee_bps: f64,
+    paused: bool,
+}
+
+impl SwapState {
+    fn parse(data: &[u8], now: i64) -> Option<Self> {
+        if data.len() < SWAP_INFO_LEN {
+            return None;
+        }
+        let (numerator, denominator) = (read_u64(data, TRADE_FEE_NUMERATOR_OFFSET), read_u64(data, TRADE_FEE_DENOMINATOR_OFFSET));
+        let fee_bps = if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 * 10_000.0 };
+
+        Some(Self {
+            amp_factor: amp_factor(
+                read_u64(data, INITIAL_AMP_OFFSET),
+                read_u64(data, TARGET_AMP_OFFSET),
+                read_i64(data, START_RAMP_OFFSET),
+                read_i64(data, STOP_RAMP_OFFSET),
+                now,
+            ),
+            fee_bps,
+            paused: data[IS_PAUSED_OFFSET] != 0,
+        })
+    }
+}
+
+// The amplification coefficient moves linearly from initial to target while a
+// ramp is in progress, as the program interpolates it
+fn amp_factor(initial: u64, target: u64, start_ramp_ts: i64, stop_ramp_ts: i64, now: i64) -> f64 {
+    if now >= stop_ramp_ts || stop_ramp_ts <= start_ramp_ts {
+        return target as f64;
+    }
+    let progress = (now - start_ramp_ts).max(0) as f64 / (stop_ramp_ts - start_ramp_ts) as f64;
+    initial as f64 + (target as f64 - initial as f64) * progress
+}
+
+// StableSwap invariant for two coins, with leverage Ann = A·n:
+//   Ann·(x + y) + D = Ann·D + D³ / (4·x·y)
+// solved for D by Newton's method from D = x + y
+fn compute_d(amp_factor: f64, x: f64, y: f64) -> f64 {
+    let sum = x + y;
+    if sum == 0.0 {
+        return 0.0;
+    }
+    let leverage = amp_factor * 2.0;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = d * d / (2.0 * x) * d / (2.0 * y);
+        let d_previous = d;
+        d = (leverage * sum + 2.0 * d_p) * d / ((leverage - 1.0) * d + 3.0 * d_p);
+        if (d - d_previous).abs() <= d * 1e-12 {
+            break;
+        }
+    }
+    d
+}
+
+// Marginal price of x in y on the invariant, -dy/dx = ∂F/∂x / ∂F/∂y. Balanced
+// pools price at 1 whatever the amplification; the further the balances drift
+// apart, the more the price bends, and the less the higher A is.
+fn marginal_price(amp_factor: f64, x: f64, y: f64) -> f64 {
+    let leverage = amp_factor * 2.0;
+    let d = compute_d(amp_factor, x, y);
+    let d3 = d * d * d;
+    (leverage + d3 / (4.0 * x * x * y)) / (leverage + d3 / (4.0 * x * y * y))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+// A pool's accounts and its mints' decimals, resolved once
+#[derive(Debug, Clone, Copy)]
+struct ResolvedPool {
+    header: PoolHeader,
+    base_decimals: u8,
+    quote_decimals: u8,
+}
+
+// Decoded state of one pool for a single tick
+struct PoolData {
+    pool: ResolvedPool,
+    base_reserve: u64,
+    quote_reserve: u64,
+    lp_supply: u64,
+    amp_factor: f64,
+    fee_bps: f64,
+}
+
+pub struct SaberMonitor {
+    rpc_client: RpcClient,
+    pools: Vec<(Pubkey, PoolConfig)>,
+    resolved: HashMap<Pubkey, ResolvedPool>,
+    last_snapshot: HashMap<Pubkey, ReserveSnapshot>,
+    rpc: RpcProvider,
+    backoff: Backoff,
+    // Highest slot read so far; later reads are pinned at or after it
+    last_slot: u64,
+    // Which pools each poll reads, per their active hours
+    schedule: PollSchedule,
+    // Pools dropped during the current fetch, reported once it is done
+    dropped: Vec<HealthEvent>,
+}
+
+impl SaberMonitor {
+    pub fn new(pools: Vec<PoolConfig>, schedule: &ScheduleConfig, rpc: &RpcProvider) -> Self {
+        let rpc_client = rpc.client(CommitmentConfig::confirmed());
+
+        let pools: Vec<(Pubkey, PoolConfig)> = pools
+            .into_iter()
+            .map(|pool| (pool.pubkey().expect("Invalid pool address"), pool))
+            .collect();
+        let schedule = PollSchedule::new(SOURCE, &pools, schedule);
+
+        Self {
+            rpc_client,
+            pools,
+            resolved: HashMap::new(),
+            last_snapshot: HashMap::new(),
+            rpc: rpc.clone(),
+            backoff: Backoff::new(),
+            last_slot: 0,
+            schedule,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub async fn start_monitoring(
+        &mut self,
+        tx: Arc<broadcast::Sender<MarketEvent>>,
+        health_tx: Arc<broadcast::Sender<HealthEvent>>,
+        heartbeat: &Heartbeat,
+        pause: &Pause,
+        stats: &RollingStats,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        // Resume the 2s cadence after a backoff instead of bursting missed ticks
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(source = SOURCE, pools = self.pools.len(), "starting monitoring");
+
+        loop {
+            heartbeat.beat();
+            shutdown.run_until_cancelled(pause.wait_resumed(SOURCE, heartbeat)).await;
+            shutdown.run_until_cancelled(interval.tick()).await;
+
+            // Stop between fetches, never in the middle of one
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+
+            let fetch_started = std::time::Instant::now();
+            let result = self.fetch_pool_data().await;
+            metrics::observe_fetch(SOURCE, fetch_started);
+
+            // Dropped pools are gone for good; once none are left there's nothing to watch
+            if !self.dropped.is_empty() {
+                for event in self.dropped.drain(..) {
+                    let _ = health_tx.send(event);
+                }
+                if self.pools.is_empty() {
+                    tracing::error!(source = SOURCE, "no pools left to watch");
+                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok((slot, pools)) => {
+                    if self.backoff.reset() {
+                        tracing::info!(source = SOURCE, "fetches recovered");
+                        let _ = health_tx.send(HealthEvent::recovered(SOURCE));
+                    }
+                    // No tip lookup while every pool is parked between keep-alives
+                    let slot_lag = if pools.is_empty() {
+                        0
+                    } else {
+                        self.rpc_client
+                            .get_slot_with_commitment(CommitmentConfig::processed())
+                            .await
+                            .unwrap_or(slot)
+                            .saturating_sub(slot)
+                    };
+                    for (address, symbol, data) in pools {
+                        // The program runs the invariant on raw amounts, so the
+                        // price comes out in atoms and is scaled to UI units after
+                        let raw_price = marginal_price(data.amp_factor, data.base_reserve as f64, data.quote_reserve as f64);
+                        let current_price = raw_price * 10_f64.powi(data.pool.base_decimals as i32 - data.pool.quote_decimals as i32);
+                        let snapshot = ReserveSnapshot {
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            price: current_price,
+                        };
+                        let change_percent = if let Some(cached) = self.last_snapshot.get(&address) {
+                            ((current_price - cached.price) / cached.price) * 100.0
+                        } else {
+                            0.0
+                        };
+
+                        let tvl_usd = tvl::pool_tvl_usd(
+                            &symbol,
+                            data.base_reserve,
+                            data.quote_reserve,
+                            data.pool.base_decimals,
+                            data.pool.quote_decimals,
+                            current_price,
+                        );
+
+                        if sanity::reject(&tx, SOURCE, &symbol, &address.to_string(), current_price, slot) {
+                            continue;
+                        }
+                        let rolling = stats.record(&address.to_string(), current_price);
+                        let update = PriceUpdate {
+                            symbol,
+                            source: SOURCE.to_string(),
+                            pool: address.to_string(),
+                            price: current_price,
+                            change_percent,
+                            timestamp: unix_now(),
+                            base_reserve: data.base_reserve,
+                            quote_reserve: data.quote_reserve,
+                            base_decimals: data.pool.base_decimals,
+                            quote_decimals: data.pool.quote_decimals,
+                            change_reason: classify_change(self.last_snapshot.get(&address), &snapshot),
+                            lp_supply: Some(data.lp_supply),
+                            fee_bps: data.fee_bps,
+                            slot,
+                            slot_lag,
+                            tvl_usd,
+                            high_24h: rolling.high,
+                            low_24h: rolling.low,
+                            volume_24h: rolling.volume,
+                            bid: None,
+                            ask: None,
+                            active_index: None,
+                            microprice: None,
+                            imbalance: None,
+                            change_1m: rolling.change_1m,
+                            change_5m: rolling.change_5m,
+                            change_24h: rolling.change_24h,
+                            unchanged: false,
+                        };
+
+                        let _ = dedup::publish(&tx, update);
+                        self.last_snapshot.insert(address, snapshot);
+                    }
+                }
+                Err(e) => {
+                    metrics::inc_rpc_error(SOURCE);
+                    let delay = self.backoff.next_delay(self.rpc.retry_after());
+                    tracing::warn!(source = SOURCE, failures = self.backoff.failures(), retry_in_ms = delay.as_millis() as u64, error = %e, "fetch error, retrying");
+                    let _ = health_tx.send(HealthEvent::degraded(SOURCE, &e, self.backoff.failures(), delay));
+                    shutdown.run_until_cancelled(tokio::time::sleep(delay)).await;
+                }
+            }
+        }
+    }
+
+    // Read every due swap with its two reserve accounts and LP mint in one
+    // getMultipleAccounts call
+    async fn fetch_pool_data(&mut self) -> Result<(u64, Vec<(Pubkey, String, PoolData)>)> {
+        // Parked pools are only read once per keep-alive
+        let due: Vec<(Pubkey, String)> = self
+            .schedule
+            .due(&self.pools)
+            .into_iter()
+            .map(|(address, pool)| (*address, pool.symbol.clone()))
+            .collect();
+        if due.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        self.resolve_pools(&due).await?;
+
+        let due: Vec<(Pubkey, String, ResolvedPool)> = due
+            .into_iter()
+            .filter_map(|(address, symbol)| self.resolved.get(&address).map(|pool| (address, symbol, *pool)))
+            .collect();
+        let accounts: Vec<Pubkey> = due
+            .iter()
+            .flat_map(|(address, _, pool)| [*address, pool.header.token_a_reserves, pool.header.token_b_reserves, pool.header.pool_mint])
+            .collect();
+        let response = rpc::get_multiple_accounts_since(&self.rpc_client, &accounts, CommitmentConfig::confirmed(), self.last_slot).await?;
+        let slot = response.context.slot;
+        self.last_slot = self.last_slot.max(slot);
+
+        let now = unix_now() as i64;
+
+        let mut results = Vec::with_capacity(due.len());
+        for ((address, symbol, pool), accounts) in due.into_iter().zip(response.value.chunks(4)) {
+            let [swap, token_a_reserves, token_b_reserves, pool_mint] = accounts else {
+                continue;
+            };
+            let Some(state) = swap.as_ref().and_then(|account| SwapState::parse(&account.data, now)) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read swap");
+                continue;
+            };
+            if state.paused {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "swap is paused, skipping");
+                continue;
+            }
+
+            let amount = |account: &Option<Account>| account.as_ref().and_then(|account| token::token_account_amount(&account.data).ok());
+            let supply = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_supply(&account.data).ok());
+            let (Some(base_reserve), Some(quote_reserve), Some(lp_supply)) =
+                (amount(token_a_reserves), amount(token_b_reserves), supply(pool_mint))
+            else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read swap reserves");
+                continue;
+            };
+            if base_reserve == 0 || quote_reserve == 0 {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "swap has an empty reserve, no price");
+                continue;
+            }
+
+            results.push((address, symbol, PoolData {
+                pool,
+                base_reserve,
+                quote_reserve,
+                lp_supply,
+                amp_factor: state.amp_factor,
+                fee_bps: state.fee_bps,
+            }));
+        }
+
+        Ok((slot, results))
+    }
+
+    // Resolve the pools not seen yet: one call for the swap accounts, then one
+    // for their token mints (for decimals)
+    async fn resolve_pools(&mut self, due: &[(Pubkey, String)]) -> Result<()> {
+        let missing: Vec<&(Pubkey, String)> = due.iter().filter(|(address, _)| !self.resolved.contains_key(address)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = missing.iter().map(|(address, _)| *address).collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        let mut headers = Vec::with_capacity(missing.len());
+        for ((address, symbol), account) in missing.into_iter().zip(accounts) {
+            let Some(account) = account else {
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "swap account not found");
+                continue;
+            };
+            match PoolHeader::parse(&account.data) {
+                Ok(header) => headers.push((*address, symbol, header)),
+                Err(e) if !e.is_transient() => self.dropped.extend(health::drop_pool(SOURCE, &mut self.pools, address, &e)),
+                Err(e) => {
+                    metrics::inc_decode_error(SOURCE);
+                    tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, error = %e, "failed to parse swap");
+                }
+            }
+        }
+        if headers.is_empty() {
+            return Ok(());
+        }
+
+        let addresses: Vec<Pubkey> = headers
+            .iter()
+            .flat_map(|(_, _, header)| [header.token_a_mint, header.token_b_mint])
+            .collect();
+        let accounts = rpc::get_multiple_accounts(&self.rpc_client, &addresses).await?;
+        for ((address, symbol, header), accounts) in headers.into_iter().zip(accounts.chunks(2)) {
+            let [token_a_mint, token_b_mint] = accounts else {
+                continue;
+            };
+            let decimals = |account: &Option<Account>| account.as_ref().and_then(|account| token::mint_decimals(&account.data).ok());
+            let (Some(base_decimals), Some(quote_decimals)) = (decimals(token_a_mint), decimals(token_b_mint)) else {
+                metrics::inc_decode_error(SOURCE);
+                tracing::warn!(source = SOURCE, symbol = %symbol, pool = %address, "failed to read swap mints");
+                continue;
+            };
+
+            self.resolved.insert(address, ResolvedPool {
+                header,
+                base_decimals,
+                quote_decimals,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Monitor for SaberMonitor {
+    async fn run(&mut self, context: &MonitorContext, heartbeat: &Heartbeat) -> Result<()> {
+        self.start_monitoring(context.tx.clone(), context.health_tx.clone(), heartbeat, &context.pause, &context.stats, &context.shutdown).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ann·(x + y) + D - Ann·D - D³ / (4·x·y), relative to Ann·D
+    fn invariant_error(amp_factor: f64, x: f64, y: f64, d: f64) -> f64 {
+        let leverage = amp_factor * 2.0;
+        (leverage * (x + y) + d - leverage * d - d * d * d / (4.0 * x * y)).abs() / (leverage * d)
+    }
+
+    #[test]
+    fn compute_d_of_a_balanced_pool_is_its_sum() {
+        assert_eq!(compute_d(100.0, 1e6, 1e6), 2e6);
+        assert_eq!(compute_d(100.0, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn compute_d_converges_on_imbalanced_pools() {
+        for (amp_factor, x, y) in [(100.0, 1e6, 1e3), (2000.0, 5e8, 3e8), (1.0, 1e9, 1.0), (100.0, 1.0, 1e9)] {
+            let d = compute_d(amp_factor, x, y);
+            assert!(invariant_error(amp_factor, x, y, d) < 1e-11, "A = {}, x = {}, y = {}: D = {}", amp_factor, x, y, d);
+            // Between the constant-product and constant-sum invariants
+            assert!(d > 2.0 * (x * y).sqrt() && d < x + y);
+        }
+    }
+
+    #[test]
+    fn marginal_price_of_a_balanced_pool_is_one() {
+        assert_eq!(marginal_price(100.0, 1e6, 1e6), 1.0);
+        assert!(marginal_price(100.0, 2e6, 1e6) < 1.0);
+    }
+}
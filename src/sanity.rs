@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::{SanityConfig, SanityMode};
+use crate::events::MarketEvent;
+use crate::metrics;
+
+// A price read that strayed more than max_deviation_percent from the pool's
+// recent median, e.g. off an empty vault mid-rebalance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAnomaly {
+    pub source: String,
+    pub symbol: String,
+    pub pool: String,
+    pub price: f64,
+    pub median: f64,
+    pub deviation_percent: f64,
+    // Held back instead of published as a price update
+    pub rejected: bool,
+    pub slot: u64,
+    pub timestamp: u64,
+}
+
+static SETTINGS: OnceLock<SanityConfig> = OnceLock::new();
+
+// pool address -> its latest prices, oldest first
+static RECENT: LazyLock<Mutex<HashMap<String, VecDeque<f64>>>> = LazyLock::new(Mutex::default);
+
+pub fn init(config: &SanityConfig) {
+    let _ = SETTINGS.set(config.clone());
+}
+
+// Checked by the monitors on every price they read, before it goes into the
+// rolling stats or out as a PriceUpdate. An outlier is published as a
+// PriceAnomaly; returns true when the price must not be published as well.
+pub fn reject(tx: &broadcast::Sender<MarketEvent>, source: &str, symbol: &str, pool: &str, price: f64, slot: u64) -> bool {
+    let Some(config) = SETTINGS.get().filter(|config| config.enabled) else {
+        return false;
+    };
+
+    let median = {
+        let mut recent = RECENT.lock().unwrap();
+        let window = recent.entry(pool.to_string()).or_default();
+        let median = (window.len() >= config.min_samples.max(1)).then(|| median(window));
+        // Outliers go into the window too: a real repricing stops being one
+        // once it has held for half the window
+        if price.is_finite() {
+            window.push_back(price);
+            while window.len() > config.window.max(1) {
+                window.pop_front();
+            }
+        }
+        median
+    };
+    let Some(median) = median.filter(|median| *median > 0.0) else {
+        return false;
+    };
+
+    let deviation_percent = (price - median) / median * 100.0;
+    if deviation_percent.abs() <= config.max_deviation_percent {
+        return false;
+    }
+
+    let rejected = config.mode == SanityMode::Reject;
+    metrics::inc_anomaly(source);
+    let _ = tx.send(MarketEvent::Anomaly(PriceAnomaly {
+        source: source.to_string(),
+        symbol: symbol.to_string(),
+        pool: pool.to_string(),
+        price,
+        median,
+        deviation_percent,
+        rejected,
+        slot,
+        timestamp: unix_now(),
+    }));
+    rejected
+}
+
+fn median(window: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<f64> = window.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
@@ -0,0 +1,111 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, ScheduleConfig};
+
+// Daily UTC window in which a pool is polled at full rate, e.g. "13:30-20:00".
+// A window ending before it starts wraps past midnight ("22:00-06:00").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveHours {
+    start: u32,  // Minutes after midnight UTC
+    end: u32,
+}
+
+impl ActiveHours {
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+impl FromStr for ActiveHours {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let parse_time = |time: &str| -> Option<u32> {
+            let (hours, minutes) = time.trim().split_once(':')?;
+            let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+            (hours <= 24 && minutes < 60 && hours * 60 + minutes <= 24 * 60).then_some(hours * 60 + minutes)
+        };
+
+        let (start, end) = s
+            .split_once('-')
+            .and_then(|(start, end)| Some((parse_time(start)?, parse_time(end)?)))
+            .ok_or_else(|| anyhow::anyhow!("Invalid active_hours {:?}: expected HH:MM-HH:MM (UTC)", s))?;
+        if start == end {
+            return Err(anyhow::anyhow!("Invalid active_hours {:?}: window is empty", s));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for ActiveHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}-{:02}:{:02} UTC", self.start / 60, self.start % 60, self.end / 60, self.end % 60)
+    }
+}
+
+// Which pools a polling monitor reads on a given tick: pools inside their
+// active hours (or without any) every tick, parked pools once per keep-alive
+pub struct PollSchedule {
+    source: &'static str,
+    keepalive: Duration,
+    hours: HashMap<Pubkey, ActiveHours>,
+    last_polled: HashMap<Pubkey, Instant>,
+    parked: HashSet<Pubkey>,
+}
+
+impl PollSchedule {
+    pub fn new(source: &'static str, pools: &[(Pubkey, PoolConfig)], config: &ScheduleConfig) -> Self {
+        // Windows are checked by Config::validate, so a bad one can't get this far
+        let hours = pools
+            .iter()
+            .filter_map(|(address, pool)| Some((*address, pool.active_hours().ok()??)))
+            .collect();
+
+        Self {
+            source,
+            keepalive: Duration::from_secs(config.keepalive_secs),
+            hours,
+            last_polled: HashMap::new(),
+            parked: HashSet::new(),
+        }
+    }
+
+    // Pools to fetch now, in config order. Marks them as polled.
+    pub fn due<'a>(&mut self, pools: &'a [(Pubkey, PoolConfig)]) -> Vec<&'a (Pubkey, PoolConfig)> {
+        let minute_of_day = (unix_now() % 86_400 / 60) as u32;
+        let now = Instant::now();
+
+        let mut due = Vec::with_capacity(pools.len());
+        for entry in pools {
+            let (address, pool) = entry;
+            let active = self.hours.get(address).is_none_or(|hours| hours.contains(minute_of_day));
+            if active {
+                if self.parked.remove(address) {
+                    tracing::info!(source = %self.source, symbol = %pool.symbol, "in active hours, polling at full rate");
+                }
+            } else if self.parked.insert(*address) {
+                tracing::info!(source = %self.source, symbol = %pool.symbol, hours = %self.hours[address], keepalive_secs = self.keepalive.as_secs(), "outside active hours, polling at the keepalive rate");
+            }
+
+            let keepalive_elapsed = self
+                .last_polled
+                .get(address)
+                .is_none_or(|polled| now.duration_since(*polled) >= self.keepalive);
+            if active || keepalive_elapsed {
+                self.last_polled.insert(*address, now);
+                due.push(entry);
+            }
+        }
+        due
+    }
+}
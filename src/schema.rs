@@ -0,0 +1,80 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::raydium::PriceUpdate;
+
+// Versions of the PriceUpdate JSON published on the streaming API. A client
+// picks one at subscribe time and keeps getting exactly the fields it knew
+// about; fields added later are left out of its frames. Adding a field to
+// PriceUpdate means bumping LATEST and listing the field under it here.
+pub const LATEST: u32 = 9;
+// Clients that don't ask for a version get the shape the stream had when it
+// became versioned, so they don't see fields appear under them either
+pub const DEFAULT: u32 = 5;
+
+// Fields every version has: the PriceUpdate the stream first shipped with
+const BASE_FIELDS: &[&str] = &[
+    "symbol", "price", "change_percent", "timestamp", "source", "pool", "base_reserve", "quote_reserve",
+    "base_decimals", "quote_decimals", "change_reason", "lp_supply", "fee_bps",
+];
+
+// Fields each later version added
+const ADDED_IN: &[(u32, &[&str])] = &[
+    (2, &["slot", "slot_lag"]),
+    (3, &["tvl_usd"]),
+    (4, &["high_24h", "low_24h", "volume_24h"]),
+    (5, &["bid", "ask"]),
+    (6, &["active_index"]),
+    (7, &["microprice", "imbalance"]),
+    (8, &["change_1m", "change_5m", "change_24h"]),
+    (9, &["unchanged"]),
+];
+
+#[derive(Debug, Serialize)]
+pub struct SchemaVersion {
+    pub version: u32,
+    pub fields: Vec<&'static str>,  // Every field a frame of this version carries
+}
+
+#[derive(Debug, Serialize)]
+pub struct Schemas {
+    pub latest: u32,
+    pub default: u32,
+    pub versions: Vec<SchemaVersion>,
+}
+
+pub fn supported(version: u32) -> bool {
+    (1..=LATEST).contains(&version)
+}
+
+pub fn schemas() -> Schemas {
+    Schemas {
+        latest: LATEST,
+        default: DEFAULT,
+        versions: (1..=LATEST)
+            .map(|version| SchemaVersion {
+                version,
+                fields: BASE_FIELDS.iter().chain(added_after(0, version)).copied().collect(),
+            })
+            .collect(),
+    }
+}
+
+// The update as a client on `version` expects it
+pub fn price_update(update: &PriceUpdate, version: u32) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(update)?;
+    if let Value::Object(fields) = &mut value {
+        for field in added_after(version, LATEST) {
+            fields.remove(*field);
+        }
+    }
+    Ok(value)
+}
+
+// Fields added in versions above `from`, up to and including `to`
+fn added_after(from: u32, to: u32) -> impl Iterator<Item = &'static &'static str> {
+    ADDED_IN
+        .iter()
+        .filter(move |(version, _)| *version > from && *version <= to)
+        .flat_map(|(_, fields)| fields.iter())
+}
@@ -0,0 +1,175 @@
+use memmap2::MmapMut;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+use crate::clock::unix_nanos;
+use crate::raydium::PriceUpdate;
+
+// SHARED-MEMORY RING LAYOUT (version 1):
+// ======================================
+// All integers are little-endian, the file is created by the watcher and
+// mapped read-only by consumers.
+//
+//   offset  size  field
+//   0       8     magic        = "DEXWRING"
+//   8       4     version      = 1
+//   12      4     slot_size    = 128
+//   16      8     capacity     = number of slots (power of two)
+//   24      8     created_ns   = wall clock when the writer (re)initialised the ring
+//   32      32    reserved
+//   64      8     write_seq    = number of records published so far (atomic)
+//   72      56    reserved     (keeps write_seq on its own cache line)
+//   128     ...   slots[capacity], 128 bytes each
+//
+// Slot (128 bytes):
+//
+//   0       8     seq          = seqlock, odd while the writer is inside the slot
+//   8       8     publish_ns   = wall clock (ns) when the record was written
+//   16      8     timestamp    = PriceUpdate.timestamp (unix seconds)
+//   24      8     price        (f64)
+//   32      8     change_pct   (f64)
+//   40      8     base_reserve
+//   48      8     quote_reserve
+//   56      16    source       NUL-padded ASCII, e.g. "Raydium"
+//   72      16    symbol       NUL-padded ASCII, e.g. "SOL/USDC"
+//   88      8     slot         = PriceUpdate.slot (0 from writers that predate it)
+//   96      32    reserved
+//
+// Record n (0-based) lives in slot n & (capacity - 1) and is complete when
+// that slot's seq == 2 * (n + 1). A reader copies the slot, re-reads seq and
+// retries if it changed; a seq larger than expected means the reader was lapped.
+// There is a single writer; any number of readers can follow the ring.
+
+pub const MAGIC: [u8; 8] = *b"DEXWRING";
+pub const VERSION: u32 = 1;
+pub const HEADER_SIZE: usize = 128;
+pub const SLOT_SIZE: usize = 128;
+
+#[repr(C)]
+struct Header {
+    magic: [u8; 8],
+    version: u32,
+    slot_size: u32,
+    capacity: u64,
+    created_ns: u64,
+    _reserved0: [u8; 32],
+    write_seq: AtomicU64,
+    _reserved1: [u8; 56],
+}
+
+// Fixed-size, C-compatible copy of a PriceUpdate
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TickRecord {
+    pub publish_ns: u64,
+    pub timestamp: u64,
+    pub price: f64,
+    pub change_percent: f64,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub source: [u8; 16],
+    pub symbol: [u8; 16],
+    pub slot: u64,
+    pub _reserved: [u8; 32],
+}
+
+#[repr(C)]
+struct Slot {
+    seq: AtomicU64,
+    record: TickRecord,
+}
+
+const _: () = assert!(std::mem::size_of::<Header>() == HEADER_SIZE);
+const _: () = assert!(std::mem::size_of::<Slot>() == SLOT_SIZE);
+
+impl TickRecord {
+    pub fn from_update(update: &PriceUpdate) -> Self {
+        Self {
+            publish_ns: unix_nanos(),
+            timestamp: update.timestamp,
+            price: update.price,
+            change_percent: update.change_percent,
+            base_reserve: update.base_reserve,
+            quote_reserve: update.quote_reserve,
+            source: fixed_str(&update.source),
+            symbol: fixed_str(&update.symbol),
+            slot: update.slot,
+            _reserved: [0; 32],
+        }
+    }
+}
+
+pub struct ShmRing {
+    mmap: MmapMut,
+    capacity: u64,
+    next: u64,
+}
+
+impl ShmRing {
+    // Create (or reinitialise) the ring file, e.g. under /dev/shm
+    pub fn create(path: &Path, capacity: u64) -> anyhow::Result<Self> {
+        if capacity == 0 || !capacity.is_power_of_two() {
+            return Err(anyhow::anyhow!("Ring capacity must be a power of two, got {}", capacity));
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((HEADER_SIZE + capacity as usize * SLOT_SIZE) as u64)?;
+
+        // SAFETY: the file is sized above and only this process writes to it
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+
+        let mut ring = Self { mmap, capacity, next: 0 };
+        let header = ring.header_mut();
+        header.magic = MAGIC;
+        header.version = VERSION;
+        header.slot_size = SLOT_SIZE as u32;
+        header.capacity = capacity;
+        header.created_ns = unix_nanos();
+        header.write_seq.store(0, Ordering::Release);
+
+        Ok(ring)
+    }
+
+    pub fn publish(&mut self, record: &TickRecord) {
+        let n = self.next;
+        let slot = self.slot_ptr(n & (self.capacity - 1));
+
+        // SAFETY: single writer; readers detect torn reads through seq
+        unsafe {
+            let seq = &(*slot).seq;
+            // Seqlock write: odd seq while the payload is being replaced
+            seq.store(2 * n + 1, Ordering::Relaxed);
+            fence(Ordering::Release);
+            std::ptr::addr_of_mut!((*slot).record).write_volatile(*record);
+            seq.store(2 * (n + 1), Ordering::Release);
+        }
+
+        self.next = n + 1;
+        self.header_mut().write_seq.store(self.next, Ordering::Release);
+    }
+
+    fn header_mut(&mut self) -> &mut Header {
+        // SAFETY: the mapping is at least HEADER_SIZE bytes and page aligned
+        unsafe { &mut *(self.mmap.as_mut_ptr() as *mut Header) }
+    }
+
+    fn slot_ptr(&mut self, index: u64) -> *mut Slot {
+        let offset = HEADER_SIZE + index as usize * SLOT_SIZE;
+        // SAFETY: index < capacity, so the slot lies within the mapping
+        unsafe { self.mmap.as_mut_ptr().add(offset) as *mut Slot }
+    }
+}
+
+fn fixed_str(value: &str) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    let bytes = value.as_bytes();
+    let len = bytes.len().min(out.len());
+    out[..len].copy_from_slice(&bytes[..len]);
+    out
+}
@@ -0,0 +1,296 @@
+use clap::{Args, Subcommand};
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::display;
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+use crate::record::SessionRecord;
+use crate::sql;
+use crate::swaps::{SwapDirection, SwapEvent};
+
+const SECS_PER_YEAR: f64 = 365.0 * 86_400.0;
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum SimulateCommand {
+    #[command(about = "A hypothetical liquidity position on a watched pool")]
+    Lp(LpOptions),
+}
+
+// `simulate lp --pool=ADDRESS --size=USD (--lower=PRICE --upper=PRICE | --width=PERCENT) [--fee-bps=BPS]`:
+// a hypothetical liquidity position on a watched pool, replayed over the recorded history
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct LpOptions {
+    #[arg(long, help = "Pool address")]
+    pub pool: String,
+    #[arg(long, help = "Position value in quote tokens at the first recorded price")]
+    pub size: f64,
+    #[arg(long, help = "Lower end of the price range")]
+    pub lower: Option<f64>,
+    #[arg(long, help = "Upper end of the price range")]
+    pub upper: Option<f64>,
+    #[arg(long, help = "Range centered on the first recorded price, e.g. 10 for -10%..+10%")]
+    pub width: Option<f64>,
+    #[arg(long, help = "Fee tier to assume instead of the pool's")]
+    pub fee_bps: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LpArgs {
+    pub pool: String,
+    pub size: f64,  // Position value in quote tokens at the first recorded price
+    pub range: Range,
+    pub fee_bps: Option<f64>,  // Overrides the pool's recorded fee tier
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Range {
+    Prices { lower: f64, upper: f64 },
+    // Centered on the first recorded price, e.g. 10 for -10%..+10%
+    Width { percent: f64 },
+}
+
+impl LpArgs {
+    pub fn new(options: &LpOptions) -> anyhow::Result<Self> {
+        let range = match (options.lower, options.upper, options.width) {
+            (Some(lower), Some(upper), None) if 0.0 < lower && lower < upper => Range::Prices { lower, upper },
+            (None, None, Some(percent)) if 0.0 < percent && percent < 100.0 => Range::Width { percent },
+            _ => return Err(anyhow::anyhow!("Give either --lower and --upper with 0 < lower < upper, or --width between 0 and 100")),
+        };
+        if options.size <= 0.0 {
+            return Err(anyhow::anyhow!("--size must be positive"));
+        }
+        Ok(Self {
+            pool: options.pool.clone(),
+            size: options.size,
+            range,
+            fee_bps: options.fee_bps,
+        })
+    }
+}
+
+// Position outcome over the replayed window, amounts in quote tokens
+#[derive(Debug, Clone)]
+pub struct LpResult {
+    pub symbol: String,
+    pub source: String,
+    pub lower: f64,
+    pub upper: f64,
+    pub start_price: f64,
+    pub end_price: f64,
+    pub duration_secs: u64,
+    pub in_range_percent: f64,  // Time-weighted
+    pub fees: f64,
+    pub volume_based: bool,     // Fees from the 24h volume rather than decoded swaps
+    pub position_value: f64,    // At the end price
+    pub hodl_value: f64,        // The starting tokens, held instead
+    pub impermanent_loss: f64,  // position_value - hodl_value, zero or negative
+}
+
+impl LpResult {
+    pub fn net(&self) -> f64 {
+        self.fees + self.impermanent_loss
+    }
+}
+
+// Replay the recorded history of one pool against a hypothetical position
+pub fn run(config: &Config, args: &LpArgs) -> anyhow::Result<()> {
+    let (updates, swaps) = load_history(config, &args.pool)?;
+    let result = simulate(args, &updates, &swaps)?;
+    print_result(args, &result);
+    Ok(())
+}
+
+// Price updates and swaps of the pool from the archive and session bundles,
+// in time order. Events present in both are kept once.
+fn load_history(config: &Config, pool: &str) -> anyhow::Result<(Vec<PriceUpdate>, Vec<SwapEvent>)> {
+    let (events, sessions) = sql::history_files(config);
+    if events.is_empty() && sessions.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No archive files in {} and no session bundles here to replay",
+            config.archive.dir.display()
+        ));
+    }
+
+    let mut updates: BTreeMap<(u64, u64), PriceUpdate> = BTreeMap::new();
+    let mut swaps: BTreeMap<(u64, String), SwapEvent> = BTreeMap::new();
+    let mut keep = |update: Option<PriceUpdate>, swap: Option<SwapEvent>| {
+        if let Some(update) = update.filter(|update| update.pool == pool && update.price > 0.0) {
+            updates.insert((update.timestamp, update.slot), update);
+        }
+        if let Some(swap) = swap.filter(|swap| swap.pool == pool) {
+            swaps.insert((swap.timestamp, swap.signature.clone()), swap);
+        }
+    };
+
+    for path in &events {
+        for_each_line(path, |line| match serde_json::from_str::<MarketEvent>(line) {
+            Ok(MarketEvent::Price(update)) => keep(Some(update), None),
+            Ok(MarketEvent::Swap(swap)) => keep(None, Some(swap)),
+            _ => {}
+        })?;
+    }
+    for path in &sessions {
+        for_each_line(path, |line| match serde_json::from_str::<SessionRecord>(line) {
+            Ok(SessionRecord::Update(update)) => keep(Some(update), None),
+            Ok(SessionRecord::Swap(swap)) => keep(None, Some(swap)),
+            _ => {}
+        })?;
+    }
+
+    Ok((updates.into_values().collect(), swaps.into_values().collect()))
+}
+
+fn for_each_line(path: &Path, mut handle: impl FnMut(&str)) -> anyhow::Result<()> {
+    let file = std::fs::File::open(path).map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    for line in BufReader::new(GzDecoder::new(file)).lines() {
+        match line {
+            Ok(line) => handle(&line),
+            // A file still being written ends mid-stream; keep what was read
+            Err(e) => {
+                tracing::warn!(error = %e, path = %path.display(), "stopped reading early");
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Concentrated liquidity over [lower, upper], as on Orca and Raydium CLMM; a
+// run of DLMM bins with liquidity spread evenly behaves nearly the same.
+// The position earns its share of each swap's fee while the swap's price is in
+// range, the share being its liquidity against the pool's, which is taken from
+// the reserves as if they were all in range. For concentrated pools that
+// overstates the pool's liquidity, so fees there come out conservative.
+// Without decoded swaps the pool's 24h volume is spread evenly over time instead.
+pub fn simulate(args: &LpArgs, updates: &[PriceUpdate], swaps: &[SwapEvent]) -> anyhow::Result<LpResult> {
+    let (Some(first), Some(last)) = (updates.first(), updates.last()) else {
+        return Err(anyhow::anyhow!("No recorded prices for pool {}", args.pool));
+    };
+    if updates.len() < 2 {
+        return Err(anyhow::anyhow!("Only one recorded price for pool {}, nothing to replay", args.pool));
+    }
+
+    let (lower, upper) = match args.range {
+        Range::Prices { lower, upper } => (lower, upper),
+        Range::Width { percent } => (first.price * (1.0 - percent / 100.0), first.price * (1.0 + percent / 100.0)),
+    };
+    let position = Position::new(lower, upper, args.size, first.price);
+    let fee_rate = args.fee_bps.unwrap_or(first.fee_bps) / 10_000.0;
+
+    // Pool liquidity at a point in time: the latest update at or before it
+    let pool_liquidity = |timestamp: u64| {
+        let index = updates.partition_point(|update| update.timestamp <= timestamp).saturating_sub(1);
+        let update = &updates[index];
+        let base = update.base_reserve as f64 / 10_f64.powi(update.base_decimals as i32);
+        let quote = update.quote_reserve as f64 / 10_f64.powi(update.quote_decimals as i32);
+        (base * quote).sqrt()
+    };
+    let share = |timestamp: u64| position.liquidity / (position.liquidity + pool_liquidity(timestamp));
+
+    let mut fees = 0.0;
+    let mut in_range_secs = 0;
+    for pair in updates.windows(2) {
+        let (update, next) = (&pair[0], &pair[1]);
+        let elapsed = next.timestamp.saturating_sub(update.timestamp);
+        if position.in_range(update.price) {
+            in_range_secs += elapsed;
+            if swaps.is_empty() {
+                let volume = update.volume_24h.unwrap_or(0.0) * elapsed as f64 / 86_400.0;
+                fees += volume * fee_rate * share(update.timestamp);
+            }
+        }
+    }
+    for swap in swaps.iter().filter(|swap| (first.timestamp..=last.timestamp).contains(&swap.timestamp)) {
+        if position.in_range(swap.price) {
+            let volume = match swap.direction {
+                SwapDirection::Buy => swap.amount_in,
+                SwapDirection::Sell => swap.amount_in * swap.price,
+            };
+            fees += volume * fee_rate * share(swap.timestamp);
+        }
+    }
+
+    let duration_secs = last.timestamp.saturating_sub(first.timestamp);
+    let (base, quote) = position.amounts(first.price);
+    let position_value = position.value(last.price);
+    let hodl_value = base * last.price + quote;
+    Ok(LpResult {
+        symbol: first.symbol.clone(),
+        source: first.source.clone(),
+        lower,
+        upper,
+        start_price: first.price,
+        end_price: last.price,
+        duration_secs,
+        in_range_percent: if duration_secs > 0 { in_range_secs as f64 / duration_secs as f64 * 100.0 } else { 0.0 },
+        fees,
+        volume_based: swaps.is_empty(),
+        position_value,
+        hodl_value,
+        impermanent_loss: position_value - hodl_value,
+    })
+}
+
+// A concentrated-liquidity position in UI units, prices in quote per base
+struct Position {
+    sqrt_lower: f64,
+    sqrt_upper: f64,
+    liquidity: f64,
+}
+
+impl Position {
+    // Sized to be worth `value` quote tokens at `price`
+    fn new(lower: f64, upper: f64, value: f64, price: f64) -> Self {
+        let mut position = Self { sqrt_lower: lower.sqrt(), sqrt_upper: upper.sqrt(), liquidity: 1.0 };
+        position.liquidity = value / position.value(price);
+        position
+    }
+
+    fn in_range(&self, price: f64) -> bool {
+        let sqrt_price = price.sqrt();
+        self.sqrt_lower <= sqrt_price && sqrt_price <= self.sqrt_upper
+    }
+
+    // (base, quote) held at a price: all base below the range, all quote above it
+    fn amounts(&self, price: f64) -> (f64, f64) {
+        let sqrt_price = price.sqrt().clamp(self.sqrt_lower, self.sqrt_upper);
+        (
+            self.liquidity * (1.0 / sqrt_price - 1.0 / self.sqrt_upper),
+            self.liquidity * (sqrt_price - self.sqrt_lower),
+        )
+    }
+
+    fn value(&self, price: f64) -> f64 {
+        let (base, quote) = self.amounts(price);
+        base * price + quote
+    }
+}
+
+fn print_result(args: &LpArgs, result: &LpResult) {
+    let (_, quote) = result.symbol.split_once('/').unwrap_or(("", ""));
+    let amount = |value: f64| format!("{} {}", display::signed(value, 2), quote);
+    let percent = |value: f64| format!("{:+.2}%", value / args.size * 100.0);
+    let hours = result.duration_secs as f64 / 3_600.0;
+    let apr = if result.duration_secs > 0 {
+        result.net() / args.size * SECS_PER_YEAR / result.duration_secs as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    // The report is the command's output, not log lines
+    println!("{} on {} ({}), {} {} over {:.1}h of history", result.symbol, result.source, args.pool, display::number(args.size, 2), quote, hours);
+    println!("  range            {} - {}", display::number(result.lower, 6), display::number(result.upper, 6));
+    println!("  price            {} -> {}", display::number(result.start_price, 6), display::number(result.end_price, 6));
+    println!("  time in range    {:.1}%", result.in_range_percent);
+    println!("  fees earned      {}  {}", amount(result.fees), percent(result.fees));
+    println!("  impermanent loss {}  {}", amount(result.impermanent_loss), percent(result.impermanent_loss));
+    println!("  net vs holding   {}  {}  ({:+.1}% APR)", amount(result.net()), percent(result.net()), apr);
+    println!("  position value   {}, holding {}", display::number(result.position_value, 2), display::number(result.hodl_value, 2));
+    if result.volume_based {
+        println!("  {}", display::render("⚠️ No swaps recorded for this pool; fees are estimated from its 24h volume. Enable [swaps] for per-swap fees."));
+    }
+}
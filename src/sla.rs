@@ -0,0 +1,246 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::aggregate;
+use crate::clock::unix_now;
+use crate::config::{PoolConfig, SlaConfig};
+use crate::events::MarketEvent;
+use crate::pause::Pause;
+use crate::warmup::Ready;
+
+const SECS_PER_DAY: u64 = 86_400;
+// Keeps the daily report within chat message limits
+const MAX_LISTED_FEEDS: usize = 20;
+
+// Today's availability so far and the completed days before it, read by the REST API
+pub type SlaReports = Arc<RwLock<SlaHistory>>;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SlaHistory {
+    pub today: Option<SlaReport>,
+    pub days: VecDeque<SlaReport>,  // Completed UTC days, newest first
+}
+
+// Availability of every feed over one UTC day
+#[derive(Debug, Clone, Serialize)]
+pub struct SlaReport {
+    pub date: String,  // YYYY-MM-DD
+    pub interval_secs: u64,
+    pub target_percent: f64,
+    pub availability_percent: f64,  // Over all feeds' intervals together
+    pub feeds: Vec<FeedSla>,        // Least available first
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedSla {
+    pub source: String,
+    pub symbol: String,
+    pub pool: String,
+    pub expected: u64,  // Intervals the feed was expected to update in
+    pub fresh: u64,     // Of those, intervals with at least one update
+    pub availability_percent: f64,
+    pub longest_gap_secs: u64,  // Longest run of intervals without an update
+    pub meets_target: bool,
+}
+
+struct Feed {
+    source: String,
+    symbol: String,
+    updated: bool,
+    expected: u64,
+    fresh: u64,
+    gap: u64,
+    longest_gap: u64,
+}
+
+impl Feed {
+    fn new(source: String, symbol: String) -> Self {
+        Self { source, symbol, updated: false, expected: 0, fresh: 0, gap: 0, longest_gap: 0 }
+    }
+}
+
+// Per-feed uptime: time is cut into fixed intervals and a feed is available in
+// an interval if at least one price update for its pool arrived during it.
+// Intervals before warm-up ends and while a source is paused aren't expected,
+// so planned maintenance doesn't count against the provider.
+pub struct SlaTracker {
+    config: SlaConfig,
+    // pool address -> counters for the current day
+    feeds: BTreeMap<String, Feed>,
+    day: u64,
+}
+
+impl SlaTracker {
+    pub fn new(config: SlaConfig, pools: &[(String, PoolConfig)]) -> Self {
+        let feeds = pools
+            .iter()
+            .map(|(source, pool)| (pool.address.clone(), Feed::new(source.clone(), pool.symbol.clone())))
+            .collect();
+        Self { config, feeds, day: unix_now() / SECS_PER_DAY }
+    }
+
+    pub async fn run(
+        mut self,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        pause: Pause,
+        ready: Ready,
+        reports: SlaReports,
+        tx: Arc<broadcast::Sender<SlaReport>>,
+    ) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(self.config.interval_secs.max(1)));
+        // The first tick fires immediately, before any interval has passed
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) if !aggregate::is_aggregate(&update) => {
+                        // Pools from outside the config (e.g. discovered pairs) count once they show up
+                        self.feeds
+                            .entry(update.pool)
+                            .or_insert_with(|| Feed::new(update.source, update.symbol))
+                            .updated = true;
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "SLA tracker lagged, skipped events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    if ready.is_ready() {
+                        self.close_interval(&pause);
+                    }
+
+                    let now = unix_now();
+                    let mut history = reports.write().await;
+                    if now / SECS_PER_DAY != self.day {
+                        let report = self.report();
+                        history.days.push_front(report.clone());
+                        history.days.truncate(self.config.history_days);
+                        let _ = tx.send(report);
+
+                        self.day = now / SECS_PER_DAY;
+                        for feed in self.feeds.values_mut() {
+                            *feed = Feed::new(std::mem::take(&mut feed.source), std::mem::take(&mut feed.symbol));
+                        }
+                    }
+                    history.today = Some(self.report());
+                }
+            }
+        }
+    }
+
+    fn close_interval(&mut self, pause: &Pause) {
+        let paused = pause.state();
+        for feed in self.feeds.values_mut() {
+            let updated = std::mem::take(&mut feed.updated);
+            if paused.is_paused(&feed.source) {
+                continue;
+            }
+
+            feed.expected += 1;
+            if updated {
+                feed.fresh += 1;
+                feed.gap = 0;
+            } else {
+                feed.gap += 1;
+                feed.longest_gap = feed.longest_gap.max(feed.gap);
+            }
+        }
+    }
+
+    fn report(&self) -> SlaReport {
+        let target = self.config.target_percent;
+        let mut feeds: Vec<FeedSla> = self
+            .feeds
+            .iter()
+            .filter(|(_, feed)| feed.expected > 0)
+            .map(|(pool, feed)| {
+                let availability_percent = percent(feed.fresh, feed.expected);
+                FeedSla {
+                    source: feed.source.clone(),
+                    symbol: feed.symbol.clone(),
+                    pool: pool.clone(),
+                    expected: feed.expected,
+                    fresh: feed.fresh,
+                    availability_percent,
+                    longest_gap_secs: feed.longest_gap * self.config.interval_secs,
+                    meets_target: availability_percent >= target,
+                }
+            })
+            .collect();
+        feeds.sort_by(|a, b| a.availability_percent.total_cmp(&b.availability_percent));
+
+        let (fresh, expected) = feeds.iter().fold((0, 0), |(fresh, expected), feed| (fresh + feed.fresh, expected + feed.expected));
+        SlaReport {
+            date: date(self.day),
+            interval_secs: self.config.interval_secs,
+            target_percent: target,
+            availability_percent: percent(fresh, expected),
+            feeds,
+        }
+    }
+}
+
+impl SlaReport {
+    // Notification text: the overall figure, per source, then the feeds that missed the target
+    pub fn text(&self) -> String {
+        let mut by_source: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+        for feed in &self.feeds {
+            let (fresh, expected) = by_source.entry(feed.source.as_str()).or_default();
+            *fresh += feed.fresh;
+            *expected += feed.expected;
+        }
+
+        let mut lines = vec![format!(
+            "📈 Feed SLA for {}: {:.2}% available (target {}%)",
+            self.date, self.availability_percent, self.target_percent,
+        )];
+        for (source, (fresh, expected)) in by_source {
+            lines.push(format!("  {}: {:.2}%", source, percent(fresh, expected)));
+        }
+
+        let missed: Vec<&FeedSla> = self.feeds.iter().filter(|feed| !feed.meets_target).collect();
+        if missed.is_empty() {
+            lines.push(format!("✅ All {} feeds met the target", self.feeds.len()));
+        } else {
+            lines.push(format!("⚠️ {} of {} feeds missed the target:", missed.len(), self.feeds.len()));
+            for feed in missed.iter().take(MAX_LISTED_FEEDS) {
+                lines.push(format!(
+                    "  {} on {}: {:.2}%, longest gap {}s",
+                    feed.symbol, feed.source, feed.availability_percent, feed.longest_gap_secs,
+                ));
+            }
+            if missed.len() > MAX_LISTED_FEEDS {
+                lines.push(format!("  …and {} more, see GET /sla/{}", missed.len() - MAX_LISTED_FEEDS, self.date));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn percent(fresh: u64, expected: u64) -> f64 {
+    if expected == 0 {
+        100.0
+    } else {
+        fresh as f64 / expected as f64 * 100.0
+    }
+}
+
+// Days since the Unix epoch as a UTC calendar date
+fn date(days: u64) -> String {
+    // Civil-from-days over 400-year eras starting in March
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Relative price move below which the pool price counts as unchanged
+const PRICE_EPSILON: f64 = 1e-6;
+// Withdrawals that shrink sqrt(k) by less than this many bps look like fee collection, not LP exits
+const FEE_COLLECTION_MAX_SHARE_BPS: u128 = 10;
+
+// Pool state retained between ticks
+#[derive(Debug, Clone, Copy)]
+pub struct ReserveSnapshot {
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub price: f64,
+}
+
+// What most likely moved the reserves since the previous tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeReason {
+    Initial,          // First observation of the pool
+    Unchanged,
+    Swap,             // Reserves moved in opposite directions
+    LiquidityAdd,     // Both sides grew at a stable price
+    LiquidityRemove,  // Both sides shrank at a stable price
+    FeeCollection,    // Small withdrawal at a stable price
+    Unknown,          // Several kinds of activity within one tick
+}
+
+impl fmt::Display for ChangeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ChangeReason::Initial => "initial",
+            ChangeReason::Unchanged => "unchanged",
+            ChangeReason::Swap => "swap",
+            ChangeReason::LiquidityAdd => "liquidity add",
+            ChangeReason::LiquidityRemove => "liquidity remove",
+            ChangeReason::FeeCollection => "fee collection",
+            ChangeReason::Unknown => "unknown",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// Classify a reserve change by comparing invariants between two snapshots:
+// - swaps trade one token for the other, so reserves move in opposite directions
+//   (and x * y = k only grows by the fee on constant-product pools)
+// - deposits and withdrawals move both reserves the same way without moving the price
+// - fee collection is a withdrawal too small to be a real LP exit
+pub fn classify_change(previous: Option<&ReserveSnapshot>, current: &ReserveSnapshot) -> ChangeReason {
+    let Some(previous) = previous else {
+        return ChangeReason::Initial;
+    };
+
+    let base_delta = current.base_reserve as i128 - previous.base_reserve as i128;
+    let quote_delta = current.quote_reserve as i128 - previous.quote_reserve as i128;
+
+    if base_delta == 0 && quote_delta == 0 {
+        return ChangeReason::Unchanged;
+    }
+
+    if base_delta.signum() * quote_delta.signum() < 0 {
+        return ChangeReason::Swap;
+    }
+
+    let price_stable = previous.price > 0.0
+        && ((current.price - previous.price) / previous.price).abs() < PRICE_EPSILON;
+
+    let growing = base_delta >= 0 && quote_delta >= 0;
+    match (growing, price_stable) {
+        (true, true) => ChangeReason::LiquidityAdd,
+        (false, true) => {
+            // sqrt(k) scales linearly with the share of the pool that left. Both
+            // roots fit in u64, so comparing them in bps stays exact in u128.
+            let root_before = invariant(previous.base_reserve, previous.quote_reserve).isqrt();
+            let root_after = invariant(current.base_reserve, current.quote_reserve).isqrt();
+            let fee_sized = root_after
+                .checked_mul(10_000)
+                .zip(root_before.checked_mul(10_000 - FEE_COLLECTION_MAX_SHARE_BPS))
+                .map(|(after, threshold)| root_before > 0 && after > threshold);
+
+            match fee_sized {
+                Some(true) => ChangeReason::FeeCollection,
+                Some(false) => ChangeReason::LiquidityRemove,
+                None => ChangeReason::Unknown,
+            }
+        }
+        // Same-direction moves with a price change mean a swap plus a deposit/withdrawal
+        (_, false) => ChangeReason::Unknown,
+    }
+}
+
+// Constant-product invariant x * y in raw atoms. The product of two u64
+// reserves always fits in u128, so it never needs an f64 round trip.
+pub fn invariant(base_reserve: u64, quote_reserve: u64) -> u128 {
+    base_reserve as u128 * quote_reserve as u128
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+// History files the views are made of: archive files (uploaded ones kept with
+// keep_local included) and session bundles in the working directory
+pub fn history_files(config: &Config) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let matching = |dir: &Path, prefix: &str| -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy())
+                    .is_some_and(|name| name.starts_with(prefix) && name.ends_with(".jsonl.gz"))
+            })
+            .collect();
+        files.sort();
+        files
+    };
+
+    let mut events = matching(&config.archive.dir, "events-");
+    events.extend(matching(&config.archive.dir.join("uploaded"), "events-"));
+    let sessions = matching(Path::new("."), "session-");
+    (events, sessions)
+}
+
+#[cfg(not(feature = "duckdb"))]
+pub fn run(_config: &Config, _query: &str) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("`sql` requires building with --features duckdb"))
+}
+
+// Query the history with an in-memory DuckDB. Views set up beforehand:
+//   events    every archived market event, one column per field across kinds
+//   prices    the price updates among them
+//   sessions  every line of the session bundles, tagged by `kind`
+// Other files can be read directly, e.g. FROM read_json_auto('bundle.jsonl.gz').
+#[cfg(feature = "duckdb")]
+pub fn run(config: &Config, query: &str) -> anyhow::Result<()> {
+    let connection = duckdb::Connection::open_in_memory()?;
+
+    let (events, sessions) = history_files(config);
+    if !events.is_empty() {
+        connection.execute_batch(&format!(
+            "CREATE VIEW events AS SELECT * FROM {};
+             CREATE VIEW prices AS SELECT * FROM events WHERE event = 'price';",
+            read_json(&events)
+        ))?;
+    }
+    if !sessions.is_empty() {
+        connection.execute_batch(&format!("CREATE VIEW sessions AS SELECT * FROM {};", read_json(&sessions)))?;
+    }
+    if events.is_empty() && sessions.is_empty() {
+        tracing::warn!(dir = %config.archive.dir.display(), "no archive files and no session bundles; only explicit file reads will work");
+    }
+
+    let mut statement = connection.prepare(query)?;
+    let mut rows = statement.query([])?;
+    let columns = rows.as_ref().map(|statement| statement.column_names()).unwrap_or_default();
+
+    let mut table: Vec<Vec<String>> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut cells = Vec::with_capacity(columns.len());
+        for index in 0..columns.len() {
+            cells.push(format_value(row.get::<_, duckdb::types::Value>(index)?));
+        }
+        table.push(cells);
+    }
+
+    print_table(&columns, &table);
+    Ok(())
+}
+
+// Newline-delimited JSON across files whose lines don't all share one shape,
+// with the source file as a column
+#[cfg(feature = "duckdb")]
+fn read_json(files: &[PathBuf]) -> String {
+    let files: Vec<String> = files
+        .iter()
+        .map(|path| format!("'{}'", path.display().to_string().replace('\'', "''")))
+        .collect();
+    format!(
+        "read_json_auto([{}], format = 'newline_delimited', union_by_name = true, filename = true)",
+        files.join(", ")
+    )
+}
+
+#[cfg(feature = "duckdb")]
+fn format_value(value: duckdb::types::Value) -> String {
+    use duckdb::types::Value;
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::TinyInt(value) => value.to_string(),
+        Value::SmallInt(value) => value.to_string(),
+        Value::Int(value) => value.to_string(),
+        Value::BigInt(value) => value.to_string(),
+        Value::HugeInt(value) => value.to_string(),
+        Value::UTinyInt(value) => value.to_string(),
+        Value::USmallInt(value) => value.to_string(),
+        Value::UInt(value) => value.to_string(),
+        Value::UBigInt(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::Double(value) => value.to_string(),
+        Value::Text(value) => value,
+        value => format!("{:?}", value),
+    }
+}
+
+// Left-aligned columns under a header, then the row count
+#[cfg(feature = "duckdb")]
+fn print_table(columns: &[String], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            rows.iter()
+                .map(|row| row[index].chars().count())
+                .chain(std::iter::once(column.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    // Query results are the command's output, not log lines
+    println!("{}", line(columns));
+    println!("{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("  "));
+    for row in rows {
+        println!("{}", line(row));
+    }
+    println!("({} rows)", rows.len());
+}
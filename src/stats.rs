@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::StatsConfig;
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+use crate::swaps::{SwapDirection, SwapEvent};
+
+const WINDOW_24H: u64 = 24 * 60 * 60;
+// History is kept in one-minute buckets, so a day is at most 1440 entries per pool
+const BUCKET_SECS: u64 = 60;
+// Realized volatility is annualized over a 365-day year; Solana trades around the clock
+const YEAR_SECS: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+// Rolling figures attached to every price update
+#[derive(Debug, Clone, Copy)]
+pub struct Rolling24h {
+    pub high: f64,
+    pub low: f64,
+    pub volume: Option<f64>,  // Quote tokens; None unless swaps are decoded
+    // Percent change against the price standing 1m, 5m and 24h ago; None
+    // until the pool has been watched that long
+    pub change_1m: Option<f64>,
+    pub change_5m: Option<f64>,
+    pub change_24h: Option<f64>,
+}
+
+#[derive(Default)]
+struct PoolHistory {
+    prices: VecDeque<(u64, f64, f64, f64)>,  // (minute, high, low, last price), oldest first
+    volume: VecDeque<(u64, f64)>,            // (minute, quote volume), oldest first
+}
+
+// 24h high, low, volume and price changes per pool from what the watcher has
+// seen since it started. Shared by the monitors, so it survives their restarts.
+#[derive(Clone)]
+pub struct RollingStats {
+    pools: Arc<Mutex<HashMap<String, PoolHistory>>>,
+    track_volume: bool,
+}
+
+impl RollingStats {
+    pub fn new(track_volume: bool) -> Self {
+        Self {
+            pools: Arc::new(Mutex::new(HashMap::new())),
+            track_volume,
+        }
+    }
+
+    // Add a fresh price for a pool and return its rolling stats, this price included
+    pub fn record(&self, pool: &str, price: f64) -> Rolling24h {
+        let now = unix_now();
+        let minute = now / BUCKET_SECS;
+        let mut pools = self.pools.lock().unwrap();
+        let history = pools.entry(pool.to_string()).or_default();
+
+        match history.prices.back_mut() {
+            Some((last, high, low, close)) if *last == minute => {
+                *high = high.max(price);
+                *low = low.min(price);
+                *close = price;
+            }
+            _ => history.prices.push_back((minute, price, price, price)),
+        }
+        // The newest bucket that ended a day ago is kept: its last price is where the 24h change starts
+        let day_ago = now.saturating_sub(WINDOW_24H);
+        while history.prices.get(1).is_some_and(|&(minute, _, _, _)| (minute + 1) * BUCKET_SECS <= day_ago) {
+            history.prices.pop_front();
+        }
+        prune(&mut history.volume, now, |&(minute, _)| minute);
+
+        let (high, low) = history
+            .prices
+            .iter()
+            .filter(|&&(minute, _, _, _)| !outside_24h(minute, now))
+            .fold((f64::MIN, f64::MAX), |(high, low), &(_, bucket_high, bucket_low, _)| (high.max(bucket_high), low.min(bucket_low)));
+
+        Rolling24h {
+            high,
+            low,
+            volume: self.track_volume.then(|| history.volume.iter().map(|(_, quote)| quote).sum()),
+            change_1m: change_since(&history.prices, now.saturating_sub(60), price),
+            change_5m: change_since(&history.prices, now.saturating_sub(5 * 60), price),
+            change_24h: change_since(&history.prices, day_ago, price),
+        }
+    }
+
+    pub fn on_swap(&self, swap: &SwapEvent) {
+        let quote = match swap.direction {
+            SwapDirection::Buy => swap.amount_in,
+            SwapDirection::Sell => swap.amount_out,
+        };
+        let minute = swap.timestamp / BUCKET_SECS;
+        let mut pools = self.pools.lock().unwrap();
+        let volume = &mut pools.entry(swap.pool.clone()).or_default().volume;
+
+        // Block times can arrive slightly out of order
+        match volume.iter().rposition(|(bucket, _)| *bucket <= minute) {
+            Some(index) if volume[index].0 == minute => volume[index].1 += quote,
+            Some(index) => volume.insert(index + 1, (minute, quote)),
+            None => volume.push_front((minute, quote)),
+        }
+    }
+
+    // Feed decoded swaps from the market bus into the volume buckets
+    pub async fn run(self, mut rx: broadcast::Receiver<MarketEvent>) {
+        loop {
+            match rx.recv().await {
+                Ok(MarketEvent::Swap(swap)) => self.on_swap(&swap),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "rolling stats lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+// Moving average and dispersion of one pool's price over one window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowStats {
+    pub window_secs: u64,
+    // Time-decayed: a price's weight falls to 1/e after window_secs
+    pub ema: f64,
+    // Of the prices published within the window
+    pub stddev: f64,
+    // Square root of the summed squared log returns within the window, scaled
+    // to a year, in percent
+    pub realized_volatility_percent: f64,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsUpdate {
+    pub source: String,
+    pub symbol: String,
+    pub pool: String,
+    pub windows: Vec<WindowStats>,
+    pub timestamp: u64,
+}
+
+struct PoolSeries {
+    source: String,
+    symbol: String,
+    // (received, price) within the longest window, oldest first
+    prices: VecDeque<(Instant, f64)>,
+    // One EMA per window, in the configured order
+    emas: Vec<f64>,
+}
+
+// EMA, standard deviation and realized volatility per pool over each
+// configured window, published every interval_secs as `stats` events
+pub struct StatsTracker {
+    config: StatsConfig,
+    pools: HashMap<String, PoolSeries>,
+}
+
+impl StatsTracker {
+    pub fn new(config: StatsConfig) -> Self {
+        Self { config, pools: HashMap::new() }
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, tx: Arc<broadcast::Sender<MarketEvent>>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick fires immediately, before any pool has reported
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => self.on_price_update(&update, Instant::now()),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "stats tracker lagged, skipped updates");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    for update in self.stats(Instant::now(), unix_now()) {
+                        let _ = tx.send(MarketEvent::Stats(update));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn on_price_update(&mut self, update: &PriceUpdate, now: Instant) {
+        if !(update.price.is_finite() && update.price > 0.0) {
+            return;
+        }
+        let windows = &self.config.windows_secs;
+        let pool = self.pools.entry(update.pool.clone()).or_insert_with(|| PoolSeries {
+            source: update.source.clone(),
+            symbol: update.symbol.clone(),
+            prices: VecDeque::new(),
+            emas: vec![update.price; windows.len()],
+        });
+
+        let elapsed = pool.prices.back().map_or(Duration::ZERO, |(received, _)| now.duration_since(*received));
+        for (ema, window_secs) in pool.emas.iter_mut().zip(windows) {
+            let alpha = 1.0 - (-elapsed.as_secs_f64() / *window_secs as f64).exp();
+            *ema += alpha * (update.price - *ema);
+        }
+
+        pool.prices.push_back((now, update.price));
+        let longest = Duration::from_secs(windows.iter().copied().max().unwrap_or(0));
+        while pool.prices.front().is_some_and(|(received, _)| *received + longest < now) {
+            pool.prices.pop_front();
+        }
+    }
+
+    pub fn stats(&self, now: Instant, timestamp: u64) -> Vec<StatsUpdate> {
+        let mut updates: Vec<StatsUpdate> = self
+            .pools
+            .iter()
+            .map(|(address, pool)| StatsUpdate {
+                source: pool.source.clone(),
+                symbol: pool.symbol.clone(),
+                pool: address.clone(),
+                windows: self
+                    .config
+                    .windows_secs
+                    .iter()
+                    .zip(&pool.emas)
+                    .map(|(&window_secs, &ema)| window_stats(&pool.prices, window_secs, ema, now))
+                    .collect(),
+                timestamp,
+            })
+            .collect();
+        updates.sort_by(|a, b| (&a.symbol, &a.source, &a.pool).cmp(&(&b.symbol, &b.source, &b.pool)));
+        updates
+    }
+}
+
+fn window_stats(prices: &VecDeque<(Instant, f64)>, window_secs: u64, ema: f64, now: Instant) -> WindowStats {
+    let window = Duration::from_secs(window_secs);
+    let prices: Vec<f64> = prices
+        .iter()
+        .filter(|(received, _)| *received + window >= now)
+        .map(|(_, price)| *price)
+        .collect();
+
+    let count = prices.len() as f64;
+    let mean = prices.iter().sum::<f64>() / count.max(1.0);
+    let stddev = if prices.len() > 1 {
+        (prices.iter().map(|price| (price - mean).powi(2)).sum::<f64>() / (count - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let squared_returns: f64 = prices.windows(2).map(|pair| (pair[1] / pair[0]).ln().powi(2)).sum();
+
+    WindowStats {
+        window_secs,
+        ema,
+        stddev,
+        realized_volatility_percent: (squared_returns * YEAR_SECS / window_secs as f64).sqrt() * 100.0,
+        samples: prices.len(),
+    }
+}
+
+fn prune<T>(buckets: &mut VecDeque<T>, now: u64, minute: impl Fn(&T) -> u64) {
+    while buckets.front().is_some_and(|bucket| outside_24h(minute(bucket), now)) {
+        buckets.pop_front();
+    }
+}
+
+fn outside_24h(minute: u64, now: u64) -> bool {
+    now.saturating_sub(minute * BUCKET_SECS) > WINDOW_24H
+}
+
+// Percent change from the last price of the newest bucket that ended by
+// `since`, so at most a minute older than asked for. None when the history
+// doesn't reach back that far.
+fn change_since(prices: &VecDeque<(u64, f64, f64, f64)>, since: u64, price: f64) -> Option<f64> {
+    let (_, _, _, then) = prices.iter().rev().find(|&&(minute, _, _, _)| (minute + 1) * BUCKET_SECS <= since)?;
+    (*then > 0.0).then(|| (price - then) / then * 100.0)
+}
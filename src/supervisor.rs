@@ -0,0 +1,207 @@
+use futures::FutureExt;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+use crate::config::SupervisorConfig;
+use crate::exit;
+use crate::health::HealthEvent;
+use crate::latency;
+use crate::metrics;
+
+// Latest heartbeat of every supervised task, for stall diagnostics
+static HEARTBEATS: LazyLock<Mutex<BTreeMap<&'static str, Heartbeat>>> = LazyLock::new(Default::default);
+
+static STARTED: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+// Progress signal of a supervised task: its main loop beats once per iteration
+#[derive(Clone)]
+pub struct Heartbeat {
+    // Milliseconds since STARTED
+    last: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    fn new() -> Self {
+        let heartbeat = Self { last: Arc::new(AtomicU64::new(0)) };
+        heartbeat.beat();
+        heartbeat
+    }
+
+    pub fn beat(&self) {
+        self.last.store(STARTED.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn age(&self) -> Duration {
+        STARTED.elapsed().saturating_sub(Duration::from_millis(self.last.load(Ordering::Relaxed)))
+    }
+}
+
+// How the watchdog decides whether a supervised task is stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liveness {
+    // Event-driven and possibly idle for long stretches; never restarted for stalling
+    Unchecked,
+    // Iterates continuously (polls, timers); stuck once it stops beating for stall_secs
+    Loop,
+}
+
+enum Outcome {
+    Ended,
+    Panicked(String, String, Box<dyn std::any::Any + Send>),
+    Stalled(Duration),
+}
+
+// Run a monitor or sink task, catching panics instead of letting them take the
+// task down for good. Each panic is published as a health event with its
+// backtrace and the task is rebuilt from `task` after the restart delay. Past
+// `max_restarts` within the window the panic is propagated, so the JoinHandle
+// reports it like an unsupervised task would. A task returning normally is not
+// restarted.
+//
+// Tasks with Liveness::Loop are also watched for stalls: one that stops beating
+// its heartbeat is aborted and rebuilt under the same policy. A task wedged in
+// a blocking call keeps its thread until the call returns, but its replacement
+// starts right away.
+pub fn supervise<F, Fut>(
+    name: &'static str,
+    policy: SupervisorConfig,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+    liveness: Liveness,
+    task: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Heartbeat) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let window = Duration::from_secs(policy.restart_window_secs);
+        let stall_after = Duration::from_secs(policy.stall_secs);
+        let mut restarts: VecDeque<Instant> = VecDeque::new();
+
+        loop {
+            let heartbeat = Heartbeat::new();
+            HEARTBEATS.lock().unwrap().insert(name, heartbeat.clone());
+
+            // Everything the task logs carries its name, e.g. task{name="Raydium"}
+            let future = latency::track_rpc_time(task(heartbeat.clone())).instrument(tracing::info_span!("task", name));
+            let mut handle = tokio::spawn(async move {
+                AssertUnwindSafe(future).catch_unwind().await.map_err(|payload| {
+                    let (message, backtrace) =
+                        exit::take_thread_panic().unwrap_or_else(|| (payload_message(&*payload), String::new()));
+                    (message, backtrace, payload)
+                })
+            });
+
+            let mut watchdog = tokio::time::interval(Duration::from_secs(policy.watchdog_interval_secs.max(1)));
+            let outcome = loop {
+                tokio::select! {
+                    result = &mut handle => break match result {
+                        Ok(Ok(())) => Outcome::Ended,
+                        Ok(Err((message, backtrace, payload))) => Outcome::Panicked(message, backtrace, payload),
+                        Err(e) => Outcome::Panicked(e.to_string(), String::new(), Box::new(e.to_string())),
+                    },
+                    _ = watchdog.tick(), if liveness == Liveness::Loop => {
+                        let age = heartbeat.age();
+                        if age >= stall_after {
+                            handle.abort();
+                            break Outcome::Stalled(age);
+                        }
+                    }
+                }
+            };
+
+            let now = Instant::now();
+            while restarts.front().is_some_and(|at| now.duration_since(*at) > window) {
+                restarts.pop_front();
+            }
+
+            match outcome {
+                Outcome::Ended => return,
+                Outcome::Panicked(message, backtrace, payload) => {
+                    tracing::error!(task = name, message = %message, "task panicked");
+                    let _ = health_tx.send(HealthEvent::panicked(name, &message, &backtrace, restarts.len() as u32));
+                    if restarts.len() >= policy.max_restarts {
+                        tracing::error!(task = name, restarts = restarts.len() + 1, restart_window_secs = policy.restart_window_secs, "task panicked too often, not restarting");
+                        std::panic::resume_unwind(payload);
+                    }
+                }
+                Outcome::Stalled(age) => {
+                    let diagnostic = stall_diagnostic(name, age, &health_tx);
+                    tracing::warn!(task = name, diagnostic = %diagnostic, "task stalled");
+                    let _ = health_tx.send(HealthEvent::stalled(name, &diagnostic, restarts.len() as u32));
+                    if restarts.len() >= policy.max_restarts {
+                        tracing::error!(task = name, restarts = restarts.len() + 1, restart_window_secs = policy.restart_window_secs, "task stalled too often, not restarting");
+                        return;
+                    }
+                }
+            }
+            restarts.push_back(now);
+
+            tokio::time::sleep(Duration::from_secs(policy.restart_delay_secs)).await;
+            metrics::inc_reconnect(name);
+            tracing::info!(task = name, "restarting task");
+        }
+    })
+}
+
+// Warn while a bus keeps more than half its capacity queued, i.e. some consumer
+// isn't keeping up. Which one can't be told from the sender side, so the
+// warning lists every task's heartbeat age.
+pub async fn watch_bus<T>(name: &'static str, bus: Arc<broadcast::Sender<T>>, capacity: usize, policy: SupervisorConfig) {
+    let stall_after = Duration::from_secs(policy.stall_secs);
+    let mut check = tokio::time::interval(Duration::from_secs(policy.watchdog_interval_secs.max(1)));
+    let mut backed_up_since: Option<Instant> = None;
+
+    loop {
+        check.tick().await;
+        let queued = bus.len();
+        if queued * 2 < capacity {
+            backed_up_since = None;
+            continue;
+        }
+
+        let since = *backed_up_since.get_or_insert_with(Instant::now);
+        if since.elapsed() >= stall_after {
+            tracing::warn!(task = name, queued = %queued, capacity = %capacity, stuck_secs = since.elapsed().as_secs(), heartbeats = %heartbeat_ages(), "bus not draining");
+            // Warn again after another stall period if it persists
+            backed_up_since = Some(Instant::now());
+        }
+    }
+}
+
+fn stall_diagnostic(name: &str, age: Duration, health_tx: &broadcast::Sender<HealthEvent>) -> String {
+    format!(
+        "{} made no progress for {}s, restarting; health bus {} queued; heartbeats: {}",
+        name,
+        age.as_secs(),
+        health_tx.len(),
+        heartbeat_ages(),
+    )
+}
+
+fn heartbeat_ages() -> String {
+    HEARTBEATS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, heartbeat)| format!("{} {}s", name, heartbeat.age().as_secs()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
@@ -0,0 +1,239 @@
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_lifinity_amm_v2_decoder::accounts::amm::Amm as LifinityAmm;
+use carbon_meteora_dlmm_decoder::accounts::lb_pair::LbPair;
+use carbon_raydium_amm_v4_decoder::accounts::amm_info::AmmInfo as RaydiumAmmInfo;
+use carbon_raydium_clmm_decoder::accounts::pool_state::PoolState as RaydiumClmmPool;
+use futures_util::StreamExt;
+use orca_whirlpools_client::Whirlpool;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionTokenBalance};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::PoolConfig;
+use crate::events::MarketEvent;
+use crate::metrics;
+use crate::pump::PoolHeader as PumpSwapPool;
+use crate::rpc::RpcProvider;
+use crate::saber::PoolHeader as SaberPool;
+use crate::transactions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapDirection {
+    Buy,   // Quote in, base out
+    Sell,  // Base in, quote out
+}
+
+// One swap against a watched pool, amounts in UI units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapEvent {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub signature: String,
+    pub slot: u64,
+    pub signer: String,
+    pub direction: SwapDirection,
+    pub amount_in: f64,
+    pub amount_out: f64,
+    pub price: f64,           // Quote per base actually paid or received
+    pub timestamp: u64,
+}
+
+// Token vaults of a pool: (base, quote)
+type PoolVaults = (Pubkey, Pubkey);
+
+// Subscribe to the logs of every transaction touching a watched pool, and for
+// the ones that log a swap instruction, read the swap from the pool's vault
+// balance changes. Vaults moving in opposite directions is a swap; both moving
+// the same way is a deposit or withdrawal and is left to the liquidity tracker.
+pub struct SwapMonitor {
+    rpc: RpcProvider,
+    rpc_client: RpcClient,
+    // (source, pool config)
+    pools: Vec<(String, PoolConfig)>,
+}
+
+impl SwapMonitor {
+    pub fn new(pools: Vec<(String, PoolConfig)>, rpc: &RpcProvider) -> Self {
+        Self {
+            rpc: rpc.clone(),
+            rpc_client: rpc.client(CommitmentConfig::confirmed()),
+            pools,
+        }
+    }
+
+    pub async fn run(self, tx: Arc<broadcast::Sender<MarketEvent>>) {
+        let monitor = Arc::new(self);
+
+        // logsSubscribe accepts a single address per subscription
+        let handles: Vec<_> = (0..monitor.pools.len())
+            .map(|index| {
+                let monitor = monitor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let (source, pool) = &monitor.pools[index];
+                    loop {
+                        if let Err(e) = monitor.watch_pool(source, pool, &tx).await {
+                            tracing::error!(source = %source, symbol = %pool.symbol, error = %e, "swap subscription error");
+                            metrics::inc_rpc_error("Swaps");
+                        }
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                        metrics::inc_reconnect("Swaps");
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    async fn watch_pool(&self, source: &str, pool: &PoolConfig, tx: &broadcast::Sender<MarketEvent>) -> anyhow::Result<()> {
+        let address = pool.pubkey()?;
+        let vaults = self.resolve_vaults(source, &address).await?;
+
+        let pubsub_client = PubsubClient::new(&self.rpc.ws_url()).await?;
+        let (mut notifications, unsubscribe) = pubsub_client
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![address.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+
+        tracing::info!(source = %source, symbol = %pool.symbol, "watching swaps");
+
+        while let Some(notification) = notifications.next().await {
+            // Skip failed transactions and anything that isn't a swap before paying for getTransaction
+            if notification.value.err.is_some() || !logs_swap(&notification.value.logs) {
+                continue;
+            }
+
+            let signature = Signature::from_str(&notification.value.signature)?;
+            match transactions::fetch_transaction(&self.rpc_client, &signature).await {
+                Ok(transaction) => {
+                    if let Some(event) = decode_swap(source, pool, vaults, &notification.value.signature, &transaction) {
+                        let _ = tx.send(MarketEvent::Swap(event));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, signature = %signature, "failed to fetch transaction");
+                    metrics::inc_rpc_error("Swaps");
+                }
+            }
+        }
+
+        unsubscribe().await;
+        Err(anyhow::anyhow!("Subscription closed"))
+    }
+
+    async fn resolve_vaults(&self, source: &str, address: &Pubkey) -> anyhow::Result<PoolVaults> {
+        let data = self.rpc_client.get_account_data(address).await?;
+
+        match source {
+            "Raydium" => {
+                let amm_info = <RaydiumAmmInfo as CarbonDeserialize>::deserialize(&data)
+                    .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium pool {}", address))?;
+                Ok((amm_info.token_coin, amm_info.token_pc))
+            }
+            "Raydium CLMM" => {
+                let pool_state = <RaydiumClmmPool as CarbonDeserialize>::deserialize(&data)
+                    .ok_or_else(|| anyhow::anyhow!("Failed to parse Raydium CLMM pool {}", address))?;
+                Ok((pool_state.token_vault0, pool_state.token_vault1))
+            }
+            "Orca" => {
+                let whirlpool = Whirlpool::from_bytes(&data)?;
+                Ok((whirlpool.token_vault_a, whirlpool.token_vault_b))
+            }
+            "Meteora" => {
+                let lb_pair = <LbPair as CarbonDeserialize>::deserialize(&data)
+                    .ok_or_else(|| anyhow::anyhow!("Failed to parse Meteora pool {}", address))?;
+                Ok((lb_pair.reserve_x, lb_pair.reserve_y))
+            }
+            "Lifinity" => {
+                let amm = <LifinityAmm as CarbonDeserialize>::deserialize(&data)
+                    .ok_or_else(|| anyhow::anyhow!("Failed to parse Lifinity pool {}", address))?;
+                Ok((amm.token_a_account, amm.token_b_account))
+            }
+            "PumpSwap" => {
+                let pool = PumpSwapPool::parse(&data)?;
+                Ok((pool.base_vault, pool.quote_vault))
+            }
+            "Saber" => {
+                let pool = SaberPool::parse(&data)?;
+                Ok((pool.token_a_reserves, pool.token_b_reserves))
+            }
+            _ => Err(anyhow::anyhow!("Unknown source {}", source)),
+        }
+    }
+}
+
+// Swap instructions as logged by each program: Anchor programs (Whirlpool, DLMM,
+// Raydium CLMM, Lifinity) and Saber log the instruction name, PumpSwap names its
+// swaps Buy and Sell, Raydium AMM v4 emits a ray_log on swaps
+fn logs_swap(logs: &[String]) -> bool {
+    logs.iter().any(|line| {
+        line.starts_with("Program log: Instruction: Swap")
+            || line.starts_with("Program log: Instruction: TwoHopSwap")
+            || line.starts_with("Program log: Instruction: Buy")
+            || line.starts_with("Program log: Instruction: Sell")
+            || line.starts_with("Program log: ray_log")
+    })
+}
+
+fn decode_swap(
+    source: &str,
+    pool: &PoolConfig,
+    (base_vault, quote_vault): PoolVaults,
+    signature: &str,
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<SwapEvent> {
+    let meta = transaction.transaction.meta.as_ref()?;
+    let account_keys = transactions::account_keys(transaction)?;
+    let base_index = account_keys.iter().position(|key| *key == base_vault)?;
+    let quote_index = account_keys.iter().position(|key| *key == quote_vault)?;
+
+    let (pre, post) = transactions::token_balances(meta);
+    let amount = |balances: &[UiTransactionTokenBalance], index: usize| {
+        balances
+            .iter()
+            .find(|balance| balance.account_index as usize == index)
+            .map(transactions::ui_amount)
+            .unwrap_or(0.0)
+    };
+    let base_delta = amount(&post, base_index) - amount(&pre, base_index);
+    let quote_delta = amount(&post, quote_index) - amount(&pre, quote_index);
+
+    // Net effect on the pool; several swaps through it in one transaction are combined
+    let (direction, amount_in, amount_out) = if base_delta < 0.0 && quote_delta > 0.0 {
+        (SwapDirection::Buy, quote_delta, -base_delta)
+    } else if base_delta > 0.0 && quote_delta < 0.0 {
+        (SwapDirection::Sell, base_delta, -quote_delta)
+    } else {
+        return None;
+    };
+
+    Some(SwapEvent {
+        symbol: pool.symbol.clone(),
+        source: source.to_string(),
+        pool: pool.address.clone(),
+        signature: signature.to_string(),
+        slot: transaction.slot,
+        signer: account_keys.first()?.to_string(),
+        direction,
+        amount_in,
+        amount_out,
+        price: quote_delta.abs() / base_delta.abs(),
+        timestamp: transactions::block_timestamp(transaction),
+    })
+}
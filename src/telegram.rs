@@ -0,0 +1,51 @@
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::alerts::Alert;
+use crate::config::TelegramConfig;
+
+// Telegram bot sink: forwards every alert to the configured chats
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    config: TelegramConfig,
+}
+
+impl TelegramNotifier {
+    pub fn new(config: TelegramConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub async fn run(self, mut rx: broadcast::Receiver<Alert>) {
+        loop {
+            match rx.recv().await {
+                Ok(alert) => {
+                    if let Err(e) = self.send(&alert.text()).await {
+                        tracing::error!(error = %e, "Telegram alert failed");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "Telegram sink lagged, dropped alerts");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub async fn send(&self, text: &str) -> anyhow::Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.config.bot_token);
+
+        for chat_id in &self.config.chat_ids {
+            self.client
+                .post(&url)
+                .json(&json!({ "chat_id": chat_id, "text": text }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::{Result, WatcherError};
+
+// Minimal SPL Token account parsing, so monitors don't need the spl-token crate
+
+// Mint layout: mint_authority (36) | supply (8) | decimals (1) | is_initialized (1) | freeze_authority (36)
+const MINT_LEN: usize = 82;
+const MINT_SUPPLY_OFFSET: usize = 36;
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+pub fn mint_decimals(data: &[u8]) -> Result<u8> {
+    if data.len() < MINT_LEN {
+        return Err(WatcherError::DecodeError(format!("Invalid mint account data size: {} bytes", data.len())));
+    }
+
+    Ok(data[MINT_DECIMALS_OFFSET])
+}
+
+pub fn mint_supply(data: &[u8]) -> Result<u64> {
+    if data.len() < MINT_LEN {
+        return Err(WatcherError::DecodeError(format!("Invalid mint account data size: {} bytes", data.len())));
+    }
+
+    let supply = &data[MINT_SUPPLY_OFFSET..MINT_SUPPLY_OFFSET + 8];
+    Ok(u64::from_le_bytes(supply.try_into().map_err(WatcherError::decode)?))
+}
+
+// Token account layout: mint (32) | owner (32) | amount (8) | ...
+// Token-2022 accounts share the layout and append extensions past 165 bytes.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(WatcherError::DecodeError(format!("Invalid token account data size: {} bytes", data.len())));
+    }
+
+    let amount = &data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8];
+    Ok(u64::from_le_bytes(amount.try_into().map_err(WatcherError::decode)?))
+}
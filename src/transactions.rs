@@ -0,0 +1,71 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, UiLoadedAddresses, UiTransactionEncoding, UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+use std::str::FromStr;
+
+use crate::clock::unix_now;
+
+// Helpers for decoding transactions announced over logsSubscribe
+
+// A transaction is announced over the websocket before getTransaction can serve it
+const FETCH_ATTEMPTS: u32 = 5;
+
+pub async fn fetch_transaction(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> anyhow::Result<EncodedConfirmedTransactionWithStatusMeta> {
+    let mut attempt = 1;
+    loop {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        match rpc_client.get_transaction_with_config(signature, config).await {
+            Ok(transaction) => return Ok(transaction),
+            Err(_) if attempt < FETCH_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(tokio::time::Duration::from_millis(400)).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+// Static keys followed by the ones loaded from address lookup tables, in the
+// order token balance account indexes refer to
+pub fn account_keys(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Option<Vec<Pubkey>> {
+    let meta = transaction.transaction.meta.as_ref()?;
+    let decoded = transaction.transaction.transaction.decode()?;
+
+    let mut account_keys = decoded.message.static_account_keys().to_vec();
+    if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone()) {
+        account_keys.extend(
+            loaded.writable.iter().chain(&loaded.readonly).filter_map(|key| Pubkey::from_str(key).ok()),
+        );
+    }
+
+    Some(account_keys)
+}
+
+// (pre, post) token balances; accounts created or closed in the transaction
+// only appear on one side
+pub fn token_balances(meta: &UiTransactionStatusMeta) -> (Vec<UiTransactionTokenBalance>, Vec<UiTransactionTokenBalance>) {
+    (
+        Option::from(meta.pre_token_balances.clone()).unwrap_or_default(),
+        Option::from(meta.post_token_balances.clone()).unwrap_or_default(),
+    )
+}
+
+pub fn ui_amount(balance: &UiTransactionTokenBalance) -> f64 {
+    balance.ui_token_amount.ui_amount_string.parse::<f64>().unwrap_or(0.0)
+}
+
+pub fn block_timestamp(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> u64 {
+    transaction.block_time.map(|t| t as u64).unwrap_or_else(unix_now)
+}
@@ -0,0 +1,298 @@
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind, KeyModifiers};
+use futures_util::StreamExt;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::display;
+use crate::events::MarketEvent;
+use crate::health::HealthEvent;
+use crate::raydium::PriceUpdate;
+
+// Prices kept per pool for its sparkline
+const SPARKLINE_POINTS: usize = 40;
+const SPARKLINE_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// Log lines kept for the bottom pane
+const MAX_LOG_LINES: usize = 500;
+const REDRAW_MS: u64 = 250;
+
+// Log output captured for the dashboard's log pane while it owns the terminal
+#[derive(Clone, Default)]
+pub struct LogLines(Arc<Mutex<VecDeque<String>>>);
+
+impl LogLines {
+    fn last(&self, count: usize) -> Vec<String> {
+        let lines = self.0.lock().unwrap();
+        lines.iter().skip(lines.len().saturating_sub(count)).cloned().collect()
+    }
+}
+
+impl Write for LogLines {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut lines = self.0.lock().unwrap();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            if lines.len() == MAX_LOG_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct PoolRow {
+    update: PriceUpdate,
+    history: VecDeque<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    Degraded,
+    Down,
+    Paused,
+}
+
+struct SourceHealth {
+    status: Status,
+    detail: String,
+    last_update: u64,
+}
+
+#[derive(Default)]
+struct Dashboard {
+    // Keyed by pool address
+    pools: HashMap<String, PoolRow>,
+    sources: BTreeMap<String, SourceHealth>,
+    ready: Option<(usize, usize)>,
+}
+
+// Runs until q or Ctrl+C is pressed; raw mode keeps Ctrl+C from raising SIGINT,
+// so the caller treats a clean return as a shutdown request
+pub async fn run(mut rx: broadcast::Receiver<MarketEvent>, logs: LogLines) {
+    // Also restores the terminal before the panic hook reports a panic
+    let mut terminal = ratatui::init();
+    let mut dashboard = Dashboard::default();
+    let mut keys = EventStream::new();
+    let mut redraw = tokio::time::interval(tokio::time::Duration::from_millis(REDRAW_MS));
+    redraw.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => dashboard.on_event(event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped = skipped, "dashboard lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            key = keys.next() => match key {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                    if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        break;
+                    }
+                }
+                Some(Ok(_)) => {}
+                // No terminal to read from any more
+                Some(Err(_)) | None => break,
+            },
+            _ = redraw.tick() => draw(&mut terminal, &dashboard, &logs),
+        }
+    }
+    ratatui::restore();
+}
+
+fn draw(terminal: &mut DefaultTerminal, dashboard: &Dashboard, logs: &LogLines) {
+    if let Err(e) = terminal.draw(|frame| render(frame, dashboard, logs)) {
+        tracing::error!(error = %e, "failed to draw the dashboard");
+    }
+}
+
+impl Dashboard {
+    fn on_event(&mut self, event: MarketEvent) {
+        match event {
+            MarketEvent::Price(update) => self.on_price(update),
+            MarketEvent::Health(event) => self.on_health(event),
+            _ => {}
+        }
+    }
+
+    fn on_price(&mut self, update: PriceUpdate) {
+        let source = self.source(&update.source);
+        source.last_update = update.timestamp;
+        // A fresh price is the best sign a source has recovered
+        if source.status == Status::Down {
+            source.status = Status::Ok;
+            source.detail.clear();
+        }
+
+        let row = self.pools.entry(update.pool.clone()).or_insert_with(|| PoolRow {
+            update: update.clone(),
+            history: VecDeque::with_capacity(SPARKLINE_POINTS),
+        });
+        if update.price > 0.0 {
+            if row.history.len() == SPARKLINE_POINTS {
+                row.history.pop_front();
+            }
+            row.history.push_back(update.price);
+        }
+        row.update = update;
+    }
+
+    fn on_health(&mut self, event: HealthEvent) {
+        let (source, status, detail) = match event {
+            HealthEvent::Ready { pools_ready, pools_total, .. } => {
+                self.ready = Some((pools_ready, pools_total));
+                return;
+            }
+            HealthEvent::Reconnecting { source, error, .. } => (source, Status::Down, error),
+            HealthEvent::Degraded { source, failures, error, .. } => (source, Status::Degraded, format!("{} failures: {}", failures, error)),
+            HealthEvent::PoolDropped { source, symbol, error, .. } => (source, Status::Degraded, format!("dropped {}: {}", symbol, error)),
+            HealthEvent::Stopped { source, reason, .. } => (source, Status::Down, format!("stopped: {}", reason)),
+            HealthEvent::Panicked { source, message, .. } => (source, Status::Down, format!("panicked: {}", message)),
+            HealthEvent::Stalled { source, diagnostic, .. } => (source, Status::Down, format!("stalled: {}", diagnostic)),
+            HealthEvent::Stale { source, silent_secs, .. } => (source, Status::Degraded, format!("no update for {}s", silent_secs)),
+            HealthEvent::Recovered { source, .. } | HealthEvent::Resumed { source, .. } => (source, Status::Ok, String::new()),
+            HealthEvent::Paused { source, .. } => (source, Status::Paused, String::new()),
+            HealthEvent::DecoderMismatch { source, field, .. } => (source, Status::Degraded, format!("decoder mismatch on {}", field)),
+            HealthEvent::ProgramUpgraded { source, .. } => (source, Status::Degraded, "program upgraded".to_string()),
+        };
+        let health = self.source(&source);
+        health.status = status;
+        health.detail = detail;
+    }
+
+    fn source(&mut self, source: &str) -> &mut SourceHealth {
+        self.sources.entry(source.to_string()).or_insert(SourceHealth {
+            status: Status::Ok,
+            detail: String::new(),
+            last_update: 0,
+        })
+    }
+
+    // Median price of every pool quoting each symbol, what the spreads are taken against
+    fn medians(&self) -> HashMap<&str, f64> {
+        let mut prices: HashMap<&str, Vec<f64>> = HashMap::new();
+        for row in self.pools.values().filter(|row| row.update.price > 0.0) {
+            prices.entry(row.update.symbol.as_str()).or_default().push(row.update.price);
+        }
+        prices
+            .into_iter()
+            .map(|(symbol, mut prices)| {
+                prices.sort_by(|a, b| a.total_cmp(b));
+                let middle = prices.len() / 2;
+                let median = if prices.len() % 2 == 0 { (prices[middle - 1] + prices[middle]) / 2.0 } else { prices[middle] };
+                (symbol, median)
+            })
+            .collect()
+    }
+}
+
+fn render(frame: &mut Frame, dashboard: &Dashboard, logs: &LogLines) {
+    let health_rows = dashboard.sources.len().max(1) as u16 + 3;
+    let [pools_area, health_area, logs_area] =
+        Layout::vertical([Constraint::Min(8), Constraint::Length(health_rows), Constraint::Length(10)]).areas(frame.area());
+
+    let medians = dashboard.medians();
+    let mut rows: Vec<&PoolRow> = dashboard.pools.values().collect();
+    rows.sort_by(|a, b| (&a.update.symbol, &a.update.source).cmp(&(&b.update.symbol, &b.update.source)));
+    let pool_rows = rows.iter().map(|row| {
+        let update = &row.update;
+        let (base, quote) = update.symbol.split_once('/').unwrap_or((update.symbol.as_str(), ""));
+        let spread_bps = medians
+            .get(update.symbol.as_str())
+            .filter(|median| **median > 0.0 && update.price > 0.0)
+            .map(|median| (update.price - median) / median * 10_000.0);
+        let change_color = if update.change_percent > 0.0 {
+            Color::Green
+        } else if update.change_percent < 0.0 {
+            Color::Red
+        } else {
+            Color::Reset
+        };
+        Row::new(vec![
+            Line::from(update.symbol.clone()),
+            Line::from(update.source.clone()),
+            Line::from(format!("${}", display::number(update.price, 4))).right_aligned(),
+            Line::from(format!("{}%", display::signed(update.change_percent, 2))).right_aligned().fg(change_color),
+            Line::from(format!(
+                "{} {} / {} {}",
+                display::number(update.base_reserve as f64 / 10_f64.powi(update.base_decimals as i32), 2),
+                base,
+                display::number(update.quote_reserve as f64 / 10_f64.powi(update.quote_decimals as i32), 2),
+                quote,
+            )),
+            Line::from(spread_bps.map(|bps| format!("{} bps", display::signed(bps, 1))).unwrap_or_default()).right_aligned(),
+            Line::from(sparkline(&row.history)).cyan(),
+        ])
+    });
+    let header = Row::new(["Symbol", "Source", "Price", "Change", "Reserves", "vs median", "Recent"])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+    let title = match dashboard.ready {
+        Some((ready, total)) => format!(" Pools ({}/{} ready) — q to quit ", ready, total),
+        None => " Pools (warming up) — q to quit ".to_string(),
+    };
+    let widths = [
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Length(9),
+        Constraint::Min(24),
+        Constraint::Length(12),
+        Constraint::Length(SPARKLINE_POINTS as u16),
+    ];
+    frame.render_widget(Table::new(pool_rows, widths).header(header).block(Block::bordered().title(title)), pools_area);
+
+    let now = unix_now();
+    let health_rows = dashboard.sources.iter().map(|(source, health)| {
+        let (marker, color) = match health.status {
+            Status::Ok => ("● ok", Color::Green),
+            Status::Degraded => ("● degraded", Color::Yellow),
+            Status::Down => ("● down", Color::Red),
+            Status::Paused => ("● paused", Color::Blue),
+        };
+        let age = if health.last_update == 0 {
+            "no data".to_string()
+        } else {
+            format!("{}s ago", now.saturating_sub(health.last_update))
+        };
+        Row::new(vec![Line::from(source.clone()), Line::from(marker).fg(color), Line::from(age), Line::from(health.detail.clone())])
+    });
+    let widths = [Constraint::Length(14), Constraint::Length(12), Constraint::Length(10), Constraint::Min(20)];
+    let header = Row::new(["Source", "Status", "Last price", "Detail"]).style(Style::new().add_modifier(Modifier::BOLD));
+    frame.render_widget(Table::new(health_rows, widths).header(header).block(Block::bordered().title(" Monitors ")), health_area);
+
+    let visible = logs_area.height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = logs.last(visible).into_iter().map(ListItem::new).collect();
+    frame.render_widget(List::new(items).block(Block::bordered().title(" Log ")), logs_area);
+}
+
+// Block characters scaled between the lowest and highest recent price
+fn sparkline(history: &VecDeque<f64>) -> String {
+    let low = history.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = history.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = high - low;
+    history
+        .iter()
+        .map(|price| {
+            if range <= 0.0 {
+                return SPARKLINE_BLOCKS[SPARKLINE_BLOCKS.len() / 2];
+            }
+            let level = ((price - low) / range * (SPARKLINE_BLOCKS.len() - 1) as f64).round() as usize;
+            SPARKLINE_BLOCKS[level.min(SPARKLINE_BLOCKS.len() - 1)]
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::events::MarketEvent;
+
+pub const USD_STABLES: [&str; 2] = ["USDC", "USDT"];
+
+// Pool value in USD: both reserves in quote terms, for pools quoted in a USD
+// stable. Other quotes (e.g. */SOL) get None rather than a guessed rate.
+// Display-only, so f64 is enough; a non-finite total is dropped.
+pub fn pool_tvl_usd(
+    symbol: &str,
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+    price: f64,
+) -> Option<f64> {
+    let (_, quote) = symbol.split_once('/')?;
+    if !USD_STABLES.contains(&quote) || price <= 0.0 {
+        return None;
+    }
+
+    let base_amount = base_reserve as f64 / 10_f64.powi(base_decimals as i32);
+    let quote_amount = quote_reserve as f64 / 10_f64.powi(quote_decimals as i32);
+    Some(quote_amount + base_amount * price).filter(|tvl| tvl.is_finite())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueTvl {
+    pub source: String,
+    pub pool: String,
+    pub tvl_usd: f64,
+    pub share_percent: f64,  // Of the symbol's total across venues
+}
+
+// Latest TVL of every venue quoting one symbol, largest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TvlSummary {
+    pub symbol: String,
+    pub total_usd: f64,
+    pub venues: Vec<VenueTvl>,
+    pub timestamp: u64,
+}
+
+struct PoolTvl {
+    symbol: String,
+    source: String,
+    tvl_usd: f64,
+}
+
+// Periodic cross-venue TVL comparison built from the price stream
+pub struct TvlReporter {
+    // pool address -> latest TVL of that pool
+    pools: HashMap<String, PoolTvl>,
+}
+
+impl TvlReporter {
+    pub fn new() -> Self {
+        Self { pools: HashMap::new() }
+    }
+
+    pub async fn run(
+        mut self,
+        interval_secs: u64,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<TvlSummary>>,
+    ) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs.max(1)));
+        // The first tick fires immediately, before any pool has reported
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => {
+                        if let Some(tvl_usd) = update.tvl_usd {
+                            self.pools.insert(update.pool, PoolTvl { symbol: update.symbol, source: update.source, tvl_usd });
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "TVL reporter lagged, skipped events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    for summary in self.summaries(unix_now()) {
+                        let _ = tx.send(summary);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn summaries(&self, now: u64) -> Vec<TvlSummary> {
+        let mut by_symbol: BTreeMap<&str, Vec<(&str, &PoolTvl)>> = BTreeMap::new();
+        for (pool, tvl) in &self.pools {
+            by_symbol.entry(tvl.symbol.as_str()).or_default().push((pool.as_str(), tvl));
+        }
+
+        by_symbol
+            .into_iter()
+            .map(|(symbol, pools)| {
+                let total_usd: f64 = pools.iter().map(|(_, tvl)| tvl.tvl_usd).sum();
+                let mut venues: Vec<VenueTvl> = pools
+                    .into_iter()
+                    .map(|(pool, tvl)| VenueTvl {
+                        source: tvl.source.clone(),
+                        pool: pool.to_string(),
+                        tvl_usd: tvl.tvl_usd,
+                        share_percent: if total_usd > 0.0 { tvl.tvl_usd / total_usd * 100.0 } else { 0.0 },
+                    })
+                    .collect();
+                venues.sort_by(|a, b| b.tvl_usd.total_cmp(&a.tvl_usd));
+
+                TvlSummary {
+                    symbol: symbol.to_string(),
+                    total_usd,
+                    venues,
+                    timestamp: now,
+                }
+            })
+            .collect()
+    }
+}
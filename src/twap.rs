@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::config::TwapConfig;
+use crate::events::MarketEvent;
+use crate::raydium::PriceUpdate;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapWindow {
+    pub window_secs: u64,
+    pub price: f64,
+    // Less than window_secs until the pool has been watched for a whole window
+    pub covered_secs: f64,
+}
+
+// Time-weighted average prices of one pool, one per configured window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapUpdate {
+    pub source: String,
+    pub symbol: String,
+    pub pool: String,
+    pub windows: Vec<TwapWindow>,
+    pub timestamp: u64,
+}
+
+struct PoolPrices {
+    source: String,
+    symbol: String,
+    // (received, price), oldest first; each price holds until the next one
+    samples: VecDeque<(Instant, f64)>,
+}
+
+// Rolling TWAPs per pool. Every price counts for as long as it stood, so a
+// price pushed for a single block barely moves the average, unlike the last
+// price. Published every interval_secs for every pool that has a price.
+pub struct TwapTracker {
+    config: TwapConfig,
+    // pool address -> prices within the longest window
+    pools: HashMap<String, PoolPrices>,
+}
+
+impl TwapTracker {
+    pub fn new(config: TwapConfig) -> Self {
+        Self { config, pools: HashMap::new() }
+    }
+
+    pub async fn run(mut self, mut rx: broadcast::Receiver<MarketEvent>, tx: Arc<broadcast::Sender<MarketEvent>>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick fires immediately, before any pool has reported
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(MarketEvent::Price(update)) => self.on_price_update(&update, Instant::now()),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "TWAP tracker lagged, skipped updates");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    for update in self.twaps(Instant::now(), unix_now()) {
+                        let _ = tx.send(MarketEvent::Twap(update));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn on_price_update(&mut self, update: &PriceUpdate, now: Instant) {
+        if !(update.price.is_finite() && update.price > 0.0) {
+            return;
+        }
+        let pool = self.pools.entry(update.pool.clone()).or_insert_with(|| PoolPrices {
+            source: update.source.clone(),
+            symbol: update.symbol.clone(),
+            samples: VecDeque::new(),
+        });
+        pool.samples.push_back((now, update.price));
+
+        // Keep the newest sample at or before the longest window's start: it
+        // is the price standing when the window opens
+        let longest = Duration::from_secs(self.config.windows_secs.iter().copied().max().unwrap_or(0));
+        while pool.samples.len() > 1 && pool.samples[1].0 + longest <= now {
+            pool.samples.pop_front();
+        }
+    }
+
+    pub fn twaps(&self, now: Instant, timestamp: u64) -> Vec<TwapUpdate> {
+        let mut updates: Vec<TwapUpdate> = self
+            .pools
+            .iter()
+            .map(|(address, pool)| TwapUpdate {
+                source: pool.source.clone(),
+                symbol: pool.symbol.clone(),
+                pool: address.clone(),
+                windows: self
+                    .config
+                    .windows_secs
+                    .iter()
+                    .filter_map(|&window_secs| twap(&pool.samples, Duration::from_secs(window_secs), now).map(|(price, covered)| TwapWindow {
+                        window_secs,
+                        price,
+                        covered_secs: covered.as_secs_f64(),
+                    }))
+                    .collect(),
+                timestamp,
+            })
+            .filter(|update| !update.windows.is_empty())
+            .collect();
+        updates.sort_by(|a, b| (&a.symbol, &a.source, &a.pool).cmp(&(&b.symbol, &b.source, &b.pool)));
+        updates
+    }
+}
+
+// Average over [now - window, now] of the price standing at each instant, and
+// how much of the window the samples cover. None before the first sample.
+fn twap(samples: &VecDeque<(Instant, f64)>, window: Duration, now: Instant) -> Option<(f64, Duration)> {
+    let start = now.checked_sub(window).unwrap_or(now);
+    let mut weighted = 0.0;
+    let mut covered = Duration::ZERO;
+    for (index, &(received, price)) in samples.iter().enumerate() {
+        let until = samples.get(index + 1).map_or(now, |(next, _)| *next);
+        let from = received.max(start);
+        if until <= from {
+            continue;
+        }
+        let span = until - from;
+        weighted += price * span.as_secs_f64();
+        covered += span;
+    }
+
+    if covered.is_zero() {
+        // Only a sample from this very instant: it is the whole history
+        return samples.back().filter(|(received, _)| *received >= start).map(|(_, price)| (*price, Duration::ZERO));
+    }
+    Some((weighted / covered.as_secs_f64(), covered))
+}
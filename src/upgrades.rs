@@ -0,0 +1,128 @@
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::UpgradesConfig;
+use crate::health::HealthEvent;
+use crate::programs;
+use crate::rpc::RpcProvider;
+
+// UpgradeableLoaderState is bincode: a u32 variant tag, then the variant's fields.
+// Program { programdata_address } points at the ProgramData account, which
+// starts with the slot the program was last deployed at.
+const PROGRAM_TAG: u32 = 2;
+const PROGRAMDATA_TAG: u32 = 3;
+// Tag and deployment slot; the program binary after them can be megabytes
+const PROGRAMDATA_HEADER_LEN: usize = 12;
+
+// Every interval, read the ProgramData account of each watched DEX program and
+// compare its deployment slot with the last one seen. A newer slot means the
+// program was upgraded and the account layouts our decoders assume may have
+// changed under them.
+pub async fn run(
+    config: UpgradesConfig,
+    sources: Vec<String>,
+    rpc: RpcProvider,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+) {
+    let rpc_client = rpc.client(CommitmentConfig::confirmed());
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(config.interval_secs.max(1)));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    // (source, program, programdata), resolved once the program accounts can be read
+    let mut watched: Vec<(String, Pubkey, Pubkey)> = Vec::new();
+    let mut deployed: HashMap<Pubkey, u64> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        if watched.is_empty() {
+            match resolve_programdata(&rpc_client, &sources).await {
+                Ok(resolved) => watched = resolved,
+                Err(e) => {
+                    tracing::warn!(error = %e, "program upgrade watch could not read the DEX programs");
+                    continue;
+                }
+            }
+            if watched.is_empty() {
+                tracing::info!("no upgradeable DEX programs to watch");
+                return;
+            }
+        }
+
+        let addresses: Vec<Pubkey> = watched.iter().map(|(_, _, programdata)| *programdata).collect();
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig { offset: 0, length: PROGRAMDATA_HEADER_LEN }),
+            ..Default::default()
+        };
+        let accounts = match rpc_client.get_multiple_accounts_with_config(&addresses, config).await {
+            Ok(response) => response.value,
+            Err(e) => {
+                tracing::warn!(error = %e, "program upgrade check failed");
+                continue;
+            }
+        };
+
+        for ((source, program, programdata), account) in watched.iter().zip(accounts) {
+            let Some(slot) = account.and_then(|account| deployment_slot(&account.data)) else {
+                tracing::warn!(source = %source, programdata = %programdata, "ProgramData account is missing or unreadable");
+                continue;
+            };
+
+            match deployed.insert(*program, slot) {
+                None => tracing::info!(source = %source, slot = slot, program = %program, "watching program for upgrades"),
+                Some(previous_slot) if slot > previous_slot => {
+                    tracing::warn!(source = %source, program = %program, slot = slot, previous_slot = %previous_slot, "program upgraded, verify the decoders still parse its accounts");
+                    let _ = health_tx.send(HealthEvent::program_upgraded(source, program, previous_slot, slot));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+// ProgramData address of each source's program. Programs that aren't owned by
+// the upgradeable loader can't change and are left out.
+async fn resolve_programdata(rpc_client: &RpcClient, sources: &[String]) -> anyhow::Result<Vec<(String, Pubkey, Pubkey)>> {
+    let programs: Vec<(String, Pubkey)> = sources
+        .iter()
+        .filter_map(|source| programs::program_id(source).map(|program| (source.clone(), program)))
+        .collect();
+    let addresses: Vec<Pubkey> = programs.iter().map(|(_, program)| *program).collect();
+    let accounts = rpc_client.get_multiple_accounts(&addresses).await?;
+
+    let mut resolved = Vec::with_capacity(programs.len());
+    for ((source, program), account) in programs.into_iter().zip(accounts) {
+        let Some(account) = account else {
+            return Err(anyhow::anyhow!("{} program account {} not found", source, program));
+        };
+        match programdata_address(&account.owner, &account.data) {
+            Some(programdata) => resolved.push((source, program, programdata)),
+            None => tracing::info!(source = %source, program = %program, "program is not upgradeable, not watching it"),
+        }
+    }
+    Ok(resolved)
+}
+
+fn programdata_address(owner: &Pubkey, data: &[u8]) -> Option<Pubkey> {
+    if *owner != programs::BPF_LOADER_UPGRADEABLE_ID || data.len() < 36 || read_tag(data) != PROGRAM_TAG {
+        return None;
+    }
+    Some(Pubkey::new_from_array(data[4..36].try_into().unwrap()))
+}
+
+fn deployment_slot(data: &[u8]) -> Option<u64> {
+    if data.len() < PROGRAMDATA_HEADER_LEN || read_tag(data) != PROGRAMDATA_TAG {
+        return None;
+    }
+    Some(u64::from_le_bytes(data[4..12].try_into().unwrap()))
+}
+
+fn read_tag(data: &[u8]) -> u32 {
+    u32::from_le_bytes(data[0..4].try_into().unwrap())
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::clock::unix_now;
+use crate::events::MarketEvent;
+use crate::swaps::{SwapDirection, SwapEvent};
+
+const WINDOW_1M: u64 = 60;
+const WINDOW_5M: u64 = 5 * 60;
+const WINDOW_24H: u64 = 24 * 60 * 60;
+
+// Traded amounts over one rolling window, UI units
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VolumeWindow {
+    pub base_volume: f64,
+    pub quote_volume: f64,
+    pub swaps: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeUpdate {
+    pub symbol: String,
+    pub source: String,
+    pub pool: String,
+    pub volume_1m: VolumeWindow,
+    pub volume_5m: VolumeWindow,
+    pub volume_24h: VolumeWindow,
+    pub timestamp: u64,
+}
+
+// One decoded swap, reduced to what the counters need
+struct Fill {
+    timestamp: u64,
+    base: f64,
+    quote: f64,
+}
+
+struct PoolVolume {
+    symbol: String,
+    source: String,
+    fills: VecDeque<Fill>,  // Last 24h, oldest first
+}
+
+// Rolling per-pool volume built from the swap stream, published every interval
+pub struct VolumeTracker {
+    pools: HashMap<String, PoolVolume>,
+}
+
+impl VolumeTracker {
+    pub fn new() -> Self {
+        Self { pools: HashMap::new() }
+    }
+
+    pub async fn run(
+        mut self,
+        interval_secs: u64,
+        mut rx: broadcast::Receiver<MarketEvent>,
+        tx: Arc<broadcast::Sender<VolumeUpdate>>,
+    ) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs.max(1)));
+
+        loop {
+            tokio::select! {
+                swap = rx.recv() => match swap {
+                    Ok(MarketEvent::Swap(swap)) => self.on_swap(swap),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped = skipped, "volume tracker lagged, skipped events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => {
+                    for update in self.snapshot(unix_now()) {
+                        let _ = tx.send(update);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn on_swap(&mut self, swap: SwapEvent) {
+        let (base, quote) = match swap.direction {
+            SwapDirection::Buy => (swap.amount_out, swap.amount_in),
+            SwapDirection::Sell => (swap.amount_in, swap.amount_out),
+        };
+
+        let pool = self.pools.entry(swap.pool).or_insert_with(|| PoolVolume {
+            symbol: swap.symbol,
+            source: swap.source,
+            fills: VecDeque::new(),
+        });
+        pool.fills.push_back(Fill {
+            timestamp: swap.timestamp,
+            base,
+            quote,
+        });
+    }
+
+    pub fn snapshot(&mut self, now: u64) -> Vec<VolumeUpdate> {
+        self.pools
+            .iter_mut()
+            .map(|(address, pool)| {
+                while pool.fills.front().is_some_and(|fill| now.saturating_sub(fill.timestamp) > WINDOW_24H) {
+                    pool.fills.pop_front();
+                }
+
+                let window = |secs: u64| {
+                    pool.fills
+                        .iter()
+                        .filter(|fill| now.saturating_sub(fill.timestamp) <= secs)
+                        .fold(VolumeWindow::default(), |mut window, fill| {
+                            window.base_volume += fill.base;
+                            window.quote_volume += fill.quote;
+                            window.swaps += 1;
+                            window
+                        })
+                };
+
+                VolumeUpdate {
+                    symbol: pool.symbol.clone(),
+                    source: pool.source.clone(),
+                    pool: address.clone(),
+                    volume_1m: window(WINDOW_1M),
+                    volume_5m: window(WINDOW_5M),
+                    volume_24h: window(WINDOW_24H),
+                    timestamp: now,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,255 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::EncodedConfirmedTransactionWithStatusMeta;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::{PoolConfig, WalletsConfig};
+use crate::holders::{self, HolderMint};
+use crate::metrics;
+use crate::rpc::{self, RpcProvider};
+use crate::programs;
+use crate::transactions;
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+// Native SOL movements smaller than this are rent/fees, not part of the trade
+const SOL_DUST: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletActivity {
+    Swap,             // One token out, one token in
+    LiquidityAdd,     // Tokens out (LP tokens or a position NFT may come back)
+    LiquidityRemove,  // Tokens in (LP tokens or a position NFT may go out)
+}
+
+// Signed balance change of one mint in the wallet, UI units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenDelta {
+    pub mint: String,
+    pub amount: f64,
+}
+
+// A swap or liquidity change by a watched wallet on one of the monitored DEX programs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTradeEvent {
+    pub wallet: String,
+    pub signature: String,
+    pub slot: u64,
+    pub source: String,             // DEX whose program the transaction invoked
+    pub pool: Option<String>,       // Watched pool touched by the transaction, if any
+    pub symbol: Option<String>,
+    pub activity: WalletActivity,
+    pub deltas: Vec<TokenDelta>,
+    pub timestamp: u64,
+}
+
+// Subscribe to the logs of every transaction mentioning a watched wallet and
+// decode the ones that hit a DEX program. The activity is read from the
+// wallet's balance changes rather than the instruction data, so it also covers
+// swaps routed through aggregators.
+pub struct WalletWatcher {
+    rpc: RpcProvider,
+    rpc_client: RpcClient,
+    wallets: Vec<Pubkey>,
+    // pool address -> (source, pool config)
+    pools: HashMap<Pubkey, (String, PoolConfig)>,
+    // pool address -> LP mint, for the pools that issue LP tokens
+    lp_mints: HashMap<Pubkey, String>,
+}
+
+impl WalletWatcher {
+    pub fn new(config: WalletsConfig, pools: Vec<(String, PoolConfig)>, rpc: &RpcProvider) -> Self {
+
+        let wallets = config.pubkeys().expect("Invalid wallet address");
+        let pools = pools
+            .into_iter()
+            .map(|(source, pool)| (pool.pubkey().expect("Invalid pool address"), (source, pool)))
+            .collect();
+
+        Self {
+            rpc: rpc.clone(),
+            rpc_client: rpc.client(CommitmentConfig::confirmed()),
+            wallets,
+            pools,
+            lp_mints: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self, tx: Arc<broadcast::Sender<WalletTradeEvent>>) {
+        self.lp_mints = self.resolve_lp_mints().await;
+        let watcher = Arc::new(self);
+
+        // logsSubscribe accepts a single address per subscription
+        let handles: Vec<_> = watcher
+            .wallets
+            .iter()
+            .map(|wallet| {
+                let watcher = watcher.clone();
+                let tx = tx.clone();
+                let wallet = *wallet;
+                tokio::spawn(async move {
+                    loop {
+                        if let Err(e) = watcher.watch_wallet(&wallet, &tx).await {
+                            tracing::error!(error = %e, wallet = %wallet, "wallet subscription error");
+                            metrics::inc_rpc_error("Wallets");
+                        }
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                        metrics::inc_reconnect("Wallets");
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    // Read every watched pool once to learn its LP mint. Without them LP tokens
+    // received for a single token are taken for a swap, so a failure is only
+    // logged.
+    async fn resolve_lp_mints(&self) -> HashMap<Pubkey, String> {
+        let addresses: Vec<Pubkey> = self.pools.keys().copied().collect();
+        let accounts = match rpc::get_multiple_accounts(&self.rpc_client, &addresses).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to read pool LP mints");
+                return HashMap::new();
+            }
+        };
+
+        addresses
+            .iter()
+            .zip(accounts)
+            .filter_map(|(address, account)| {
+                let (source, _) = self.pools.get(address)?;
+                let mints = holders::pool_mints(source, address, &account?.data).ok()?;
+                let (_, lp_mint) = mints.into_iter().find(|(kind, _)| *kind == HolderMint::Lp)?;
+                Some((*address, lp_mint.to_string()))
+            })
+            .collect()
+    }
+
+    async fn watch_wallet(&self, wallet: &Pubkey, tx: &broadcast::Sender<WalletTradeEvent>) -> anyhow::Result<()> {
+        let pubsub_client = PubsubClient::new(&self.rpc.ws_url()).await?;
+        let (mut notifications, unsubscribe) = pubsub_client
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![wallet.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+
+        tracing::info!(wallet = %wallet, "watching wallet");
+
+        while let Some(notification) = notifications.next().await {
+            if notification.value.err.is_some() {
+                continue;
+            }
+
+            let signature = Signature::from_str(&notification.value.signature)?;
+            match transactions::fetch_transaction(&self.rpc_client, &signature).await {
+                Ok(transaction) => {
+                    if let Some(event) = self.decode_trade(wallet, &notification.value.signature, &transaction) {
+                        let _ = tx.send(event);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, signature = %signature, "failed to fetch transaction");
+                    metrics::inc_rpc_error("Wallets");
+                }
+            }
+        }
+
+        unsubscribe().await;
+        Err(anyhow::anyhow!("Subscription closed"))
+    }
+
+    fn decode_trade(
+        &self,
+        wallet: &Pubkey,
+        signature: &str,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Option<WalletTradeEvent> {
+        let meta = transaction.transaction.meta.as_ref()?;
+        let account_keys = transactions::account_keys(transaction)?;
+
+        let source = account_keys.iter().find_map(programs::dex_name)?;
+        let pool = account_keys.iter().find_map(|key| self.pools.get(key).map(|(_, pool)| (key, pool)));
+
+        // Per-mint balance change of every token account the wallet owns
+        let owner = wallet.to_string();
+        let mut deltas: HashMap<String, f64> = HashMap::new();
+        let (pre, post) = transactions::token_balances(meta);
+        for (balances, sign) in [(pre, -1.0), (post, 1.0)] {
+            for balance in balances {
+                if Option::<String>::from(balance.owner.clone()).as_deref() != Some(owner.as_str()) {
+                    continue;
+                }
+                *deltas.entry(balance.mint.clone()).or_default() += sign * transactions::ui_amount(&balance);
+            }
+        }
+
+        // Native SOL, wrapped and unwrapped inside the same transaction, counts as WSOL
+        if let Some(index) = account_keys.iter().position(|key| key == wallet) {
+            let mut lamports = *meta.post_balances.get(index)? as i64 - *meta.pre_balances.get(index)? as i64;
+            if index == 0 {
+                lamports += meta.fee as i64;
+            }
+            let sol = lamports as f64 / 1e9;
+            if sol.abs() >= SOL_DUST {
+                *deltas.entry(WSOL_MINT.to_string()).or_default() += sol;
+            }
+        }
+
+        let deltas: Vec<TokenDelta> = deltas
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0.0)
+            .map(|(mint, amount)| TokenDelta { mint, amount })
+            .collect();
+        let lp_mint = pool.and_then(|(address, _)| self.lp_mints.get(address));
+        let activity = classify_activity(&deltas, lp_mint.map(String::as_str))?;
+
+        Some(WalletTradeEvent {
+            wallet: owner,
+            signature: signature.to_string(),
+            slot: transaction.slot,
+            source: source.to_string(),
+            pool: pool.map(|(address, _)| address.to_string()),
+            symbol: pool.map(|(_, pool)| pool.symbol.clone()),
+            activity,
+            deltas,
+            timestamp: transactions::block_timestamp(transaction),
+        })
+    }
+}
+
+// Shape of the wallet's balance changes:
+//   1 out, pool's LP mint in    -> liquidity add (single-sided deposit)
+//   pool's LP mint out, 1 in    -> liquidity remove (single-sided withdrawal)
+//   1 out, 1 in                 -> swap
+//   2+ out (plus LP/NFT in)     -> liquidity add, also single-sided (1 out, 0 in)
+//   2+ in  (plus LP/NFT out)    -> liquidity remove, also single-sided (0 out, 1 in)
+fn classify_activity(deltas: &[TokenDelta], lp_mint: Option<&str>) -> Option<WalletActivity> {
+    let spent = deltas.iter().filter(|delta| delta.amount < 0.0).count();
+    let received = deltas.iter().filter(|delta| delta.amount > 0.0).count();
+    let lp_delta = deltas.iter().find(|delta| Some(delta.mint.as_str()) == lp_mint).map(|delta| delta.amount);
+
+    match (spent, received) {
+        (1, 1) if lp_delta.is_some_and(|amount| amount > 0.0) => Some(WalletActivity::LiquidityAdd),
+        (1, 1) if lp_delta.is_some_and(|amount| amount < 0.0) => Some(WalletActivity::LiquidityRemove),
+        (1, 1) => Some(WalletActivity::Swap),
+        (1.., 0..=1) => Some(WalletActivity::LiquidityAdd),
+        (0..=1, 1..) => Some(WalletActivity::LiquidityRemove),
+        _ => None,
+    }
+}
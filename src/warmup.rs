@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+use crate::config::{PoolConfig, WarmupConfig};
+use crate::health::HealthEvent;
+use crate::events::MarketEvent;
+
+// Whether the startup barrier has passed. Cheap to clone; consumers that
+// compare pools check it before acting on what they've seen so far.
+#[derive(Clone)]
+pub struct Ready(watch::Receiver<bool>);
+
+impl Ready {
+    pub fn is_ready(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+pub fn channel() -> (watch::Sender<bool>, Ready) {
+    let (tx, rx) = watch::channel(false);
+    (tx, Ready(rx))
+}
+
+// Track the first valid price of every configured pool and open the barrier
+// once the quorum is reached: publish HealthEvent::Ready and flip `ready`.
+// `pools` are (source, pool) pairs as the monitors tag their updates.
+pub async fn run(
+    pools: Vec<(String, PoolConfig)>,
+    config: WarmupConfig,
+    mut rx: broadcast::Receiver<MarketEvent>,
+    health_tx: Arc<broadcast::Sender<HealthEvent>>,
+    ready: watch::Sender<bool>,
+) {
+    // (source, pool address) -> "Source SYMBOL" for the missing list
+    let mut pending: BTreeMap<(String, String), String> = pools
+        .into_iter()
+        .map(|(source, pool)| {
+            let label = format!("{} {}", source, pool.symbol);
+            ((source, pool.address), label)
+        })
+        .collect();
+    let total = pending.len();
+    let quorum = ((total as f64 * config.quorum_percent / 100.0).ceil() as usize).min(total);
+    let mut priced = 0;
+
+    let timeout = tokio::time::sleep(Duration::from_secs(config.timeout_secs));
+    tokio::pin!(timeout);
+    let mut warned = false;
+
+    tracing::info!(quorum = %quorum, total = %total, "warming up: waiting for a first price");
+
+    while priced < quorum {
+        tokio::select! {
+            update = rx.recv() => match update {
+                Ok(MarketEvent::Price(update)) => {
+                    if !(update.price.is_finite() && update.price > 0.0) {
+                        continue;
+                    }
+                    if pending.remove(&(update.source, update.pool)).is_some() {
+                        priced += 1;
+                    }
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = &mut timeout, if !warned => {
+                warned = true;
+                tracing::warn!(timeout_secs = %config.timeout_secs, priced = %priced, quorum = %quorum, missing = %pending.values().cloned().collect::<Vec<_>>().join(", "), "warm-up still waiting");
+            }
+        }
+    }
+
+    let missing: Vec<String> = pending.into_values().collect();
+    if missing.is_empty() {
+        tracing::info!(total = %total, "warm-up complete");
+    } else {
+        tracing::info!(priced = %priced, total = %total, missing = %missing.join(", "), "warm-up complete, pools still missing");
+    }
+    let _ = health_tx.send(HealthEvent::ready(priced, total, missing));
+    let _ = ready.send(true);
+}